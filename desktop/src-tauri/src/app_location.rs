@@ -0,0 +1,147 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::{i18n, process};
+
+/// The `.app` bundle the shell runs from, if it runs from one.
+pub(crate) fn app_bundle() -> Option<PathBuf> {
+    let exe = std::env::current_exe().ok()?;
+    exe.ancestors()
+        .find(|dir| dir.extension().is_some_and(|ext| ext == "app"))
+        .map(Path::to_path_buf)
+}
+
+/// Gatekeeper runs quarantined apps that weren't moved by Finder from a
+/// randomized read-only mount; apps opened straight from the DMG live under
+/// /Volumes. Either way the bundle dir is read-only and goes away.
+fn is_ephemeral(bundle: &Path) -> bool {
+    bundle.to_string_lossy().contains("/AppTranslocation/") || bundle.starts_with("/Volumes")
+}
+
+/// /Applications, or ~/Applications for users who can't write there.
+fn applications_dir() -> PathBuf {
+    let system = PathBuf::from("/Applications");
+    if can_write(&system) {
+        return system;
+    }
+    home_dir().join("Applications")
+}
+
+fn home_dir() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+/// Permission bits don't tell whether this user may create entries, so try.
+fn can_write(dir: &Path) -> bool {
+    let probe = dir.join(format!(".nanoclaw-write-test-{}", std::process::id()));
+    let ok = std::fs::write(&probe, b"").is_ok();
+    let _ = std::fs::remove_file(&probe);
+    ok
+}
+
+/// Copy the running bundle into Applications, replacing an older copy (which
+/// goes to the Trash), and clear the quarantine flag so the copy isn't
+/// translocated again. Returns the installed bundle.
+fn install(bundle: &Path) -> Result<PathBuf, String> {
+    let name = bundle
+        .file_name()
+        .ok_or_else(|| "The app bundle has no name".to_string())?;
+    let dir = applications_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let dest = dir.join(name);
+
+    if dest.exists() {
+        let trashed = home_dir().join(".Trash").join(format!(
+            "{}-{}",
+            name.to_string_lossy(),
+            std::process::id()
+        ));
+        std::fs::rename(&dest, &trashed)
+            .map_err(|e| format!("Failed to move the old copy to the Trash: {}", e))?;
+    }
+
+    let output = process::runner()
+        .output(
+            Command::new("ditto")
+                .arg(bundle)
+                .arg(&dest)
+                .stdin(Stdio::null()),
+        )
+        .map_err(|e| format!("Failed to run ditto: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to copy the app: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    // The user asked for this copy, as Finder's move would have
+    let _ = process::runner().output(
+        Command::new("xattr")
+            .args(["-dr", "com.apple.quarantine"])
+            .arg(&dest)
+            .stdin(Stdio::null()),
+    );
+    Ok(dest)
+}
+
+/// Open `bundle` once this process has exited, so the backend is stopped
+/// before the new copy starts its own.
+fn relaunch_after_exit(bundle: &Path) -> Result<(), String> {
+    let script = "while kill -0 \"$1\" 2>/dev/null; do sleep 0.2; done; open \"$2\"";
+    process::runner()
+        .spawn(
+            Command::new("/bin/sh")
+                .args(["-c", script, "sh", &std::process::id().to_string()])
+                .arg(bundle)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null()),
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to schedule relaunch: {}", e))
+}
+
+/// Warn when the app runs from the DMG or a translocated copy, and offer to
+/// move it to Applications and relaunch from there.
+pub(crate) fn check(app: &AppHandle, locale: i18n::Locale) {
+    let Some(bundle) = app_bundle().filter(|bundle| is_ephemeral(bundle)) else {
+        return;
+    };
+    eprintln!("Running from a temporary location: {}", bundle.display());
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let t = |key| i18n::tr(locale, key);
+        let confirmed = app
+            .dialog()
+            .message(t("location.body"))
+            .title(t("location.title"))
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                t("location.move").to_string(),
+                t("location.not_now").to_string(),
+            ))
+            .blocking_show();
+        if !confirmed {
+            return;
+        }
+
+        match install(&bundle).and_then(|dest| relaunch_after_exit(&dest)) {
+            Ok(()) => app.exit(0),
+            Err(e) => {
+                eprintln!("{}", e);
+                app.dialog()
+                    .message(format!("{}\n\n{}", t("location.move_failed"), e))
+                    .title(t("location.title"))
+                    .kind(MessageDialogKind::Error)
+                    .blocking_show();
+            }
+        }
+    });
+}
@@ -0,0 +1,101 @@
+use tauri::menu::{Menu, MenuBuilder, MenuItemBuilder, PredefinedMenuItem, SubmenuBuilder};
+use tauri::{AppHandle, Wry};
+
+use crate::{i18n, windows, zoom};
+
+/// Build the menu bar: App, Edit, View and Window. The Edit items are what
+/// make ⌘C/⌘V/⌘Z reach the webview; ⌘W closes the front window the way its
+/// close button would. Called again when the locale changes.
+pub(crate) fn install(app: &AppHandle) -> tauri::Result<()> {
+    app.set_menu(build(app)?)?;
+    Ok(())
+}
+
+fn build(app: &AppHandle) -> tauri::Result<Menu<Wry>> {
+    let locale = crate::current_locale(app);
+    let t = |key| Some(i18n::tr(locale, key));
+    // Ids carry an "app:" prefix: the tray's handler sees these events too,
+    // and has its own "quit" and "check_updates"
+    let item = |id: &str, key, accelerator: Option<&str>| {
+        let mut builder = MenuItemBuilder::with_id(id, i18n::tr(locale, key));
+        if let Some(accelerator) = accelerator {
+            builder = builder.accelerator(accelerator);
+        }
+        builder.build(app)
+    };
+
+    let app_submenu = SubmenuBuilder::new(app, "NanoClaw")
+        .item(&PredefinedMenuItem::about(app, t("menu.about"), None)?)
+        .separator()
+        .item(&item("app:settings", "menu.settings", Some("CmdOrCtrl+,"))?)
+        .item(&item("app:check_updates", "tray.check_updates", None)?)
+        .separator()
+        .item(&PredefinedMenuItem::services(app, t("menu.services"))?)
+        .separator()
+        .item(&PredefinedMenuItem::hide(app, t("menu.hide"))?)
+        .item(&PredefinedMenuItem::hide_others(
+            app,
+            t("menu.hide_others"),
+        )?)
+        .item(&PredefinedMenuItem::show_all(app, t("menu.show_all"))?)
+        .separator()
+        // Not the predefined Quit: app.exit goes through the shutdown cleanup
+        .item(&item("app:quit", "menu.quit", Some("CmdOrCtrl+Q"))?)
+        .build()?;
+
+    let edit = SubmenuBuilder::new(app, i18n::tr(locale, "menu.edit"))
+        .item(&PredefinedMenuItem::undo(app, t("menu.undo"))?)
+        .item(&PredefinedMenuItem::redo(app, t("menu.redo"))?)
+        .separator()
+        .item(&PredefinedMenuItem::cut(app, t("menu.cut"))?)
+        .item(&PredefinedMenuItem::copy(app, t("menu.copy"))?)
+        .item(&PredefinedMenuItem::paste(app, t("menu.paste"))?)
+        .item(&PredefinedMenuItem::select_all(app, t("menu.select_all"))?)
+        .build()?;
+
+    let [zoom_in, zoom_out, zoom_reset] = zoom::menu_items(app)?;
+    let view = SubmenuBuilder::new(app, i18n::tr(locale, "menu.view"))
+        .item(&zoom_in)
+        .item(&zoom_out)
+        .item(&zoom_reset)
+        .separator()
+        .item(&PredefinedMenuItem::fullscreen(app, t("menu.fullscreen"))?)
+        .build()?;
+
+    let window = SubmenuBuilder::new(app, i18n::tr(locale, "menu.window"))
+        .item(&PredefinedMenuItem::minimize(app, t("menu.minimize"))?)
+        .item(&PredefinedMenuItem::maximize(app, t("menu.maximize"))?)
+        .separator()
+        .item(&item("app:main_window", "menu.main_window", None)?)
+        .item(&item("app:logs", "menu.logs", None)?)
+        .separator()
+        .item(&PredefinedMenuItem::close_window(
+            app,
+            t("menu.close_window"),
+        )?)
+        .build()?;
+    // macOS lists the open windows below
+    window.set_as_windows_menu_for_nsapp()?;
+
+    MenuBuilder::new(app)
+        .items(&[&app_submenu, &edit, &view, &window])
+        .build()
+}
+
+/// Handle a click on one of the menu bar's own items.
+pub(crate) fn on_menu_event(app: &AppHandle, id: &str) {
+    if zoom::on_menu_event(app, id) {
+        return;
+    }
+    match id {
+        "app:settings" | "app:logs" => {
+            if let Err(e) = windows::open(app, &id["app:".len()..]) {
+                eprintln!("{}", e);
+            }
+        }
+        "app:check_updates" => crate::check_for_updates_from_tray(app),
+        "app:main_window" => crate::show_main_window(app),
+        "app:quit" => app.exit(0),
+        _ => {}
+    }
+}
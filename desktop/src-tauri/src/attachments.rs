@@ -0,0 +1,230 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+
+const ATTACHMENTS_DIR: &str = "attachments";
+/// Files dropped on a window wait here until the frontend attaches them.
+const DROP_STAGING_DIR: &str = "staging/dropped";
+/// Where a group's folder is mounted inside its agent container.
+const CONTAINER_GROUP_DIR: &str = "/workspace/group";
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Attachment {
+    pub name: String,
+    /// Path the agent sees inside its container
+    pub container_path: String,
+    pub size: u64,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StagedFile {
+    pub name: String,
+    /// Staged copy, to pass to `attach_files`
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DroppedFiles {
+    pub files: Vec<StagedFile>,
+    /// Drops that couldn't be staged (folders, unreadable files)
+    pub errors: Vec<String>,
+}
+
+/// Group folders are plain names; reject anything that could escape `groups/`.
+pub(crate) fn group_dir(data_dir: &Path, group_id: &str) -> Result<PathBuf, String> {
+    let valid = !group_id.is_empty()
+        && group_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+    if !valid {
+        return Err(format!("Invalid group id: {}", group_id));
+    }
+    Ok(data_dir.join("groups").join(group_id))
+}
+
+/// `name`, or `name (2).ext`, `name (3).ext`, ... if that already exists in `dir`.
+fn unique_path(dir: &Path, name: &str) -> PathBuf {
+    let candidate = dir.join(name);
+    if !candidate.exists() {
+        return candidate;
+    }
+    let path = Path::new(name);
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(name);
+    let ext = path.extension().and_then(|s| s.to_str());
+    (2..)
+        .map(|n| match ext {
+            Some(ext) => dir.join(format!("{} ({}).{}", stem, n, ext)),
+            None => dir.join(format!("{} ({})", stem, n)),
+        })
+        .find(|p| !p.exists())
+        .unwrap_or(candidate)
+}
+
+/// Copy files into the group's `attachments/` folder, which its agent container mounts.
+fn attach(data_dir: &Path, group_id: &str, files: &[PathBuf]) -> Result<Vec<Attachment>, String> {
+    let dir = group_dir(data_dir, group_id)?.join(ATTACHMENTS_DIR);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create attachments dir: {}", e))?;
+
+    let mut attached = Vec::new();
+    for file in files {
+        if !file.is_file() {
+            return Err(format!("Not a file: {}", file.display()));
+        }
+        let name = file
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| format!("Invalid file name: {}", file.display()))?;
+        let dest = unique_path(&dir, name);
+        let size = std::fs::copy(file, &dest)
+            .map_err(|e| format!("Failed to copy {}: {}", file.display(), e))?;
+        let name = dest
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        attached.push(Attachment {
+            container_path: format!("{}/{}/{}", CONTAINER_GROUP_DIR, ATTACHMENTS_DIR, name),
+            name,
+            size,
+        });
+    }
+    Ok(attached)
+}
+
+/// Attach files the webview names by path. Only staged drops are accepted;
+/// anything else has to come through the native picker, so a compromised
+/// page can't copy arbitrary files into a container's reach.
+pub(crate) fn attach_staged(
+    data_dir: &Path,
+    group_id: &str,
+    files: &[PathBuf],
+) -> Result<Vec<Attachment>, String> {
+    let staging = data_dir.join(DROP_STAGING_DIR);
+    let root = staging
+        .canonicalize()
+        .map_err(|e| format!("Staging dir not found: {}", e))?;
+    let files = files
+        .iter()
+        .map(|file| {
+            let file = file
+                .canonicalize()
+                .map_err(|e| format!("File not found: {}", e))?;
+            if !file.starts_with(&root) || !file.is_file() {
+                return Err("Only dropped files can be attached by path".to_string());
+            }
+            Ok(file)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    attach(data_dir, group_id, &files)
+}
+
+/// Copy dropped files into the staging area. The originals may be moved or
+/// deleted right after the drop, so the copy is what gets attached later.
+pub(crate) fn stage_dropped(data_dir: &Path, paths: &[PathBuf]) -> DroppedFiles {
+    let dir = data_dir.join(DROP_STAGING_DIR);
+    let mut dropped = DroppedFiles {
+        files: Vec::new(),
+        errors: Vec::new(),
+    };
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        dropped
+            .errors
+            .push(format!("Failed to create staging dir: {}", e));
+        return dropped;
+    }
+    for path in paths {
+        if !path.is_file() {
+            dropped
+                .errors
+                .push(format!("Only files can be attached: {}", path.display()));
+            continue;
+        }
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            dropped
+                .errors
+                .push(format!("Invalid file name: {}", path.display()));
+            continue;
+        };
+        let dest = unique_path(&dir, name);
+        match std::fs::copy(path, &dest) {
+            Ok(size) => dropped.files.push(StagedFile {
+                name: name.to_string(),
+                path: dest,
+                size,
+            }),
+            Err(e) => dropped
+                .errors
+                .push(format!("Failed to copy {}: {}", path.display(), e)),
+        }
+    }
+    dropped
+}
+
+/// Forget drops from earlier sessions that were never attached.
+pub(crate) fn clear_staging(data_dir: &Path) {
+    let _ = std::fs::remove_dir_all(data_dir.join(DROP_STAGING_DIR));
+}
+
+/// Show the native open dialog and attach whatever the user picks.
+/// Returns an empty list if the dialog is dismissed.
+pub(crate) fn pick_and_attach(
+    app: &AppHandle,
+    data_dir: &Path,
+    group_id: &str,
+) -> Result<Vec<Attachment>, String> {
+    // Validate before showing UI
+    group_dir(data_dir, group_id)?;
+    let Some(picked) = app
+        .dialog()
+        .file()
+        .set_title("Attach Files")
+        .blocking_pick_files()
+    else {
+        return Ok(Vec::new());
+    };
+    let files = picked
+        .into_iter()
+        .map(|p| p.into_path().map_err(|e| format!("Invalid path: {}", e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    attach(data_dir, group_id, &files)
+}
+
+/// Show the native save dialog and copy a file from the data dir (e.g. an
+/// agent artifact) to the chosen location. Returns the destination, or None
+/// if the dialog is dismissed.
+pub(crate) fn save_copy(
+    app: &AppHandle,
+    data_dir: &Path,
+    source: &Path,
+) -> Result<Option<PathBuf>, String> {
+    let source = source
+        .canonicalize()
+        .map_err(|e| format!("File not found: {}", e))?;
+    let root = data_dir
+        .canonicalize()
+        .map_err(|e| format!("Data dir not found: {}", e))?;
+    if !source.starts_with(&root) || !source.is_file() {
+        return Err("Only files inside the data directory can be exported".to_string());
+    }
+
+    let mut dialog = app.dialog().file();
+    if let Some(name) = source.file_name().and_then(|n| n.to_str()) {
+        dialog = dialog.set_file_name(name);
+    }
+    let Some(dest) = dialog.blocking_save_file() else {
+        return Ok(None);
+    };
+    let dest = dest
+        .into_path()
+        .map_err(|e| format!("Invalid path: {}", e))?;
+    std::fs::copy(&source, &dest)
+        .map_err(|e| format!("Failed to save {}: {}", dest.display(), e))?;
+    Ok(Some(dest))
+}
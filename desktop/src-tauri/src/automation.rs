@@ -0,0 +1,92 @@
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Manager, Url};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::{backend, chat_stream, i18n, settings};
+
+/// How long a link opened at launch waits for the backend to come up.
+const READY_WAIT: Duration = Duration::from_secs(60);
+
+/// Actions for Shortcuts ("Open URL"), AppleScript (`open location`) and
+/// launchers like Raycast, as `nanoclaw://` links:
+///
+/// - `nanoclaw://show` shows the main window
+/// - `nanoclaw://restart-backend` restarts the backend
+/// - `nanoclaw://send?group=<id>&prompt=<text>` sends a prompt to a
+///   conversation; the user confirms it unless URL automation is allowed,
+///   since any web page can open a link.
+///
+/// Returns whether `route` was one of them.
+pub(crate) fn handle(app: &AppHandle, route: &str, url: &Url) -> bool {
+    match route {
+        "show" => crate::show_main_window(app),
+        "restart-backend" => {
+            let app = app.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = app.state::<backend::BackendManager>().restart() {
+                    eprintln!("{}", e);
+                }
+            });
+        }
+        "send" => {
+            let param = |name: &str| {
+                url.query_pairs()
+                    .find(|(key, _)| key == name)
+                    .map(|(_, value)| value.into_owned())
+                    .filter(|value| !value.trim().is_empty())
+            };
+            let (Some(group_id), Some(prompt)) = (param("group"), param("prompt")) else {
+                eprintln!("nanoclaw://send needs group and prompt parameters");
+                return true;
+            };
+            let app = app.clone();
+            std::thread::spawn(move || send(&app, group_id, prompt));
+        }
+        _ => return false,
+    }
+    true
+}
+
+fn send(app: &AppHandle, group_id: String, prompt: String) {
+    let prefs = settings::load(&crate::user_data_dir(app));
+    if !prefs.url_automation {
+        let locale = i18n::resolve(prefs.locale.as_deref());
+        let t = |key| i18n::tr(locale, key);
+        let confirmed = app
+            .dialog()
+            .message(format!(
+                "{}\n\n{}: {}",
+                t("automation.send_body"),
+                group_id,
+                prompt
+            ))
+            .title(t("automation.send_title"))
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                t("automation.send").to_string(),
+                t("automation.cancel").to_string(),
+            ))
+            .blocking_show();
+        if !confirmed {
+            return;
+        }
+    }
+
+    // A link that launched the app arrives before the backend is up
+    let manager = app.state::<backend::BackendManager>();
+    let updates = manager.subscribe();
+    let started = Instant::now();
+    while !manager.status().ready {
+        let remaining = READY_WAIT.saturating_sub(started.elapsed());
+        if remaining.is_zero() || updates.recv_timeout(remaining).is_err() {
+            eprintln!("nanoclaw://send: the backend didn't become ready");
+            return;
+        }
+    }
+    if let Err(e) = chat_stream::start(app, group_id.clone(), prompt) {
+        eprintln!("nanoclaw://send: {}", e);
+        return;
+    }
+    crate::navigate_main_window(app, "chat", Some(group_id));
+}
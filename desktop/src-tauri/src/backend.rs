@@ -0,0 +1,1111 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ExitStatus};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use nix::sys::signal::Signal;
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_dialog::{
+    DialogExt, MessageDialogButtons, MessageDialogKind, MessageDialogResult,
+};
+
+use crate::error::ShellError;
+use crate::settings::NotificationCategory;
+use crate::{
+    heartbeat, i18n, logs, notifications, power, process, settings, setup, telemetry, tunnel,
+    windows,
+};
+
+/// How long a fresh backend gets to answer its first health check.
+const READY_TIMEOUT: Duration = Duration::from_secs(20);
+/// Health checks run this often while waiting for readiness...
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// ...and this often otherwise, to notice an exit stdout didn't report.
+const HEALTH_TICK_INTERVAL: Duration = Duration::from_secs(2);
+/// An exit this soon after spawning counts as a failure to start.
+const QUICK_EXIT: Duration = Duration::from_secs(30);
+/// Output lines searched for the cause of a failed start.
+const FAILURE_LOG_LINES: usize = 40;
+/// Delay before the first automatic restart, doubled for each further crash.
+const RESTART_BACKOFF: Duration = Duration::from_secs(2);
+const MAX_RESTART_DELAY: Duration = Duration::from_secs(30);
+/// Output lines quoted in the crash-loop dialog.
+const CRASH_LOOP_LOG_LINES: usize = 10;
+
+/// What starting the backend amounted to.
+pub(crate) enum Launch {
+    /// A new process; the manager owns it from here.
+    Spawned(Child),
+    /// A backend started elsewhere already answers on the configured address.
+    Reused,
+    /// Nothing to start, e.g. `--no-backend` with no backend around.
+    Skipped,
+    /// A remote backend is configured; the manager follows its health.
+    Remote,
+}
+
+/// Requests handled, one at a time, by the manager thread.
+pub(crate) enum Message {
+    Start(Sender<Result<(), ShellError>>),
+    /// Replies once the process has exited (or been killed).
+    Stop(Sender<()>),
+    Restart(Sender<Result<(), ShellError>>),
+    /// One line for the backend's stdin.
+    Send(String, Sender<Result<(), ShellError>>),
+    /// Bring the main window forward at the next ready, as the user asked
+    /// for this start.
+    RevealWhenReady,
+    HealthTick,
+    /// Stdout of the process from that spawn generation closed.
+    Exited(u64),
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Default, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackendStatus {
+    /// The shell owns a running backend process.
+    pub running: bool,
+    /// The backend (ours or a reused one) answers health checks.
+    pub ready: bool,
+    /// A deliberate stop is in progress, so the exit isn't a crash.
+    pub stopping: bool,
+    pub pid: Option<u32>,
+}
+
+/// Counters for the tray and `get_backend_stats`, since the app started.
+#[derive(Serialize, Clone, Copy, Default, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackendStats {
+    /// How long the backend process the shell owns has been running.
+    pub uptime_secs: Option<u64>,
+    /// Times a backend was spawned again after the first, whether by the
+    /// user or after a crash.
+    pub restarts: u32,
+    pub crashes: u32,
+    /// Milliseconds since the epoch.
+    pub last_crash_at: Option<u64>,
+}
+
+#[derive(Default)]
+struct Counters {
+    spawned_at: Option<Instant>,
+    spawns: u32,
+    crashes: u32,
+    last_crash_at: Option<u64>,
+}
+
+/// Handle to the manager thread, which alone owns the backend process.
+/// Everything else sends it messages and reads the published status.
+#[derive(Clone)]
+pub(crate) struct BackendManager {
+    tx: Sender<Message>,
+    status: Arc<Mutex<BackendStatus>>,
+    subscribers: Arc<Mutex<Vec<Sender<BackendStatus>>>>,
+    counters: Arc<Mutex<Counters>>,
+}
+
+impl BackendManager {
+    /// The handle plus the receiving end to pass to `start_manager` once an
+    /// AppHandle exists. Requests sent before then wait in the channel.
+    pub fn new() -> (Self, Receiver<Message>) {
+        let (tx, rx) = mpsc::channel();
+        let manager = BackendManager {
+            tx,
+            status: Arc::new(Mutex::new(BackendStatus::default())),
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            counters: Arc::new(Mutex::new(Counters::default())),
+        };
+        (manager, rx)
+    }
+
+    fn request<T>(&self, message: impl FnOnce(Sender<T>) -> Message) -> Option<T> {
+        let (reply, rx) = mpsc::channel();
+        self.tx.send(message(reply)).ok()?;
+        rx.recv().ok()
+    }
+
+    /// Start the backend unless one is already running or reachable. Errors
+    /// are failures to start it at all; a backend that starts and then exits
+    /// is reported through `backend-stopped`.
+    pub fn start(&self) -> Result<(), ShellError> {
+        self.request(Message::Start)
+            .unwrap_or_else(|| Err("Backend manager is not running".into()))
+    }
+
+    /// Stop the backend and wait for it to exit.
+    pub fn stop(&self) {
+        self.request(Message::Stop);
+    }
+
+    pub fn restart(&self) -> Result<(), ShellError> {
+        self.request(Message::Restart)
+            .unwrap_or_else(|| Err("Backend manager is not running".into()))
+    }
+
+    /// Show and focus the main window once the backend is next ready. For
+    /// starts the user asked for; send it before `start` or `restart`.
+    pub fn reveal_when_ready(&self) {
+        let _ = self.tx.send(Message::RevealWhenReady);
+    }
+
+    /// Write a control message line to the backend's stdin.
+    pub fn send_command(&self, line: String) -> Result<(), ShellError> {
+        self.request(|reply| Message::Send(line, reply))
+            .unwrap_or_else(|| Err("Backend manager is not running".into()))
+    }
+
+    pub fn status(&self) -> BackendStatus {
+        *self.status.lock().unwrap()
+    }
+
+    /// For the panic hook, which may run while the status is locked.
+    pub fn try_status(&self) -> Option<BackendStatus> {
+        self.status.try_lock().ok().map(|status| *status)
+    }
+
+    pub fn stats(&self) -> BackendStats {
+        let counters = self.counters.lock().unwrap();
+        BackendStats {
+            uptime_secs: counters.spawned_at.map(|at| at.elapsed().as_secs()),
+            restarts: counters.spawns.saturating_sub(1),
+            crashes: counters.crashes,
+            last_crash_at: counters.last_crash_at,
+        }
+    }
+
+    /// Receive every status change from now on.
+    pub fn subscribe(&self) -> Receiver<BackendStatus> {
+        let (tx, rx) = mpsc::channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+}
+
+/// What the manager thread needs from the app around it: launching and
+/// probing the backend, and telling the rest of the app what happened.
+/// `AppHandle` is the real one; tests drive the actor with a stand-in.
+pub(crate) trait Host: Send + 'static {
+    fn launch_backend(&self) -> Result<Launch, ShellError>;
+    /// Read the child's output, sending `Exited(generation)` once stdout closes.
+    fn forward_output(&self, child: &mut Child, tx: Sender<Message>, generation: u64);
+    fn backend_healthy(&self) -> bool;
+    fn settings(&self) -> settings::Settings;
+    fn announce(&self, event: &str, payload: serde_json::Value);
+    fn write_heartbeat(&self, status: &BackendStatus);
+    /// Startup telemetry, and the main window brought forward if `reveal`.
+    fn on_ready(&self, reveal: bool);
+    /// The process is gone, crashed or not.
+    fn on_exit(&self);
+    fn diagnose_exit(&self, status: Option<ExitStatus>) -> ShellError;
+    /// Record and notify a crash.
+    fn on_crash(&self);
+    fn on_crash_loop(&self);
+    fn stop_orphans(&self);
+    fn launch_tunnel(&self) -> Result<Option<Child>, String>;
+}
+
+impl Host for AppHandle {
+    fn launch_backend(&self) -> Result<Launch, ShellError> {
+        crate::launch_backend(self)
+    }
+
+    fn forward_output(&self, child: &mut Child, tx: Sender<Message>, generation: u64) {
+        forward_output(self, child, tx, generation);
+    }
+
+    fn backend_healthy(&self) -> bool {
+        crate::is_backend_healthy(&crate::backend_host(), crate::backend_port())
+    }
+
+    fn settings(&self) -> settings::Settings {
+        settings::load(&crate::user_data_dir(self))
+    }
+
+    fn announce(&self, event: &str, payload: serde_json::Value) {
+        let _ = self.emit(event, payload);
+    }
+
+    fn write_heartbeat(&self, status: &BackendStatus) {
+        heartbeat::write(self, status);
+    }
+
+    fn on_ready(&self, reveal: bool) {
+        telemetry::record_startup(self);
+        if reveal {
+            if let Some(window) = self.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+    }
+
+    fn on_exit(&self) {
+        // No backend, no agent run to stay awake for
+        self.state::<power::KeepAwake>().release();
+    }
+
+    fn diagnose_exit(&self, status: Option<ExitStatus>) -> ShellError {
+        classify_exit(self, status)
+    }
+
+    fn on_crash(&self) {
+        telemetry::record(self, "backend_crash", serde_json::json!({}));
+        let locale = crate::current_locale(self);
+        notifications::notify(
+            self,
+            NotificationCategory::BackendCrashes,
+            i18n::tr(locale, "notify.backend_crashed"),
+            i18n::tr(locale, "notify.backend_crashed_body"),
+            Some(("logs".to_string(), None)),
+        );
+    }
+
+    fn on_crash_loop(&self) {
+        show_crash_loop_dialog(self);
+    }
+
+    fn stop_orphans(&self) {
+        crate::stop_orphans(self);
+    }
+
+    fn launch_tunnel(&self) -> Result<Option<Child>, String> {
+        tunnel::launch(self, crate::backend_port())
+    }
+}
+
+struct Actor<H: Host> {
+    host: H,
+    manager: BackendManager,
+    child: Option<Child>,
+    /// Bumped per spawn so a late `Exited` from an old process is ignored.
+    generation: u64,
+    ready: bool,
+    stopping: bool,
+    /// Set while a fresh process hasn't answered a health check yet.
+    ready_deadline: Option<Instant>,
+    /// Following a remote backend rather than owning a process.
+    remote: bool,
+    spawned_at: Option<Instant>,
+    /// Recent crashes, to tell a crash loop from a one-off.
+    crashes: VecDeque<Instant>,
+    /// When the pending automatic restart is due.
+    restart_at: Option<Instant>,
+    /// The tunnel client, started once the backend is ready and kept across
+    /// its crashes so the tunnel URL stays the same.
+    tunnel: Option<Child>,
+    /// When the tunnel client is due to be started again after it exited.
+    tunnel_restart_at: Option<Instant>,
+    /// Show the main window at the next ready: the first one since the app
+    /// launched, or one the user asked for. Crash restarts and background
+    /// restarts come up without stealing focus.
+    reveal_on_ready: bool,
+}
+
+/// Run the manager thread. `rx` is the receiver from `BackendManager::new`.
+pub(crate) fn start_manager(app: AppHandle, manager: BackendManager, rx: Receiver<Message>) {
+    std::thread::spawn(move || {
+        let mut actor = Actor::new(app, manager);
+        loop {
+            let timeout = if actor.ready_deadline.is_some() || actor.restart_at.is_some() {
+                READY_POLL_INTERVAL
+            } else {
+                HEALTH_TICK_INTERVAL
+            };
+            let message = match rx.recv_timeout(timeout) {
+                Ok(message) => message,
+                Err(RecvTimeoutError::Timeout) => Message::HealthTick,
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+            actor.handle(message);
+        }
+    });
+}
+
+impl<H: Host> Actor<H> {
+    fn new(host: H, manager: BackendManager) -> Self {
+        Actor {
+            host,
+            manager,
+            child: None,
+            generation: 0,
+            ready: false,
+            stopping: false,
+            ready_deadline: None,
+            remote: false,
+            spawned_at: None,
+            crashes: VecDeque::new(),
+            restart_at: None,
+            tunnel: None,
+            tunnel_restart_at: None,
+            reveal_on_ready: true,
+        }
+    }
+
+    fn handle(&mut self, message: Message) {
+        match message {
+            Message::Start(reply) => {
+                self.forget_crashes();
+                let _ = reply.send(self.start());
+            }
+            Message::Stop(reply) => {
+                self.stop();
+                let _ = reply.send(());
+            }
+            Message::Restart(reply) => {
+                self.forget_crashes();
+                self.stop();
+                let _ = reply.send(self.start());
+            }
+            Message::Send(line, reply) => {
+                let _ = reply.send(self.send(&line));
+            }
+            Message::RevealWhenReady => self.reveal_on_ready = true,
+            Message::HealthTick => self.health_tick(),
+            Message::Exited(generation) if generation == self.generation => {
+                // Stdout can close before the process is gone; the next tick
+                // catches the exit then
+                if let Some(status) = self.exited() {
+                    self.on_exit(status);
+                }
+            }
+            Message::Exited(_) => {}
+        }
+        self.publish();
+    }
+
+    fn start(&mut self) -> Result<(), ShellError> {
+        if let Some(child) = self.child.as_mut() {
+            if let Ok(None) = child.try_wait() {
+                return Ok(());
+            }
+            self.child = None;
+        }
+
+        let launch = match self.host.launch_backend() {
+            Ok(launch) => launch,
+            Err(e) => {
+                self.host.announce("backend-failed", json(&e));
+                return Err(e);
+            }
+        };
+        match launch {
+            Launch::Spawned(mut child) => {
+                self.generation += 1;
+                self.spawned_at = Some(Instant::now());
+                {
+                    let mut counters = self.manager.counters.lock().unwrap();
+                    counters.spawned_at = self.spawned_at;
+                    counters.spawns += 1;
+                }
+                self.host
+                    .forward_output(&mut child, self.manager.tx.clone(), self.generation);
+                self.child = Some(child);
+                self.ready = false;
+                self.stopping = false;
+                self.ready_deadline = Some(Instant::now() + READY_TIMEOUT);
+            }
+            Launch::Reused => self.mark_ready(),
+            Launch::Skipped => {}
+            Launch::Remote => {
+                self.remote = true;
+                self.check_remote();
+                if !self.ready {
+                    eprintln!("Remote backend isn't reachable yet; waiting for it");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.stop_tunnel();
+        let was_ready = self.ready;
+        self.restart_at = None;
+        self.ready = false;
+        self.ready_deadline = None;
+        self.remote = false;
+        self.stopping = true;
+        if let Some(child) = self.child.as_mut() {
+            // SIGTERM for a graceful shutdown, SIGKILL if it doesn't come
+            let timeout = Duration::from_secs(self.host.settings().shutdown_timeout_secs);
+            let _ = process::runner().signal(child.id(), Signal::SIGTERM);
+            let started = Instant::now();
+            let status = loop {
+                match child.try_wait() {
+                    Ok(None) if started.elapsed() < timeout => {
+                        std::thread::sleep(Duration::from_millis(100))
+                    }
+                    Ok(None) => {
+                        let _ = child.kill();
+                        break child.wait().ok();
+                    }
+                    Ok(status) => break status,
+                    Err(_) => break None,
+                }
+            };
+            self.on_exit(status);
+        } else {
+            self.stopping = false;
+            if was_ready {
+                // A reused or remote backend we no longer count on
+                self.host
+                    .announce("backend-stopped", serde_json::Value::Null);
+            }
+        }
+        self.host.stop_orphans();
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), ShellError> {
+        let Some(stdin) = self.child.as_mut().and_then(|child| child.stdin.as_mut()) else {
+            return Err("The backend wasn't started by NanoClaw, so it can't take commands".into());
+        };
+        writeln!(stdin, "{}", line)
+            .and_then(|_| stdin.flush())
+            .map_err(|e| format!("Failed to write to the backend: {}", e).into())
+    }
+
+    /// The owned process's exit status once it has exited (None inside if
+    /// that can't be read); None while it runs or when there is no process.
+    fn exited(&mut self) -> Option<Option<ExitStatus>> {
+        match self.child.as_mut()?.try_wait() {
+            Ok(None) => None,
+            Ok(status) => Some(status),
+            Err(_) => Some(None),
+        }
+    }
+
+    fn health_tick(&mut self) {
+        self.check_tunnel();
+        if let Some(status) = self.exited() {
+            self.on_exit(status);
+            return;
+        }
+        if self.restart_at.is_some_and(|at| Instant::now() >= at) {
+            self.restart_at = None;
+            eprintln!("Restarting the backend after a crash");
+            if let Err(e) = self.start() {
+                eprintln!("{}", e);
+                self.schedule_restart();
+            }
+            return;
+        }
+        if self.remote {
+            self.check_remote();
+            return;
+        }
+        let Some(deadline) = self.ready_deadline else {
+            return;
+        };
+        if self.child.is_none() || Instant::now() >= deadline {
+            self.ready_deadline = None;
+        } else if self.host.backend_healthy() {
+            self.mark_ready();
+        }
+    }
+
+    /// Follow the remote backend's health: ready while it answers, stopped
+    /// when it goes away. Nothing to restart from here, so no crash handling.
+    fn check_remote(&mut self) {
+        let healthy = self.host.backend_healthy();
+        if healthy && !self.ready {
+            self.mark_ready();
+        } else if !healthy && self.ready {
+            eprintln!("Remote backend stopped answering health checks");
+            self.ready = false;
+            self.host
+                .announce("backend-stopped", serde_json::Value::Null);
+        }
+    }
+
+    fn mark_ready(&mut self) {
+        self.ready = true;
+        self.ready_deadline = None;
+        if !self.remote {
+            self.start_tunnel();
+        }
+        self.host.announce("backend-ready", serde_json::Value::Null);
+        self.host
+            .on_ready(std::mem::take(&mut self.reveal_on_ready));
+    }
+
+    /// The owned process is gone: clear it and tell everyone, as a crash
+    /// unless the shell was stopping it. A crash before the backend got going
+    /// is also diagnosed and reported as `backend-failed`.
+    fn on_exit(&mut self, status: Option<ExitStatus>) {
+        let failed_to_start = !self.ready
+            || self
+                .spawned_at
+                .is_some_and(|spawned| spawned.elapsed() < QUICK_EXIT);
+        self.child = None;
+        self.spawned_at = None;
+        self.ready = false;
+        self.ready_deadline = None;
+        let crashed = !self.stopping;
+        self.stopping = false;
+        {
+            let mut counters = self.manager.counters.lock().unwrap();
+            counters.spawned_at = None;
+            if crashed {
+                counters.crashes += 1;
+                counters.last_crash_at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .ok()
+                    .map(|d| d.as_millis() as u64);
+            }
+        }
+
+        self.host.on_exit();
+        self.host
+            .announce("backend-stopped", serde_json::Value::Null);
+        if crashed && failed_to_start {
+            let error = self.host.diagnose_exit(status);
+            eprintln!("Backend failed to start: {}", error);
+            self.host.announce("backend-failed", json(&error));
+        }
+        if crashed {
+            self.host.on_crash();
+            self.schedule_restart();
+        }
+    }
+
+    /// The user started or restarted the backend; earlier crashes no longer
+    /// count towards a crash loop.
+    fn forget_crashes(&mut self) {
+        self.crashes.clear();
+        self.restart_at = None;
+    }
+
+    /// Count a crash and restart after a backoff, unless auto-restart is off
+    /// or this makes a crash loop, which stops the retries and asks the user
+    /// to look into it.
+    fn schedule_restart(&mut self) {
+        let policy = self.host.settings().auto_restart;
+        if !policy.enabled {
+            return;
+        }
+        let now = Instant::now();
+        let window = Duration::from_secs(policy.window_secs);
+        self.crashes.retain(|at| now.duration_since(*at) < window);
+        self.crashes.push_back(now);
+        let crashes = self.crashes.len() as u32;
+
+        if crashes >= policy.max_crashes.max(1) {
+            eprintln!(
+                "Backend crashed {} times in {}s; not restarting it again",
+                crashes, policy.window_secs
+            );
+            self.forget_crashes();
+            self.stop_tunnel();
+            self.host.announce("backend-crash-loop", crashes.into());
+            self.host.on_crash_loop();
+            return;
+        }
+        let delay = RESTART_BACKOFF
+            .saturating_mul(2u32.saturating_pow(crashes - 1))
+            .min(MAX_RESTART_DELAY);
+        self.restart_at = Some(now + delay);
+    }
+
+    /// Start the configured tunnel client unless it already runs. Failing to
+    /// start it leaves the backend as it is.
+    fn start_tunnel(&mut self) {
+        self.tunnel_restart_at = None;
+        if self.tunnel.is_some() {
+            return;
+        }
+        match self.host.launch_tunnel() {
+            Ok(child) => self.tunnel = child,
+            Err(e) => {
+                eprintln!("Tunnel not started: {}", e);
+                self.host.announce("tunnel-failed", e.into());
+            }
+        }
+    }
+
+    fn stop_tunnel(&mut self) {
+        self.tunnel_restart_at = None;
+        if let Some(mut child) = self.tunnel.take() {
+            tunnel::stop(&mut child);
+            self.host.announce("tunnel-url", serde_json::Value::Null);
+        }
+    }
+
+    /// Start the tunnel client again a while after it exits on its own.
+    fn check_tunnel(&mut self) {
+        if let Some(child) = self.tunnel.as_mut() {
+            if let Ok(None) = child.try_wait() {
+                return;
+            }
+            eprintln!("Tunnel client exited; starting it again");
+            self.tunnel = None;
+            tunnel::forget_url();
+            self.host.announce("tunnel-url", serde_json::Value::Null);
+            self.tunnel_restart_at = Some(Instant::now() + MAX_RESTART_DELAY);
+        } else if self
+            .tunnel_restart_at
+            .is_some_and(|at| Instant::now() >= at)
+        {
+            self.start_tunnel();
+        }
+    }
+
+    /// Refresh the heartbeat and share the current status with readers and
+    /// subscribers, if it changed.
+    fn publish(&self) {
+        let status = BackendStatus {
+            running: self.child.is_some(),
+            ready: self.ready,
+            stopping: self.stopping,
+            pid: self.child.as_ref().map(|child| child.id()),
+        };
+        self.host.write_heartbeat(&status);
+        {
+            let mut current = self.manager.status.lock().unwrap();
+            if *current == status {
+                return;
+            }
+            *current = status;
+        }
+        self.manager
+            .subscribers
+            .lock()
+            .unwrap()
+            .retain(|subscriber| subscriber.send(status).is_ok());
+        self.host.announce("backend-status", json(&status));
+    }
+}
+
+fn json(value: &impl Serialize) -> serde_json::Value {
+    serde_json::to_value(value).unwrap_or_default()
+}
+
+/// Best guess at why a freshly started backend exited, from the end of its
+/// output and the state of `.env`.
+fn classify_exit(app: &AppHandle, status: Option<ExitStatus>) -> ShellError {
+    let snapshot = app.state::<logs::BackendLogs>().snapshot();
+    let lines: Vec<&str> = snapshot
+        .iter()
+        .skip(snapshot.len().saturating_sub(FAILURE_LOG_LINES))
+        .map(|line| line.line.as_str())
+        .collect();
+    classify(&lines, status, || {
+        crate::invalid_env_lines(&crate::user_data_dir(app))
+    })
+}
+
+/// `classify_exit` on the output lines; `invalid_env` is only consulted when
+/// the output doesn't explain the exit.
+fn classify(
+    lines: &[&str],
+    status: Option<ExitStatus>,
+    invalid_env: impl FnOnce() -> Vec<usize>,
+) -> ShellError {
+    let find = |needle: &str| lines.iter().find(|line| line.contains(needle));
+
+    if find("EADDRINUSE").is_some() {
+        return ShellError::PortConflict {
+            port: crate::backend_port(),
+        };
+    }
+    if let Some(line) = find("Cannot find module") {
+        // Error: Cannot find module '/path/to/module'
+        let module = line.split('\'').nth(1).unwrap_or_default();
+        if module.ends_with("dist/index.js") {
+            return ShellError::BackendNotBuilt {
+                path: module.into(),
+            };
+        }
+        return ShellError::ModuleMissing {
+            module: module.to_string(),
+        };
+    }
+    if find("Docker is required but not running").is_some() {
+        return ShellError::DockerUnavailable;
+    }
+    let invalid_env = invalid_env();
+    if !invalid_env.is_empty() {
+        return ShellError::EnvInvalid { lines: invalid_env };
+    }
+    ShellError::BackendExited {
+        code: status.and_then(|status| status.code()),
+        log: lines.join("\n"),
+    }
+}
+
+/// Tell the user restarts stopped, quoting the last output, with the way to
+/// the logs or a diagnostics summary.
+fn show_crash_loop_dialog(app: &AppHandle) {
+    let snapshot = app.state::<logs::BackendLogs>().snapshot();
+    let tail: Vec<&str> = snapshot
+        .iter()
+        .skip(snapshot.len().saturating_sub(CRASH_LOOP_LOG_LINES))
+        .map(|line| line.line.as_str())
+        .collect();
+    let tail = tail.join("\n");
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let locale = crate::current_locale(&app);
+        let t = |key| i18n::tr(locale, key);
+        let open_logs = t("crash_loop.open_logs");
+        let run_diagnostics = t("crash_loop.diagnostics");
+        let choice = app
+            .dialog()
+            .message(format!("{}\n\n{}", t("crash_loop.body"), tail))
+            .title(t("crash_loop.title"))
+            .kind(MessageDialogKind::Error)
+            .buttons(MessageDialogButtons::YesNoCancelCustom(
+                open_logs.to_string(),
+                run_diagnostics.to_string(),
+                t("crash_loop.close").to_string(),
+            ))
+            .blocking_show_with_result();
+        match choice {
+            MessageDialogResult::Yes => {}
+            MessageDialogResult::Custom(label) if label == open_logs => {}
+            MessageDialogResult::No => return show_diagnostics(&app, locale),
+            MessageDialogResult::Custom(label) if label == run_diagnostics => {
+                return show_diagnostics(&app, locale);
+            }
+            _ => return,
+        }
+        if let Err(e) = windows::open(&app, "logs") {
+            eprintln!("{}", e);
+        }
+    });
+}
+
+/// Failing setup checks, whatever holds the backend port and `.env`
+/// problems, shown in a dialog.
+fn show_diagnostics(app: &AppHandle, locale: i18n::Locale) {
+    let mut findings: Vec<String> = setup::refresh(app)
+        .checks()
+        .into_iter()
+        .filter(|(_, ok)| !ok)
+        .map(|(name, _)| format!("Setup check failed: {}", name))
+        .collect();
+    let port = crate::backend_port();
+    for owner in crate::port_owners(port, &crate::backend_dir(app)) {
+        findings.push(format!(
+            "Port {} is in use by pid {}: {}",
+            port, owner.pid, owner.command
+        ));
+    }
+    let invalid_env = crate::invalid_env_lines(&crate::user_data_dir(app));
+    if !invalid_env.is_empty() {
+        findings.push(ShellError::EnvInvalid { lines: invalid_env }.to_string());
+    }
+
+    let message = if findings.is_empty() {
+        i18n::tr(locale, "diagnostics.all_passed").to_string()
+    } else {
+        findings.join("\n")
+    };
+    app.dialog()
+        .message(message)
+        .title(i18n::tr(locale, "diagnostics.title"))
+        .blocking_show();
+}
+
+/// Record the process's output in the log buffer. The end of stdout is
+/// reported to the manager as a likely exit.
+fn forward_output(app: &AppHandle, child: &mut Child, tx: Sender<Message>, generation: u64) {
+    if let Some(stdout) = child.stdout.take() {
+        let app = app.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                logs::record(&app, "stdout", line);
+            }
+            let _ = tx.send(Message::Exited(generation));
+        });
+    }
+    if let Some(stderr) = child.stderr.take() {
+        let app = app.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                logs::record(&app, "stderr", line);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    use super::*;
+
+    fn exit_code(code: i32) -> Option<ExitStatus> {
+        Some(ExitStatus::from_raw(code << 8))
+    }
+
+    #[test]
+    fn port_in_use_is_a_conflict() {
+        let lines = ["Error: listen EADDRINUSE: address already in use :::3000"];
+        let error = classify(&lines, exit_code(1), Vec::new);
+        assert!(matches!(error, ShellError::PortConflict { .. }));
+    }
+
+    #[test]
+    fn missing_entry_point_means_not_built() {
+        let lines = ["Error: Cannot find module '/app/backend/dist/index.js'"];
+        match classify(&lines, exit_code(1), Vec::new) {
+            ShellError::BackendNotBuilt { path } => {
+                assert_eq!(path.to_string_lossy(), "/app/backend/dist/index.js")
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn other_missing_module_is_named() {
+        let lines = ["Error: Cannot find module 'better-sqlite3'"];
+        match classify(&lines, exit_code(1), Vec::new) {
+            ShellError::ModuleMissing { module } => assert_eq!(module, "better-sqlite3"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn docker_down_is_reported() {
+        let lines = ["FATAL: Docker is required but not running"];
+        let error = classify(&lines, exit_code(1), Vec::new);
+        assert!(matches!(error, ShellError::DockerUnavailable));
+    }
+
+    #[test]
+    fn invalid_env_only_checked_when_output_is_silent() {
+        let lines = ["Error: listen EADDRINUSE"];
+        let error = classify(&lines, exit_code(1), || panic!("env consulted"));
+        assert!(matches!(error, ShellError::PortConflict { .. }));
+
+        match classify(&[], exit_code(1), || vec![3, 7]) {
+            ShellError::EnvInvalid { lines } => assert_eq!(lines, vec![3, 7]),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unexplained_exit_quotes_the_output() {
+        let lines = ["starting", "boom"];
+        match classify(&lines, exit_code(2), Vec::new) {
+            ShellError::BackendExited { code, log } => {
+                assert_eq!(code, Some(2));
+                assert_eq!(log, "starting\nboom");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    /// A stand-in backend spawned through the runner dies on its own; its
+    /// real exit status is what gets classified.
+    #[test]
+    fn stand_in_backend_exit_is_classified() {
+        let _turn = process::fake::install();
+        process::fake::spawn_as(&["sh", "-c", "exit 3"]);
+
+        let mut child = process::runner()
+            .spawn(Command::new("node").arg("dist/index.js"))
+            .unwrap();
+        let status = child.wait().ok();
+
+        assert_eq!(
+            process::fake::commands(),
+            vec![vec!["node", "dist/index.js"]]
+        );
+        match classify(&[], status, Vec::new) {
+            ShellError::BackendExited { code, .. } => assert_eq!(code, Some(3)),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    type Events = Arc<Mutex<Vec<String>>>;
+
+    /// Launches the backend through the runner, so `fake::spawn_as` decides
+    /// what runs, and records what the actor announces.
+    struct FakeHost {
+        events: Events,
+    }
+
+    impl Host for FakeHost {
+        fn launch_backend(&self) -> Result<Launch, ShellError> {
+            let child = process::runner()
+                .spawn(Command::new("node").arg("dist/index.js"))
+                .map_err(|e| ShellError::from(e.to_string()))?;
+            Ok(Launch::Spawned(child))
+        }
+
+        fn forward_output(&self, _child: &mut Child, _tx: Sender<Message>, _generation: u64) {}
+
+        fn backend_healthy(&self) -> bool {
+            false
+        }
+
+        fn settings(&self) -> settings::Settings {
+            let mut settings = settings::Settings::default();
+            settings.shutdown_timeout_secs = 0;
+            settings.auto_restart = settings::AutoRestart {
+                enabled: true,
+                max_crashes: 3,
+                window_secs: 60,
+            };
+            settings
+        }
+
+        fn announce(&self, event: &str, _payload: serde_json::Value) {
+            self.events.lock().unwrap().push(event.to_string());
+        }
+
+        fn write_heartbeat(&self, _status: &BackendStatus) {}
+        fn on_ready(&self, _reveal: bool) {}
+        fn on_exit(&self) {}
+
+        fn diagnose_exit(&self, status: Option<ExitStatus>) -> ShellError {
+            classify(&[], status, Vec::new)
+        }
+
+        fn on_crash(&self) {}
+        fn on_crash_loop(&self) {}
+        fn stop_orphans(&self) {}
+
+        fn launch_tunnel(&self) -> Result<Option<Child>, String> {
+            Ok(None)
+        }
+    }
+
+    fn actor() -> (Actor<FakeHost>, Events) {
+        let events = Events::default();
+        let host = FakeHost {
+            events: events.clone(),
+        };
+        (Actor::new(host, BackendManager::new().0), events)
+    }
+
+    fn start(actor: &mut Actor<FakeHost>) -> Result<(), ShellError> {
+        let (reply, rx) = mpsc::channel();
+        actor.handle(Message::Start(reply));
+        rx.recv().unwrap()
+    }
+
+    /// Report stdout closing for the current spawn until the actor sees the
+    /// stand-in has exited.
+    fn exit_current(actor: &mut Actor<FakeHost>) {
+        let started = Instant::now();
+        while actor.child.is_some() {
+            assert!(
+                started.elapsed() < Duration::from_secs(5),
+                "stand-in still running"
+            );
+            actor.handle(Message::Exited(actor.generation));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn restart_delay(actor: &Actor<FakeHost>) -> Duration {
+        actor.restart_at.unwrap() - Instant::now()
+    }
+
+    #[test]
+    fn crashes_back_off_then_stop_as_a_crash_loop() {
+        let _turn = process::fake::install();
+        process::fake::spawn_as(&["sh", "-c", "exit 1"]);
+        let (mut actor, events) = actor();
+
+        start(&mut actor).unwrap();
+        exit_current(&mut actor);
+        let delay = restart_delay(&actor);
+        assert!(delay > Duration::from_secs(1) && delay <= RESTART_BACKOFF);
+
+        // The restart is due: the next tick starts it again
+        actor.restart_at = Some(Instant::now());
+        actor.handle(Message::HealthTick);
+        assert_eq!(actor.generation, 2);
+        exit_current(&mut actor);
+        let delay = restart_delay(&actor);
+        assert!(delay > RESTART_BACKOFF && delay <= RESTART_BACKOFF * 2);
+
+        actor.restart_at = Some(Instant::now());
+        actor.handle(Message::HealthTick);
+        exit_current(&mut actor);
+        assert_eq!(actor.restart_at, None);
+        assert!(actor.crashes.is_empty());
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.iter().filter(|e| *e == "backend-failed").count(), 3);
+        assert_eq!(
+            events.iter().filter(|e| *e == "backend-crash-loop").count(),
+            1
+        );
+        assert_eq!(actor.manager.counters.lock().unwrap().crashes, 3);
+    }
+
+    #[test]
+    fn exit_of_an_earlier_spawn_is_ignored() {
+        let _turn = process::fake::install();
+        process::fake::spawn_as(&["sleep", "30"]);
+        let (mut actor, events) = actor();
+
+        start(&mut actor).unwrap();
+        let (reply, rx) = mpsc::channel();
+        actor.handle(Message::Restart(reply));
+        rx.recv().unwrap().unwrap();
+        assert_eq!(actor.generation, 2);
+        events.lock().unwrap().clear();
+
+        // The first process's stdout closing says nothing about the second
+        actor.handle(Message::Exited(1));
+        // Nor does stdout closing while the process is still running
+        actor.handle(Message::Exited(2));
+        assert!(actor.child.is_some());
+        assert!(events.lock().unwrap().is_empty());
+        assert_eq!(actor.manager.counters.lock().unwrap().crashes, 0);
+
+        let (reply, rx) = mpsc::channel();
+        actor.handle(Message::Stop(reply));
+        rx.recv().unwrap();
+    }
+
+    #[test]
+    fn restart_replaces_the_process_without_counting_a_crash() {
+        let _turn = process::fake::install();
+        process::fake::spawn_as(&["sleep", "30"]);
+        let (mut actor, events) = actor();
+
+        start(&mut actor).unwrap();
+        let first = actor.child.as_ref().unwrap().id();
+        let (reply, rx) = mpsc::channel();
+        actor.handle(Message::Restart(reply));
+        rx.recv().unwrap().unwrap();
+
+        assert_eq!(process::fake::signals(), vec![(first, Signal::SIGTERM)]);
+        assert_ne!(actor.child.as_ref().unwrap().id(), first);
+        assert_eq!(actor.generation, 2);
+        assert_eq!(actor.restart_at, None);
+        assert_eq!(actor.manager.counters.lock().unwrap().crashes, 0);
+        assert_eq!(actor.manager.counters.lock().unwrap().spawns, 2);
+        {
+            let events = events.lock().unwrap();
+            assert!(events.contains(&"backend-stopped".to_string()));
+            assert!(!events.contains(&"backend-failed".to_string()));
+        }
+        assert_eq!(
+            *actor.manager.status.lock().unwrap(),
+            BackendStatus {
+                running: true,
+                ready: false,
+                stopping: false,
+                pid: actor.child.as_ref().map(|child| child.id()),
+            }
+        );
+
+        let (reply, rx) = mpsc::channel();
+        actor.handle(Message::Stop(reply));
+        rx.recv().unwrap();
+        assert!(actor.child.is_none());
+    }
+}
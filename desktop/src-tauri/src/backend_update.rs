@@ -0,0 +1,203 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::settings::UpdateChannel;
+use crate::{http, process};
+
+/// Manifest describing the latest backend bundle on a channel:
+/// `{ "version": "1.2.3", "url": "...tar.gz", "signatureUrl": "...sig" }`
+fn feed_url(channel: UpdateChannel) -> &'static str {
+    match channel {
+        UpdateChannel::Stable => {
+            "https://github.com/Aixtrade/nanoclaw/releases/latest/download/backend.json"
+        }
+        UpdateChannel::Beta => {
+            "https://github.com/Aixtrade/nanoclaw/releases/download/beta/backend.json"
+        }
+    }
+}
+
+/// PEM public key used to verify bundle signatures (`openssl dgst -sha256 -sign`).
+/// Hot updates are disabled in builds without it.
+const BACKEND_UPDATE_PUBKEY: Option<&str> = option_env!("NANOCLAW_BACKEND_PUBKEY");
+
+const BUNDLES_DIR: &str = "backend-bundles";
+const CURRENT_MARKER: &str = "current";
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BackendManifest {
+    version: String,
+    url: String,
+    signature_url: String,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackendUpdateInfo {
+    pub current_version: String,
+    pub latest_version: String,
+    pub available: bool,
+}
+
+fn bundles_root(data_dir: &Path) -> PathBuf {
+    data_dir.join(BUNDLES_DIR)
+}
+
+/// Directory of the active hot-updated backend, if one is installed and intact.
+/// Layout: `<data>/backend-bundles/<version>/dist/index.js`, with `current`
+/// holding the active version.
+pub(crate) fn active_bundle(data_dir: &Path) -> Option<(String, PathBuf)> {
+    let root = bundles_root(data_dir);
+    let version = std::fs::read_to_string(root.join(CURRENT_MARKER)).ok()?;
+    let version = version.trim().to_string();
+    if version.is_empty() {
+        return None;
+    }
+    let dir = root.join(&version);
+    dir.join("dist/index.js").exists().then_some((version, dir))
+}
+
+/// Compare dotted numeric versions ("1.10.0" > "1.9.3"); pre-release suffixes are ignored.
+pub(crate) fn is_newer(candidate: &str, current: &str) -> bool {
+    let parse = |v: &str| -> Vec<u64> {
+        v.trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map_while(|part| part.parse().ok())
+            .collect()
+    };
+    parse(candidate) > parse(current)
+}
+
+fn fetch_manifest(channel: UpdateChannel) -> Result<BackendManifest, String> {
+    let body = http::curl_get(feed_url(channel), Duration::from_secs(15))?;
+    serde_json::from_slice(&body).map_err(|e| format!("Invalid backend update manifest: {}", e))
+}
+
+pub(crate) fn check(
+    data_dir: &Path,
+    bundled_version: &str,
+    channel: UpdateChannel,
+) -> Result<BackendUpdateInfo, String> {
+    let current_version = active_bundle(data_dir)
+        .map(|(version, _)| version)
+        .unwrap_or_else(|| bundled_version.to_string());
+    let manifest = fetch_manifest(channel)?;
+    Ok(BackendUpdateInfo {
+        available: is_newer(&manifest.version, &current_version),
+        current_version,
+        latest_version: manifest.version,
+    })
+}
+
+fn verify_signature(file: &Path, signature: &Path, staging: &Path) -> Result<(), String> {
+    let pubkey = BACKEND_UPDATE_PUBKEY
+        .ok_or_else(|| "Backend hot updates are not enabled in this build".to_string())?;
+    let pubkey_path = staging.join("pubkey.pem");
+    std::fs::write(&pubkey_path, pubkey)
+        .map_err(|e| format!("Failed to write public key: {}", e))?;
+
+    let output = process::runner()
+        .output(
+            Command::new("openssl")
+                .args(["dgst", "-sha256", "-verify"])
+                .arg(&pubkey_path)
+                .arg("-signature")
+                .arg(signature)
+                .arg(file),
+        )
+        .map_err(|e| format!("Failed to run openssl: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err("Backend bundle signature verification failed".to_string())
+    }
+}
+
+/// Download, verify, and unpack the latest backend bundle without activating it.
+/// Returns the installed version. The app-bundled `node_modules` is linked in,
+/// since module resolution is relative to `dist/index.js`.
+pub(crate) fn download(
+    data_dir: &Path,
+    app_bundle_dir: &Path,
+    channel: UpdateChannel,
+) -> Result<String, String> {
+    let manifest = fetch_manifest(channel)?;
+    if manifest.version.is_empty()
+        || manifest
+            .version
+            .contains(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+')))
+    {
+        return Err(format!("Invalid backend version: {}", manifest.version));
+    }
+
+    let root = bundles_root(data_dir);
+    let staging = root.join(format!(".staging-{}", manifest.version));
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::create_dir_all(&staging)
+        .map_err(|e| format!("Failed to create staging dir: {}", e))?;
+
+    let result = (|| {
+        let archive = staging.join("bundle.tar.gz");
+        let signature = staging.join("bundle.sig");
+        http::curl_download(&manifest.url, &archive, DOWNLOAD_TIMEOUT)?;
+        http::curl_download(&manifest.signature_url, &signature, DOWNLOAD_TIMEOUT)?;
+        verify_signature(&archive, &signature, &staging)?;
+
+        let unpacked = staging.join("bundle");
+        std::fs::create_dir_all(&unpacked)
+            .map_err(|e| format!("Failed to create bundle dir: {}", e))?;
+        let status = process::runner()
+            .output(
+                Command::new("tar")
+                    .arg("-xzf")
+                    .arg(&archive)
+                    .arg("-C")
+                    .arg(&unpacked),
+            )
+            .map(|output| output.status)
+            .map_err(|e| format!("Failed to run tar: {}", e))?;
+        if !status.success() {
+            return Err("Failed to unpack backend bundle".to_string());
+        }
+        if !unpacked.join("dist/index.js").exists() {
+            return Err("Backend bundle is missing dist/index.js".to_string());
+        }
+
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(
+            app_bundle_dir.join("node_modules"),
+            unpacked.join("node_modules"),
+        )
+        .map_err(|e| format!("Failed to link node_modules: {}", e))?;
+
+        let dest = root.join(&manifest.version);
+        let _ = std::fs::remove_dir_all(&dest);
+        std::fs::rename(&unpacked, &dest)
+            .map_err(|e| format!("Failed to install backend bundle: {}", e))
+    })();
+
+    let _ = std::fs::remove_dir_all(&staging);
+    result.map(|_| manifest.version)
+}
+
+/// Make `version` the active backend. Pass None to fall back to the app-bundled copy.
+pub(crate) fn activate(data_dir: &Path, version: Option<&str>) -> Result<(), String> {
+    let marker = bundles_root(data_dir).join(CURRENT_MARKER);
+    match version {
+        Some(version) => std::fs::write(&marker, version)
+            .map_err(|e| format!("Failed to activate backend bundle: {}", e)),
+        None => match std::fs::remove_file(&marker) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(format!("Failed to revert backend bundle: {}", e))
+            }
+            _ => Ok(()),
+        },
+    }
+}
@@ -0,0 +1,382 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use serde::Serialize;
+use specta::Type;
+
+use crate::process;
+use crate::tasks::TaskHandle;
+
+/// What a backup holds, relative to the data dir. Everything else (logs,
+/// backend bundles, caches) can be recreated.
+const BACKUP_ENTRIES: &[&str] = &["store", "data", "groups", ".env"];
+pub(crate) const BACKUPS_DIR: &str = "backups";
+const BACKUP_PREFIX: &str = "nanoclaw-backup-";
+const STAGING_DIR: &str = ".restore-staging";
+const PREVIOUS_DIR: &str = ".restore-previous";
+/// `openssl enc` output starts with this when a salt is used.
+const ENCRYPTED_MAGIC: &[u8] = b"Salted__";
+/// The password reaches openssl through the environment, never argv.
+const PASSWORD_ENV: &str = "NANOCLAW_BACKUP_PASSWORD";
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackupInfo {
+    pub path: PathBuf,
+    pub size: u64,
+    pub encrypted: bool,
+}
+
+pub(crate) fn timestamp() -> String {
+    process::runner()
+        .output(Command::new("date").arg("+%Y%m%d-%H%M%S"))
+        .ok()
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs().to_string())
+                .unwrap_or_default()
+        })
+}
+
+/// Default location for a new backup: `<data>/backups/nanoclaw-backup-<time>.zip`.
+pub(crate) fn default_backup_path(data_dir: &Path, encrypted: bool) -> PathBuf {
+    let ext = if encrypted { "zip.enc" } else { "zip" };
+    data_dir
+        .join(BACKUPS_DIR)
+        .join(format!("{}{}.{}", BACKUP_PREFIX, timestamp(), ext))
+}
+
+fn run_openssl(args: &[&str], input: &Path, output: &Path, password: &str) -> Result<(), String> {
+    let result = process::runner()
+        .output(
+            Command::new("openssl")
+                .args(args)
+                .args(["-pass", &format!("env:{}", PASSWORD_ENV), "-in"])
+                .arg(input)
+                .arg("-out")
+                .arg(output)
+                .env(PASSWORD_ENV, password),
+        )
+        .map_err(|e| format!("Failed to run openssl: {}", e))?;
+    if result.status.success() {
+        Ok(())
+    } else {
+        let _ = std::fs::remove_file(output);
+        Err(format!(
+            "openssl failed: {}",
+            String::from_utf8_lossy(&result.stderr).trim()
+        ))
+    }
+}
+
+/// Zip the user's data into `dest`, encrypting it with `password` if given.
+pub(crate) fn create(
+    data_dir: &Path,
+    dest: &Path,
+    password: Option<&str>,
+    task: &TaskHandle,
+) -> Result<BackupInfo, String> {
+    let entries: Vec<&str> = BACKUP_ENTRIES
+        .iter()
+        .copied()
+        .filter(|entry| data_dir.join(entry).exists())
+        .collect();
+    if entries.is_empty() {
+        return Err("Nothing to back up yet".to_string());
+    }
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let zip_path = match password {
+        Some(_) => dest.with_extension("partial.zip"),
+        None => dest.to_path_buf(),
+    };
+    let _ = std::fs::remove_file(&zip_path);
+
+    let result = (|| {
+        // One zip invocation per entry, so progress can be reported between them
+        for (i, entry) in entries.iter().enumerate() {
+            if task.is_cancelled() {
+                return Err("Backup cancelled".to_string());
+            }
+            task.progress(
+                Some(i as f64 / (entries.len() + 1) as f64),
+                format!("Archiving {}", entry),
+            );
+            let output = process::runner()
+                .output(
+                    Command::new("zip")
+                        .args(["-r", "-q", "-y"])
+                        .arg(&zip_path)
+                        .arg(entry)
+                        .current_dir(data_dir),
+                )
+                .map_err(|e| format!("Failed to run zip: {}", e))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "Failed to archive {}: {}",
+                    entry,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+        }
+
+        if let Some(password) = password {
+            task.progress(None, "Encrypting backup");
+            run_openssl(
+                &["enc", "-aes-256-cbc", "-pbkdf2", "-salt"],
+                &zip_path,
+                dest,
+                password,
+            )?;
+            let _ = std::fs::remove_file(&zip_path);
+        }
+        Ok(())
+    })();
+
+    if let Err(e) = result {
+        let _ = std::fs::remove_file(&zip_path);
+        let _ = std::fs::remove_file(dest);
+        return Err(e);
+    }
+
+    let size = std::fs::metadata(dest).map(|m| m.len()).unwrap_or(0);
+    Ok(BackupInfo {
+        path: dest.to_path_buf(),
+        size,
+        encrypted: password.is_some(),
+    })
+}
+
+/// Backups in the data dir's backups folder, newest first.
+pub(crate) fn list(data_dir: &Path) -> Vec<BackupInfo> {
+    let Ok(entries) = std::fs::read_dir(data_dir.join(BACKUPS_DIR)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<(SystemTime, BackupInfo)> = entries
+        .flatten()
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with(BACKUP_PREFIX) && !name.contains(".partial.")
+        })
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            let path = entry.path();
+            Some((
+                meta.modified().ok()?,
+                BackupInfo {
+                    encrypted: is_encrypted(&path).unwrap_or(false),
+                    path,
+                    size: meta.len(),
+                },
+            ))
+        })
+        .collect();
+    backups.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+    backups.into_iter().map(|(_, info)| info).collect()
+}
+
+/// When the newest backup in the backups folder was written.
+pub(crate) fn last_backup_time(data_dir: &Path) -> Option<SystemTime> {
+    let newest = list(data_dir).into_iter().next()?;
+    std::fs::metadata(newest.path).ok()?.modified().ok()
+}
+
+/// Delete all but the `keep` newest backups in the backups folder.
+pub(crate) fn prune(data_dir: &Path, keep: usize) {
+    for old in list(data_dir).into_iter().skip(keep) {
+        if let Err(e) = std::fs::remove_file(&old.path) {
+            eprintln!("Failed to remove old backup {}: {}", old.path.display(), e);
+        }
+    }
+}
+
+fn is_encrypted(archive: &Path) -> Result<bool, String> {
+    use std::io::Read;
+    let mut magic = [0u8; 8];
+    let mut file = std::fs::File::open(archive)
+        .map_err(|e| format!("Failed to open {}: {}", archive.display(), e))?;
+    Ok(file.read_exact(&mut magic).is_ok() && magic == ENCRYPTED_MAGIC)
+}
+
+/// Replace the data dir's entries with the ones `archive` holds; entries it
+/// lacks are left alone. The backend must be stopped. Current data is kept aside until the swap succeeds, and put
+/// back if it doesn't.
+pub(crate) fn restore(
+    data_dir: &Path,
+    archive: &Path,
+    password: Option<&str>,
+    task: &TaskHandle,
+) -> Result<(), String> {
+    let staging = data_dir.join(STAGING_DIR);
+    let previous = data_dir.join(PREVIOUS_DIR);
+    let _ = std::fs::remove_dir_all(&staging);
+    let _ = std::fs::remove_dir_all(&previous);
+    std::fs::create_dir_all(&staging)
+        .map_err(|e| format!("Failed to create staging dir: {}", e))?;
+
+    let result = (|| {
+        let zip_path = if is_encrypted(archive)? {
+            let password = password
+                .ok_or_else(|| "This backup is encrypted; a password is required".to_string())?;
+            task.progress(Some(0.1), "Decrypting backup");
+            let decrypted = staging.join("backup.zip");
+            run_openssl(
+                &["enc", "-d", "-aes-256-cbc", "-pbkdf2"],
+                archive,
+                &decrypted,
+                password,
+            )
+            .map_err(|_| "Wrong password or corrupted backup".to_string())?;
+            decrypted
+        } else {
+            archive.to_path_buf()
+        };
+
+        task.progress(Some(0.3), "Extracting backup");
+        let unpacked = staging.join("contents");
+        let output = process::runner()
+            .output(
+                Command::new("unzip")
+                    .args(["-q", "-o"])
+                    .arg(&zip_path)
+                    .arg("-d")
+                    .arg(&unpacked),
+            )
+            .map_err(|e| format!("Failed to run unzip: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Failed to extract backup: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        let restored: Vec<&str> = BACKUP_ENTRIES
+            .iter()
+            .copied()
+            .filter(|entry| unpacked.join(entry).exists())
+            .collect();
+        if restored.is_empty() {
+            return Err("This file doesn't look like a NanoClaw backup".to_string());
+        }
+
+        task.progress(Some(0.7), "Replacing data");
+        swap_in(data_dir, &unpacked, &previous, &restored)
+    })();
+
+    let _ = std::fs::remove_dir_all(&staging);
+    if result.is_ok() {
+        let _ = std::fs::remove_dir_all(&previous);
+    }
+    result
+}
+
+/// Move the `restored` entries of `unpacked` into the data dir, keeping the
+/// current ones aside in `previous`. Entries the archive doesn't have stay
+/// as they are: a backup made before `.env` existed mustn't take the user's
+/// `.env` with it. On failure everything is put back.
+fn swap_in(
+    data_dir: &Path,
+    unpacked: &Path,
+    previous: &Path,
+    restored: &[&str],
+) -> Result<(), String> {
+    std::fs::create_dir_all(previous)
+        .map_err(|e| format!("Failed to create {}: {}", previous.display(), e))?;
+    let mut moved: Vec<&str> = Vec::new();
+    let mut placed: Vec<&str> = Vec::new();
+    let swap = (|| {
+        for entry in restored {
+            if data_dir.join(entry).exists() {
+                std::fs::rename(data_dir.join(entry), previous.join(entry))
+                    .map_err(|e| format!("Failed to move aside {}: {}", entry, e))?;
+                moved.push(entry);
+            }
+        }
+        for entry in restored {
+            std::fs::rename(unpacked.join(entry), data_dir.join(entry))
+                .map_err(|e| format!("Failed to restore {}: {}", entry, e))?;
+            placed.push(entry);
+        }
+        Ok(())
+    })();
+    if swap.is_err() {
+        for entry in placed {
+            let _ = remove_path(&data_dir.join(entry));
+        }
+        for entry in moved {
+            let _ = std::fs::rename(previous.join(entry), data_dir.join(entry));
+        }
+    }
+    swap
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nanoclaw-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn partial_archive_keeps_entries_it_lacks() {
+        let root = scratch("restore-partial");
+        let data = root.join("data-dir");
+        let unpacked = root.join("contents");
+        std::fs::create_dir_all(data.join("store")).unwrap();
+        std::fs::create_dir_all(data.join("groups/main")).unwrap();
+        std::fs::write(data.join("store/messages.db"), "current").unwrap();
+        std::fs::write(data.join(".env"), "ANTHROPIC_API_KEY=sk-current\n").unwrap();
+        // A backup made before the user had a .env or any groups
+        std::fs::create_dir_all(unpacked.join("store")).unwrap();
+        std::fs::write(unpacked.join("store/messages.db"), "backup").unwrap();
+
+        swap_in(&data, &unpacked, &root.join("previous"), &["store"]).unwrap();
+
+        let read = |path: &str| std::fs::read_to_string(data.join(path)).unwrap();
+        assert_eq!(read("store/messages.db"), "backup");
+        assert_eq!(read(".env"), "ANTHROPIC_API_KEY=sk-current\n");
+        assert!(data.join("groups/main").is_dir());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn failed_swap_puts_current_data_back() {
+        let root = scratch("restore-failed");
+        let data = root.join("data-dir");
+        let unpacked = root.join("contents");
+        std::fs::create_dir_all(data.join("store")).unwrap();
+        std::fs::write(data.join(".env"), "current").unwrap();
+        std::fs::create_dir_all(unpacked.join("store")).unwrap();
+        // The archive claims a .env it doesn't have, so placing it fails
+
+        let result = swap_in(&data, &unpacked, &root.join("previous"), &["store", ".env"]);
+
+        assert!(result.is_err());
+        assert!(data.join("store").is_dir());
+        assert_eq!(
+            std::fs::read_to_string(data.join(".env")).unwrap(),
+            "current"
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,157 @@
+//! Command-line control of a running NanoClaw app over its control socket.
+
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: nanoclawctl [options] <command>
+
+Commands:
+  status                     Show whether the backend is running and ready
+  restart                    Restart the backend
+  open [window]              Show the main window, or settings, logs, ...
+  send <group-id> <prompt>   Send a prompt to a conversation
+
+Options:
+  --data-dir <path>   The app's data directory (overrides NANOCLAW_DATA_DIR)
+  --profile <name>    The named profile's data
+  -h, --help          Show this help";
+
+const IDENTIFIER: &str = "com.nanoclaw.desktop";
+const CONTROL_SOCKET_FILE: &str = "control.sock";
+/// Written by the app when the data dir was moved; lives in its config dir.
+const OVERRIDE_FILE: &str = "data-dir";
+
+fn home() -> PathBuf {
+    std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .unwrap_or_default()
+}
+
+/// Where the app keeps config and (unless moved) data, as Tauri resolves it.
+fn app_dirs() -> (PathBuf, PathBuf) {
+    if cfg!(target_os = "macos") {
+        let dir = home().join("Library/Application Support").join(IDENTIFIER);
+        (dir.clone(), dir)
+    } else {
+        let xdg = |var: &str, fallback: &str| {
+            std::env::var_os(var)
+                .map(PathBuf::from)
+                .unwrap_or_else(|| home().join(fallback))
+        };
+        (
+            xdg("XDG_CONFIG_HOME", ".config").join(IDENTIFIER),
+            xdg("XDG_DATA_HOME", ".local/share").join(IDENTIFIER),
+        )
+    }
+}
+
+fn data_dir(explicit: Option<PathBuf>, profile: Option<String>) -> PathBuf {
+    let base = explicit
+        .or_else(|| std::env::var_os("NANOCLAW_DATA_DIR").map(PathBuf::from))
+        .unwrap_or_else(|| {
+            let (config, data) = app_dirs();
+            std::fs::read_to_string(config.join(OVERRIDE_FILE))
+                .ok()
+                .map(|path| PathBuf::from(path.trim()))
+                .filter(|path| path.is_dir())
+                .unwrap_or(data)
+        });
+    match profile {
+        Some(profile) => base.join("profiles").join(profile),
+        None => base,
+    }
+}
+
+fn parse(args: Vec<String>) -> Result<(PathBuf, serde_json::Value), String> {
+    let mut explicit = None;
+    let mut profile = None;
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--data-dir" => {
+                explicit = Some(PathBuf::from(
+                    args.next().ok_or("--data-dir needs a value")?,
+                ))
+            }
+            "--profile" => profile = Some(args.next().ok_or("--profile needs a value")?),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ => rest.push(arg),
+        }
+    }
+
+    let request = match rest.first().map(String::as_str) {
+        Some("status") => serde_json::json!({ "op": "status" }),
+        Some("restart") => serde_json::json!({ "op": "restart-backend" }),
+        Some("open") => serde_json::json!({ "op": "open-window", "label": rest.get(1) }),
+        Some("send") if rest.len() >= 3 => serde_json::json!({
+            "op": "send-prompt",
+            "groupId": rest[1],
+            "prompt": rest[2..].join(" "),
+        }),
+        Some(command) => return Err(format!("Unknown command or missing arguments: {}", command)),
+        None => return Err("No command given".to_string()),
+    };
+    Ok((data_dir(explicit, profile), request))
+}
+
+#[cfg(unix)]
+fn call(
+    data_dir: &std::path::Path,
+    request: &serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    use std::os::unix::net::UnixStream;
+
+    let path = data_dir.join(CONTROL_SOCKET_FILE);
+    let mut stream = UnixStream::connect(&path)
+        .map_err(|e| format!("NanoClaw isn't running ({}: {})", path.display(), e))?;
+    writeln!(stream, "{}", request).map_err(|e| format!("Failed to send request: {}", e))?;
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    let response: serde_json::Value =
+        serde_json::from_str(&line).map_err(|e| format!("Invalid response: {}", e))?;
+    if response["ok"].as_bool() == Some(true) {
+        Ok(response["result"].clone())
+    } else {
+        Err(response["error"]
+            .as_str()
+            .unwrap_or("Request failed")
+            .to_string())
+    }
+}
+
+#[cfg(not(unix))]
+fn call(
+    _data_dir: &std::path::Path,
+    _request: &serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    Err("The control socket is only available on macOS and Linux".to_string())
+}
+
+fn main() -> ExitCode {
+    let result = parse(std::env::args().skip(1).collect())
+        .and_then(|(data_dir, request)| call(&data_dir, &request));
+    match result {
+        Ok(serde_json::Value::Null) => ExitCode::SUCCESS,
+        Ok(result) => {
+            let mut out = std::io::stdout();
+            let _ = writeln!(
+                out,
+                "{}",
+                serde_json::to_string_pretty(&result).unwrap_or_default()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("nanoclawctl: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+use serde::Serialize;
+use specta::Type;
+
+/// The extra CA certificates appended to the system roots, for curl.
+const COMBINED_BUNDLE_FILE: &str = "ca-bundle.pem";
+/// The roots curl trusts by default on macOS.
+const SYSTEM_ROOTS: &str = "/etc/ssl/cert.pem";
+const PEM_BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+const PEM_END: &str = "-----END CERTIFICATE-----";
+
+/// What the shell's own HTTPS requests pass to curl as `--cacert`.
+static CURL_BUNDLE: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CaBundleInfo {
+    pub path: PathBuf,
+    /// One per certificate in the file.
+    pub subjects: Vec<String>,
+}
+
+fn subject(pem: &str) -> Result<String, String> {
+    let output = crate::process::output_with_stdin(
+        Command::new("openssl").args(["x509", "-noout", "-subject"]),
+        pem.as_bytes(),
+    )
+    .map_err(|e| format!("Failed to run openssl: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let text = text.trim();
+    Ok(text
+        .strip_prefix("subject=")
+        .unwrap_or(text)
+        .trim()
+        .to_string())
+}
+
+/// Check that `path` is a PEM file of certificates that all parse.
+pub(crate) fn inspect(path: &Path) -> Result<CaBundleInfo, String> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let mut subjects = Vec::new();
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find(PEM_BEGIN) {
+        let end = rest[start..]
+            .find(PEM_END)
+            .map(|end| start + end + PEM_END.len())
+            .ok_or_else(|| format!("{} has an unterminated certificate", path.display()))?;
+        let pem = format!("{}\n", &rest[start..end]);
+        let subject = subject(&pem).map_err(|e| {
+            format!(
+                "Certificate {} in {} doesn't parse: {}",
+                subjects.len() + 1,
+                path.display(),
+                e
+            )
+        })?;
+        subjects.push(subject);
+        rest = &rest[end..];
+    }
+    if subjects.is_empty() {
+        return Err(format!("{} contains no PEM certificates", path.display()));
+    }
+    Ok(CaBundleInfo {
+        path: path.to_path_buf(),
+        subjects,
+    })
+}
+
+/// Point the shell's curl requests at the system roots plus `extra`, or
+/// back at the defaults. The combined file lives in the data dir, since
+/// `--cacert` replaces the roots rather than adding to them.
+pub(crate) fn apply(data_dir: &Path, extra: Option<&Path>) -> Result<(), String> {
+    let Some(extra) = extra else {
+        *CURL_BUNDLE.lock().unwrap() = None;
+        let _ = std::fs::remove_file(data_dir.join(COMBINED_BUNDLE_FILE));
+        return Ok(());
+    };
+    let mut combined = std::fs::read_to_string(SYSTEM_ROOTS).unwrap_or_default();
+    if !combined.is_empty() && !combined.ends_with('\n') {
+        combined.push('\n');
+    }
+    combined.push_str(
+        &std::fs::read_to_string(extra)
+            .map_err(|e| format!("Failed to read {}: {}", extra.display(), e))?,
+    );
+    let path = data_dir.join(COMBINED_BUNDLE_FILE);
+    std::fs::write(&path, combined)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    *CURL_BUNDLE.lock().unwrap() = Some(path);
+    Ok(())
+}
+
+pub(crate) fn curl_bundle() -> Option<PathBuf> {
+    CURL_BUNDLE.lock().unwrap().clone()
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Active chat streams by group id, each with a flag that stops forwarding.
+#[derive(Default)]
+pub(crate) struct ChatStreams {
+    active: Mutex<BTreeMap<String, Arc<AtomicBool>>>,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChatStreamEvent {
+    group_id: String,
+    /// SSE event name: "message", "error" or "done"
+    event: String,
+    data: serde_json::Value,
+}
+
+/// Event name for one conversation. Tauri only allows alphanumerics and
+/// `-/:_` in event names, so anything else in the group id becomes `_`.
+pub(crate) fn channel(group_id: &str) -> String {
+    let id: String = group_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '/' | ':' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("chat-stream:{}", id)
+}
+
+fn emit(app: &AppHandle, group_id: &str, event: &str, data: serde_json::Value) {
+    let _ = app.emit(
+        &channel(group_id),
+        ChatStreamEvent {
+            group_id: group_id.to_string(),
+            event: event.to_string(),
+            data,
+        },
+    );
+}
+
+/// Send a prompt and forward the backend's SSE reply as Tauri events on the
+/// conversation's channel, so streaming works even where the webview's
+/// own fetch streaming is broken (proxies, buffering).
+pub(crate) fn start(app: &AppHandle, group_id: String, prompt: String) -> Result<(), String> {
+    let streams = app.state::<ChatStreams>();
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let mut active = streams.active.lock().unwrap();
+        if active.contains_key(&group_id) {
+            return Err("A reply is already streaming for this conversation".to_string());
+        }
+        active.insert(group_id.clone(), Arc::clone(&stop));
+    }
+
+    let body = serde_json::json!({ "prompt": prompt, "groupId": group_id }).to_string();
+    let response = match crate::backend_request_streaming("POST", "/api/chat", body.as_bytes()) {
+        Ok(response) if response.status == 200 => response,
+        Ok(mut response) => {
+            streams.active.lock().unwrap().remove(&group_id);
+            let mut text = String::new();
+            let _ = response.body.read_to_string(&mut text);
+            let message = serde_json::from_str::<serde_json::Value>(&text)
+                .ok()
+                .and_then(|v| v.get("error")?.as_str().map(str::to_string))
+                .unwrap_or(text);
+            return Err(format!("HTTP {}: {}", response.status, message));
+        }
+        Err(e) => {
+            streams.active.lock().unwrap().remove(&group_id);
+            return Err(e);
+        }
+    };
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut event = String::from("message");
+        let mut data = String::new();
+        let mut finished = false;
+
+        for line in response.body.lines() {
+            let Ok(line) = line else { break };
+            if stop.load(Ordering::SeqCst) {
+                break;
+            }
+            if let Some(name) = line.strip_prefix("event:") {
+                event = name.trim().to_string();
+            } else if let Some(chunk) = line.strip_prefix("data:") {
+                if !data.is_empty() {
+                    data.push('\n');
+                }
+                data.push_str(chunk.trim_start());
+            } else if line.is_empty() && !data.is_empty() {
+                let payload: serde_json::Value = serde_json::from_str(&data)
+                    .unwrap_or_else(|_| serde_json::Value::String(data.clone()));
+                if event == "error" {
+                    let message = payload
+                        .get("error")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("Agent run failed");
+                    crate::notify_agent_error(&app, &group_id, message);
+                }
+                emit(&app, &group_id, &event, payload);
+                finished = event == "done";
+                event = String::from("message");
+                data.clear();
+                if finished {
+                    crate::notify_agent_completion(&app, &group_id);
+                    break;
+                }
+            }
+        }
+
+        // Always close the conversation out, even if the connection dropped
+        if !finished {
+            if !stop.load(Ordering::SeqCst) {
+                emit(
+                    &app,
+                    &group_id,
+                    "error",
+                    serde_json::json!({ "error": "Connection to the backend was lost" }),
+                );
+            }
+            emit(
+                &app,
+                &group_id,
+                "done",
+                serde_json::json!({ "sessionId": null }),
+            );
+        }
+        app.state::<ChatStreams>()
+            .active
+            .lock()
+            .unwrap()
+            .remove(&group_id);
+    });
+    Ok(())
+}
+
+/// Stop forwarding a conversation's stream. The backend run itself continues.
+pub(crate) fn stop(app: &AppHandle, group_id: &str) {
+    if let Some(stop) = app
+        .state::<ChatStreams>()
+        .active
+        .lock()
+        .unwrap()
+        .get(group_id)
+    {
+        stop.store(true, Ordering::SeqCst);
+    }
+}
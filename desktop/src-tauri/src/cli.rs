@@ -0,0 +1,107 @@
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+const USAGE: &str = "\
+Usage: nanoclaw [options]
+
+Options:
+  --data-dir <path>   Use <path> as the data directory (overrides NANOCLAW_DATA_DIR)
+  --port <port>       Backend port (overrides PORT and the .env file)
+  --profile <name>    Keep data in a separate named profile
+  --headless          Run without the main window until opened from the tray
+  --no-backend        Don't spawn a backend; connect to one that's already running
+  -h, --help          Show this help";
+
+/// Launch options for the desktop binary, for power users and test scripts.
+#[derive(Default, Debug, Clone)]
+pub(crate) struct CliArgs {
+    pub data_dir: Option<PathBuf>,
+    pub port: Option<u16>,
+    pub profile: Option<String>,
+    pub headless: bool,
+    pub no_backend: bool,
+}
+
+static ARGS: OnceLock<CliArgs> = OnceLock::new();
+
+fn parse(args: impl IntoIterator<Item = String>) -> Result<CliArgs, String> {
+    let mut parsed = CliArgs::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        // Accept both `--flag value` and `--flag=value`
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = |name: &str| {
+            inline
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+        match flag.as_str() {
+            "--data-dir" => {
+                let dir = PathBuf::from(value("--data-dir")?);
+                let dir = if dir.is_absolute() {
+                    dir
+                } else {
+                    std::env::current_dir()
+                        .map_err(|e| format!("Failed to resolve --data-dir: {}", e))?
+                        .join(dir)
+                };
+                parsed.data_dir = Some(dir);
+            }
+            "--port" => {
+                let port = value("--port")?;
+                parsed.port = Some(
+                    port.parse()
+                        .map_err(|_| format!("Invalid --port: {}", port))?,
+                );
+            }
+            "--profile" => {
+                let profile = value("--profile")?;
+                if profile.is_empty()
+                    || !profile
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+                {
+                    return Err(format!("Invalid --profile: {}", profile));
+                }
+                parsed.profile = Some(profile);
+            }
+            "--headless" => parsed.headless = true,
+            "--no-backend" => parsed.no_backend = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            // Finder used to pass a process serial number to GUI launches
+            _ if arg.starts_with("-psn_") => {}
+            _ => eprintln!("Ignoring unknown argument: {}", arg),
+        }
+    }
+    Ok(parsed)
+}
+
+/// Parse the process arguments once at startup. `--data-dir` is applied to
+/// the environment so every reader of NANOCLAW_DATA_DIR sees it; `--port` is
+/// picked up with the rest of the listen address. Exits with usage on invalid
+/// arguments.
+pub(crate) fn init() -> &'static CliArgs {
+    ARGS.get_or_init(|| {
+        let args = parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        });
+        if let Some(dir) = &args.data_dir {
+            std::env::set_var("NANOCLAW_DATA_DIR", dir);
+        }
+        args
+    })
+}
+
+pub(crate) fn args() -> &'static CliArgs {
+    ARGS.get_or_init(CliArgs::default)
+}
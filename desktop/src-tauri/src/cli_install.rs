@@ -0,0 +1,153 @@
+use std::path::{Path, PathBuf};
+
+use crate::app_location;
+
+const INSTALL_DIR: &str = "/usr/local/bin";
+const LAUNCHER_NAME: &str = "nanoclaw";
+/// First comment line of every launcher, to tell ours from another `nanoclaw`.
+const LAUNCHER_MARKER: &str = "# Installed by NanoClaw.";
+
+/// `'…'` for sh, with embedded single quotes closed, escaped and reopened.
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// The launcher: a few friendly verbs over `nanoclawctl`, starting the app
+/// when it isn't running. Paths are baked in, so moving the app means
+/// installing again.
+fn launcher_script(ctl: &Path, bundle: Option<&Path>) -> String {
+    let launch = match bundle {
+        Some(bundle) => format!("open {}", sh_quote(&bundle.to_string_lossy())),
+        None => "echo 'NanoClaw is not running' >&2; exit 1".to_string(),
+    };
+    format!(
+        r#"#!/bin/sh
+{marker} Reinstall from the app after moving it.
+CTL={ctl}
+
+case "$1" in
+  open)
+    shift
+    "$CTL" open "$@" 2>/dev/null || {{ {launch}; }}
+    ;;
+  logs)
+    "$CTL" open logs
+    ;;
+  restart)
+    "$CTL" restart
+    ;;
+  ""|-h|--help)
+    echo "Usage: nanoclaw open [window] | logs | restart | status | send <group-id> <prompt>"
+    ;;
+  *)
+    exec "$CTL" "$@"
+    ;;
+esac
+"#,
+        marker = LAUNCHER_MARKER,
+        ctl = sh_quote(&ctl.to_string_lossy()),
+        launch = launch,
+    )
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+/// Put `script` at `dest` as root, asking for an administrator password
+/// through the standard macOS prompt.
+#[cfg(target_os = "macos")]
+fn install_privileged(script: &Path, dest: &Path) -> Result<(), String> {
+    use std::process::{Command, Stdio};
+
+    let command = format!(
+        "mkdir -p {dir} && cp {src} {dest} && chmod 755 {dest}",
+        dir = sh_quote(INSTALL_DIR),
+        src = sh_quote(&script.to_string_lossy()),
+        dest = sh_quote(&dest.to_string_lossy()),
+    );
+    let applescript = format!(
+        "do shell script \"{}\" with administrator privileges",
+        command.replace('\\', "\\\\").replace('"', "\\\"")
+    );
+    let output = crate::process::runner()
+        .output(
+            Command::new("osascript")
+                .args(["-e", &applescript])
+                .stdin(Stdio::null()),
+        )
+        .map_err(|e| format!("Failed to run osascript: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // -128: the user cancelled the password prompt
+        if stderr.contains("-128") {
+            return Err("Installation cancelled".to_string());
+        }
+        return Err(format!("Failed to install the CLI: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn install_privileged(_script: &Path, dest: &Path) -> Result<(), String> {
+    Err(format!(
+        "No permission to write {}; install it with sudo",
+        dest.display()
+    ))
+}
+
+/// Install the `nanoclaw` launcher into /usr/local/bin, asking for
+/// administrator rights when the directory isn't writable. Returns its path.
+pub(crate) fn install(data_dir: &Path) -> Result<PathBuf, String> {
+    let exe =
+        std::env::current_exe().map_err(|e| format!("Failed to locate the app binary: {}", e))?;
+    let ctl = exe.with_file_name("nanoclawctl");
+    if !ctl.is_file() {
+        return Err(format!("nanoclawctl is missing from {}", ctl.display()));
+    }
+    let script = launcher_script(&ctl, app_location::app_bundle().as_deref());
+
+    let dest = Path::new(INSTALL_DIR).join(LAUNCHER_NAME);
+    let direct = std::fs::create_dir_all(INSTALL_DIR)
+        .and_then(|_| std::fs::write(&dest, &script))
+        .and_then(|_| make_executable(&dest));
+    match direct {
+        Ok(()) => Ok(dest),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            let staged = data_dir.join(format!(".{}-launcher", LAUNCHER_NAME));
+            std::fs::write(&staged, &script)
+                .map_err(|e| format!("Failed to write {}: {}", staged.display(), e))?;
+            let result = install_privileged(&staged, &dest);
+            let _ = std::fs::remove_file(&staged);
+            result.map(|_| dest)
+        }
+        Err(e) => Err(format!("Failed to install {}: {}", dest.display(), e)),
+    }
+}
+
+/// Remove the launcher `install` put in /usr/local/bin, if it's there and
+/// ours. Returns its path when it was removed.
+pub(crate) fn uninstall() -> Result<Option<PathBuf>, String> {
+    let dest = Path::new(INSTALL_DIR).join(LAUNCHER_NAME);
+    let Ok(script) = std::fs::read_to_string(&dest) else {
+        return Ok(None);
+    };
+    if !script.contains(LAUNCHER_MARKER) {
+        return Ok(None);
+    }
+    match std::fs::remove_file(&dest) {
+        Ok(()) => Ok(Some(dest)),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => Err(format!(
+            "No permission to remove {}; remove it with sudo",
+            dest.display()
+        )),
+        Err(e) => Err(format!("Failed to remove {}: {}", dest.display(), e)),
+    }
+}
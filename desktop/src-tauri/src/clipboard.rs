@@ -0,0 +1,9 @@
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+/// Put `text` on the system clipboard.
+pub(crate) fn copy(app: &AppHandle, text: &str) -> Result<(), String> {
+    app.clipboard()
+        .write_text(text)
+        .map_err(|e| format!("Failed to copy to the clipboard: {}", e))
+}
@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{backend, chat_stream, windows};
+
+/// Unix socket in the data dir for `nanoclawctl` and scripts. One JSON request
+/// per line, one JSON response per line: `{"ok":true,"result":…}` or
+/// `{"ok":false,"error":"…"}`.
+pub(crate) const CONTROL_SOCKET_FILE: &str = "control.sock";
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+enum Request {
+    Status,
+    RestartBackend,
+    /// Show the main window, or the named one (`settings`, `logs`, ...).
+    OpenWindow {
+        label: Option<String>,
+    },
+    /// Send a prompt to a conversation; the reply streams into the app.
+    #[serde(rename_all = "camelCase")]
+    SendPrompt {
+        group_id: String,
+        prompt: String,
+    },
+}
+
+pub(crate) fn socket_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(CONTROL_SOCKET_FILE)
+}
+
+fn handle(app: &AppHandle, request: Request) -> Result<serde_json::Value, String> {
+    let manager = app.state::<backend::BackendManager>();
+    match request {
+        Request::Status => {
+            let status = manager.status();
+            Ok(serde_json::json!({
+                "running": status.running,
+                "ready": status.ready,
+                "stopping": status.stopping,
+                "pid": status.pid,
+                "baseUrl": crate::backend_base_url(),
+            }))
+        }
+        Request::RestartBackend => manager
+            .restart()
+            .map(|_| serde_json::Value::Null)
+            .map_err(|e| e.to_string()),
+        Request::OpenWindow { label: None } => {
+            crate::show_main_window(app);
+            Ok(serde_json::Value::Null)
+        }
+        Request::OpenWindow { label: Some(label) } => {
+            windows::open(app, &label).map(|_| serde_json::Value::Null)
+        }
+        Request::SendPrompt { group_id, prompt } => {
+            if !manager.status().ready {
+                return Err("The backend isn't ready".to_string());
+            }
+            chat_stream::start(app, group_id, prompt).map(|_| serde_json::Value::Null)
+        }
+    }
+}
+
+/// Serve the control socket. Only this user can connect: the socket is 0600
+/// in a data dir that is already private to them.
+#[cfg(unix)]
+pub(crate) fn start_server(app: AppHandle) {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+
+    let path = socket_path(&crate::user_data_dir(&app));
+    // Left by a crashed run; the data dir lock says no live instance owns it
+    let _ = std::fs::remove_file(&path);
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Failed to open control socket {}: {}", path.display(), e);
+            return;
+        }
+    };
+    let _ = std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600));
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let app = app.clone();
+            std::thread::spawn(move || {
+                let Ok(reader) = stream.try_clone() else {
+                    return;
+                };
+                let mut writer = stream;
+                for line in BufReader::new(reader).lines().map_while(Result::ok) {
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let response = match serde_json::from_str::<Request>(&line) {
+                        Ok(request) => handle(&app, request),
+                        Err(e) => Err(format!("Invalid request: {}", e)),
+                    };
+                    let response = match response {
+                        Ok(result) => serde_json::json!({ "ok": true, "result": result }),
+                        Err(error) => serde_json::json!({ "ok": false, "error": error }),
+                    };
+                    if writeln!(writer, "{}", response).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub(crate) fn start_server(_app: AppHandle) {}
+
+/// Remove the socket when the shell quits.
+pub(crate) fn stop_server(data_dir: &Path) {
+    let _ = std::fs::remove_file(socket_path(data_dir));
+}
@@ -0,0 +1,217 @@
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::AppHandle;
+use tauri_plugin_dialog::DialogExt;
+
+use crate::tasks::TaskHandle;
+use crate::{attachments, backup};
+
+/// Long histories take a while to read out of the session databases.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Deserialize, Clone, Copy, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ExportFormat {
+    Markdown,
+    Json,
+    Html,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+/// One prompt or reply, as `GET /api/groups/:id/messages` returns it.
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Message {
+    session_id: String,
+    /// "user" or "assistant"
+    role: String,
+    content: String,
+    timestamp: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    messages: Vec<Message>,
+}
+
+struct Group {
+    id: String,
+    name: String,
+    folder: String,
+}
+
+fn fetch_messages(folder: &str) -> Result<Vec<Message>, String> {
+    let path = format!("/api/groups/{}/messages", folder);
+    let headers = crate::with_auth_header(Vec::new());
+    let response = crate::backend_request("GET", &path, &headers, None, FETCH_TIMEOUT)?;
+    if !response.is_success() {
+        return Err(format!("GET {} returned HTTP {}", path, response.status));
+    }
+    let body: MessagesResponse = serde_json::from_value(response.json()?)
+        .map_err(|e| format!("Invalid response from {}: {}", path, e))?;
+    Ok(body.messages)
+}
+
+fn speaker(role: &str) -> &'static str {
+    if role == "user" {
+        "You"
+    } else {
+        "Assistant"
+    }
+}
+
+fn render_markdown(group: &Group, messages: &[Message]) -> String {
+    let mut out = format!("# {}\n", group.name);
+    let mut session = None;
+    for message in messages {
+        if session != Some(&message.session_id) {
+            session = Some(&message.session_id);
+            let _ = write!(out, "\n## Session {}\n", message.session_id);
+        }
+        let when = message.timestamp.as_deref().unwrap_or("");
+        let _ = write!(
+            out,
+            "\n**{}** {}\n\n{}\n",
+            speaker(&message.role),
+            when,
+            message.content.trim_end()
+        );
+    }
+    out
+}
+
+fn render_json(group: &Group, messages: &[Message]) -> Result<String, String> {
+    serde_json::to_string_pretty(&serde_json::json!({
+        "id": group.id,
+        "name": group.name,
+        "folder": group.folder,
+        "messages": messages,
+    }))
+    .map_err(|e| format!("Failed to encode {}: {}", group.folder, e))
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(group: &Group, messages: &[Message]) -> String {
+    let title = html_escape(&group.name);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\n\
+         body {{ font: 15px/1.5 -apple-system, sans-serif; max-width: 760px; margin: 2em auto; padding: 0 1em; }}\n\
+         .message {{ margin: 1em 0; padding: 0.75em 1em; border-radius: 8px; background: #f2f2f5; }}\n\
+         .user {{ background: #e3eefc; }}\n\
+         .meta {{ color: #777; font-size: 12px; }}\n\
+         .content {{ white-space: pre-wrap; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+    );
+    let mut session = None;
+    for message in messages {
+        if session != Some(&message.session_id) {
+            session = Some(&message.session_id);
+            let _ = writeln!(out, "<h2>Session {}</h2>", html_escape(&message.session_id));
+        }
+        let _ = writeln!(
+            out,
+            "<div class=\"message {}\"><div class=\"meta\">{} {}</div><div class=\"content\">{}</div></div>",
+            if message.role == "user" { "user" } else { "assistant" },
+            speaker(&message.role),
+            html_escape(message.timestamp.as_deref().unwrap_or("")),
+            html_escape(message.content.trim_end())
+        );
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Ask where to put the export with the native folder dialog. None if it
+/// was dismissed.
+pub(crate) fn pick_dir(app: &AppHandle) -> Result<Option<PathBuf>, String> {
+    let Some(picked) = app
+        .dialog()
+        .file()
+        .set_title("Export Conversations")
+        .blocking_pick_folder()
+    else {
+        return Ok(None);
+    };
+    picked
+        .into_path()
+        .map(Some)
+        .map_err(|e| format!("Invalid path: {}", e))
+}
+
+/// Write one file per conversation into a new timestamped folder under
+/// `dir`, reporting progress per conversation. Returns the folder.
+pub(crate) fn export(
+    dir: &Path,
+    format: ExportFormat,
+    task: &TaskHandle,
+) -> Result<PathBuf, String> {
+    task.progress(Some(0.0), "Listing conversations");
+    let groups: Vec<Group> = crate::backend_get_json("/api/groups")?
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|g| {
+            let folder = g.get("folder")?.as_str()?.to_string();
+            Some(Group {
+                id: g.get("id")?.as_str()?.to_string(),
+                name: g
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .unwrap_or(&folder)
+                    .to_string(),
+                folder,
+            })
+        })
+        // The folder names the file and the request path
+        .filter(|g| attachments::group_dir(Path::new(""), &g.folder).is_ok())
+        .collect();
+
+    let out_dir = dir.join(format!("nanoclaw-conversations-{}", backup::timestamp()));
+    std::fs::create_dir_all(&out_dir)
+        .map_err(|e| format!("Failed to create {}: {}", out_dir.display(), e))?;
+
+    let total = groups.len().max(1) as f64;
+    for (i, group) in groups.iter().enumerate() {
+        if task.is_cancelled() {
+            return Err("Export cancelled".to_string());
+        }
+        task.progress(
+            Some(i as f64 / total),
+            format!("Exporting {} ({}/{})", group.name, i + 1, groups.len()),
+        );
+        let messages = fetch_messages(&group.folder)?;
+        let text = match format {
+            ExportFormat::Markdown => render_markdown(group, &messages),
+            ExportFormat::Json => render_json(group, &messages)?,
+            ExportFormat::Html => render_html(group, &messages),
+        };
+        let file = out_dir.join(format!("{}.{}", group.folder, format.extension()));
+        std::fs::write(&file, text)
+            .map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+    }
+    task.progress(
+        Some(1.0),
+        format!("Exported {} conversations", groups.len()),
+    );
+    Ok(out_dir)
+}
@@ -0,0 +1,151 @@
+use std::backtrace::Backtrace;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+use crate::http;
+
+/// Where reports are uploaded to, with consent. Builds without it keep
+/// reports local only.
+const CRASH_REPORT_URL: Option<&str> = option_env!("NANOCLAW_CRASH_REPORT_URL");
+const CRASHES_DIR: &str = "crashes";
+const SENT_DIR: &str = "sent";
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+type ContextFn = Box<dyn Fn() -> serde_json::Value + Send + Sync>;
+
+/// Set once the data dir is known; panics before that go to the temp dir.
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Snapshot of shell state attached to each report.
+static CONTEXT: Mutex<Option<ContextFn>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CrashReport {
+    pub message: String,
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: String,
+    pub app_version: String,
+    pub os: String,
+    /// Unix epoch milliseconds
+    pub timestamp: u64,
+    pub context: serde_json::Value,
+}
+
+fn crash_dir() -> PathBuf {
+    CRASH_DIR
+        .get()
+        .cloned()
+        .unwrap_or_else(|| std::env::temp_dir().join("nanoclaw-crashes"))
+}
+
+/// Write a report for every shell panic, then run the default hook so the
+/// message still reaches stderr.
+pub(crate) fn install_panic_hook(app_version: &'static str) {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic".to_string());
+        // try_lock: the panicking thread may hold the context lock
+        let context = CONTEXT
+            .try_lock()
+            .ok()
+            .and_then(|ctx| ctx.as_ref().map(|f| f()))
+            .unwrap_or(serde_json::Value::Null);
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        let report = CrashReport {
+            message,
+            location: info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())),
+            thread: std::thread::current().name().map(str::to_string),
+            backtrace: Backtrace::force_capture().to_string(),
+            app_version: app_version.to_string(),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            timestamp,
+            context,
+        };
+        match write_report(&crash_dir(), &report) {
+            Ok(path) => eprintln!("Crash report written to {}", path.display()),
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+        default_hook(info);
+    }));
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> Result<PathBuf, String> {
+    std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let path = dir.join(format!("crash-{}.json", report.timestamp));
+    let content = serde_json::to_string_pretty(report).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Point reports at the data dir, moving over any written before it was known.
+pub(crate) fn init_dir(data_dir: &Path) {
+    let dir = data_dir.join(CRASHES_DIR);
+    let early = crash_dir();
+    if CRASH_DIR.set(dir.clone()).is_err() {
+        return;
+    }
+    if let Ok(entries) = std::fs::read_dir(&early) {
+        let _ = std::fs::create_dir_all(&dir);
+        for entry in entries.flatten() {
+            let _ = std::fs::rename(entry.path(), dir.join(entry.file_name()));
+        }
+    }
+}
+
+/// Register the state snapshot included in reports.
+pub(crate) fn set_context(context: impl Fn() -> serde_json::Value + Send + Sync + 'static) {
+    *CONTEXT.lock().unwrap() = Some(Box::new(context));
+}
+
+/// Reports not uploaded yet, oldest first.
+pub(crate) fn pending_reports(data_dir: &Path) -> Vec<(PathBuf, CrashReport)> {
+    let Ok(entries) = std::fs::read_dir(data_dir.join(CRASHES_DIR)) else {
+        return Vec::new();
+    };
+    let mut reports: Vec<(PathBuf, CrashReport)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let content = std::fs::read_to_string(&path).ok()?;
+            let report = serde_json::from_str(&content).ok()?;
+            Some((path, report))
+        })
+        .collect();
+    reports.sort_by_key(|(_, report)| report.timestamp);
+    reports
+}
+
+/// Send pending reports, moving each into `crashes/sent` once accepted.
+pub(crate) fn upload_pending(data_dir: &Path) -> Result<usize, String> {
+    let url = CRASH_REPORT_URL
+        .ok_or_else(|| "Crash report upload is not enabled in this build".to_string())?;
+    let sent_dir = data_dir.join(CRASHES_DIR).join(SENT_DIR);
+    let mut sent = 0;
+    for (path, report) in pending_reports(data_dir) {
+        let body = serde_json::to_string(&report)
+            .map_err(|e| format!("Failed to serialize crash report: {}", e))?;
+        http::curl_post_json(url, &body, UPLOAD_TIMEOUT)?;
+        let _ = std::fs::create_dir_all(&sent_dir);
+        if let Some(name) = path.file_name() {
+            let _ = std::fs::rename(&path, sent_dir.join(name));
+        }
+        sent += 1;
+    }
+    Ok(sent)
+}
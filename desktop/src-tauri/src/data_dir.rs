@@ -0,0 +1,226 @@
+use std::fs::{File, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::AppHandle;
+use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+use crate::tasks::TaskHandle;
+use crate::{i18n, process};
+
+/// Pointer file in the app config dir naming a relocated data dir. It lives
+/// outside the data dir so it survives the move.
+const OVERRIDE_FILE: &str = "data-dir";
+/// Advisory lock held by the instance using the data dir; holds its pid.
+const LOCK_FILE: &str = "nanoclaw.lock";
+/// Runtime files and restore leftovers, not part of the data proper.
+const SKIP_NAMES: &[&str] = &[
+    "backend.sock",
+    "status.json",
+    "control.sock",
+    LOCK_FILE,
+    ".restore-staging",
+    ".restore-previous",
+];
+
+/// How long to wait for the lock, so a relaunch doesn't trip over the
+/// instance it replaces while that one is still exiting.
+const LOCK_WAIT: Duration = Duration::from_secs(3);
+/// Kept open while the data dir is in use; the lock goes with it.
+static LOCK: Mutex<Option<File>> = Mutex::new(None);
+
+pub(crate) fn read_override(config_dir: &Path) -> Option<PathBuf> {
+    let path = std::fs::read_to_string(config_dir.join(OVERRIDE_FILE)).ok()?;
+    let path = PathBuf::from(path.trim());
+    (path.is_absolute() && path.is_dir()).then_some(path)
+}
+
+pub(crate) fn write_override(config_dir: &Path, data_dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(config_dir)
+        .map_err(|e| format!("Failed to create {}: {}", config_dir.display(), e))?;
+    std::fs::write(
+        config_dir.join(OVERRIDE_FILE),
+        data_dir.to_string_lossy().as_bytes(),
+    )
+    .map_err(|e| format!("Failed to save data dir location: {}", e))
+}
+
+/// Lock `data_dir` for this process, so a second instance pointed at it (say
+/// through NANOCLAW_DATA_DIR) can't share the store. The lock is released by
+/// the OS when the process exits, however it exits, and replaces the lock on
+/// a previous data dir.
+pub(crate) fn lock(data_dir: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(data_dir)
+        .map_err(|e| format!("Failed to create {}: {}", data_dir.display(), e))?;
+    let path = data_dir.join(LOCK_FILE);
+    // Not truncated on open: the holder's pid has to survive a failed attempt
+    let mut file = std::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let started = Instant::now();
+    let locked = loop {
+        match file.try_lock() {
+            Err(TryLockError::WouldBlock) if started.elapsed() < LOCK_WAIT => {
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            result => break result,
+        }
+    };
+    match locked {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            let mut holder = String::new();
+            let _ = file.read_to_string(&mut holder);
+            return Err(match holder.trim() {
+                "" => format!("{} is in use by another NanoClaw", data_dir.display()),
+                pid => format!(
+                    "{} is in use by another NanoClaw (pid {})",
+                    data_dir.display(),
+                    pid
+                ),
+            });
+        }
+        Err(TryLockError::Error(e)) => {
+            return Err(format!("Failed to lock {}: {}", path.display(), e));
+        }
+    }
+    let _ = file
+        .set_len(0)
+        .and_then(|_| file.rewind())
+        .and_then(|_| write!(file, "{}", std::process::id()));
+    *LOCK.lock().unwrap() = Some(file);
+    Ok(())
+}
+
+/// Explain that the data dir is taken, then quit; nothing else has started.
+pub(crate) fn exit_locked(app: &AppHandle, locale: i18n::Locale, error: String) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let t = |key| i18n::tr(locale, key);
+        app.dialog()
+            .message(format!("{}\n\n{}", t("data_lock.body"), error))
+            .title(t("data_lock.title"))
+            .kind(MessageDialogKind::Error)
+            .blocking_show();
+        std::process::exit(1);
+    });
+}
+
+/// Delete everything in `data_dir` except the top-level entries named in `keep`.
+pub(crate) fn clear(data_dir: &Path, keep: &[&str]) -> Result<(), String> {
+    let entries = std::fs::read_dir(data_dir)
+        .map_err(|e| format!("Failed to read {}: {}", data_dir.display(), e))?;
+    for entry in entries.flatten() {
+        if keep.contains(&entry.file_name().to_string_lossy().as_ref()) {
+            continue;
+        }
+        let path = entry.path();
+        let result = if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        result.map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Regular files under `root` as (relative path, size), sorted.
+fn inventory(root: &Path) -> Result<Vec<(PathBuf, u64)>, String> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            if SKIP_NAMES.contains(&entry.file_name().to_string_lossy().as_ref()) {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+                let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+                files.push((relative, size));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Check `target` can receive the data: absolute, outside the current data
+/// dir, and either missing or an empty directory.
+pub(crate) fn validate_target(current: &Path, target: &Path) -> Result<(), String> {
+    if !target.is_absolute() {
+        return Err("The new data folder must be an absolute path".to_string());
+    }
+    if target.starts_with(current) || current.starts_with(target) {
+        return Err("The new data folder can't contain or be inside the current one".to_string());
+    }
+    if target.exists() {
+        let mut entries = std::fs::read_dir(target)
+            .map_err(|e| format!("Can't use {}: {}", target.display(), e))?;
+        if entries.any(|e| e.is_ok_and(|e| e.file_name() != ".DS_Store")) {
+            return Err(format!("{} is not empty", target.display()));
+        }
+    }
+    Ok(())
+}
+
+/// Copy the whole data dir to `target`, then check every file arrived with
+/// the same size. The source is left untouched.
+pub(crate) fn copy_verified(
+    current: &Path,
+    target: &Path,
+    task: &TaskHandle,
+) -> Result<(), String> {
+    task.progress(Some(0.0), "Scanning data");
+    let expected = inventory(current)?;
+
+    std::fs::create_dir_all(target)
+        .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+
+    task.progress(Some(0.1), "Copying data");
+    // `src/.` copies the directory's contents, dotfiles included
+    let output = process::runner()
+        .output(
+            Command::new("cp")
+                .arg("-Rp")
+                .arg(current.join("."))
+                .arg(target),
+        )
+        .map_err(|e| format!("Failed to run cp: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to copy data: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    task.progress(Some(0.8), "Verifying copy");
+    let copied = inventory(target)?;
+    let missing: Vec<_> = expected
+        .iter()
+        .filter(|file| copied.binary_search(file).is_err())
+        .collect();
+    if let Some((path, _)) = missing.first() {
+        return Err(format!(
+            "Copy verification failed: {} file(s) missing or different, e.g. {}",
+            missing.len(),
+            path.display()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{backend, settings};
+
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Set while dev mode runs the backend with an inspector.
+static INSPECT_PORT: Mutex<Option<u16>> = Mutex::new(None);
+
+/// Port the backend's inspector listens on, when dev mode started it with one.
+pub(crate) fn inspect_port() -> Option<u16> {
+    *INSPECT_PORT.lock().unwrap()
+}
+
+/// The `--inspect` flag for node in dev mode, recorded so
+/// `get_backend_config` can point a debugger at it.
+pub(crate) fn node_args(dev: &settings::DevMode) -> Vec<String> {
+    if !dev.enabled {
+        *INSPECT_PORT.lock().unwrap() = None;
+        return Vec::new();
+    }
+    *INSPECT_PORT.lock().unwrap() = Some(dev.inspect_port);
+    // Loopback only: the inspector runs arbitrary code for whoever connects
+    vec![format!("--inspect=127.0.0.1:{}", dev.inspect_port)]
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// In dev mode, restart the backend when `dist/index.js` is rebuilt. A change
+/// is acted on once the file has stopped changing for a poll, so a build
+/// still writing it isn't picked up half done. Emits `backend-reloaded`.
+pub(crate) fn start_dist_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let entry = crate::backend_dir(&app).join("dist/index.js");
+        let mut seen = modified(&entry);
+        let mut pending: Option<SystemTime> = None;
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let dev = settings::load(&crate::user_data_dir(&app)).dev_mode;
+            let current = modified(&entry);
+            if !dev.enabled || !dev.watch_dist || crate::remote_backend().is_some() {
+                seen = current;
+                pending = None;
+                continue;
+            }
+            if current == seen {
+                continue;
+            }
+            if pending != current {
+                pending = current;
+                continue;
+            }
+
+            seen = current;
+            pending = None;
+            if current.is_none() {
+                // Deleted mid-rebuild; wait for the new file
+                continue;
+            }
+            let manager = app.state::<backend::BackendManager>();
+            if !manager.status().running {
+                continue;
+            }
+            eprintln!("dist/index.js changed; restarting the backend");
+            match manager.restart() {
+                Ok(()) => {
+                    let _ = app.emit("backend-reloaded", ());
+                }
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+    });
+}
@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::Serialize;
+use specta::Type;
+
+use crate::backup;
+
+const ENV_FILE: &str = ".env";
+/// Earlier versions of `.env`, one file per save: `env-<time>`.
+const ENV_BACKUPS_DIR: &str = "env-backups";
+const ENV_BACKUP_PREFIX: &str = "env-";
+const MAX_ENV_BACKUPS: usize = 10;
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EnvBackup {
+    pub name: String,
+    /// Seconds since the epoch.
+    pub created_at: u64,
+}
+
+pub(crate) fn path(data_dir: &Path) -> PathBuf {
+    data_dir.join(ENV_FILE)
+}
+
+/// One `KEY=value` assignment in `.env`.
+pub(crate) struct Entry {
+    pub key: String,
+    /// With quotes, escapes and `${VAR}` references resolved.
+    pub value: String,
+    /// 0-based lines it spans; a quoted value may run over several.
+    pub lines: Range<usize>,
+}
+
+#[derive(Default)]
+pub(crate) struct Parsed {
+    pub entries: Vec<Entry>,
+    /// 1-based numbers of lines that are neither assignments, comments nor
+    /// blank.
+    pub invalid: Vec<usize>,
+}
+
+impl Parsed {
+    /// Key/value pairs in file order; a repeated key appears again, and the
+    /// later value wins when applied.
+    pub fn pairs(self) -> Vec<(String, String)> {
+        self.entries
+            .into_iter()
+            .map(|entry| (entry.key, entry.value))
+            .collect()
+    }
+}
+
+/// Parse `.env` in the data dir; a missing file has no entries.
+pub(crate) fn load(data_dir: &Path) -> Parsed {
+    std::fs::read_to_string(path(data_dir))
+        .map(|content| parse(&content))
+        .unwrap_or_default()
+}
+
+fn is_valid_key(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// Byte offset of the first `quote` in `text` that isn't backslash-escaped
+/// (single quotes have no escapes).
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote == '"' => escaped = true,
+            c if c == quote => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Resolve `$VAR`, `${VAR}` and `${VAR:-default}` against the entries so far,
+/// then the process environment. Double-quoted values also get `\n`, `\t`,
+/// `\"` and `\\` escapes; `\$` keeps a literal `$` either way.
+fn interpolate(text: &str, vars: &HashMap<String, String>, escapes: bool) -> String {
+    let lookup = |name: &str| {
+        vars.get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok())
+            .filter(|v| !v.is_empty())
+    };
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.peek().copied() {
+                Some('$') => {
+                    chars.next();
+                    out.push('$');
+                }
+                Some(next @ ('n' | 't' | '"' | '\\')) if escapes => {
+                    chars.next();
+                    out.push(match next {
+                        'n' => '\n',
+                        't' => '\t',
+                        other => other,
+                    });
+                }
+                _ => out.push('\\'),
+            },
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                let inner: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let (name, default) = match inner.split_once(":-") {
+                    Some((name, default)) => (name, Some(default)),
+                    None => (inner.as_str(), None),
+                };
+                out.push_str(
+                    &lookup(name)
+                        .or_else(|| default.map(str::to_string))
+                        .unwrap_or_default(),
+                );
+            }
+            '$' if chars
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphabetic() || *c == '_') =>
+            {
+                let mut name = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    name.push(c);
+                    chars.next();
+                }
+                out.push_str(&lookup(&name).unwrap_or_default());
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Parse dotenv syntax: `KEY=value` with optional `export ` prefix and
+/// spaces around `=`; single-quoted values are literal, double-quoted ones
+/// take escapes, both may span lines; unquoted values end at ` #`. References
+/// to other variables are expanded except in single quotes.
+pub(crate) fn parse(content: &str) -> Parsed {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut parsed = Parsed::default();
+    let mut vars: HashMap<String, String> = HashMap::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let start = i;
+        let trimmed = lines[i].trim();
+        i += 1;
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let body = trimmed
+            .strip_prefix("export")
+            .filter(|rest| rest.starts_with([' ', '\t']))
+            .map_or(trimmed, str::trim_start);
+        let Some((key, raw)) = body.split_once('=') else {
+            parsed.invalid.push(start + 1);
+            continue;
+        };
+        let key = key.trim();
+        if !is_valid_key(key) {
+            parsed.invalid.push(start + 1);
+            continue;
+        }
+
+        let raw = raw.trim_start();
+        let value = match raw.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut text = raw[1..].to_string();
+                let mut end = closing_quote(&text, quote);
+                while end.is_none() && i < lines.len() {
+                    text.push('\n');
+                    text.push_str(lines[i]);
+                    i += 1;
+                    end = closing_quote(&text, quote);
+                }
+                let Some(end) = end else {
+                    // Never closed; don't swallow the rest of the file
+                    parsed.invalid.push(start + 1);
+                    i = start + 1;
+                    continue;
+                };
+                if quote == '\'' {
+                    text[..end].to_string()
+                } else {
+                    interpolate(&text[..end], &vars, true)
+                }
+            }
+            _ => {
+                let end = raw
+                    .char_indices()
+                    .find(|&(i, c)| c == '#' && raw[..i].ends_with([' ', '\t']))
+                    .map_or(raw.len(), |(i, _)| i);
+                interpolate(raw[..end].trim_end(), &vars, false)
+            }
+        };
+        vars.insert(key.to_string(), value.clone());
+        parsed.entries.push(Entry {
+            key: key.to_string(),
+            value,
+            lines: start..i,
+        });
+    }
+    parsed
+}
+
+/// A value as written to `.env`: bare when that reads back the same,
+/// double-quoted with escapes otherwise.
+fn format_value(value: &str) -> String {
+    let bare = value
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "_-./:@,+=%".contains(c));
+    if bare {
+        return value.to_string();
+    }
+    let mut out = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '$' => out.push_str("\\$"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// `content` with `changes` applied: a changed key is rewritten where it was
+/// first assigned (other assignments of it are dropped), a new key is
+/// appended, and an empty value removes the key. Everything else, comments
+/// and `${VAR}` references included, is kept as written.
+pub(crate) fn update(content: &str, changes: &[(String, String)]) -> String {
+    let parsed = parse(content);
+    let mut out: Vec<Option<String>> = content.lines().map(|l| Some(l.to_string())).collect();
+    let mut appended = Vec::new();
+
+    for (n, (key, value)) in changes.iter().enumerate() {
+        // A key given twice takes its last value
+        if changes[n + 1..].iter().any(|(later, _)| later == key) {
+            continue;
+        }
+        let existing: Vec<&Entry> = parsed.entries.iter().filter(|e| &e.key == key).collect();
+        if existing.last().is_some_and(|e| &e.value == value) {
+            continue;
+        }
+        let line = (!value.is_empty()).then(|| format!("{}={}", key, format_value(value)));
+        let Some(first) = existing.first() else {
+            appended.extend(line);
+            continue;
+        };
+        for entry in &existing {
+            for l in entry.lines.clone() {
+                out[l] = None;
+            }
+        }
+        out[first.lines.start] = line;
+    }
+
+    let mut lines: Vec<String> = out.into_iter().flatten().collect();
+    lines.extend(appended);
+    if lines.is_empty() {
+        return String::new();
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Create (or truncate) `path` readable by the user only, since `.env` holds
+/// API keys.
+fn create_private(path: &Path) -> std::io::Result<std::fs::File> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)
+}
+
+/// Replace `.env` with `content` without ever leaving it half written: the
+/// new content goes to a temp file that is synced and renamed over it. The
+/// previous version is kept in `env-backups` first.
+pub(crate) fn write(data_dir: &Path, content: &str) -> Result<(), String> {
+    let env_path = path(data_dir);
+    if env_path.exists() {
+        back_up(data_dir)?;
+    }
+
+    let tmp = data_dir.join(format!("{}.tmp-{}", ENV_FILE, std::process::id()));
+    let written = create_private(&tmp).and_then(|mut f| {
+        f.write_all(content.as_bytes())?;
+        f.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&tmp);
+        return Err(format!("Failed to write .env: {}", e));
+    }
+    std::fs::rename(&tmp, &env_path).map_err(|e| {
+        let _ = std::fs::remove_file(&tmp);
+        format!("Failed to replace .env: {}", e)
+    })
+}
+
+/// Copy the current `.env` into the backups dir, dropping the oldest copies
+/// beyond MAX_ENV_BACKUPS.
+fn back_up(data_dir: &Path) -> Result<(), String> {
+    let dir = data_dir.join(ENV_BACKUPS_DIR);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let content = std::fs::read(path(data_dir))
+        .map_err(|e| format!("Failed to read .env for backup: {}", e))?;
+    let stamp = backup::timestamp();
+    // Saves within the same second keep their own copies
+    let dest = (0..)
+        .map(|n| match n {
+            0 => dir.join(format!("{}{}", ENV_BACKUP_PREFIX, stamp)),
+            n => dir.join(format!("{}{}-{}", ENV_BACKUP_PREFIX, stamp, n)),
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_default();
+    create_private(&dest)
+        .and_then(|mut f| f.write_all(&content))
+        .map_err(|e| format!("Failed to back up .env: {}", e))?;
+
+    for old in list(data_dir).iter().skip(MAX_ENV_BACKUPS) {
+        let _ = std::fs::remove_file(dir.join(&old.name));
+    }
+    Ok(())
+}
+
+/// Backups of `.env`, newest first.
+pub(crate) fn list(data_dir: &Path) -> Vec<EnvBackup> {
+    let Ok(entries) = std::fs::read_dir(data_dir.join(ENV_BACKUPS_DIR)) else {
+        return Vec::new();
+    };
+    let mut backups: Vec<EnvBackup> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with(ENV_BACKUP_PREFIX) {
+                return None;
+            }
+            let created_at = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()?
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()?
+                .as_secs();
+            Some(EnvBackup { name, created_at })
+        })
+        .collect();
+    backups.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| b.name.cmp(&a.name))
+    });
+    backups
+}
+
+/// Put the named backup (the newest if None) back as `.env`. The version it
+/// replaces is backed up in turn, so a restore can be undone.
+pub(crate) fn restore(data_dir: &Path, name: Option<&str>) -> Result<(), String> {
+    let name = match name {
+        Some(name) => {
+            if !name.starts_with(ENV_BACKUP_PREFIX) || name.contains(['/', '\\']) {
+                return Err(format!("Not a .env backup: {}", name));
+            }
+            name.to_string()
+        }
+        None => list(data_dir)
+            .into_iter()
+            .next()
+            .map(|backup| backup.name)
+            .ok_or_else(|| "There is no .env backup to restore".to_string())?,
+    };
+    let content = std::fs::read_to_string(data_dir.join(ENV_BACKUPS_DIR).join(&name))
+        .map_err(|e| format!("Failed to read backup {}: {}", name, e))?;
+    write(data_dir, &content)
+}
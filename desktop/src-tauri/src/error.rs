@@ -0,0 +1,198 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Serialize, Serializer};
+use specta::datatype::{reference::Reference, DataType};
+use specta::{Generics, Type, TypeCollection};
+
+/// Error returned by commands. Serialized as `{ code, message, context, hint }`
+/// so the frontend can branch on `code` and show `message` (and `hint`) as is.
+#[derive(Debug)]
+pub(crate) enum ShellError {
+    /// `node` isn't on PATH.
+    NodeMissing,
+    /// The Docker daemon isn't reachable.
+    DockerUnavailable,
+    /// `dist/index.js` hasn't been built.
+    BackendNotBuilt { path: PathBuf },
+    /// The port and every fallback near it are taken.
+    PortConflict { port: u16 },
+    /// The backend process couldn't be started.
+    SpawnFailed { io: String },
+    /// The backend couldn't load one of its modules (`node_modules` incomplete).
+    ModuleMissing { module: String },
+    /// Files in the backend bundle are missing or don't match its checksum
+    /// manifest (paths relative to `dist/`).
+    BundleCorrupt { files: Vec<String> },
+    /// `.env` has lines that aren't `KEY=value` (1-based line numbers).
+    EnvInvalid { lines: Vec<usize> },
+    /// The backend exited before it got going, for no reason recognized;
+    /// `log` is the tail of its output.
+    BackendExited { code: Option<i32>, log: String },
+    /// `docker build` failed; `log` is the tail of its output.
+    BuildFailed { log: String },
+    /// The user cancelled the task.
+    Cancelled,
+    /// Rejected arguments.
+    InvalidInput(String),
+    /// Anything not worth a code of its own yet.
+    Other(String),
+}
+
+impl ShellError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            ShellError::NodeMissing => "nodeMissing",
+            ShellError::DockerUnavailable => "dockerUnavailable",
+            ShellError::BackendNotBuilt { .. } => "backendNotBuilt",
+            ShellError::PortConflict { .. } => "portConflict",
+            ShellError::SpawnFailed { .. } => "spawnFailed",
+            ShellError::ModuleMissing { .. } => "moduleMissing",
+            ShellError::BundleCorrupt { .. } => "bundleCorrupt",
+            ShellError::EnvInvalid { .. } => "envInvalid",
+            ShellError::BackendExited { .. } => "backendExited",
+            ShellError::BuildFailed { .. } => "buildFailed",
+            ShellError::Cancelled => "cancelled",
+            ShellError::InvalidInput(_) => "invalidInput",
+            ShellError::Other(_) => "other",
+        }
+    }
+
+    fn context(&self) -> serde_json::Value {
+        match self {
+            ShellError::BackendNotBuilt { path } => serde_json::json!({ "path": path }),
+            ShellError::PortConflict { port } => serde_json::json!({ "port": port }),
+            ShellError::SpawnFailed { io } => serde_json::json!({ "io": io }),
+            ShellError::ModuleMissing { module } => serde_json::json!({ "module": module }),
+            ShellError::BundleCorrupt { files } => serde_json::json!({ "files": files }),
+            ShellError::EnvInvalid { lines } => serde_json::json!({ "lines": lines }),
+            ShellError::BackendExited { code, log } => {
+                serde_json::json!({ "code": code, "log": log })
+            }
+            ShellError::BuildFailed { log } => serde_json::json!({ "log": log }),
+            _ => serde_json::Value::Null,
+        }
+    }
+
+    /// What the user can do about it, for errors that have an obvious fix.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            ShellError::NodeMissing => {
+                Some("Install Node.js 20 or later from nodejs.org, then restart NanoClaw.")
+            }
+            ShellError::DockerUnavailable => {
+                Some("Start Docker Desktop, then restart the backend.")
+            }
+            ShellError::BackendNotBuilt { .. } => {
+                Some("Run 'npm run build' in the project root, or reinstall NanoClaw.")
+            }
+            ShellError::PortConflict { .. } => Some(
+                "Quit whatever is using the port, or pick another port in the backend settings.",
+            ),
+            ShellError::ModuleMissing { .. } => {
+                Some("Reinstall NanoClaw, or run 'npm install' in the project root.")
+            }
+            ShellError::BundleCorrupt { .. } => {
+                Some("Reinstall NanoClaw; the app's files were changed or damaged.")
+            }
+            ShellError::EnvInvalid { .. } => {
+                Some("Fix or remove the listed lines in .env; each should be KEY=value.")
+            }
+            ShellError::BackendExited { .. } => Some("Open the logs to see what happened."),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ShellError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShellError::NodeMissing => write!(f, "Node.js is not installed or not on PATH"),
+            ShellError::DockerUnavailable => write!(f, "Docker is not running"),
+            ShellError::BackendNotBuilt { path } => write!(
+                f,
+                "Backend not built: {} not found. Run 'npm run build' in project root first.",
+                path.display()
+            ),
+            ShellError::PortConflict { port } => {
+                write!(f, "No free port available for the backend near {}", port)
+            }
+            ShellError::SpawnFailed { io } => write!(f, "Failed to spawn backend: {}", io),
+            ShellError::ModuleMissing { module } => {
+                write!(f, "The backend is missing the module {}", module)
+            }
+            ShellError::BundleCorrupt { files } => {
+                write!(f, "The backend bundle is damaged: {}", files.join(", "))
+            }
+            ShellError::EnvInvalid { lines } => write!(
+                f,
+                ".env has invalid lines: {}",
+                lines
+                    .iter()
+                    .map(|line| line.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            ShellError::BackendExited {
+                code: Some(code), ..
+            } => {
+                write!(f, "The backend exited during startup with code {}", code)
+            }
+            ShellError::BackendExited { code: None, .. } => {
+                write!(f, "The backend was killed during startup")
+            }
+            ShellError::BuildFailed { log } => write!(f, "Docker build failed: {}", log),
+            ShellError::Cancelled => write!(f, "Cancelled"),
+            ShellError::InvalidInput(message) | ShellError::Other(message) => {
+                write!(f, "{}", message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ShellError {}
+
+/// Helpers still report plain strings; they surface as `other`.
+impl From<String> for ShellError {
+    fn from(message: String) -> Self {
+        ShellError::Other(message)
+    }
+}
+
+impl From<&str> for ShellError {
+    fn from(message: &str) -> Self {
+        ShellError::Other(message.to_string())
+    }
+}
+
+/// What a `ShellError` looks like on the wire, and in the TypeScript bindings.
+#[derive(Serialize, Type)]
+#[serde(rename = "ShellError")]
+struct Payload {
+    code: String,
+    message: String,
+    context: serde_json::Value,
+    hint: Option<String>,
+}
+
+impl Serialize for ShellError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Payload {
+            code: self.code().to_string(),
+            message: self.to_string(),
+            context: self.context(),
+            hint: self.hint().map(str::to_string),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl Type for ShellError {
+    fn inline(type_map: &mut TypeCollection, generics: Generics) -> DataType {
+        Payload::inline(type_map, generics)
+    }
+
+    fn reference(type_map: &mut TypeCollection, generics: &[DataType]) -> Reference {
+        Payload::reference(type_map, generics)
+    }
+}
@@ -0,0 +1,438 @@
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+use serde::Serialize;
+use specta::Type;
+
+use crate::tasks::TaskHandle;
+use crate::{attachments, backup, image, process};
+
+/// Recent container logs returned by `get_group_logs`.
+const DEFAULT_LOG_COUNT: usize = 5;
+/// Only the end of each log is returned; failed runs dump the whole input.
+const LOG_TAIL_BYTES: u64 = 64 * 1024;
+/// The backend's SQLite store, relative to the data dir.
+const STORE_DB: &str = "store/messages.db";
+pub(crate) const EXPORTS_DIR: &str = "exports";
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GroupContainer {
+    pub name: String,
+    /// Docker's state: running, paused, exited, ...
+    pub state: String,
+    /// Waiting in the warm pool rather than serving a request.
+    pub warm: bool,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GroupInfo {
+    /// Folder under `groups/`, which also names its containers.
+    pub folder: String,
+    /// Display name and chat id from the backend, when it's running.
+    pub name: Option<String>,
+    pub chat_id: Option<String>,
+    pub containers: Vec<GroupContainer>,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GroupLog {
+    pub file: String,
+    /// Unix epoch milliseconds.
+    pub modified: u64,
+    /// The end of the log, if it's long.
+    pub content: String,
+    pub truncated: bool,
+}
+
+/// How the backend turns a folder into a container name part.
+fn container_part(folder: &str) -> String {
+    folder
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// The folder part of `nanoclaw-<folder>-<millis>` / `nanoclaw-warm-<folder>-<millis>`.
+fn container_folder(name: &str) -> Option<(&str, bool)> {
+    let rest = name.strip_prefix("nanoclaw-")?;
+    let (rest, warm) = match rest.strip_prefix("warm-") {
+        Some(rest) => (rest, true),
+        None => (rest, false),
+    };
+    let (folder, stamp) = rest.rsplit_once('-')?;
+    stamp
+        .chars()
+        .all(|c| c.is_ascii_digit())
+        .then_some((folder, warm))
+}
+
+fn containers() -> Vec<GroupContainer> {
+    let Ok(output) = image::docker_output(&[
+        "ps",
+        "-a",
+        "--filter",
+        "name=nanoclaw-",
+        "--format",
+        "{{.Names}}\t{{.State}}",
+    ]) else {
+        return Vec::new();
+    };
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, state) = line.split_once('\t')?;
+            let (_, warm) = container_folder(name)?;
+            Some(GroupContainer {
+                name: name.to_string(),
+                state: state.to_string(),
+                warm,
+            })
+        })
+        .collect()
+}
+
+fn group_containers(folder: &str) -> Vec<GroupContainer> {
+    let part = container_part(folder);
+    containers()
+        .into_iter()
+        .filter(|c| container_folder(&c.name).is_some_and(|(f, _)| f == part))
+        .collect()
+}
+
+/// Every group folder with its containers. `registered` is the backend's
+/// group list (`/api/groups`), if it could be fetched.
+pub(crate) fn list(data_dir: &Path, registered: Option<serde_json::Value>) -> Vec<GroupInfo> {
+    let mut folders: Vec<String> = std::fs::read_dir(data_dir.join("groups"))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    folders.sort();
+
+    let registered = registered
+        .as_ref()
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let all_containers = containers();
+    folders
+        .into_iter()
+        .map(|folder| {
+            let entry = registered
+                .iter()
+                .find(|g| g.get("folder").and_then(|f| f.as_str()) == Some(folder.as_str()));
+            let field = |key: &str| entry.and_then(|g| g.get(key)?.as_str()).map(str::to_string);
+            let part = container_part(&folder);
+            GroupInfo {
+                name: field("name"),
+                chat_id: field("id"),
+                containers: all_containers
+                    .iter()
+                    .filter(|c| container_folder(&c.name).is_some_and(|(f, _)| f == part))
+                    .cloned()
+                    .collect(),
+                folder,
+            }
+        })
+        .collect()
+}
+
+/// Stop a group's running containers, warm ones included, leaving the rest
+/// alone. The backend starts a fresh one for the group's next message, so
+/// this is also how a stuck group is restarted. Returns the stopped names.
+pub(crate) fn stop(data_dir: &Path, group_id: &str) -> Result<Vec<String>, String> {
+    attachments::group_dir(data_dir, group_id)?;
+    let running: Vec<String> = group_containers(group_id)
+        .into_iter()
+        .filter(|c| c.state == "running" || c.state == "paused")
+        .map(|c| c.name)
+        .collect();
+    for name in &running {
+        image::docker_output(&["stop", name])
+            .map_err(|e| format!("Failed to stop {}: {}", name, e))?;
+    }
+    Ok(running)
+}
+
+fn read_tail(path: &Path) -> Option<(String, bool)> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let truncated = len > LOG_TAIL_BYTES;
+    if truncated {
+        file.seek(SeekFrom::Start(len - LOG_TAIL_BYTES)).ok()?;
+    }
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    Some((String::from_utf8_lossy(&bytes).to_string(), truncated))
+}
+
+/// The group's most recent container run logs, newest first.
+pub(crate) fn logs(
+    data_dir: &Path,
+    group_id: &str,
+    count: Option<usize>,
+) -> Result<Vec<GroupLog>, String> {
+    let dir = attachments::group_dir(data_dir, group_id)?.join("logs");
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Ok(Vec::new());
+    };
+    let mut files: Vec<(SystemTime, String)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !name.starts_with("container-") {
+                return None;
+            }
+            Some((entry.metadata().ok()?.modified().ok()?, name))
+        })
+        .collect();
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    Ok(files
+        .into_iter()
+        .take(count.unwrap_or(DEFAULT_LOG_COUNT))
+        .filter_map(|(modified, file)| {
+            let (content, truncated) = read_tail(&dir.join(&file))?;
+            Some(GroupLog {
+                modified: modified
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0),
+                file,
+                content,
+                truncated,
+            })
+        })
+        .collect())
+}
+
+/// Rows of a read-only query against the store, as JSON objects. Each
+/// `(name, value)` in `params` is bound as `@name`, passed as a hex blob so
+/// no value needs quoting, and read back with `CAST(@name AS TEXT)`.
+fn query(db: &Path, params: &[(&str, &str)], sql: &str) -> Result<serde_json::Value, String> {
+    let mut command = Command::new("sqlite3");
+    command.args(["-readonly", "-json"]).arg(db);
+    for (name, value) in params {
+        let hex: String = value.bytes().map(|b| format!("{:02x}", b)).collect();
+        command.arg(format!(".param set @{} X'{}'", name, hex));
+    }
+    let output = process::runner()
+        .output(command.arg(sql).stdin(Stdio::null()))
+        .map_err(|e| format!("Failed to run sqlite3: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Store query failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    // No rows prints nothing at all
+    if text.trim().is_empty() {
+        return Ok(serde_json::Value::Array(Vec::new()));
+    }
+    serde_json::from_str(&text).map_err(|e| format!("Invalid store query output: {}", e))
+}
+
+/// The group's rows from the store: its registration, chats and messages,
+/// scheduled tasks with their run logs, and agent session.
+fn store_entries(data_dir: &Path, folder: &str) -> Result<serde_json::Value, String> {
+    let db = data_dir.join(STORE_DB);
+    if !db.exists() {
+        return Ok(serde_json::Value::Null);
+    }
+    const FOLDER: &str = "CAST(@folder AS TEXT)";
+    let jids = format!(
+        "(SELECT jid FROM registered_groups WHERE folder = {})",
+        FOLDER
+    );
+    let tasks = format!(
+        "(SELECT id FROM scheduled_tasks WHERE group_folder = {})",
+        FOLDER
+    );
+    let rows = |sql: &str| query(&db, &[("folder", folder)], sql);
+    Ok(serde_json::json!({
+        "registeredGroups": rows(&format!("SELECT * FROM registered_groups WHERE folder = {}", FOLDER))?,
+        "chats": rows(&format!("SELECT * FROM chats WHERE jid IN {}", jids))?,
+        "messages": rows(&format!("SELECT * FROM messages WHERE chat_jid IN {} ORDER BY timestamp", jids))?,
+        "scheduledTasks": rows(&format!("SELECT * FROM scheduled_tasks WHERE group_folder = {}", FOLDER))?,
+        "taskRunLogs": rows(&format!("SELECT * FROM task_run_logs WHERE task_id IN {} ORDER BY run_at", tasks))?,
+        "sessions": rows(&format!("SELECT * FROM sessions WHERE group_folder = {}", FOLDER))?,
+    }))
+}
+
+pub(crate) fn default_export_path(data_dir: &Path, group_id: &str) -> PathBuf {
+    data_dir
+        .join(EXPORTS_DIR)
+        .join(format!("{}-{}.zip", group_id, backup::timestamp()))
+}
+
+/// Package a group for archiving or sharing: a zip with its folder under
+/// `groups/` and its store rows in `store.json`.
+pub(crate) fn export(
+    data_dir: &Path,
+    group_id: &str,
+    dest: &Path,
+    task: &TaskHandle,
+) -> Result<PathBuf, String> {
+    let dir = attachments::group_dir(data_dir, group_id)?;
+    if !dir.is_dir() {
+        return Err(format!("No such group: {}", group_id));
+    }
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let _ = std::fs::remove_file(dest);
+
+    task.progress(Some(0.0), "Reading the store");
+    let entries = store_entries(data_dir, group_id)?;
+    let staging = data_dir.join(format!(".export-{}", group_id));
+    std::fs::create_dir_all(&staging)
+        .map_err(|e| format!("Failed to create {}: {}", staging.display(), e))?;
+
+    let result = (|| {
+        let store_json = staging.join("store.json");
+        let text = serde_json::to_string_pretty(&entries)
+            .map_err(|e| format!("Failed to encode the store rows: {}", e))?;
+        std::fs::write(&store_json, text)
+            .map_err(|e| format!("Failed to write {}: {}", store_json.display(), e))?;
+        if task.is_cancelled() {
+            return Err("Export cancelled".to_string());
+        }
+
+        task.progress(Some(0.3), format!("Archiving groups/{}", group_id));
+        let zips = [
+            (
+                data_dir.to_path_buf(),
+                vec!["-r", "-q", "-y"],
+                format!("groups/{}", group_id),
+            ),
+            // -j: store.json at the top of the archive
+            (staging.clone(), vec!["-q", "-j"], "store.json".to_string()),
+        ];
+        for (cwd, flags, entry) in zips {
+            let output = process::runner()
+                .output(
+                    Command::new("zip")
+                        .args(flags)
+                        .arg(dest)
+                        .arg(&entry)
+                        .current_dir(cwd),
+                )
+                .map_err(|e| format!("Failed to run zip: {}", e))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "Failed to archive {}: {}",
+                    entry,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+        }
+        Ok(dest.to_path_buf())
+    })();
+
+    let _ = std::fs::remove_dir_all(&staging);
+    if result.is_err() {
+        let _ = std::fs::remove_file(dest);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the real sqlite3 for every command that goes through the runner.
+    fn pass_through() {
+        process::fake::script(|argv| {
+            Command::new(&argv[0])
+                .args(&argv[1..])
+                .output()
+                .expect("sqlite3")
+        });
+    }
+
+    fn store_with(dir: &Path, folders: &[&str]) {
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir.join("store")).unwrap();
+        let mut sql = String::from(
+            "CREATE TABLE registered_groups (jid TEXT, folder TEXT);
+             CREATE TABLE chats (jid TEXT);
+             CREATE TABLE messages (chat_jid TEXT, timestamp TEXT);
+             CREATE TABLE scheduled_tasks (id TEXT, group_folder TEXT);
+             CREATE TABLE task_run_logs (task_id TEXT, run_at TEXT);
+             CREATE TABLE sessions (group_folder TEXT);",
+        );
+        for (i, folder) in folders.iter().enumerate() {
+            let folder = folder.replace('\'', "''");
+            sql.push_str(&format!(
+                "INSERT INTO registered_groups VALUES ('jid{i}', '{folder}');
+                 INSERT INTO chats VALUES ('jid{i}');
+                 INSERT INTO messages VALUES ('jid{i}', '{i}');
+                 INSERT INTO sessions VALUES ('{folder}');"
+            ));
+        }
+        let status = Command::new("sqlite3")
+            .arg(dir.join(STORE_DB))
+            .arg(sql)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn folder_is_bound_not_spliced_into_the_sql() {
+        let _turn = process::fake::install();
+        process::fake::script(|_| process::fake::exited(0, ""));
+        let dir = std::env::temp_dir().join(format!("nanoclaw-bind-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("store")).unwrap();
+        std::fs::write(dir.join(STORE_DB), "").unwrap();
+
+        store_entries(&dir, "o'brien").unwrap();
+        let commands = process::fake::commands();
+        assert_eq!(commands.len(), 6);
+        for argv in commands {
+            assert_eq!(argv[4], ".param set @folder X'6f27627269656e'");
+            assert!(!argv[5].contains("brien"));
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn folders_with_quotes_only_match_themselves() {
+        let _turn = process::fake::install();
+        pass_through();
+        let dir = std::env::temp_dir().join(format!("nanoclaw-store-{}", std::process::id()));
+        store_with(&dir, &["o'brien", "x' OR '1'='1", "team"]);
+
+        let entries = store_entries(&dir, "o'brien").unwrap();
+        assert_eq!(
+            entries["registeredGroups"],
+            serde_json::json!([{ "jid": "jid0", "folder": "o'brien" }])
+        );
+        assert_eq!(entries["messages"].as_array().unwrap().len(), 1);
+        assert_eq!(entries["sessions"].as_array().unwrap().len(), 1);
+
+        let entries = store_entries(&dir, "x' OR '1'='1").unwrap();
+        assert_eq!(entries["chats"], serde_json::json!([{ "jid": "jid1" }]));
+
+        let entries = store_entries(&dir, "missing").unwrap();
+        assert_eq!(entries["registeredGroups"], serde_json::json!([]));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
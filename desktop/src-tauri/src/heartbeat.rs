@@ -0,0 +1,81 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::SystemTime;
+
+use serde::Serialize;
+use tauri::AppHandle;
+
+use crate::backend::BackendStatus;
+
+/// Rewritten by the backend manager on every health tick, so tools outside
+/// the app can find the running backend and tell a live shell (fresh
+/// `updatedAt`) from a crashed one.
+const HEARTBEAT_FILE: &str = "status.json";
+
+/// Set once the shell is quitting; late ticks mustn't bring the file back.
+static CLEARED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Heartbeat {
+    /// The shell's pid.
+    pid: u32,
+    backend_pid: Option<u32>,
+    /// `starting`, `ready`, `stopping` or `stopped`.
+    state: &'static str,
+    base_url: String,
+    port: u16,
+    remote: bool,
+    version: String,
+    /// Seconds since the epoch.
+    updated_at: u64,
+}
+
+fn state(status: &BackendStatus) -> &'static str {
+    if status.stopping {
+        "stopping"
+    } else if status.ready {
+        "ready"
+    } else if status.running {
+        "starting"
+    } else {
+        "stopped"
+    }
+}
+
+pub(crate) fn write(app: &AppHandle, status: &BackendStatus) {
+    if CLEARED.load(Ordering::SeqCst) {
+        return;
+    }
+    let heartbeat = Heartbeat {
+        pid: std::process::id(),
+        backend_pid: status.pid,
+        state: state(status),
+        base_url: crate::backend_base_url(),
+        port: crate::backend_port(),
+        remote: crate::remote_backend().is_some(),
+        version: app.package_info().version.to_string(),
+        updated_at: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default(),
+    };
+    let Ok(content) = serde_json::to_string_pretty(&heartbeat) else {
+        return;
+    };
+    // Readers never see a half-written file
+    let data = crate::user_data_dir(app);
+    let tmp = data.join(format!("{}.tmp", HEARTBEAT_FILE));
+    if let Err(e) = std::fs::write(&tmp, content + "\n")
+        .and_then(|_| std::fs::rename(&tmp, data.join(HEARTBEAT_FILE)))
+    {
+        eprintln!("Failed to write heartbeat: {}", e);
+    }
+}
+
+/// Remove the heartbeat when the shell quits, so it isn't mistaken for a
+/// running app.
+pub(crate) fn clear(data_dir: &Path) {
+    CLEARED.store(true, Ordering::SeqCst);
+    let _ = std::fs::remove_file(data_dir.join(HEARTBEAT_FILE));
+}
@@ -0,0 +1,518 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::process;
+
+pub(crate) struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn json(&self) -> Result<serde_json::Value, String> {
+        serde_json::from_slice(&self.body).map_err(|e| format!("Invalid JSON response: {}", e))
+    }
+}
+
+/// Minimal HTTP/1.1 client for talking to the local backend.
+/// Sends `Connection: close` and reads until EOF, so no keep-alive handling is needed.
+pub(crate) fn request(
+    method: &str,
+    host: &str,
+    port: u16,
+    path: &str,
+    headers: &[(String, String)],
+    body: Option<&[u8]>,
+    timeout: Duration,
+) -> Result<HttpResponse, String> {
+    let addr = format!("{}:{}", host, port);
+    let sockets: Vec<_> = addr
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", addr, e))?
+        .collect();
+
+    let mut last_err = format!("No addresses resolved for {}", addr);
+    for socket in sockets {
+        let mut stream = match TcpStream::connect_timeout(&socket, timeout) {
+            Ok(s) => s,
+            Err(e) => {
+                last_err = format!("Failed to connect to {}: {}", socket, e);
+                continue;
+            }
+        };
+
+        let _ = stream.set_read_timeout(Some(timeout));
+        let _ = stream.set_write_timeout(Some(timeout));
+
+        match exchange(&mut stream, method, host, path, headers, body) {
+            Ok(response) => return Ok(response),
+            Err(e) => last_err = e,
+        }
+    }
+
+    Err(last_err)
+}
+
+/// Same as `request`, over the backend's unix domain socket.
+#[cfg(unix)]
+pub(crate) fn request_unix(
+    socket: &Path,
+    method: &str,
+    path: &str,
+    headers: &[(String, String)],
+    body: Option<&[u8]>,
+    timeout: Duration,
+) -> Result<HttpResponse, String> {
+    let mut stream = std::os::unix::net::UnixStream::connect(socket)
+        .map_err(|e| format!("Failed to connect to {}: {}", socket.display(), e))?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+    exchange(&mut stream, method, "localhost", path, headers, body)
+}
+
+fn exchange<S: Read + Write>(
+    stream: &mut S,
+    method: &str,
+    host: &str,
+    path: &str,
+    headers: &[(String, String)],
+    body: Option<&[u8]>,
+) -> Result<HttpResponse, String> {
+    write_request(stream, method, host, path, headers, body)?;
+
+    let mut raw = Vec::new();
+    stream
+        .read_to_end(&mut raw)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+
+    parse_response(&raw)
+}
+
+/// Headers `write_request` sets itself; a second copy from the caller could
+/// make the backend read the message differently than we framed it.
+const FRAMING_HEADERS: &[&str] = &["host", "connection", "content-length", "transfer-encoding"];
+
+/// Refuse anything that could end the request line or a header early and
+/// smuggle in a request of its own: CR, LF or NUL in the path or a header,
+/// spaces in the path, header names that aren't HTTP tokens, and framing
+/// headers we set ourselves.
+pub(crate) fn validate_request(path: &str, headers: &[(String, String)]) -> Result<(), String> {
+    let breaks_line = |c: char| matches!(c, '\r' | '\n' | '\0');
+    if path.is_empty() || path.contains(|c: char| breaks_line(c) || c == ' ') {
+        return Err(format!("Invalid request path: {:?}", path));
+    }
+    for (key, val) in headers {
+        let is_token = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+        if key.is_empty() || !key.chars().all(is_token) {
+            return Err(format!("Invalid header name: {:?}", key));
+        }
+        if val.contains(breaks_line) {
+            return Err(format!("Invalid value for header {}", key));
+        }
+        if FRAMING_HEADERS.contains(&key.to_ascii_lowercase().as_str()) {
+            return Err(format!("Header {} is set by the shell", key));
+        }
+    }
+    Ok(())
+}
+
+fn write_request<S: Write>(
+    stream: &mut S,
+    method: &str,
+    host: &str,
+    path: &str,
+    headers: &[(String, String)],
+    body: Option<&[u8]>,
+) -> Result<(), String> {
+    validate_request(path, headers)?;
+    let mut head = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+        method, path, host
+    );
+    for (key, val) in headers {
+        head.push_str(&format!("{}: {}\r\n", key, val));
+    }
+    if let Some(body) = body {
+        head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    head.push_str("\r\n");
+
+    stream
+        .write_all(head.as_bytes())
+        .map_err(|e| format!("Failed to send request: {}", e))?;
+    if let Some(body) = body {
+        stream
+            .write_all(body)
+            .map_err(|e| format!("Failed to send request body: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Response whose body is read as it arrives, for server-sent event streams.
+pub(crate) struct StreamingResponse {
+    pub status: u16,
+    pub body: Box<dyn BufRead + Send>,
+}
+
+/// Like `request`, but returns once the headers are in. No read timeout is
+/// set, since streams can stay quiet for a long time between events.
+pub(crate) fn request_streaming(
+    method: &str,
+    host: &str,
+    port: u16,
+    path: &str,
+    headers: &[(String, String)],
+    body: Option<&[u8]>,
+    connect_timeout: Duration,
+) -> Result<StreamingResponse, String> {
+    let addr = format!("{}:{}", host, port);
+    let mut last_err = format!("No addresses resolved for {}", addr);
+    for socket in addr
+        .to_socket_addrs()
+        .map_err(|e| format!("Failed to resolve {}: {}", addr, e))?
+    {
+        match TcpStream::connect_timeout(&socket, connect_timeout) {
+            Ok(stream) => return open_stream(stream, method, host, path, headers, body),
+            Err(e) => last_err = format!("Failed to connect to {}: {}", socket, e),
+        }
+    }
+    Err(last_err)
+}
+
+#[cfg(unix)]
+pub(crate) fn request_unix_streaming(
+    socket: &Path,
+    method: &str,
+    path: &str,
+    headers: &[(String, String)],
+    body: Option<&[u8]>,
+) -> Result<StreamingResponse, String> {
+    let stream = std::os::unix::net::UnixStream::connect(socket)
+        .map_err(|e| format!("Failed to connect to {}: {}", socket.display(), e))?;
+    open_stream(stream, method, "localhost", path, headers, body)
+}
+
+fn open_stream<S: Read + Write + Send + 'static>(
+    mut stream: S,
+    method: &str,
+    host: &str,
+    path: &str,
+    headers: &[(String, String)],
+    body: Option<&[u8]>,
+) -> Result<StreamingResponse, String> {
+    write_request(&mut stream, method, host, path, headers, body)?;
+
+    let mut reader = BufReader::new(stream);
+    let mut status_line = String::new();
+    reader
+        .read_line(&mut status_line)
+        .map_err(|e| format!("Failed to read response: {}", e))?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|v| v.parse::<u16>().ok())
+        .ok_or_else(|| format!("Malformed HTTP status line: {}", status_line.trim()))?;
+
+    let mut chunked = false;
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| format!("Failed to read response headers: {}", e))?;
+        if read == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((key, val)) = line.split_once(':') {
+            chunked |= key.trim().eq_ignore_ascii_case("transfer-encoding")
+                && val.to_ascii_lowercase().contains("chunked");
+        }
+    }
+
+    let body: Box<dyn BufRead + Send> = if chunked {
+        Box::new(BufReader::new(ChunkedReader {
+            inner: reader,
+            remaining: 0,
+            started: false,
+            done: false,
+        }))
+    } else {
+        Box::new(reader)
+    };
+    Ok(StreamingResponse { status, body })
+}
+
+/// Incremental decoder for `Transfer-Encoding: chunked` bodies.
+struct ChunkedReader<R> {
+    inner: R,
+    remaining: usize,
+    started: bool,
+    done: bool,
+}
+
+impl<R: BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        if self.remaining == 0 {
+            let mut line = String::new();
+            if self.started {
+                // CRLF closing the previous chunk
+                self.inner.read_line(&mut line)?;
+                line.clear();
+            }
+            self.started = true;
+            if self.inner.read_line(&mut line)? == 0 {
+                self.done = true;
+                return Ok(0);
+            }
+            let size_text = line.split(';').next().unwrap_or("").trim();
+            let size = usize::from_str_radix(size_text, 16).map_err(|_| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Invalid chunk size: {}", size_text),
+                )
+            })?;
+            if size == 0 {
+                self.done = true;
+                return Ok(0);
+            }
+            self.remaining = size;
+        }
+        let limit = buf.len().min(self.remaining);
+        let read = self.inner.read(&mut buf[..limit])?;
+        if read == 0 {
+            self.done = true;
+        }
+        self.remaining -= read;
+        Ok(read)
+    }
+}
+
+fn parse_response(raw: &[u8]) -> Result<HttpResponse, String> {
+    let header_end = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .ok_or_else(|| "Malformed HTTP response: missing header terminator".to_string())?;
+
+    let head = String::from_utf8_lossy(&raw[..header_end]);
+    let mut lines = head.lines();
+    let status_line = lines.next().unwrap_or("");
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|v| v.parse::<u16>().ok())
+        .ok_or_else(|| format!("Malformed HTTP status line: {}", status_line))?;
+
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, val)| (key.trim().to_string(), val.trim().to_string()))
+        .collect();
+    let chunked = headers.iter().any(|(key, val)| {
+        key.eq_ignore_ascii_case("transfer-encoding")
+            && val.to_ascii_lowercase().contains("chunked")
+    });
+
+    let payload = &raw[header_end + 4..];
+    let body = if chunked {
+        decode_chunked(payload)?
+    } else {
+        payload.to_vec()
+    };
+
+    Ok(HttpResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+fn decode_chunked(mut data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = data
+            .windows(2)
+            .position(|w| w == b"\r\n")
+            .ok_or_else(|| "Malformed chunked body".to_string())?;
+        let size_text = String::from_utf8_lossy(&data[..line_end]);
+        let size_text = size_text.split(';').next().unwrap_or("").trim();
+        let size = usize::from_str_radix(size_text, 16)
+            .map_err(|_| format!("Invalid chunk size: {}", size_text))?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Ok(body);
+        }
+        if data.len() < size {
+            return Err("Truncated chunked body".to_string());
+        }
+        body.extend_from_slice(&data[..size]);
+        data = data.get(size + 2..).unwrap_or(&[]);
+    }
+}
+
+/// The system curl, trusting the extra CA bundle if one is configured.
+fn curl() -> Command {
+    let mut command = Command::new("curl");
+    if let Some(bundle) = crate::certs::curl_bundle() {
+        command.arg("--cacert").arg(bundle);
+    }
+    command
+}
+
+/// Fetch an external (usually HTTPS) URL with the system curl, which brings
+/// TLS and the user's proxy environment without extra dependencies.
+pub(crate) fn curl_get(url: &str, timeout: Duration) -> Result<Vec<u8>, String> {
+    let output = process::runner()
+        .output(curl().args(["-fsSL", "--max-time", &timeout.as_secs().to_string(), url]))
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "GET {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// `curl_get` with request headers, which go over stdin so credentials in
+/// them don't show up in `ps`.
+pub(crate) fn curl_get_with_headers(
+    url: &str,
+    headers: &[(String, String)],
+    timeout: Duration,
+) -> Result<Vec<u8>, String> {
+    let header_lines: String = headers
+        .iter()
+        .map(|(key, val)| format!("{}: {}\n", key, val))
+        .collect();
+    let output = process::output_with_stdin(
+        curl().args([
+            "-fsSL",
+            "--max-time",
+            &timeout.as_secs().to_string(),
+            "-H",
+            "@-",
+            url,
+        ]),
+        header_lines.as_bytes(),
+    )
+    .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "GET {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Reachability probe: the HTTP status of a GET to `url`, whatever it is.
+/// Errors only when no HTTP response arrived (DNS, TLS, connect, timeout).
+pub(crate) fn curl_status(url: &str, timeout: Duration) -> Result<u16, String> {
+    let output = process::runner()
+        .output(curl().args([
+            "-sS",
+            "-o",
+            "/dev/null",
+            "-w",
+            "%{http_code}",
+            "--max-time",
+            &timeout.as_secs().to_string(),
+            url,
+        ]))
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    let status = String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .unwrap_or(0);
+    if !output.status.success() || status == 0 {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(status)
+}
+
+/// POST a JSON body to an external URL, failing on a non-2xx status.
+pub(crate) fn curl_post_json(url: &str, body: &str, timeout: Duration) -> Result<(), String> {
+    let output = process::output_with_stdin(
+        curl().args([
+            "-fsS",
+            "--max-time",
+            &timeout.as_secs().to_string(),
+            "-H",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+            url,
+        ]),
+        body.as_bytes(),
+    )
+    .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "POST {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// POST a form-encoded body to an external URL and return the response body,
+/// failing on a non-2xx status. The body goes over stdin so secrets in it
+/// don't show up in `ps`.
+pub(crate) fn curl_post_form(url: &str, body: &str, timeout: Duration) -> Result<Vec<u8>, String> {
+    let output = process::output_with_stdin(
+        curl().args([
+            "-fsS",
+            "--max-time",
+            &timeout.as_secs().to_string(),
+            "-H",
+            "Accept: application/json",
+            "-H",
+            "Content-Type: application/x-www-form-urlencoded",
+            "--data-binary",
+            "@-",
+            url,
+        ]),
+        body.as_bytes(),
+    )
+    .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "POST {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Download an external URL straight to a file.
+pub(crate) fn curl_download(url: &str, dest: &Path, timeout: Duration) -> Result<(), String> {
+    let output = process::runner()
+        .output(
+            curl()
+                .args(["-fsSL", "--max-time", &timeout.as_secs().to_string(), "-o"])
+                .arg(dest)
+                .arg(url),
+        )
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Download of {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
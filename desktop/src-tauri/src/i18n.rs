@@ -0,0 +1,241 @@
+#[cfg(target_os = "macos")]
+use std::process::Command;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Locale {
+    En,
+    Zh,
+}
+
+/// (key, English, Simplified Chinese)
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("tray.open_chat", "Open Chat", "打开聊天"),
+    ("tray.open_in_browser", "Open in Browser", "在浏览器中打开"),
+    ("tray.restart_backend", "Restart Backend", "重启后端"),
+    ("tray.copy_api_url", "Copy API URL", "复制 API 地址"),
+    ("tray.copy_api_token", "Copy API Token…", "复制 API 令牌…"),
+    ("tray.always_on_top", "Keep Window on Top", "窗口置顶"),
+    ("tray.check_updates", "Check for Updates…", "检查更新…"),
+    (
+        "tray.remove_all_data",
+        "Remove All NanoClaw Data…",
+        "移除所有 NanoClaw 数据…",
+    ),
+    ("tray.quit", "Quit", "退出"),
+    ("tray.backend_stats", "Backend Status", "后端状态"),
+    ("tray.uptime", "Uptime: {}", "运行时长：{}"),
+    ("tray.not_running", "Not running", "未运行"),
+    ("tray.restarts", "Restarts: {}", "重启次数：{}"),
+    ("tray.crashes", "Crashes: {}", "崩溃次数：{}"),
+    ("tray.last_crash", "Last crash: {} ago", "上次崩溃：{}前"),
+    ("tray.no_crashes", "No crashes", "无崩溃"),
+    ("tray.recent_chats", "Recent Chats", "最近聊天"),
+    ("menu.about", "About NanoClaw", "关于 NanoClaw"),
+    ("menu.settings", "Settings…", "设置…"),
+    ("menu.services", "Services", "服务"),
+    ("menu.hide", "Hide NanoClaw", "隐藏 NanoClaw"),
+    ("menu.hide_others", "Hide Others", "隐藏其他"),
+    ("menu.show_all", "Show All", "全部显示"),
+    ("menu.quit", "Quit NanoClaw", "退出 NanoClaw"),
+    ("menu.edit", "Edit", "编辑"),
+    ("menu.undo", "Undo", "撤销"),
+    ("menu.redo", "Redo", "重做"),
+    ("menu.cut", "Cut", "剪切"),
+    ("menu.copy", "Copy", "拷贝"),
+    ("menu.paste", "Paste", "粘贴"),
+    ("menu.select_all", "Select All", "全选"),
+    ("menu.view", "View", "显示"),
+    ("menu.fullscreen", "Enter Full Screen", "进入全屏幕"),
+    ("menu.zoom_in", "Zoom In", "放大"),
+    ("menu.zoom_out", "Zoom Out", "缩小"),
+    ("menu.actual_size", "Actual Size", "实际大小"),
+    ("menu.window", "Window", "窗口"),
+    ("menu.minimize", "Minimize", "最小化"),
+    ("menu.maximize", "Zoom", "缩放"),
+    ("menu.main_window", "NanoClaw", "NanoClaw"),
+    ("menu.logs", "Logs", "日志"),
+    ("menu.close_window", "Close Window", "关闭窗口"),
+    ("tray.running_tasks", "Running Tasks", "运行中的任务"),
+    ("notify.reply_ready", "Reply ready", "回复已完成"),
+    ("notify.reply_failed", "Reply failed", "回复失败"),
+    (
+        "notify.backend_crashed",
+        "Backend stopped unexpectedly",
+        "后端意外停止",
+    ),
+    (
+        "notify.backend_crashed_body",
+        "Open the logs to see what happened.",
+        "打开日志查看详情。",
+    ),
+    (
+        "notify.backend_restarted",
+        "Backend restarted",
+        "后端已重启",
+    ),
+    (
+        "notify.container_reaped",
+        "Stopped an idle agent container",
+        "已停止空闲的智能体容器",
+    ),
+    (
+        "notify.backend_restarted_memory",
+        "It was using more memory than the watchdog allows.",
+        "后端占用内存超过了看门狗的上限。",
+    ),
+    (
+        "crash_loop.title",
+        "The backend keeps crashing",
+        "后端反复崩溃",
+    ),
+    (
+        "crash_loop.body",
+        "NanoClaw stopped restarting it. Its last output:",
+        "NanoClaw 已停止自动重启。最后的输出：",
+    ),
+    ("crash_loop.open_logs", "Open Logs", "打开日志"),
+    ("crash_loop.diagnostics", "Run Diagnostics", "运行诊断"),
+    ("crash_loop.close", "Close", "关闭"),
+    ("diagnostics.title", "Diagnostics", "诊断"),
+    ("diagnostics.all_passed", "All checks passed.", "所有检查均已通过。"),
+    (
+        "location.title",
+        "Move NanoClaw to Applications?",
+        "将 NanoClaw 移到“应用程序”？",
+    ),
+    (
+        "location.body",
+        "NanoClaw is running from the disk image or a temporary location, where it can't keep its files or update itself.",
+        "NanoClaw 正在从磁盘映像或临时位置运行，无法保存文件或自动更新。",
+    ),
+    ("location.move", "Move and Relaunch", "移动并重新打开"),
+    ("location.not_now", "Not Now", "暂不"),
+    (
+        "location.move_failed",
+        "NanoClaw couldn't be moved. Drag it to Applications in Finder instead.",
+        "无法移动 NanoClaw，请在访达中将其拖到“应用程序”。",
+    ),
+    (
+        "automation.send_title",
+        "Send a prompt from a link?",
+        "通过链接发送提示？",
+    ),
+    (
+        "automation.send_body",
+        "A link asked NanoClaw to send this prompt. Allow it only if you started it, e.g. from a shortcut.",
+        "有链接请求 NanoClaw 发送以下提示。仅在由你发起（例如通过快捷指令）时才允许。",
+    ),
+    ("automation.send", "Send", "发送"),
+    ("automation.cancel", "Cancel", "取消"),
+    (
+        "data_lock.title",
+        "NanoClaw is already running",
+        "NanoClaw 已在运行",
+    ),
+    (
+        "data_lock.body",
+        "Another NanoClaw is using the same data folder. Quit it first, or start this one with a different data folder.",
+        "另一个 NanoClaw 正在使用同一个数据文件夹。请先退出它，或为这个实例指定其他数据文件夹。",
+    ),
+    (
+        "uninstall.confirm_title",
+        "Remove all NanoClaw data?",
+        "移除所有 NanoClaw 数据？",
+    ),
+    (
+        "uninstall.confirm_body",
+        "This stops NanoClaw and deletes its conversations, settings, backups, agent containers and images, login items and saved tokens. It can't be undone. Afterwards, drag NanoClaw to the Trash to finish uninstalling.",
+        "这将停止 NanoClaw，并删除其对话、设置、备份、智能体容器和镜像、登录项以及保存的令牌。此操作无法撤销。之后将 NanoClaw 拖到废纸篓即可完成卸载。",
+    ),
+    ("uninstall.remove", "Remove Everything", "全部移除"),
+    (
+        "copy_token.title",
+        "Copy the API token?",
+        "复制 API 令牌？",
+    ),
+    (
+        "copy_token.body",
+        "Anyone with this token can drive NanoClaw's agents through its API. Paste it only into tools you trust.",
+        "持有此令牌的人可以通过 API 操控 NanoClaw 的智能体。请只粘贴到你信任的工具中。",
+    ),
+    ("copy_token.copy", "Copy", "复制"),
+    ("copy_token.cancel", "Cancel", "取消"),
+    ("uninstall.cancel", "Cancel", "取消"),
+    (
+        "uninstall.done_title",
+        "NanoClaw data removed",
+        "NanoClaw 数据已移除",
+    ),
+    (
+        "uninstall.done_body",
+        "NanoClaw will quit now. What was removed:",
+        "NanoClaw 即将退出。已移除的内容：",
+    ),
+];
+
+impl Locale {
+    /// Parse a BCP 47 / POSIX locale tag such as "zh-Hans", "zh_CN.UTF-8" or "en_US".
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let lang = tag
+            .split(['-', '_', '.'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Locale::En),
+            "zh" => Some(Locale::Zh),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Zh => "zh",
+        }
+    }
+}
+
+/// Best-effort system locale. GUI apps on macOS don't get LANG from the shell,
+/// so ask the global defaults domain first and fall back to the usual env vars.
+fn system_locale() -> Option<Locale> {
+    #[cfg(target_os = "macos")]
+    {
+        if let Ok(output) = crate::process::runner().output(Command::new("defaults").args([
+            "read",
+            "-g",
+            "AppleLocale",
+        ])) {
+            if output.status.success() {
+                let tag = String::from_utf8_lossy(&output.stdout);
+                if let Some(locale) = Locale::from_tag(tag.trim()) {
+                    return Some(locale);
+                }
+            }
+        }
+    }
+
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .find_map(|tag| Locale::from_tag(&tag))
+}
+
+/// Resolve the UI locale: explicit preference first, then system, then English.
+pub(crate) fn resolve(preference: Option<&str>) -> Locale {
+    preference
+        .and_then(Locale::from_tag)
+        .or_else(system_locale)
+        .unwrap_or(Locale::En)
+}
+
+/// Look up a translated string, falling back to English and then to the key itself.
+pub(crate) fn tr(locale: Locale, key: &'static str) -> &'static str {
+    match MESSAGES.iter().find(|(k, _, _)| *k == key) {
+        Some((_, en, zh)) => match locale {
+            Locale::En => en,
+            Locale::Zh => zh,
+        },
+        None => key,
+    }
+}
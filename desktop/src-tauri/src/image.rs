@@ -0,0 +1,418 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use serde::Serialize;
+use specta::Type;
+
+use crate::error::ShellError;
+use crate::settings::{ImageBuildOptions, ImageTrust, UpdateChannel};
+use crate::tasks::TaskHandle;
+use crate::{integrity, path_env, process};
+
+/// Tag the backend runs agent containers from.
+const LOCAL_IMAGE: &str = "nanoclaw-agent-agno:latest";
+/// Published images are signed keylessly by the release workflow.
+const SIGNER_IDENTITY: &str = "^https://github.com/Aixtrade/nanoclaw/.github/workflows/";
+const SIGNER_ISSUER: &str = "https://token.actions.githubusercontent.com";
+/// Image label holding the hash of the container-agno sources it was built from.
+const SOURCE_HASH_LABEL: &str = "com.nanoclaw.source-hash";
+/// Mounted over the image at run time (agent-runner sources) or synced into
+/// the group (skills), so changes there don't need a rebuild.
+const RUNTIME_PATHS: &[&str] = &["agent-runner/src", "skills", "skills-user"];
+/// Build output lines kept for the error message when a build fails.
+const BUILD_ERROR_TAIL: usize = 20;
+
+/// Prebuilt images are published per channel as `<repo>:stable` / `<repo>:beta`.
+const REMOTE_REPOSITORY: &str = "ghcr.io/aixtrade/nanoclaw-agent-agno";
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ImageUpdateInfo {
+    pub reference: String,
+    pub local_digest: Option<String>,
+    pub remote_digest: String,
+    pub available: bool,
+}
+
+fn remote_reference(channel: UpdateChannel) -> String {
+    format!("{}:{}", REMOTE_REPOSITORY, channel.as_str())
+}
+
+pub(crate) fn docker_output(args: &[&str]) -> Result<String, String> {
+    let output = process::runner()
+        .output(Command::new("docker").args(args).stdin(Stdio::null()))
+        .map_err(|e| format!("Failed to run docker: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// What `remove_all` removed.
+#[derive(Default)]
+pub(crate) struct Removed {
+    pub containers: Vec<String>,
+    pub images: Vec<String>,
+}
+
+/// Force-remove every `nanoclaw-*` container and agent image, for a factory
+/// reset. Docker being absent or stopped just means there's nothing to remove.
+pub(crate) fn remove_all() -> Result<Removed, String> {
+    let mut removed = Removed::default();
+    let Ok(containers) = docker_output(&[
+        "ps",
+        "-a",
+        "--filter",
+        "name=nanoclaw-",
+        "--format",
+        "{{.Names}}",
+    ]) else {
+        return Ok(removed);
+    };
+    for name in containers.lines().filter(|l| !l.is_empty()) {
+        docker_output(&["rm", "-f", name])
+            .map_err(|e| format!("Failed to remove container {}: {}", name, e))?;
+        removed.containers.push(name.to_string());
+    }
+
+    let images = docker_output(&["images", "--format", "{{.Repository}}:{{.Tag}}"])?;
+    for image in images.lines().filter(|image| {
+        let repository = image.split(':').next().unwrap_or("");
+        let name = repository.rsplit('/').next().unwrap_or("");
+        name.starts_with("nanoclaw-")
+    }) {
+        docker_output(&["rmi", "-f", image])
+            .map_err(|e| format!("Failed to remove image {}: {}", image, e))?;
+        removed.images.push(image.to_string());
+    }
+    Ok(removed)
+}
+
+/// Compare the registry digest of the channel's image with the digest the
+/// local copy was pulled at. Locally built images have no repo digest, so
+/// they always report an update as available.
+pub(crate) fn check_update(channel: UpdateChannel) -> Result<ImageUpdateInfo, String> {
+    let reference = remote_reference(channel);
+    let remote_digest = docker_output(&[
+        "buildx",
+        "imagetools",
+        "inspect",
+        "--format",
+        "{{.Manifest.Digest}}",
+        &reference,
+    ])
+    .map_err(|e| format!("Failed to query {}: {}", reference, e))?;
+
+    let local_digest = docker_output(&[
+        "image",
+        "inspect",
+        "--format",
+        "{{range .RepoDigests}}{{println .}}{{end}}",
+        &reference,
+    ])
+    .ok()
+    .and_then(|digests| {
+        digests
+            .lines()
+            .filter_map(|line| line.split_once('@').map(|(_, digest)| digest.to_string()))
+            .next()
+    });
+
+    Ok(ImageUpdateInfo {
+        available: local_digest.as_deref() != Some(remote_digest.as_str()),
+        reference,
+        local_digest,
+        remote_digest,
+    })
+}
+
+pub(crate) fn is_digest(value: &str) -> bool {
+    value
+        .strip_prefix("sha256:")
+        .is_some_and(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Check the release workflow signed `reference` (a digest reference), with
+/// cosign. Without cosign installed this fails only if signatures are required.
+fn verify_signature(reference: &str, trust: &ImageTrust) -> Result<bool, String> {
+    let Some(cosign) = path_env::which("cosign") else {
+        if trust.require_signature {
+            return Err("cosign isn't installed, so the image signature can't be checked".into());
+        }
+        return Ok(false);
+    };
+    let output = process::runner()
+        .output(
+            Command::new(cosign)
+                .args([
+                    "verify",
+                    "--certificate-identity-regexp",
+                    SIGNER_IDENTITY,
+                    "--certificate-oidc-issuer",
+                    SIGNER_ISSUER,
+                    reference,
+                ])
+                .stdin(Stdio::null()),
+        )
+        .map_err(|e| format!("Failed to run cosign: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Signature check failed for {}: {}",
+            reference,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(true)
+}
+
+/// Pull the channel's prebuilt image by digest and tag it for use, once it
+/// passes the checks: it must be the pinned digest if one is set, and carry
+/// the release signature when cosign is available (or required). Returns the
+/// digest that was verified and tagged.
+pub(crate) fn pull(
+    channel: UpdateChannel,
+    trust: &ImageTrust,
+    task: &TaskHandle,
+) -> Result<String, ShellError> {
+    let tag_reference = remote_reference(channel);
+    let digest = match trust.pinned_digest.as_deref().filter(|d| !d.is_empty()) {
+        Some(pinned) if !is_digest(pinned) => {
+            return Err(ShellError::InvalidInput(format!(
+                "Invalid pinned digest: {}",
+                pinned
+            )))
+        }
+        Some(pinned) => pinned.to_string(),
+        None => {
+            task.progress(None, format!("Resolving {}", tag_reference));
+            docker_output(&[
+                "buildx",
+                "imagetools",
+                "inspect",
+                "--format",
+                "{{.Manifest.Digest}}",
+                &tag_reference,
+            ])
+            .map_err(|e| format!("Failed to query {}: {}", tag_reference, e))?
+        }
+    };
+    if !is_digest(&digest) {
+        return Err(format!("Unexpected digest from the registry: {}", digest).into());
+    }
+    // Pulling by digest makes Docker check the content against it
+    let reference = format!("{}@{}", REMOTE_REPOSITORY, digest);
+
+    task.progress(None, "Checking the image signature");
+    if !verify_signature(&reference, trust)? {
+        eprintln!("cosign not found; {} pulled by digest only", reference);
+    }
+    if task.is_cancelled() {
+        return Err(ShellError::Cancelled);
+    }
+
+    task.progress(None, format!("Pulling {}", reference));
+    docker_output(&["pull", &reference])
+        .map_err(|e| format!("Failed to pull {}: {}", reference, e))?;
+    for tag in [LOCAL_IMAGE, tag_reference.as_str()] {
+        docker_output(&["tag", &reference, tag])
+            .map_err(|e| format!("Failed to tag {}: {}", tag, e))?;
+    }
+    Ok(digest)
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let relative = relative.to_string_lossy().to_string();
+        if RUNTIME_PATHS.contains(&relative.as_str()) || relative.ends_with(".DS_Store") {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(root, &path, files);
+        } else {
+            files.push(relative);
+        }
+    }
+}
+
+/// Hash of what goes into the image from `container_dir`: file paths and
+/// contents, in a stable order.
+pub(crate) fn source_hash(container_dir: &Path) -> Result<String, String> {
+    let mut files = Vec::new();
+    collect_files(container_dir, container_dir, &mut files);
+    files.sort();
+    let mut data = Vec::new();
+    for file in files {
+        let content = std::fs::read(container_dir.join(&file))
+            .map_err(|e| format!("Failed to read {}: {}", file, e))?;
+        data.extend_from_slice(file.as_bytes());
+        data.push(0);
+        data.extend_from_slice(&content);
+        data.push(0);
+    }
+    integrity::sha256_hex(&data)
+}
+
+/// Source hash the local image was built from; None when there's no image or
+/// it wasn't built by the app (e.g. pulled).
+pub(crate) fn built_source_hash() -> Option<String> {
+    let format = format!("{{{{index .Config.Labels \"{}\"}}}}", SOURCE_HASH_LABEL);
+    docker_output(&["image", "inspect", "-f", &format, LOCAL_IMAGE])
+        .ok()
+        .filter(|hash| !hash.is_empty() && hash != "<no value>")
+}
+
+/// Whether the local image was built from other container-agno sources than
+/// the bundle's, e.g. after an app update changed them.
+pub(crate) fn is_stale(container_dir: &Path) -> bool {
+    match (built_source_hash(), source_hash(container_dir)) {
+        (Some(built), Ok(current)) => built != current,
+        // Can't tell; don't nag
+        _ => false,
+    }
+}
+
+/// Fraction of build steps done, from plain-progress lines like `#7 [3/6] RUN ...`.
+fn build_step_fraction(line: &str) -> Option<f64> {
+    let start = line.find('[')?;
+    let end = start + line[start..].find(']')?;
+    let step = line[start + 1..end].rsplit(' ').next()?;
+    let (done, total) = step.split_once('/')?;
+    let (done, total): (f64, f64) = (done.parse().ok()?, total.parse().ok()?);
+    (total > 0.0).then(|| (done - 1.0).max(0.0) / total)
+}
+
+/// `docker build` arguments for the agent image with the user's options.
+/// `proxy_env` goes in as Docker's predefined proxy build args, unless the
+/// options set them.
+fn build_args(
+    container_dir: &Path,
+    options: &ImageBuildOptions,
+    proxy_env: &[(String, String)],
+) -> Result<Vec<String>, String> {
+    let mut args: Vec<String> = vec!["build".into(), "--progress=plain".into()];
+    args.push("--label".into());
+    args.push(format!(
+        "{}={}",
+        SOURCE_HASH_LABEL,
+        source_hash(container_dir)?
+    ));
+    for (key, value) in &options.build_args {
+        let valid = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(format!("Invalid build argument name: {}", key));
+        }
+        args.push("--build-arg".into());
+        args.push(format!("{}={}", key, value));
+    }
+    for (key, value) in proxy_env {
+        if !options.build_args.contains_key(key) {
+            args.push("--build-arg".into());
+            args.push(format!("{}={}", key, value));
+        }
+    }
+    if options.no_cache {
+        args.push("--no-cache".into());
+    }
+    args.push("-t".into());
+    args.push(LOCAL_IMAGE.into());
+    if let Some(tag) = options.extra_tag.as_deref().filter(|t| !t.is_empty()) {
+        if tag.starts_with('-') || tag.contains(char::is_whitespace) {
+            return Err(format!("Invalid image tag: {}", tag));
+        }
+        args.push("-t".into());
+        args.push(tag.into());
+    }
+    args.push(".".into());
+    Ok(args)
+}
+
+/// `docker build` the agent image, reporting step progress to the task and
+/// killing the build if the task is cancelled.
+pub(crate) fn build(
+    container_dir: &Path,
+    options: &ImageBuildOptions,
+    proxy_env: &[(String, String)],
+    task: &TaskHandle,
+) -> Result<(), ShellError> {
+    let mut child = process::runner()
+        .spawn(
+            Command::new("docker")
+                .args(build_args(container_dir, options, proxy_env)?)
+                .current_dir(container_dir)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped()),
+        )
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ShellError::DockerUnavailable,
+            _ => ShellError::Other(format!("Failed to run docker build: {}", e)),
+        })?;
+
+    // BuildKit writes its progress to stderr
+    let stderr = child.stderr.take().expect("Failed to capture stderr");
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut tail = VecDeque::with_capacity(BUILD_ERROR_TAIL);
+    let mut handle_line = |line: String| {
+        if let Some(fraction) = build_step_fraction(&line) {
+            task.progress(Some(fraction), line.clone());
+        }
+        if tail.len() == BUILD_ERROR_TAIL {
+            tail.pop_front();
+        }
+        tail.push_back(line);
+    };
+
+    loop {
+        while let Ok(line) = rx.try_recv() {
+            handle_line(line);
+        }
+        if task.is_cancelled() {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(ShellError::Cancelled);
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => {
+                // The reader thread ends at EOF, which closes the channel
+                for line in rx.iter() {
+                    handle_line(line);
+                }
+                if status.success() {
+                    return Ok(());
+                }
+                let log = tail.into_iter().collect::<Vec<_>>().join("\n");
+                if log.contains("Cannot connect to the Docker daemon") {
+                    return Err(ShellError::DockerUnavailable);
+                }
+                return Err(ShellError::BuildFailed { log });
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(200)),
+            Err(e) => {
+                return Err(ShellError::Other(format!(
+                    "Failed to wait for docker build: {}",
+                    e
+                )))
+            }
+        }
+    }
+}
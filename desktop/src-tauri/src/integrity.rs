@@ -0,0 +1,57 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::process;
+
+/// Checksums of everything in `dist/`, in `shasum -a 256` format, written at
+/// build time by scripts/write-dist-manifest.sh.
+const MANIFEST: &str = "SHA256SUMS";
+
+/// Check the backend's `dist/` against its manifest, returning the files that
+/// are missing or modified. A bundle without a manifest (a source checkout,
+/// or a hot update built without one) isn't checked.
+pub(crate) fn verify(dist: &Path) -> Result<(), Vec<String>> {
+    if !dist.join(MANIFEST).exists() {
+        return Ok(());
+    }
+    let output = process::runner().output(
+        Command::new("shasum")
+            .args(["-a", "256", "-c", "--quiet", MANIFEST])
+            .current_dir(dist)
+            .stdin(Stdio::null()),
+    );
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            eprintln!("Skipping bundle check: failed to run shasum: {}", e);
+            return Ok(());
+        }
+    };
+    if output.status.success() {
+        return Ok(());
+    }
+
+    // `./index.js: FAILED`, or `./index.js: FAILED open or read` when missing
+    let files: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_once(": FAILED"))
+        .map(|(path, _)| path.trim_start_matches("./").to_string())
+        .collect();
+    if files.is_empty() {
+        // Nothing listed, so the manifest itself didn't parse
+        return Err(vec![MANIFEST.to_string()]);
+    }
+    Err(files)
+}
+
+/// Hex SHA-256 of `data`, from `shasum` so no hashing crate is needed.
+pub(crate) fn sha256_hex(data: &[u8]) -> Result<String, String> {
+    let output = process::output_with_stdin(Command::new("shasum").args(["-a", "256"]), data)
+        .map_err(|e| format!("Failed to run shasum: {}", e))?;
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .next()
+        .filter(|hex| hex.len() == 64)
+        .map(str::to_string)
+        .ok_or_else(|| "shasum gave no digest".to_string())
+}
@@ -0,0 +1,172 @@
+use std::net::{IpAddr, UdpSocket};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+
+use qrcode::render::svg;
+use qrcode::QrCode;
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+
+use crate::{backend, path_env, process, settings};
+
+/// Bound by the backend in LAN mode: every interface.
+pub(crate) const LAN_HOST: &str = "0.0.0.0";
+/// Bonjour service type companion clients browse for.
+const SERVICE_TYPE: &str = "_nanoclaw._tcp";
+
+/// The `dns-sd -R` (or `avahi-publish`) process holding the registration;
+/// it lasts as long as the process does.
+static ADVERTISER: Mutex<Option<Child>> = Mutex::new(None);
+
+/// What a phone needs to reach this NanoClaw, plus the same as a QR code.
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LanPairing {
+    /// The backend on this machine's LAN address.
+    pub url: String,
+    pub token: String,
+    /// `nanoclaw://pair?url=…&token=…`, what the QR code holds.
+    pub pairing_uri: String,
+    pub qr_svg: String,
+}
+
+/// LAN access is opted into and the backend binds beyond loopback.
+pub(crate) fn is_enabled(prefs: &settings::Settings) -> bool {
+    prefs.allow_lan_access
+        && prefs
+            .backend_host
+            .as_deref()
+            .is_some_and(|host| !crate::is_loopback_host(host))
+}
+
+/// The address other machines reach this one on: the source address of the
+/// default route. Connecting a UDP socket sends nothing.
+fn lan_address() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:80").ok()?;
+    let ip = socket.local_addr().ok()?.ip();
+    (!ip.is_loopback() && !ip.is_unspecified()).then_some(ip)
+}
+
+fn query_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+pub(crate) fn pairing(port: u16, token: &str) -> Result<LanPairing, String> {
+    let ip = lan_address().ok_or("This machine has no LAN address")?;
+    let url = match ip {
+        IpAddr::V4(ip) => format!("http://{}:{}", ip, port),
+        IpAddr::V6(ip) => format!("http://[{}]:{}", ip, port),
+    };
+    let pairing_uri = format!(
+        "nanoclaw://pair?url={}&token={}",
+        query_encode(&url),
+        query_encode(token)
+    );
+    let qr_svg = QrCode::new(pairing_uri.as_bytes())
+        .map_err(|e| format!("Failed to make a QR code: {}", e))?
+        .render::<svg::Color>()
+        .min_dimensions(240, 240)
+        .build();
+    Ok(LanPairing {
+        url,
+        token: token.to_string(),
+        pairing_uri,
+        qr_svg,
+    })
+}
+
+/// This machine's name as its owner knows it, e.g. "Sam's MacBook Air".
+fn instance_name() -> String {
+    let run = |cmd: &mut Command| {
+        process::runner()
+            .output(cmd)
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|name| !name.is_empty())
+    };
+    run(Command::new("scutil").args(["--get", "ComputerName"]))
+        .or_else(|| run(&mut Command::new("hostname")))
+        .unwrap_or_else(|| "NanoClaw".to_string())
+}
+
+/// `dns-sd` ships with macOS; elsewhere Avahi's tool does the same.
+fn advertise_command(name: &str, port: u16) -> Option<Command> {
+    let txt = format!("version={}", env!("CARGO_PKG_VERSION"));
+    if let Some(dns_sd) = path_env::which("dns-sd") {
+        let mut cmd = Command::new(dns_sd);
+        cmd.args(["-R", name, SERVICE_TYPE, "local", &port.to_string(), &txt]);
+        return Some(cmd);
+    }
+    let mut cmd = Command::new(path_env::which("avahi-publish")?);
+    cmd.args(["-s", name, SERVICE_TYPE, &port.to_string(), &txt]);
+    Some(cmd)
+}
+
+fn start_advertising(port: u16) {
+    let mut advertiser = ADVERTISER.lock().unwrap();
+    if let Some(child) = advertiser.as_mut() {
+        if let Ok(None) = child.try_wait() {
+            return;
+        }
+    }
+    let name = instance_name();
+    let Some(mut cmd) = advertise_command(&name, port) else {
+        eprintln!("Neither dns-sd nor avahi-publish found; not advertising the backend");
+        return;
+    };
+    let spawned = process::runner().spawn(
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null()),
+    );
+    match spawned {
+        Ok(child) => {
+            eprintln!(
+                "Advertising {} as \"{}\" on port {}",
+                SERVICE_TYPE, name, port
+            );
+            *advertiser = Some(child);
+        }
+        Err(e) => eprintln!("Failed to advertise the backend: {}", e),
+    }
+}
+
+/// Take the Bonjour registration down.
+pub(crate) fn stop_advertising() {
+    if let Some(mut child) = ADVERTISER.lock().unwrap().take() {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+/// Advertise the backend over Bonjour while it's ready in LAN mode, so
+/// companion clients find it without typing an address. Follows the
+/// backend's status for the life of the app; call before the manager starts.
+pub(crate) fn advertise(app: &AppHandle) {
+    let changes = app.state::<backend::BackendManager>().subscribe();
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for status in changes {
+            let wanted = status.ready
+                && crate::remote_backend().is_none()
+                && crate::backend_socket_path().is_none()
+                && is_enabled(&settings::load(&crate::user_data_dir(&app)));
+            if wanted {
+                start_advertising(crate::backend_port());
+            } else {
+                stop_advertising();
+            }
+        }
+    });
+}
@@ -1,93 +1,199 @@
-use std::io::{BufRead, BufReader, Read, Write};
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
 use std::net::{TcpStream, ToSocketAddrs};
-use std::path::PathBuf;
-use std::process::{Child, Command, Stdio};
-use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use nix::sys::signal::{self, Signal};
-use nix::unistd::Pid;
+use nix::sys::signal::Signal;
 use serde::Serialize;
+use specta::Type;
 #[cfg(target_os = "macos")]
 use tauri::include_image;
-use tauri::menu::{MenuBuilder, MenuItemBuilder};
-use tauri::tray::TrayIconBuilder;
-use tauri::{AppHandle, Emitter, Manager, RunEvent, WindowEvent};
+use tauri::menu::{CheckMenuItemBuilder, Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, RunEvent, UriSchemeResponder, Url, WindowEvent, Wry};
+use tauri_plugin_deep_link::DeepLinkExt;
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::error::ShellError;
+use crate::settings::NotificationCategory;
+
+mod app_location;
+#[cfg(target_os = "macos")]
+mod app_menu;
+mod attachments;
+mod automation;
+mod backend;
+mod backend_update;
+mod backup;
+mod certs;
+mod chat_stream;
+mod cli;
+mod cli_install;
+mod clipboard;
+mod control;
+mod conversations;
+mod crash;
+mod data_dir;
+mod dev_mode;
+mod env_file;
+mod error;
+mod groups;
+mod heartbeat;
+mod http;
+mod i18n;
+mod image;
+mod integrity;
+mod lan;
+mod local_llm;
+mod logs;
+mod models;
+mod network;
+mod notifications;
+mod oauth;
+mod orphans;
+mod path_env;
+mod power;
+mod process;
+mod proxy;
+mod quick_chat;
+mod reaper;
+mod resources;
+mod scheduled_restart;
+mod secrets;
+mod settings;
+mod setup;
+mod shutdown;
+mod startup;
+mod storage;
+mod suspend;
+mod tasks;
+mod telemetry;
+mod tunnel;
+mod uninstall;
+mod updater;
+mod window_state;
+mod windows;
+mod zoom;
 
 #[cfg(target_os = "macos")]
 const TRAY_TEMPLATE_ICON: tauri::image::Image<'_> = include_image!("./icons/trayTemplate.png");
 
-struct BackendState {
-    child: Option<Child>,
-    ready: bool,
+const TRAY_ID: &str = "main";
+const TRAY_RECENT_CHATS_LIMIT: usize = 5;
+const TRAY_LABEL_MAX_CHARS: usize = 40;
+const TRAY_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+const WINDOW_STATE_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+const WAKE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Wall-clock time advancing this much more than monotonic time means the machine slept.
+const WAKE_GAP_THRESHOLD: Duration = Duration::from_secs(30);
+/// How long the backend gets to answer health checks after wake before it's restarted.
+const WAKE_HEALTH_GRACE: Duration = Duration::from_secs(15);
+const BACKEND_SOCKET_FILE: &str = "backend.sock";
+/// Ports after the preferred one tried before asking the OS for any free port.
+const PORT_SCAN_RANGE: u16 = 20;
+/// URI scheme the webview uses to reach a socket-bound backend through the shell.
+const API_SCHEME: &str = "nanoclaw-api";
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const RESOURCE_SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+/// Watchdog limits below this would restart a backend that's merely busy.
+const MIN_WATCHDOG_THRESHOLD_MB: u64 = 256;
+const MAX_WATCHDOG_THRESHOLD_MB: u64 = 64 * 1024;
+/// A few samples at least, so one spike doesn't restart the backend.
+const MIN_WATCHDOG_SUSTAIN_SECS: u64 = 60;
+/// `docker stop` grace for containers stopped along with the backend.
+const CONTAINER_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+/// Each warm container holds a full agent runtime in memory.
+const MAX_WARM_POOL_SIZE: u32 = 8;
+
+/// Pending reply count shown next to the tray icon while the window is hidden,
+/// plus notifications queued during Focus.
+#[derive(Default)]
+struct TrayBadge {
+    count: Mutex<u32>,
+    shown: Mutex<u32>,
 }
 
-#[derive(Serialize)]
+/// Latest backend activity shown in the tray, kept so the menu can be rebuilt
+/// (e.g. after a locale change) without re-querying the backend.
+#[derive(Default)]
+struct TrayMenuState {
+    activity: Mutex<TrayActivity>,
+}
+
+#[derive(Serialize, Clone, Type)]
 #[serde(rename_all = "camelCase")]
 struct BackendConfig {
     base_url: String,
-    auth_token: Option<String>,
+    /// Node inspector port, when dev mode runs the backend with `--inspect`.
+    inspect_port: Option<u16>,
+}
+
+#[derive(Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+struct ListenConfig {
+    host: String,
+    port: u16,
+    socket_path: Option<String>,
+}
+
+#[derive(Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+struct ProxyResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Type)]
 #[serde(rename_all = "camelCase")]
 struct DirConfig {
     bundle_dir: String,
     user_data_dir: String,
 }
 
-#[derive(Serialize, Clone)]
+/// Snapshot of backend activity rendered into the tray menu.
+#[derive(Clone, Default, PartialEq)]
+struct TrayActivity {
+    recent_chats: Vec<TrayEntry>,
+    running_tasks: Vec<TrayEntry>,
+}
+
+#[derive(Clone, PartialEq)]
+struct TrayEntry {
+    chat_id: String,
+    label: String,
+}
+
+#[derive(Serialize, Clone, Type)]
 #[serde(rename_all = "camelCase")]
-struct SetupStatus {
-    node_installed: bool,
-    node_version: String,
-    docker_running: bool,
-    container_image_built: bool,
-    container_resources_ready: bool,
-    api_key_configured: bool,
-    user_data_dir: String,
+struct ScheduledBackupResult {
+    backup: Option<backup::BackupInfo>,
+    error: Option<String>,
 }
 
-/// macOS GUI apps don't inherit the user's shell PATH.
-/// Resolve the full PATH from an interactive login shell and set it for this process,
-/// so all Command::new() calls can find node, docker, etc.
-/// Uses -i (interactive) so that .zshrc/.bashrc are sourced (needed for nvm, etc.).
-fn fix_path_env() {
-    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/zsh".to_string());
-    // -i -l: interactive login — sources .zprofile/.zshrc so nvm, pyenv, etc. are loaded
-    if let Ok(output) = Command::new(&shell)
-        .args(["-i", "-l", "-c", "echo $PATH"])
-        .stdin(Stdio::null())
-        .stderr(Stdio::null())
-        .output()
-    {
-        if output.status.success() {
-            let raw = String::from_utf8_lossy(&output.stdout);
-            // Interactive shells may print extra lines (motd, etc.) — take the last non-empty line
-            let shell_path = raw
-                .lines()
-                .rev()
-                .find(|l| !l.trim().is_empty() && l.contains('/'))
-                .unwrap_or("")
-                .trim()
-                .to_string();
-            if !shell_path.is_empty() {
-                std::env::set_var("PATH", &shell_path);
-                return;
-            }
-        }
-    }
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+struct SettingChanged {
+    key: String,
+    value: serde_json::Value,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+struct BindWarning {
+    host: String,
+    lan_access_allowed: bool,
+}
 
-    // Fallback: append common macOS binary locations
-    let current = std::env::var("PATH").unwrap_or_default();
-    let extra = [
-        "/opt/homebrew/bin",
-        "/opt/homebrew/sbin",
-        "/usr/local/bin",
-        "/usr/local/sbin",
-    ];
-    let combined = format!("{}:{}", current, extra.join(":"));
-    std::env::set_var("PATH", &combined);
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+struct NavigatePayload {
+    route: String,
+    id: Option<String>,
 }
 
 fn is_release_build() -> bool {
@@ -110,65 +216,295 @@ fn bundle_dir(app: &AppHandle) -> PathBuf {
     }
 }
 
-fn user_data_dir(app: &AppHandle) -> PathBuf {
+/// Directory holding the backend's `dist/`: a hot-updated bundle in the data
+/// dir when one is active and newer than the app's own copy, otherwise the
+/// app bundle. `node_modules` and `container-agno` always come from `bundle_dir`.
+fn backend_dir(app: &AppHandle) -> PathBuf {
+    let bundled_version = app.package_info().version.to_string();
+    backend_update::active_bundle(&user_data_dir(app))
+        .filter(|(version, _)| backend_update::is_newer(version, &bundled_version))
+        .map(|(_, dir)| dir)
+        .unwrap_or_else(|| bundle_dir(app))
+}
+
+pub(crate) fn user_data_dir(app: &AppHandle) -> PathBuf {
     if let Ok(dir) = std::env::var("NANOCLAW_DATA_DIR") {
         return PathBuf::from(dir);
     }
-    if is_release_build() {
-        // ~/Library/Application Support/com.nanoclaw.desktop/
-        app.path()
-            .app_data_dir()
-            .expect("Failed to resolve app data dir")
+    let base = if is_release_build() {
+        // Set by move_data_dir
+        if let Some(dir) = app
+            .path()
+            .app_config_dir()
+            .ok()
+            .and_then(|config| data_dir::read_override(&config))
+        {
+            dir
+        } else {
+            // ~/Library/Application Support/com.nanoclaw.desktop/
+            app.path()
+                .app_data_dir()
+                .expect("Failed to resolve app data dir")
+        }
     } else {
         let manifest = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         manifest.parent().unwrap().parent().unwrap().to_path_buf()
+    };
+    // --profile keeps a separate data set next to the default one
+    match &cli::args().profile {
+        Some(profile) => base.join("profiles").join(profile),
+        None => base,
     }
 }
 
 /// Read .env file from user data dir and return key=value pairs
 fn load_user_env(data_dir: &PathBuf) -> Vec<(String, String)> {
-    let env_path = data_dir.join(".env");
-    let mut pairs = Vec::new();
-    if let Ok(content) = std::fs::read_to_string(&env_path) {
-        for line in content.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
-            }
-            if let Some(eq_pos) = trimmed.find('=') {
-                let key = trimmed[..eq_pos].trim().to_string();
-                let val = trimmed[eq_pos + 1..].trim().to_string();
-                if !key.is_empty() {
-                    pairs.push((key, val));
-                }
-            }
+    env_file::load(data_dir).pairs()
+}
+
+/// 1-based numbers of `.env` lines load_user_env skips as malformed.
+fn invalid_env_lines(data_dir: &Path) -> Vec<usize> {
+    env_file::load(data_dir).invalid
+}
+
+/// Where the backend listens and the tokens that reach it. Commands change
+/// this while other threads read it, so it lives here rather than in the
+/// process env, where `set_var` would race every `getenv` and spawn. The
+/// backend gets it through `Command::env`.
+struct BackendRuntime {
+    host: Option<String>,
+    port: Option<u16>,
+    socket_path: Option<PathBuf>,
+    api_token: Option<String>,
+    remote_url: Option<String>,
+    remote_token: Option<String>,
+}
+
+static BACKEND_RUNTIME: RwLock<BackendRuntime> = RwLock::new(BackendRuntime {
+    host: None,
+    port: None,
+    socket_path: None,
+    api_token: None,
+    remote_url: None,
+    remote_token: None,
+});
+
+fn backend_runtime() -> RwLockReadGuard<'static, BackendRuntime> {
+    BACKEND_RUNTIME.read().unwrap()
+}
+
+fn update_backend_runtime(f: impl FnOnce(&mut BackendRuntime)) {
+    f(&mut BACKEND_RUNTIME.write().unwrap());
+}
+
+fn backend_host() -> String {
+    if let Some((host, _)) = remote_backend() {
+        return host;
+    }
+    backend_runtime()
+        .host
+        .clone()
+        .unwrap_or_else(|| "127.0.0.1".to_string())
+}
+
+/// Host and port of an `http://host[:port]` backend URL; the port defaults to 80.
+fn parse_backend_url(url: &str) -> Result<(String, u16), String> {
+    let url = url.trim();
+    let Some(rest) = url.strip_prefix("http://") else {
+        if url.starts_with("https://") {
+            return Err("HTTPS backends aren't supported yet; use http:// on a trusted network or through a tunnel".to_string());
         }
+        return Err(format!("Backend URL must start with http://: {}", url));
+    };
+    let authority = rest.trim_end_matches('/');
+    if authority.contains(['/', '?', '#', '@']) {
+        return Err(format!(
+            "Backend URL must be just http://host[:port]: {}",
+            url
+        ));
+    }
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (
+            host,
+            port.parse::<u16>()
+                .map_err(|_| format!("Invalid port in backend URL: {}", port))?,
+        ),
+        _ => (authority, 80),
+    };
+    if host.is_empty() || port == 0 {
+        return Err(format!("Invalid backend URL: {}", url));
     }
-    pairs
+    Ok((host.to_string(), port))
 }
 
-fn backend_host() -> String {
-    std::env::var("HTTP_HOST").unwrap_or_else(|_| "127.0.0.1".to_string())
+/// The remote backend the shell connects to instead of starting its own, if
+/// one is configured.
+fn remote_backend() -> Option<(String, u16)> {
+    backend_runtime()
+        .remote_url
+        .as_deref()
+        .and_then(|url| parse_backend_url(url).ok())
 }
 
-fn backend_port() -> u16 {
-    std::env::var("PORT")
+/// Record the configured remote backend and its token, or clear them, so
+/// every helper that talks to the backend follows it.
+fn apply_remote_backend(data_dir: &Path, url: Option<&str>) {
+    let token = url.and_then(|_| secrets::load_remote_token(data_dir));
+    update_backend_runtime(|runtime| {
+        runtime.remote_url = url.map(str::to_string);
+        runtime.remote_token = token;
+    });
+}
+
+/// Apply the listen address: --port, then HTTP_HOST/PORT from the launch
+/// environment (which keeps env overrides working for development), then the
+/// persisted settings.
+fn init_listen_config(prefs: &settings::Settings) {
+    let host = std::env::var("HTTP_HOST")
         .ok()
-        .and_then(|v| v.parse::<u16>().ok())
-        .unwrap_or(3000)
+        .or_else(|| prefs.backend_host.clone());
+    let port = cli::args()
+        .port
+        .or_else(|| std::env::var("PORT").ok().and_then(|v| v.parse().ok()))
+        .or(prefs.backend_port);
+    update_backend_runtime(|runtime| {
+        runtime.host = host;
+        runtime.port = port;
+    });
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .trim_matches(['[', ']'])
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+fn backend_port() -> u16 {
+    if let Some((_, port)) = remote_backend() {
+        return port;
+    }
+    backend_runtime().port.unwrap_or(3000)
+}
+
+/// Unix socket the backend listens on, when the socket transport is active.
+fn backend_socket_path() -> Option<PathBuf> {
+    if !cfg!(unix) || remote_backend().is_some() {
+        return None;
+    }
+    backend_runtime().socket_path.clone()
+}
+
+/// Use the unix socket when enabled, unless LAN access was explicitly requested
+/// (other machines can't reach a socket). Recorded so every helper that talks
+/// to the backend, and the backend itself, agree on it.
+fn select_backend_transport(data_dir: &Path, prefs: &settings::Settings, bind_host: &str) {
+    let lan_bind = prefs.allow_lan_access && !is_loopback_host(bind_host);
+    let socket_path =
+        (cfg!(unix) && prefs.unix_socket && !lan_bind).then(|| data_dir.join(BACKEND_SOCKET_FILE));
+    update_backend_runtime(|runtime| runtime.socket_path = socket_path);
+}
+
+/// First free port starting at `preferred`, then any port the OS hands out.
+fn allocate_port(host: &str, preferred: u16) -> Option<u16> {
+    let is_free = |port: u16| std::net::TcpListener::bind((host, port)).is_ok();
+    (preferred..=preferred.saturating_add(PORT_SCAN_RANGE))
+        .find(|&port| port != 0 && is_free(port))
+        .or_else(|| {
+            std::net::TcpListener::bind((host, 0))
+                .and_then(|listener| listener.local_addr())
+                .map(|addr| addr.port())
+                .ok()
+        })
 }
 
 fn backend_base_url() -> String {
+    if backend_socket_path().is_some() {
+        return if cfg!(windows) {
+            format!("http://{}.localhost", API_SCHEME)
+        } else {
+            format!("{}://localhost", API_SCHEME)
+        };
+    }
     format!("http://{}:{}", backend_host(), backend_port())
 }
 
+/// Send a request to the local backend over whichever transport is active.
+fn backend_request(
+    method: &str,
+    path: &str,
+    headers: &[(String, String)],
+    body: Option<&[u8]>,
+    timeout: Duration,
+) -> Result<http::HttpResponse, String> {
+    #[cfg(unix)]
+    if let Some(socket) = backend_socket_path() {
+        return http::request_unix(&socket, method, path, headers, body, timeout);
+    }
+    http::request(
+        method,
+        &backend_host(),
+        backend_port(),
+        path,
+        headers,
+        body,
+        timeout,
+    )
+}
+
 fn backend_auth_token() -> Option<String> {
-    std::env::var("NANOCLAW_API_TOKEN")
+    let runtime = backend_runtime();
+    let token = if runtime.remote_url.is_some() {
+        &runtime.remote_token
+    } else {
+        &runtime.api_token
+    };
+    token.clone().filter(|v| !v.is_empty())
+}
+
+fn set_api_token(token: &str) {
+    update_backend_runtime(|runtime| runtime.api_token = Some(token.to_string()));
+}
+
+/// Make sure the shell and backend share an API token. An explicit
+/// NANOCLAW_API_TOKEN (launch env or .env) wins; otherwise the stored token
+/// is used, generated on first run, unless the user opted out.
+fn init_api_token(data_dir: &PathBuf, required: bool) {
+    if backend_runtime().api_token.is_some() {
+        return;
+    }
+    let explicit = std::env::var("NANOCLAW_API_TOKEN")
         .ok()
         .filter(|v| !v.is_empty())
+        .or_else(|| {
+            load_user_env(data_dir)
+                .into_iter()
+                .find(|(key, val)| key == "NANOCLAW_API_TOKEN" && !val.is_empty())
+                .map(|(_, val)| val)
+        });
+    let token = match explicit {
+        Some(token) => Some(token),
+        None if required => match secrets::ensure_api_token(data_dir) {
+            Ok(token) => Some(token),
+            Err(e) => {
+                eprintln!("Failed to set up API token: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+    if let Some(token) = token {
+        set_api_token(&token);
+    }
 }
 
 fn is_backend_healthy(host: &str, port: u16) -> bool {
+    if backend_socket_path().is_some() {
+        return backend_request("GET", "/api/health", &[], None, Duration::from_millis(1500))
+            .is_ok_and(|r| r.status == 200);
+    }
+
     let addr = format!("{}:{}", host, port);
     let sockets: Vec<_> = match addr.to_socket_addrs() {
         Ok(iter) => iter.collect(),
@@ -210,168 +546,224 @@ fn is_backend_healthy(host: &str, port: u16) -> bool {
     false
 }
 
-fn is_nanoclaw_backend_listening_on_port(bundle: &PathBuf) -> bool {
-    let port = backend_port();
-    let lsof_output = Command::new("lsof")
-        .args([
-            "-nP",
-            &format!("-iTCP:{}", port),
-            "-sTCP:LISTEN",
-            "-t",
-        ])
-        .output();
-
-    let output = match lsof_output {
-        Ok(v) => v,
-        Err(_) => return false,
-    };
-
-    let backend_entry = bundle.join("dist/index.js");
-    let backend_entry_text = backend_entry.to_string_lossy();
-
-    let pids = String::from_utf8_lossy(&output.stdout);
-    for line in pids.lines().filter(|v| !v.trim().is_empty()) {
-        let pid = match line.trim().parse::<i32>() {
-            Ok(v) if v > 0 => v,
-            _ => continue,
-        };
-
-        let cmd_output = Command::new("ps")
-            .args(["-p", &pid.to_string(), "-o", "command="])
-            .output();
-
-        let cmd = match cmd_output {
-            Ok(v) => String::from_utf8_lossy(&v.stdout).trim().to_string(),
-            Err(_) => String::new(),
-        };
-
-        let is_nanoclaw_backend =
-            cmd.contains("node") && cmd.contains(backend_entry_text.as_ref());
-
-        if is_nanoclaw_backend {
-            return true;
-        }
-    }
-
-    false
-}
-
-fn wait_for_backend_ready(app: AppHandle, state: Arc<Mutex<BackendState>>) {
-    let host = backend_host();
-    let port = backend_port();
-
+/// Serve `nanoclaw-api://` requests from the webview by forwarding them to the
+/// socket-bound backend. Responses are buffered, not streamed.
+fn proxy_api_scheme(request: tauri::http::Request<Vec<u8>>, responder: UriSchemeResponder) {
     std::thread::spawn(move || {
-        for _ in 0..80 {
-            let still_running = {
-                let s = state.lock().unwrap();
-                s.child.is_some()
-            };
-
-            if !still_running {
-                return;
-            }
-
-            if is_backend_healthy(&host, port) {
-                let mut should_emit = false;
-                {
-                    let mut s = state.lock().unwrap();
-                    if s.child.is_some() && !s.ready {
-                        s.ready = true;
-                        should_emit = true;
-                    }
-                }
+        let path = request
+            .uri()
+            .path_and_query()
+            .map(|p| p.as_str().to_string())
+            .unwrap_or_else(|| "/".to_string());
+        let headers: Vec<(String, String)> = request
+            .headers()
+            .iter()
+            .filter(|(key, _)| {
+                !matches!(
+                    key.as_str(),
+                    "host" | "connection" | "content-length" | "transfer-encoding"
+                )
+            })
+            .filter_map(|(key, val)| Some((key.to_string(), val.to_str().ok()?.to_string())))
+            .collect();
+        let headers = with_auth_header(headers);
+        let body = (!request.body().is_empty()).then_some(request.body().as_slice());
 
-                if should_emit {
-                    let _ = app.emit("backend-ready", ());
-                    if let Some(window) = app.get_webview_window("main") {
-                        let _ = window.show();
-                        let _ = window.set_focus();
+        let result = backend_request(
+            request.method().as_str(),
+            &path,
+            &headers,
+            body,
+            Duration::from_secs(300),
+        );
+        let response = match result {
+            Ok(upstream) => {
+                let mut builder = tauri::http::Response::builder().status(upstream.status);
+                for (key, val) in &upstream.headers {
+                    let lower = key.to_ascii_lowercase();
+                    if !matches!(
+                        lower.as_str(),
+                        "connection" | "content-length" | "transfer-encoding"
+                    ) {
+                        builder = builder.header(key, val);
                     }
                 }
-                return;
+                builder.body(upstream.body)
+            }
+            Err(e) => tauri::http::Response::builder()
+                .status(502)
+                .header("Content-Type", "application/json")
+                .header("Access-Control-Allow-Origin", "*")
+                .body(serde_json::json!({ "error": e }).to_string().into_bytes()),
+        };
+        match response {
+            Ok(response) => responder.respond(response),
+            Err(e) => {
+                eprintln!("Failed to build API proxy response: {}", e);
+                let mut fallback = tauri::http::Response::new(Vec::new());
+                *fallback.status_mut() = tauri::http::StatusCode::BAD_GATEWAY;
+                responder.respond(fallback);
             }
-
-            std::thread::sleep(Duration::from_millis(250));
         }
     });
 }
 
-fn mark_backend_ready(app: &AppHandle, state: &Arc<Mutex<BackendState>>) {
-    {
-        let mut s = state.lock().unwrap();
-        s.ready = true;
+/// Open a streaming request to the backend (with auth) over the active transport.
+fn backend_request_streaming(
+    method: &str,
+    path: &str,
+    body: &[u8],
+) -> Result<http::StreamingResponse, String> {
+    let headers = with_auth_header(vec![
+        ("Content-Type".to_string(), "application/json".to_string()),
+        ("Accept".to_string(), "text/event-stream".to_string()),
+    ]);
+    #[cfg(unix)]
+    if let Some(socket) = backend_socket_path() {
+        return http::request_unix_streaming(&socket, method, path, &headers, Some(body));
     }
-    let _ = app.emit("backend-ready", ());
-    if let Some(window) = app.get_webview_window("main") {
-        let _ = window.show();
-        let _ = window.set_focus();
+    http::request_streaming(
+        method,
+        &backend_host(),
+        backend_port(),
+        path,
+        &headers,
+        Some(body),
+        Duration::from_secs(3),
+    )
+}
+
+/// Replace any Authorization header with the shell's API token, if one is configured.
+fn with_auth_header(mut headers: Vec<(String, String)>) -> Vec<(String, String)> {
+    headers.retain(|(key, _)| !key.eq_ignore_ascii_case("authorization"));
+    if let Some(token) = backend_auth_token() {
+        headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
     }
+    headers
 }
 
-fn kill_orphan_backend_on_port(bundle: &PathBuf) {
-    let port = backend_port();
-    let lsof_output = Command::new("lsof")
-        .args([
-            "-nP",
-            &format!("-iTCP:{}", port),
-            "-sTCP:LISTEN",
-            "-t",
-        ])
-        .output();
+/// GET a JSON document from the local backend, attaching the API token if configured.
+fn backend_get_json(path: &str) -> Result<serde_json::Value, String> {
+    let headers = with_auth_header(Vec::new());
+    let response = backend_request("GET", path, &headers, None, Duration::from_secs(3))?;
 
-    let output = match lsof_output {
-        Ok(v) => v,
-        Err(_) => return,
-    };
+    if !response.is_success() {
+        return Err(format!("GET {} returned HTTP {}", path, response.status));
+    }
+    response.json()
+}
 
-    let backend_entry = bundle.join("dist/index.js");
-    let backend_entry_text = backend_entry.to_string_lossy();
+/// A process listening on the backend port.
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+struct PortOwner {
+    pid: i32,
+    command: String,
+    user: String,
+    is_nanoclaw_backend: bool,
+}
 
-    let pids = String::from_utf8_lossy(&output.stdout);
-    for line in pids.lines().filter(|v| !v.trim().is_empty()) {
-        let pid = match line.trim().parse::<i32>() {
-            Ok(v) if v > 0 => v,
-            _ => continue,
-        };
+#[derive(Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+struct PortDiagnosis {
+    host: String,
+    port: u16,
+    owners: Vec<PortOwner>,
+}
 
-        let cmd_output = Command::new("ps")
-            .args(["-p", &pid.to_string(), "-o", "command="])
-            .output();
+/// Processes listening on `port`, via `lsof -F` field output (one `p<pid>`
+/// line per process followed by its `L<login>`). A process counts as our
+/// backend only if it is node running this bundle's `dist/index.js`.
+fn port_owners(port: u16, backend: &Path) -> Vec<PortOwner> {
+    let output = match process::runner().output(
+        Command::new("lsof")
+            .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-FpL"])
+            .stdin(Stdio::null()),
+    ) {
+        Ok(v) => v,
+        Err(_) => return Vec::new(),
+    };
 
-        let cmd = match cmd_output {
-            Ok(v) => String::from_utf8_lossy(&v.stdout).trim().to_string(),
-            Err(_) => String::new(),
-        };
+    let mut owners: Vec<(i32, String)> = Vec::new();
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        if let Some(pid) = line.strip_prefix('p') {
+            match pid.trim().parse::<i32>() {
+                Ok(pid) if pid > 0 && !owners.iter().any(|(p, _)| *p == pid) => {
+                    owners.push((pid, String::new()))
+                }
+                _ => {}
+            }
+        } else if let (Some(user), Some(last)) = (line.strip_prefix('L'), owners.last_mut()) {
+            last.1 = user.to_string();
+        }
+    }
+
+    let backend_entry = backend.join("dist/index.js");
+    let backend_entry_text = backend_entry.to_string_lossy();
+    owners
+        .into_iter()
+        .map(|(pid, user)| {
+            let command = process::runner()
+                .output(Command::new("ps").args(["-p", &pid.to_string(), "-o", "command="]))
+                .map(|v| String::from_utf8_lossy(&v.stdout).trim().to_string())
+                .unwrap_or_default();
+            let is_nanoclaw_backend =
+                command.contains("node") && command.contains(backend_entry_text.as_ref());
+            PortOwner {
+                pid,
+                command,
+                user,
+                is_nanoclaw_backend,
+            }
+        })
+        .collect()
+}
 
-        let is_nanoclaw_backend =
-            cmd.contains("node") && cmd.contains(backend_entry_text.as_ref());
+fn is_nanoclaw_backend_listening_on_port(backend: &Path) -> bool {
+    port_owners(backend_port(), backend)
+        .iter()
+        .any(|owner| owner.is_nanoclaw_backend)
+}
 
-        if is_nanoclaw_backend {
-            let _ = signal::kill(Pid::from_raw(pid), Signal::SIGTERM);
+fn kill_orphan_backend_on_port(backend: &Path) {
+    for owner in port_owners(backend_port(), backend) {
+        if owner.is_nanoclaw_backend {
+            let _ = process::runner().signal(owner.pid as u32, Signal::SIGTERM);
         }
     }
 }
 
-fn spawn_backend(app: &AppHandle, state: &Arc<Mutex<BackendState>>) {
+/// Spawn a backend process, or find one already serving the configured
+/// address. Only the backend manager calls this; it owns the returned child.
+fn launch_backend(app: &AppHandle) -> Result<backend::Launch, ShellError> {
+    if let Some((host, port)) = remote_backend() {
+        eprintln!("Using remote backend at {}:{}", host, port);
+        return Ok(backend::Launch::Remote);
+    }
+
     let bundle = bundle_dir(app);
+    let backend = backend_dir(app);
     let data = user_data_dir(app);
-    let node_entry = bundle.join("dist/index.js");
+    let node_entry = backend.join("dist/index.js");
     let host = backend_host();
-    let port = backend_port();
+    let prefs = settings::load(&data);
+    let user_env = load_user_env(&data);
+    let port = cli::args()
+        .port
+        .or_else(|| {
+            user_env
+                .iter()
+                .find(|(key, _)| key == "PORT")
+                .and_then(|(_, val)| val.parse::<u16>().ok())
+        })
+        .unwrap_or_else(backend_port);
+    let bind_host = user_env
+        .iter()
+        .find(|(key, _)| key == "HTTP_HOST")
+        .map(|(_, val)| val.clone())
+        .unwrap_or_else(|| host.clone());
 
-    {
-        let mut s = state.lock().unwrap();
-        if let Some(child) = s.child.as_mut() {
-            match child.try_wait() {
-                Ok(Some(_)) | Err(_) => {
-                    s.child = None;
-                }
-                Ok(None) => {
-                    return;
-                }
-            }
-        }
-    }
+    select_backend_transport(&data, &prefs, &bind_host);
 
     // Another NanoClaw backend is already running on configured host/port.
     // Reuse it instead of spawning a duplicate process that will fail with EADDRINUSE.
@@ -380,398 +772,3108 @@ fn spawn_backend(app: &AppHandle, state: &Arc<Mutex<BackendState>>) {
             "Backend already reachable at {}:{}; skipping local spawn",
             host, port
         );
-        mark_backend_ready(app, state);
-        return;
+        return Ok(backend::Launch::Reused);
     }
 
     // Health checks can occasionally miss a backend during startup transitions.
     // Fallback to process-based detection so we avoid spawning a duplicate.
-    if is_nanoclaw_backend_listening_on_port(&bundle) {
+    if is_nanoclaw_backend_listening_on_port(&backend) {
         eprintln!(
             "Backend already listening at {}:{}; skipping local spawn",
             host, port
         );
-        mark_backend_ready(app, state);
-        return;
+        return Ok(backend::Launch::Reused);
     }
 
-    if !node_entry.exists() {
+    if cli::args().no_backend {
         eprintln!(
-            "Backend not built: {} not found. Run 'npm run build' in project root first.",
-            node_entry.display()
+            "--no-backend: no backend reachable at {}:{}; start one and restart from the tray",
+            host, port
         );
-        return;
+        return Ok(backend::Launch::Skipped);
+    }
+
+    if !node_entry.exists() {
+        return Err(ShellError::BackendNotBuilt { path: node_entry });
+    }
+    // In dev mode dist/ is rebuilt under the manifest's feet
+    if is_release_build() && !prefs.dev_mode.enabled {
+        if let Err(files) = integrity::verify(&backend.join("dist")) {
+            eprintln!("Backend bundle failed verification: {}", files.join(", "));
+            let error = ShellError::BundleCorrupt { files };
+            let _ = app.emit("bundle-corrupt", &error);
+            return Err(error);
+        }
     }
 
-    let mut cmd = Command::new("node");
-    cmd.arg(&node_entry)
+    let mut cmd = power::node_command(prefs.prevent_app_nap);
+    cmd.args(dev_mode::node_args(&prefs.dev_mode))
+        .arg(&node_entry)
         .current_dir(&data) // process.cwd() = user data dir
         .env("NANOCLAW_BUNDLE_DIR", &bundle)
-        .env("NANOCLAW_DATA_DIR", &data);
+        .env("NANOCLAW_DATA_DIR", &data)
+        .env("HTTP_HOST", &host);
+    match backend_socket_path() {
+        Some(socket) => cmd.env("NANOCLAW_SOCKET_PATH", socket),
+        None => cmd.env_remove("NANOCLAW_SOCKET_PATH"),
+    };
 
-    // Load .env from user data dir and pass as env vars
-    for (key, val) in load_user_env(&data) {
-        cmd.env(&key, &val);
+    // Proxies and CAs first, so .env and settings can still override them
+    for (key, val) in proxy::resolve(&prefs.proxy).env() {
+        cmd.env(key, val);
+    }
+    if let Some(bundle) = &prefs.extra_ca_bundle {
+        cmd.env("NODE_EXTRA_CA_CERTS", bundle);
     }
 
-    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    // Load .env from user data dir and pass as env vars, then the overrides
+    // from settings and OAuth sign-ins on top
+    let oauth_env = oauth::env(&data, &prefs);
+    let extra_env = user_env
+        .iter()
+        .map(|(key, val)| (key, val))
+        .chain(&prefs.backend_env)
+        .chain(oauth_env.iter().map(|(key, val)| (key, val)));
+    for (key, val) in extra_env.clone() {
+        cmd.env(key, val);
+    }
+    cmd.env("NANOCLAW_WARM_POOL_SIZE", prefs.warm_pool_size.to_string());
+    if prefs.gpu_passthrough {
+        if setup::gpu_available(app) {
+            cmd.env("NANOCLAW_CONTAINER_GPUS", "all");
+        } else {
+            eprintln!("GPU passthrough is on, but Docker can't provide GPUs; skipping");
+        }
+    }
+    let api_token = backend_auth_token();
+    match &api_token {
+        Some(token) => cmd.env("NANOCLAW_API_TOKEN", token),
+        None => cmd.env_remove("NANOCLAW_API_TOKEN"),
+    };
+    app.state::<logs::BackendLogs>().set_secrets(
+        extra_env
+            .map(|(key, val)| (key.as_str(), val.as_str()))
+            .chain(api_token.as_deref().map(|t| ("NANOCLAW_API_TOKEN", t))),
+    );
 
-    let child = cmd.spawn();
+    if backend_socket_path().is_none() {
+        // Something other than our backend may hold the preferred port; pick
+        // another and record it so the shell and frontend connect to it.
+        let chosen = allocate_port(&bind_host, port).ok_or(ShellError::PortConflict { port })?;
+        update_backend_runtime(|runtime| runtime.port = Some(chosen));
+        cmd.env("PORT", chosen.to_string());
+        orphans::remember_port(&data, chosen);
+        if chosen != port {
+            eprintln!("Port {} is in use; starting backend on {}", port, chosen);
+            let _ = app.emit("backend-config-changed", get_backend_config());
+        }
+    }
 
-    match child {
-        Ok(mut child) => {
-            let stdout = child.stdout.take().expect("Failed to capture stdout");
-            let stderr = child.stderr.take().expect("Failed to capture stderr");
+    // A non-loopback bind exposes the API (and the keys behind it) to the LAN.
+    // Only allow it with the explicit opt-in; otherwise fall back to loopback.
+    if let Some(socket) = backend_socket_path() {
+        // Don't let a crashed backend's socket file block the bind
+        let _ = std::fs::remove_file(&socket);
+    } else if !is_loopback_host(&bind_host) {
+        // Without a token anyone on the network could drive the agents
+        let lan_access_allowed = prefs.allow_lan_access && api_token.is_some();
+        if lan_access_allowed {
+            eprintln!(
+                "WARNING: backend listening on {}; it is reachable from the network",
+                bind_host
+            );
+        } else if prefs.allow_lan_access {
+            eprintln!(
+                "WARNING: HTTP_HOST={} ignored; LAN access needs an API token",
+                bind_host
+            );
+            cmd.env("HTTP_HOST", "127.0.0.1");
+        } else {
+            eprintln!(
+                "WARNING: HTTP_HOST={} ignored; enable LAN access in settings to bind beyond loopback",
+                bind_host
+            );
+            cmd.env("HTTP_HOST", "127.0.0.1");
+        }
+        let _ = app.emit(
+            "backend-bind-warning",
+            BindWarning {
+                host: bind_host,
+                lan_access_allowed,
+            },
+        );
+    }
 
-            {
-                let mut s = state.lock().unwrap();
-                s.child = Some(child);
-                s.ready = false;
-            }
+    // Control messages from send_backend_command arrive on stdin
+    cmd.env("NANOCLAW_STDIN_COMMANDS", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
 
-            wait_for_backend_ready(app.clone(), Arc::clone(state));
+    let child = process::runner()
+        .spawn(&mut cmd)
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => ShellError::NodeMissing,
+            _ => ShellError::SpawnFailed { io: e.to_string() },
+        })?;
+    Ok(backend::Launch::Spawned(child))
+}
 
-            // Forward backend stdout and detect process exit
-            let app_handle = app.clone();
-            let state_clone = Arc::clone(state);
-            std::thread::spawn(move || {
-                let reader = BufReader::new(stdout);
-                for line in reader.lines() {
-                    match line {
-                        Ok(line) => eprintln!("[backend] {}", line),
-                        Err(_) => break,
-                    }
-                }
-                // Backend process ended
-                {
-                    let mut s = state_clone.lock().unwrap();
-                    s.ready = false;
-                    s.child = None;
-                }
-                let _ = app_handle.emit("backend-stopped", ());
-            });
+/// Stop a NanoClaw backend left on the port by an earlier run, and any agent
+/// containers. Runs in the background after every stop.
+fn stop_orphans(app: &AppHandle) {
+    // Whatever runs on this machine isn't the remote backend's to stop
+    if remote_backend().is_some() {
+        return;
+    }
+    let backend = backend_dir(app);
+    std::thread::spawn(move || {
+        kill_orphan_backend_on_port(&backend);
+        stop_containers(CONTAINER_STOP_TIMEOUT);
+    });
+}
 
-            // Forward stderr
-            std::thread::spawn(move || {
-                let reader = BufReader::new(stderr);
-                for line in reader.lines() {
-                    match line {
-                        Ok(line) => eprintln!("[backend:err] {}", line),
-                        Err(_) => break,
-                    }
-                }
-            });
-        }
-        Err(e) => {
-            eprintln!("Failed to spawn backend: {}", e);
-        }
+/// Stop the running agent containers, giving each up to `timeout` before
+/// Docker kills it. One `docker stop` for all of them stops them in parallel.
+fn stop_containers(timeout: Duration) {
+    let output = process::runner().output(Command::new("docker").args([
+        "ps",
+        "--filter",
+        "name=nanoclaw-",
+        "--format",
+        "{{.Names}}",
+    ]));
+    let Ok(output) = output else {
+        return;
+    };
+    let names = String::from_utf8_lossy(&output.stdout);
+    let names: Vec<&str> = names.lines().filter(|l| !l.is_empty()).collect();
+    if names.is_empty() {
+        return;
     }
+    let _ = process::runner().output(
+        Command::new("docker")
+            .args(["stop", "--time", &timeout.as_secs().to_string()])
+            .args(&names)
+            .stdin(Stdio::null()),
+    );
 }
 
-fn kill_backend(app: &AppHandle, state: &Arc<Mutex<BackendState>>) {
-    let mut s = state.lock().unwrap();
-    if let Some(ref child) = s.child {
-        let pid = child.id() as i32;
-        // Send SIGTERM for graceful shutdown
-        let _ = signal::kill(Pid::from_raw(pid), Signal::SIGTERM);
+fn show_main_window(app: &AppHandle) {
+    suspend::resume(app);
+    match windows::ensure_main(app) {
+        Ok(window) => {
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+        Err(e) => eprintln!("{}", e),
     }
-    s.ready = false;
-    // Don't set child to None yet — the stdout thread will do that when the process exits
+    update_tray_badge(app, 0);
 
-    drop(s);
+    // Coming back after a notification: open what it was about
+    if let Some((route, id)) = notifications::take_pending_route(app) {
+        let _ = app.emit("navigate", NavigatePayload { route, id });
+    }
+}
 
-    // Also stop any orphaned nanoclaw containers
-    let bundle = bundle_dir(app);
-    std::thread::spawn(move || {
-        kill_orphan_backend_on_port(&bundle);
-
-        let output = Command::new("docker")
-            .args(["ps", "--filter", "name=nanoclaw-", "--format", "{{.Names}}"])
-            .output();
-        if let Ok(output) = output {
-            let names = String::from_utf8_lossy(&output.stdout);
-            for name in names.lines().filter(|l| !l.is_empty()) {
-                let _ = Command::new("docker").args(["stop", name]).output();
-            }
-        }
-    });
+fn is_main_window_visible(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false)
 }
 
-fn wait_for_backend_exit(state: &Arc<Mutex<BackendState>>, timeout: Duration) {
-    let start = Instant::now();
-    loop {
-        let stopped = {
-            let mut s = state.lock().unwrap();
-            match s.child.as_mut() {
-                Some(child) => match child.try_wait() {
-                    Ok(Some(_)) => {
-                        s.child = None;
-                        true
-                    }
-                    Ok(None) => false,
-                    Err(_) => {
-                        s.child = None;
-                        true
-                    }
-                },
-                None => true,
-            }
-        };
+fn update_tray_badge(app: &AppHandle, count: u32) {
+    *app.state::<TrayBadge>().count.lock().unwrap() = count;
+    refresh_tray_badge(app);
+}
 
-        if stopped {
+/// Render the badge as tray title text (shown beside the icon on macOS).
+fn refresh_tray_badge(app: &AppHandle) {
+    let badge = app.state::<TrayBadge>();
+    let count = *badge.count.lock().unwrap() + notifications::queued_count(app);
+    {
+        let mut shown = badge.shown.lock().unwrap();
+        if *shown == count {
             return;
         }
+        *shown = count;
+    }
 
-        if start.elapsed() >= timeout {
-            let maybe_pid = {
-                let s = state.lock().unwrap();
-                s.child.as_ref().map(|child| child.id() as i32)
-            };
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let title = (count > 0).then(|| count.to_string());
+        let _ = tray.set_title(title);
+    }
+}
 
-            if let Some(pid) = maybe_pid {
-                let _ = signal::kill(Pid::from_raw(pid), Signal::SIGKILL);
-            }
-            return;
-        }
+/// Show the main window and ask the frontend to navigate to a route (e.g. a chat).
+fn navigate_main_window(app: &AppHandle, route: &str, id: Option<String>) {
+    show_main_window(app);
+    let _ = app.emit(
+        "navigate",
+        NavigatePayload {
+            route: route.to_string(),
+            id,
+        },
+    );
+}
 
-        std::thread::sleep(Duration::from_millis(100));
+/// Route a `nanoclaw://` link: `nanoclaw://settings` and `nanoclaw://logs` open
+/// their windows, automation actions (`show`, `send`, ...) run; anything else
+/// (e.g. `nanoclaw://chat/<id>`) shows the main window and emits a `navigate`
+/// event with the route and optional id.
+fn handle_deep_link(app: &AppHandle, url: &Url) {
+    if url.scheme() != "nanoclaw" {
+        return;
     }
-}
+    let Some(route) = url.host_str().filter(|h| !h.is_empty()) else {
+        show_main_window(app);
+        return;
+    };
+    if automation::handle(app, route, url) {
+        return;
+    }
+    let id = url
+        .path_segments()
+        .and_then(|mut segments| segments.find(|s| !s.is_empty()))
+        .map(|s| s.to_string());
 
-#[tauri::command]
-fn get_backend_status(state: tauri::State<Arc<Mutex<BackendState>>>) -> bool {
-    state.lock().unwrap().ready
+    match route {
+        "settings" | "logs" => {
+            if let Err(e) = windows::open(app, route) {
+                eprintln!("{}", e);
+            }
+        }
+        _ => navigate_main_window(app, route, id),
+    }
 }
 
-#[tauri::command]
-fn get_backend_config() -> BackendConfig {
-    BackendConfig {
-        base_url: backend_base_url(),
-        auth_token: backend_auth_token(),
+fn truncate_label(text: &str) -> String {
+    let single_line = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if single_line.chars().count() <= TRAY_LABEL_MAX_CHARS {
+        return single_line;
     }
+    let truncated: String = single_line.chars().take(TRAY_LABEL_MAX_CHARS - 1).collect();
+    format!("{}…", truncated.trim_end())
+}
+
+/// Query the backend for recent conversations and in-progress agent runs.
+/// Both endpoints are optional — older backends just yield fewer entries.
+fn fetch_tray_activity() -> TrayActivity {
+    let mut activity = TrayActivity::default();
+
+    if let Ok(serde_json::Value::Array(groups)) = backend_get_json("/api/groups") {
+        let mut groups: Vec<_> = groups
+            .iter()
+            .filter_map(|g| {
+                let id = g.get("id")?.as_str()?.to_string();
+                let name = g.get("name").and_then(|v| v.as_str()).unwrap_or(&id);
+                // Groups nobody has chatted in yet count from when they were added
+                let last_active = g
+                    .get("last_message_time")
+                    .and_then(|v| v.as_str())
+                    .or_else(|| g.get("added_at").and_then(|v| v.as_str()))
+                    .unwrap_or("");
+                Some((
+                    last_active.to_string(),
+                    TrayEntry {
+                        label: truncate_label(name),
+                        chat_id: id,
+                    },
+                ))
+            })
+            .collect();
+        // ISO timestamps sort lexicographically; most recently active first
+        groups.sort_by(|a, b| b.0.cmp(&a.0));
+        activity.recent_chats = groups
+            .into_iter()
+            .take(TRAY_RECENT_CHATS_LIMIT)
+            .map(|(_, entry)| entry)
+            .collect();
+    }
+
+    if let Ok(serde_json::Value::Array(tasks)) = backend_get_json("/api/tasks/running") {
+        activity.running_tasks = tasks
+            .iter()
+            .filter_map(|t| {
+                let chat_id = t.get("chat_jid")?.as_str()?.to_string();
+                // Scheduled tasks by their prompt, chat replies by their group
+                let label = t
+                    .get("prompt")
+                    .and_then(|v| v.as_str())
+                    .or_else(|| t.get("name").and_then(|v| v.as_str()))
+                    .unwrap_or(&chat_id);
+                Some(TrayEntry {
+                    label: truncate_label(label),
+                    chat_id,
+                })
+            })
+            .collect();
+    }
+
+    activity
+}
+
+/// Tell the user an agent run failed while they weren't looking.
+fn notify_agent_error(app: &AppHandle, chat_id: &str, error: &str) {
+    if is_main_window_visible(app) {
+        return;
+    }
+    notifications::notify(
+        app,
+        NotificationCategory::Errors,
+        i18n::tr(current_locale(app), "notify.reply_failed"),
+        error,
+        Some(("chat".to_string(), Some(chat_id.to_string()))),
+    );
+}
+
+/// Tell the user an agent reply finished while they weren't looking.
+fn notify_agent_completion(app: &AppHandle, chat_id: &str) {
+    if is_main_window_visible(app) {
+        return;
+    }
+    let title = app
+        .state::<TrayMenuState>()
+        .activity
+        .lock()
+        .unwrap()
+        .recent_chats
+        .iter()
+        .find(|entry| entry.chat_id == chat_id)
+        .map(|entry| entry.label.clone())
+        .unwrap_or_else(|| chat_id.to_string());
+    let body = i18n::tr(current_locale(app), "notify.reply_ready");
+    notifications::notify(
+        app,
+        NotificationCategory::Completions,
+        &title,
+        body,
+        Some(("chat".to_string(), Some(chat_id.to_string()))),
+    );
+}
+
+fn current_locale(app: &AppHandle) -> i18n::Locale {
+    let prefs = settings::load(&user_data_dir(app));
+    i18n::resolve(prefs.locale.as_deref())
+}
+
+/// `2h 5m`, `5m` or `<1m`; the tray only counts minutes.
+fn format_duration(secs: u64) -> String {
+    match (secs / 3600, secs % 3600 / 60) {
+        (0, 0) => "<1m".to_string(),
+        (0, minutes) => format!("{}m", minutes),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
+
+/// The read-only lines of the tray's backend status submenu.
+fn backend_stats_lines(locale: i18n::Locale, stats: &backend::BackendStats) -> Vec<String> {
+    let t = |key| i18n::tr(locale, key);
+    let uptime = match stats.uptime_secs {
+        Some(secs) => t("tray.uptime").replace("{}", &format_duration(secs)),
+        None => t("tray.not_running").to_string(),
+    };
+    let last_crash = match stats.last_crash_at {
+        Some(at) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(at, |d| d.as_millis() as u64);
+            t("tray.last_crash").replace("{}", &format_duration(now.saturating_sub(at) / 1000))
+        }
+        None => t("tray.no_crashes").to_string(),
+    };
+    vec![
+        uptime,
+        t("tray.restarts").replace("{}", &stats.restarts.to_string()),
+        t("tray.crashes").replace("{}", &stats.crashes.to_string()),
+        last_crash,
+    ]
+}
+
+fn build_tray_menu(app: &AppHandle, activity: &TrayActivity) -> tauri::Result<Menu<Wry>> {
+    let prefs = settings::load(&user_data_dir(app));
+    let locale = i18n::resolve(prefs.locale.as_deref());
+    let t = |key| i18n::tr(locale, key);
+    let mut builder = MenuBuilder::new(app);
+
+    if !activity.recent_chats.is_empty() {
+        let header = MenuItemBuilder::new(t("tray.recent_chats"))
+            .enabled(false)
+            .build(app)?;
+        builder = builder.item(&header);
+        for entry in &activity.recent_chats {
+            let item = MenuItemBuilder::with_id(format!("chat:{}", entry.chat_id), &entry.label)
+                .build(app)?;
+            builder = builder.item(&item);
+        }
+        builder = builder.separator();
+    }
+
+    if !activity.running_tasks.is_empty() {
+        let header = MenuItemBuilder::new(t("tray.running_tasks"))
+            .enabled(false)
+            .build(app)?;
+        builder = builder.item(&header);
+        for entry in &activity.running_tasks {
+            let item = MenuItemBuilder::with_id(format!("task:{}", entry.chat_id), &entry.label)
+                .build(app)?;
+            builder = builder.item(&item);
+        }
+        builder = builder.separator();
+    }
+
+    let open_item = MenuItemBuilder::with_id("open", t("tray.open_chat")).build(app)?;
+    let browser_item =
+        MenuItemBuilder::with_id("open_in_browser", t("tray.open_in_browser")).build(app)?;
+    let restart_item = MenuItemBuilder::with_id("restart", t("tray.restart_backend")).build(app)?;
+    let stats = app.state::<backend::BackendManager>().stats();
+    let mut stats_menu = SubmenuBuilder::new(app, t("tray.backend_stats"));
+    for line in backend_stats_lines(locale, &stats) {
+        stats_menu = stats_menu.item(&MenuItemBuilder::new(line).enabled(false).build(app)?);
+    }
+    let stats_menu = stats_menu.build()?;
+    let copy_url_item =
+        MenuItemBuilder::with_id("copy_api_url", t("tray.copy_api_url")).build(app)?;
+    let copy_token_item = MenuItemBuilder::with_id("copy_api_token", t("tray.copy_api_token"))
+        .enabled(backend_auth_token().is_some())
+        .build(app)?;
+    let on_top_item = CheckMenuItemBuilder::with_id("always_on_top", t("tray.always_on_top"))
+        .checked(prefs.always_on_top)
+        .build(app)?;
+    let updates_item =
+        MenuItemBuilder::with_id("check_updates", t("tray.check_updates")).build(app)?;
+    let remove_data_item =
+        MenuItemBuilder::with_id("remove_all_data", t("tray.remove_all_data")).build(app)?;
+    let quit_item = MenuItemBuilder::with_id("quit", t("tray.quit")).build(app)?;
+
+    builder
+        .item(&open_item)
+        .item(&browser_item)
+        .item(&restart_item)
+        .item(&stats_menu)
+        .separator()
+        .item(&copy_url_item)
+        .item(&copy_token_item)
+        .separator()
+        .item(&on_top_item)
+        .item(&updates_item)
+        .separator()
+        .item(&remove_data_item)
+        .item(&quit_item)
+        .build()
+}
+
+/// Rebuild the tray menu from the last known backend activity.
+fn rebuild_tray_menu(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let activity = app
+        .state::<TrayMenuState>()
+        .activity
+        .lock()
+        .unwrap()
+        .clone();
+    match build_tray_menu(app, &activity) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => eprintln!("Failed to rebuild tray menu: {}", e),
+    }
+}
+
+/// Periodically refresh the tray menu with backend activity.
+/// Polls readiness often so the menu fills in right after startup, but only
+/// queries the backend API every TRAY_REFRESH_INTERVAL. The status submenu is
+/// redrawn whenever its text would change, about once a minute.
+fn start_tray_refresh(app: AppHandle) {
+    std::thread::spawn(move || {
+        let manager = app.state::<backend::BackendManager>().inner().clone();
+        // Status changes wake the loop early, so the tray follows the backend
+        // coming up or going down without waiting out the poll
+        let status_changes = manager.subscribe();
+        let mut last_fetch: Option<Instant> = None;
+        let mut was_ready = false;
+        let mut shown_stats = Vec::new();
+
+        loop {
+            let ready = manager.status().ready;
+            let due = last_fetch.is_none_or(|t| t.elapsed() >= TRAY_REFRESH_INTERVAL);
+
+            let next = if !ready {
+                Some(TrayActivity::default())
+            } else if !was_ready || due {
+                last_fetch = Some(Instant::now());
+                Some(fetch_tray_activity())
+            } else {
+                None
+            };
+            was_ready = ready;
+
+            if let Some(next) = next {
+                let changed = {
+                    let tray_state = app.state::<TrayMenuState>();
+                    let mut current = tray_state.activity.lock().unwrap();
+                    let changed = *current != next;
+                    *current = next;
+                    changed
+                };
+                if changed {
+                    rebuild_tray_menu(&app);
+                }
+            }
+            let stats = backend_stats_lines(current_locale(&app), &manager.stats());
+            if stats != shown_stats {
+                rebuild_tray_menu(&app);
+                shown_stats = stats;
+            }
+
+            let _ = status_changes.recv_timeout(Duration::from_secs(2));
+        }
+    });
+}
+
+/// Take automatic backups per the backup schedule. A due backup waits until
+/// the backend is up with no agent task running, so the store is quiet while
+/// it's being copied, and for AC power when low-power mode is on.
+fn start_backup_scheduler(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(BACKUP_CHECK_INTERVAL);
+
+        let data = user_data_dir(&app);
+        let schedule = settings::load(&data).backup_schedule;
+        let Some(interval) = schedule.frequency.interval() else {
+            continue;
+        };
+        let due = backup::last_backup_time(&data)
+            .and_then(|t| SystemTime::now().duration_since(t).ok())
+            .is_none_or(|age| age >= interval);
+        let idle = app.state::<backend::BackendManager>().status().ready
+            && app
+                .state::<TrayMenuState>()
+                .activity
+                .lock()
+                .unwrap()
+                .running_tasks
+                .is_empty();
+        if !due || !idle || app.state::<power::PowerMonitor>().defer_work() {
+            continue;
+        }
+
+        // A manual backup already in progress counts for this round
+        let Ok(task) = tasks::start(&app, "create_backup", true) else {
+            continue;
+        };
+        let result = backup::create(
+            &data,
+            &backup::default_backup_path(&data, false),
+            None,
+            &task,
+        );
+        task.finish(&result);
+        if result.is_ok() {
+            backup::prune(&data, schedule.retention.max(1) as usize);
+        }
+        let payload = match result {
+            Ok(info) => ScheduledBackupResult {
+                backup: Some(info),
+                error: None,
+            },
+            Err(e) => {
+                eprintln!("Scheduled backup failed: {}", e);
+                ScheduledBackupResult {
+                    backup: None,
+                    error: Some(e),
+                }
+            }
+        };
+        let _ = app.emit("scheduled-backup", payload);
+    });
+}
+
+/// Sample CPU and memory of the backend and its containers every
+/// RESOURCE_SAMPLE_INTERVAL, keeping the latest for `get_resource_usage`
+/// and streaming it as `resource-usage` events. Also drives the memory
+/// watchdog.
+fn start_resource_sampler(app: AppHandle) {
+    std::thread::spawn(move || {
+        let monitor = app.state::<resources::ResourceMonitor>();
+        monitor.set_watchdog(settings::load(&user_data_dir(&app)).memory_watchdog);
+        let mut over_limit_since: Option<Instant> = None;
+        loop {
+            let usage = sample_resources(app.state::<backend::BackendManager>().status().pid);
+            monitor.set(usage.clone());
+            let _ = app.emit("resource-usage", usage.clone());
+            check_memory_watchdog(&app, &monitor.watchdog(), &usage, &mut over_limit_since);
+            std::thread::sleep(RESOURCE_SAMPLE_INTERVAL);
+        }
+    });
+}
+
+/// Restart the backend once its RSS has stayed above the watchdog limit for
+/// the configured period. Node backends occasionally leak over long uptimes.
+fn check_memory_watchdog(
+    app: &AppHandle,
+    watchdog: &settings::MemoryWatchdog,
+    usage: &resources::ResourceUsage,
+    over_limit_since: &mut Option<Instant>,
+) {
+    let limit = watchdog.threshold_mb.saturating_mul(1024 * 1024);
+    let rss = match &usage.backend {
+        Some(backend) if watchdog.enabled && backend.rss_bytes > limit => backend.rss_bytes,
+        _ => {
+            *over_limit_since = None;
+            return;
+        }
+    };
+    let since = *over_limit_since.get_or_insert_with(Instant::now);
+    if since.elapsed() < Duration::from_secs(watchdog.sustain_secs) {
+        return;
+    }
+    *over_limit_since = None;
+
+    logs::record(
+        app,
+        "stderr",
+        format!(
+            "[watchdog] Backend RSS {} MB above {} MB for {}s; restarting",
+            rss / (1024 * 1024),
+            watchdog.threshold_mb,
+            watchdog.sustain_secs
+        ),
+    );
+    let locale = current_locale(app);
+    notifications::notify(
+        app,
+        NotificationCategory::BackendCrashes,
+        i18n::tr(locale, "notify.backend_restarted"),
+        i18n::tr(locale, "notify.backend_restarted_memory"),
+        Some(("logs".to_string(), None)),
+    );
+    if let Err(e) = app.state::<backend::BackendManager>().restart() {
+        eprintln!("{}", e);
+    }
+}
+
+fn sample_resources(pid: Option<u32>) -> resources::ResourceUsage {
+    resources::ResourceUsage {
+        backend: pid.and_then(resources::process_usage),
+        // Agent containers only run under a live backend; don't poke Docker otherwise
+        containers: if pid.is_some() {
+            resources::container_usage()
+        } else {
+            Vec::new()
+        },
+        sampled_at: SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    }
+}
+
+/// Periodically persist window geometry captured from move/resize events.
+fn start_window_state_saver(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(WINDOW_STATE_FLUSH_INTERVAL);
+        app.state::<window_state::WindowStateTracker>()
+            .flush(&user_data_dir(&app));
+    });
+}
+
+/// After sleep, docker containers and sockets are often wedged while the shell
+/// still reports ready. Give the backend a grace period to answer health checks,
+/// then restart it (which also stops stale containers).
+fn recover_after_wake(app: &AppHandle) {
+    let _ = app.emit("system-wake", ());
+    // Docker may have stopped or restarted while asleep
+    setup::invalidate(app);
+    orphans::scan(app);
+
+    let backend = app.state::<backend::BackendManager>();
+    let status = backend.status();
+    if !status.running && !status.ready {
+        return;
+    }
+
+    let host = backend_host();
+    let port = backend_port();
+    let start = Instant::now();
+    while start.elapsed() < WAKE_HEALTH_GRACE {
+        if is_backend_healthy(&host, port) {
+            return;
+        }
+        std::thread::sleep(Duration::from_secs(1));
+    }
+
+    eprintln!("Backend unresponsive after wake; restarting");
+    if let Err(e) = backend.restart() {
+        eprintln!("{}", e);
+    }
+}
+
+/// Detect system wake by comparing wall-clock and monotonic time: the
+/// monotonic clock stops while the machine sleeps, the wall clock doesn't.
+fn start_wake_monitor(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last_wall = SystemTime::now();
+        let mut last_mono = Instant::now();
+        loop {
+            std::thread::sleep(WAKE_POLL_INTERVAL);
+
+            let wall_elapsed = SystemTime::now()
+                .duration_since(last_wall)
+                .unwrap_or_default();
+            let mono_elapsed = last_mono.elapsed();
+            last_wall = SystemTime::now();
+            last_mono = Instant::now();
+
+            if wall_elapsed > mono_elapsed + WAKE_GAP_THRESHOLD {
+                eprintln!(
+                    "System wake detected (slept ~{}s); checking backend",
+                    (wall_elapsed - mono_elapsed).as_secs()
+                );
+                recover_after_wake(&app);
+            }
+        }
+    });
+}
+
+/// The backend's address for tools outside the app: its URL, or the socket
+/// path when it serves a unix socket (for `curl --unix-socket`).
+fn backend_url_for_tools() -> String {
+    if let Some(socket) = backend_socket_path() {
+        return socket.display().to_string();
+    }
+    let host = backend_host();
+    // Listening on every interface; this machine reaches it on loopback
+    let host = if host == lan::LAN_HOST || host == "::" {
+        "127.0.0.1".to_string()
+    } else {
+        host
+    };
+    format!("http://{}:{}", host, backend_port())
+}
+
+/// Where a browser opens the backend: signed in through a one-time login link
+/// when it requires a token, so the token stays out of the browser history.
+fn browser_url() -> Result<String, String> {
+    if backend_socket_path().is_some() {
+        return Err("The backend serves a unix socket, which browsers can't open".to_string());
+    }
+    let base = backend_url_for_tools();
+    if backend_auth_token().is_none() {
+        return Ok(format!("{}/", base));
+    }
+    let headers = with_auth_header(Vec::new());
+    let response = backend_request(
+        "POST",
+        "/api/auth/one-time",
+        &headers,
+        None,
+        Duration::from_secs(3),
+    )?;
+    if !response.is_success() {
+        return Err(format!(
+            "The backend didn't issue a login link: HTTP {}",
+            response.status
+        ));
+    }
+    let code = response
+        .json()?
+        .get("code")
+        .and_then(|code| code.as_str())
+        .map(str::to_string)
+        .ok_or("The backend sent no login code")?;
+    Ok(format!("{}/auth/one-time?code={}", base, code))
+}
+
+fn open_in_browser_from_tray(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let opened = browser_url().and_then(|url| {
+            app.opener()
+                .open_url(url, None::<&str>)
+                .map_err(|e| format!("Failed to open the browser: {}", e))
+        });
+        if let Err(e) = opened {
+            eprintln!("{}", e);
+        }
+    });
+}
+
+/// Copy the API token after the user confirms, since it unlocks the API.
+fn copy_api_token_from_tray(app: &AppHandle) {
+    let Some(token) = backend_auth_token() else {
+        return;
+    };
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let locale = current_locale(&app);
+        let t = |key| i18n::tr(locale, key);
+        let confirmed = app
+            .dialog()
+            .message(t("copy_token.body"))
+            .title(t("copy_token.title"))
+            .kind(MessageDialogKind::Warning)
+            .buttons(MessageDialogButtons::OkCancelCustom(
+                t("copy_token.copy").to_string(),
+                t("copy_token.cancel").to_string(),
+            ))
+            .blocking_show();
+        if confirmed {
+            if let Err(e) = clipboard::copy(&app, &token) {
+                eprintln!("{}", e);
+            }
+        }
+    });
+}
+
+/// Check for an app update from the tray and hand the result to the frontend,
+/// which owns the confirmation UI.
+fn check_for_updates_from_tray(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match updater::check(&app).await {
+            Ok(Some(info)) => {
+                show_main_window(&app);
+                let _ = app.emit("update-available", info);
+            }
+            Ok(None) => {
+                let _ = app.emit("update-not-available", ());
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                let _ = app.emit("update-error", e);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_backend_status(manager: tauri::State<backend::BackendManager>) -> bool {
+    manager.status().ready
+}
+
+/// Open the backend in the default browser, signed in through a one-time
+/// login link when it requires a token.
+#[tauri::command]
+#[specta::specta]
+async fn open_in_browser(app: AppHandle) -> Result<(), ShellError> {
+    let url = tauri::async_runtime::spawn_blocking(browser_url)
+        .await
+        .map_err(|e| format!("Failed to open the browser: {}", e))??;
+    app.opener()
+        .open_url(url, None::<&str>)
+        .map_err(|e| format!("Failed to open the browser: {}", e).into())
+}
+
+/// Uptime, restarts and crashes of the backend since the app started.
+#[tauri::command]
+#[specta::specta]
+fn get_backend_stats(manager: tauri::State<backend::BackendManager>) -> backend::BackendStats {
+    manager.stats()
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_backend_config() -> BackendConfig {
+    BackendConfig {
+        base_url: backend_base_url(),
+        inspect_port: dev_mode::inspect_port().filter(|_| remote_backend().is_none()),
+    }
+}
+
+/// Replace the API token with a fresh one and restart the backend on it, so a
+/// leaked token stops working. The new config is pushed to the frontend.
+#[tauri::command]
+#[specta::specta]
+async fn rotate_api_token(app: AppHandle) -> Result<BackendConfig, ShellError> {
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let data = user_data_dir(&app);
+        if load_user_env(&data)
+            .iter()
+            .any(|(key, val)| key == "NANOCLAW_API_TOKEN" && !val.is_empty())
+        {
+            return Err(ShellError::InvalidInput(
+                "NANOCLAW_API_TOKEN is set in .env; change it there instead".to_string(),
+            ));
+        }
+
+        if remote_backend().is_some() {
+            return Err(ShellError::InvalidInput(
+                "The remote backend's token is set on that machine".to_string(),
+            ));
+        }
+        let token = secrets::generate_token()?;
+        secrets::store_api_token(&data, &token)?;
+        set_api_token(&token);
+        manager.restart()?;
+
+        let config = get_backend_config();
+        let _ = app.emit("backend-config-changed", config.clone());
+        Ok(config)
+    })
+    .await
+    .map_err(|e| format!("Token rotation failed: {}", e))?
+}
+
+/// Who is listening on the configured backend port, so the UI can tell the
+/// user exactly what to stop.
+#[tauri::command]
+#[specta::specta]
+async fn diagnose_port(app: AppHandle) -> Result<PortDiagnosis, ShellError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let port = backend_port();
+        PortDiagnosis {
+            host: backend_host(),
+            port,
+            owners: port_owners(port, &backend_dir(&app)),
+        }
+    })
+    .await
+    .map_err(|e| format!("Port diagnosis failed: {}", e).into())
+}
+
+/// Run blocking work off the async runtime as a registered task, so invokes
+/// don't stall each other and the UI can show progress for it.
+async fn run_task<T: Send + 'static, E: Into<ShellError> + Send + 'static>(
+    app: &AppHandle,
+    kind: &'static str,
+    cancellable: bool,
+    work: impl FnOnce(&tasks::TaskHandle) -> Result<T, E> + Send + 'static,
+) -> Result<T, ShellError> {
+    let task = tasks::start(app, kind, cancellable)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = work(&task).map_err(Into::into);
+        // Helpers report cancellation in their own words
+        let result = match result {
+            Err(_) if task.is_cancelled() => Err(ShellError::Cancelled),
+            result => result,
+        };
+        task.finish(&result);
+        result
+    })
+    .await
+    .map_err(|e| format!("{} failed: {}", kind, e))?
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn restart_backend(app: AppHandle) -> Result<(), ShellError> {
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    run_task(&app, "restart_backend", false, move |_| manager.restart()).await
+}
+
+/// Send a control message to the backend over its stdin, as one JSON object
+/// with a `command` field, e.g. `{ "command": "dumpState" }`. Only a backend
+/// the shell started can take them.
+#[tauri::command]
+#[specta::specta]
+async fn send_backend_command(
+    app: AppHandle,
+    message: serde_json::Value,
+) -> Result<(), ShellError> {
+    if !message.get("command").is_some_and(|c| c.is_string()) {
+        return Err(ShellError::InvalidInput(
+            "Backend commands are objects with a string `command` field".to_string(),
+        ));
+    }
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    // Serialized compactly, so the message is exactly one line
+    let line = message.to_string();
+    tauri::async_runtime::spawn_blocking(move || manager.send_command(line))
+        .await
+        .map_err(|e| format!("Failed to send backend command: {}", e))?
+}
+
+#[tauri::command]
+#[specta::specta]
+fn list_tasks(tasks: tauri::State<tasks::TaskManager>) -> Vec<tasks::TaskInfo> {
+    tasks.list()
+}
+
+#[tauri::command]
+#[specta::specta]
+fn cancel_task(
+    app: AppHandle,
+    tasks: tauri::State<tasks::TaskManager>,
+    id: u64,
+) -> Result<(), ShellError> {
+    tasks.cancel(&app, id).map_err(ShellError::from)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_dirs(app: AppHandle) -> DirConfig {
+    DirConfig {
+        bundle_dir: bundle_dir(&app).to_string_lossy().to_string(),
+        user_data_dir: user_data_dir(&app).to_string_lossy().to_string(),
+    }
+}
+
+/// Forward an API request to the backend with the auth token attached, so
+/// the webview needs neither CORS nor the token itself.
+#[tauri::command]
+#[specta::specta]
+async fn proxy_request(
+    method: String,
+    path: String,
+    headers: Option<Vec<(String, String)>>,
+    body: Option<String>,
+) -> Result<ProxyResponse, ShellError> {
+    let method = method.to_ascii_uppercase();
+    if !matches!(method.as_str(), "GET" | "POST" | "PUT" | "PATCH" | "DELETE") {
+        return Err(ShellError::InvalidInput(format!(
+            "Unsupported method: {}",
+            method
+        )));
+    }
+    if !path.starts_with("/api/") {
+        return Err(ShellError::InvalidInput(format!(
+            "Only /api/ paths can be proxied: {}",
+            path
+        )));
+    }
+    let headers = headers.unwrap_or_default();
+    http::validate_request(&path, &headers).map_err(ShellError::InvalidInput)?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let headers = with_auth_header(headers);
+        let response = backend_request(
+            &method,
+            &path,
+            &headers,
+            body.as_deref().map(str::as_bytes),
+            Duration::from_secs(60),
+        )?;
+        Ok(ProxyResponse {
+            status: response.status,
+            body: String::from_utf8_lossy(&response.body).to_string(),
+            headers: response.headers,
+        })
+    })
+    .await
+    .map_err(|e| format!("Proxy request failed: {}", e))?
+}
+
+/// Send a chat prompt through the shell; the reply streams as events on
+/// `chat-stream:<groupId>`.
+#[tauri::command]
+#[specta::specta]
+async fn start_chat_stream(
+    app: AppHandle,
+    group_id: String,
+    prompt: String,
+) -> Result<(), ShellError> {
+    tauri::async_runtime::spawn_blocking(move || chat_stream::start(&app, group_id, prompt))
+        .await
+        .map_err(|e| format!("Chat stream failed: {}", e))?
+        .map_err(ShellError::from)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn stop_chat_stream(app: AppHandle, group_id: String) {
+    chat_stream::stop(&app, &group_id);
+}
+
+/// Canonicalize `path` and require it to sit under the data or bundle dir, so
+/// the webview can't use file commands to probe the rest of the disk.
+fn resolve_allowed_path(app: &AppHandle, path: &Path) -> Result<PathBuf, String> {
+    let resolved = path
+        .canonicalize()
+        .map_err(|_| format!("Path not found: {}", path.display()))?;
+    let allowed = [user_data_dir(app), bundle_dir(app)]
+        .iter()
+        .filter_map(|root| root.canonicalize().ok())
+        .any(|root| resolved.starts_with(root));
+    if !allowed {
+        return Err(format!(
+            "Path is outside the app's folders: {}",
+            path.display()
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Show a file or folder in Finder / the platform file manager.
+#[tauri::command]
+#[specta::specta]
+fn reveal_in_file_manager(app: AppHandle, path: PathBuf) -> Result<(), ShellError> {
+    let path = resolve_allowed_path(&app, &path)?;
+    app.opener()
+        .reveal_item_in_dir(&path)
+        .map_err(|e| format!("Failed to reveal {}: {}", path.display(), e).into())
+}
+
+/// Every group folder with its containers and, while the backend runs, its
+/// name and chat id.
+#[tauri::command]
+#[specta::specta]
+async fn list_groups(app: AppHandle) -> Result<Vec<groups::GroupInfo>, ShellError> {
+    let data = user_data_dir(&app);
+    tauri::async_runtime::spawn_blocking(move || {
+        groups::list(&data, backend_get_json("/api/groups").ok())
+    })
+    .await
+    .map_err(|e| format!("Listing groups failed: {}", e).into())
+}
+
+/// Stop one group's containers without touching the others; its next message
+/// starts a fresh one. Returns the containers stopped.
+#[tauri::command]
+#[specta::specta]
+async fn stop_group_containers(
+    app: AppHandle,
+    group_id: String,
+) -> Result<Vec<String>, ShellError> {
+    let data = user_data_dir(&app);
+    tauri::async_runtime::spawn_blocking(move || groups::stop(&data, &group_id))
+        .await
+        .map_err(|e| format!("Stopping containers failed: {}", e))?
+        .map_err(ShellError::from)
+}
+
+/// A group's most recent container run logs, newest first (5 by default).
+#[tauri::command]
+#[specta::specta]
+fn get_group_logs(
+    app: AppHandle,
+    group_id: String,
+    count: Option<u32>,
+) -> Result<Vec<groups::GroupLog>, ShellError> {
+    Ok(groups::logs(
+        &user_data_dir(&app),
+        &group_id,
+        count.map(|c| c as usize),
+    )?)
+}
+
+/// Package a group's folder and its store rows into a zip at `dest` (default:
+/// the data dir's exports folder). Returns where it was written.
+#[tauri::command]
+#[specta::specta]
+async fn export_group(
+    app: AppHandle,
+    group_id: String,
+    dest: Option<PathBuf>,
+) -> Result<PathBuf, ShellError> {
+    let data = user_data_dir(&app);
+    let dest = dest.unwrap_or_else(|| groups::default_export_path(&data, &group_id));
+    run_task(&app, "export_group", true, move |task| {
+        groups::export(&data, &group_id, &dest, task)
+    })
+    .await
+}
+
+/// Export every conversation as Markdown, JSON or HTML into a folder the user
+/// picks, as a task with progress. Returns the folder written, or None if the
+/// dialog is dismissed.
+#[tauri::command]
+#[specta::specta]
+async fn export_conversations(
+    app: AppHandle,
+    format: conversations::ExportFormat,
+) -> Result<Option<PathBuf>, ShellError> {
+    let picker = app.clone();
+    let dir = tauri::async_runtime::spawn_blocking(move || conversations::pick_dir(&picker))
+        .await
+        .map_err(|e| format!("Exporting conversations failed: {}", e))??;
+    let Some(dir) = dir else {
+        return Ok(None);
+    };
+    run_task(&app, "export_conversations", true, move |task| {
+        conversations::export(&dir, format, task)
+    })
+    .await
+    .map(Some)
+}
+
+/// Show a conversation's folder (its artifacts and attachments) in the file manager.
+#[tauri::command]
+#[specta::specta]
+fn reveal_group_folder(app: AppHandle, group_id: String) -> Result<(), ShellError> {
+    let dir = attachments::group_dir(&user_data_dir(&app), &group_id)?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create group folder: {}", e))?;
+    reveal_in_file_manager(app, dir)
+}
+
+/// Let the user pick files with the native dialog and copy them into the
+/// group's attachments folder, where its agent can read them.
+#[tauri::command]
+#[specta::specta]
+async fn pick_attachments(
+    app: AppHandle,
+    group_id: String,
+) -> Result<Vec<attachments::Attachment>, ShellError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        attachments::pick_and_attach(&app, &user_data_dir(&app), &group_id)
+    })
+    .await
+    .map_err(|e| format!("Attaching files failed: {}", e))?
+    .map_err(ShellError::from)
+}
+
+/// Attach files dropped onto the window, by their staged path.
+#[tauri::command]
+#[specta::specta]
+async fn attach_files(
+    app: AppHandle,
+    group_id: String,
+    paths: Vec<PathBuf>,
+) -> Result<Vec<attachments::Attachment>, ShellError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        attachments::attach_staged(&user_data_dir(&app), &group_id, &paths)
+    })
+    .await
+    .map_err(|e| format!("Attaching files failed: {}", e))?
+    .map_err(ShellError::from)
+}
+
+/// Save a copy of a data-dir file wherever the user chooses.
+#[tauri::command]
+#[specta::specta]
+async fn save_file_as(app: AppHandle, path: PathBuf) -> Result<Option<String>, ShellError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        attachments::save_copy(&app, &user_data_dir(&app), &path)
+            .map(|dest| dest.map(|d| d.to_string_lossy().to_string()))
+    })
+    .await
+    .map_err(|e| format!("Saving file failed: {}", e))?
+    .map_err(ShellError::from)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_backend_listen_config() -> ListenConfig {
+    ListenConfig {
+        host: backend_host(),
+        port: backend_port(),
+        socket_path: backend_socket_path().map(|p| p.to_string_lossy().to_string()),
+    }
+}
+
+/// Validate and persist a new listen address, then restart the backend on it.
+#[tauri::command]
+#[specta::specta]
+async fn set_backend_listen_config(
+    app: AppHandle,
+    host: String,
+    port: u16,
+) -> Result<ListenConfig, ShellError> {
+    let host = host.trim().to_string();
+    if !host.eq_ignore_ascii_case("localhost") && host.parse::<std::net::IpAddr>().is_err() {
+        return Err(ShellError::InvalidInput(format!("Invalid host: {}", host)));
+    }
+    if port == 0 {
+        return Err(ShellError::InvalidInput(
+            "Port must be between 1 and 65535".to_string(),
+        ));
+    }
+
+    let data = user_data_dir(&app);
+    if let Some((key, _)) = load_user_env(&data)
+        .into_iter()
+        .find(|(key, _)| key == "HTTP_HOST" || key == "PORT")
+    {
+        return Err(ShellError::InvalidInput(format!(
+            "{} is set in .env; change it there instead",
+            key
+        )));
+    }
+    let prefs = settings::load(&data);
+    if !is_loopback_host(&host) && !prefs.allow_lan_access {
+        return Err(ShellError::InvalidInput(
+            "Enable LAN access before binding to a non-loopback host".to_string(),
+        ));
+    }
+
+    settings::update(&data, |s| {
+        s.backend_host = Some(host.clone());
+        s.backend_port = Some(port);
+    })?;
+    update_backend_runtime(|runtime| {
+        runtime.host = Some(host.clone());
+        runtime.port = Some(port);
+    });
+
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = manager.restart();
+        let _ = app.emit("backend-config-changed", get_backend_config());
+        result
+    })
+    .await
+    .map_err(|e| format!("Backend restart failed: {}", e))??;
+    Ok(get_backend_listen_config())
+}
+
+/// Turn LAN mode on or off. On, the backend binds every interface and
+/// requires a freshly generated pairing token (unless .env pins the API
+/// token); off, it goes back to loopback. Returns the pairing info when
+/// turning it on.
+#[tauri::command]
+#[specta::specta]
+async fn set_lan_mode(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<Option<lan::LanPairing>, ShellError> {
+    if remote_backend().is_some() {
+        return Err(ShellError::InvalidInput(
+            "LAN mode is for a backend on this machine".to_string(),
+        ));
+    }
+    let data = user_data_dir(&app);
+    let user_env = load_user_env(&data);
+    if user_env.iter().any(|(key, _)| key == "HTTP_HOST") {
+        return Err(ShellError::InvalidInput(
+            "HTTP_HOST is set in .env; change it there instead".to_string(),
+        ));
+    }
+    let pinned_token = user_env
+        .iter()
+        .any(|(key, val)| key == "NANOCLAW_API_TOKEN" && !val.is_empty());
+    if enabled && !pinned_token {
+        let token = secrets::generate_token()?;
+        secrets::store_api_token(&data, &token)?;
+        set_api_token(&token);
+    }
+
+    let host = if enabled { lan::LAN_HOST } else { "127.0.0.1" };
+    settings::update(&data, |s| {
+        s.allow_lan_access = enabled;
+        s.backend_host = Some(host.to_string());
+        if enabled {
+            s.require_api_token = true;
+        }
+    })?;
+    update_backend_runtime(|runtime| runtime.host = Some(host.to_string()));
+
+    let handle = app.clone();
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = manager.restart();
+        let _ = handle.emit("backend-config-changed", get_backend_config());
+        result
+    })
+    .await
+    .map_err(|e| format!("Backend restart failed: {}", e))??;
+
+    if enabled {
+        get_lan_pairing(app).map(Some)
+    } else {
+        Ok(None)
+    }
+}
+
+/// The URL, token and QR code a phone on the same network pairs with.
+#[tauri::command]
+#[specta::specta]
+fn get_lan_pairing(app: AppHandle) -> Result<lan::LanPairing, ShellError> {
+    if !lan::is_enabled(&settings::load(&user_data_dir(&app))) {
+        return Err(ShellError::InvalidInput("LAN mode is off".to_string()));
+    }
+    let token = backend_auth_token().ok_or("LAN mode needs an API token")?;
+    Ok(lan::pairing(backend_port(), &token)?)
+}
+
+/// The configured tunnel and the public URL its client reported.
+#[tauri::command]
+#[specta::specta]
+fn get_tunnel_status(app: AppHandle) -> tunnel::TunnelStatus {
+    tunnel::TunnelStatus {
+        provider: settings::load(&user_data_dir(&app)).tunnel,
+        public_url: tunnel::public_url(),
+    }
+}
+
+/// Restart the backend so it picks up changed settings, if it's running.
+/// Off the async runtime, as a restart waits for the old process to exit.
+async fn restart_if_running(app: &AppHandle) -> Result<(), ShellError> {
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if manager.status().running {
+            manager.restart()?;
+        }
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Backend restart failed: {}", e))?
+}
+
+/// Run the backend behind a tunnel client, or stop doing so. A tunnel makes
+/// the backend require an API token; the URL arrives as `tunnel-url` once the
+/// client prints it.
+#[tauri::command]
+#[specta::specta]
+async fn set_tunnel_provider(
+    app: AppHandle,
+    provider: settings::TunnelProvider,
+) -> Result<(), ShellError> {
+    if provider != settings::TunnelProvider::Off && remote_backend().is_some() {
+        return Err(ShellError::InvalidInput(
+            "A tunnel is for a backend on this machine".to_string(),
+        ));
+    }
+    let data = user_data_dir(&app);
+    settings::update(&data, |s| {
+        s.tunnel = provider;
+        if provider != settings::TunnelProvider::Off {
+            s.require_api_token = true;
+        }
+    })?;
+    if provider != settings::TunnelProvider::Off {
+        init_api_token(&data, true);
+    }
+
+    restart_if_running(&app).await
+}
+
+/// Variables the shell sets for the backend itself; overriding them would
+/// leave the two disagreeing on where the backend is.
+const SHELL_MANAGED_ENV: &[&str] = &[
+    "HTTP_HOST",
+    "PORT",
+    "NANOCLAW_API_TOKEN",
+    "NANOCLAW_BUNDLE_DIR",
+    "NANOCLAW_DATA_DIR",
+    "NANOCLAW_SOCKET_PATH",
+    "NANOCLAW_STDIN_COMMANDS",
+    "NANOCLAW_WARM_POOL_SIZE",
+    "NANOCLAW_CONTAINER_GPUS",
+];
+
+/// The proxy settings, with what macOS reports and what's in effect.
+#[tauri::command]
+#[specta::specta]
+fn get_proxy_status(app: AppHandle) -> proxy::ProxyStatus {
+    let settings = settings::load(&user_data_dir(&app)).proxy;
+    proxy::ProxyStatus {
+        system: proxy::system(),
+        effective: proxy::resolve(&settings),
+        settings,
+    }
+}
+
+/// Save the proxy settings and restart the backend with them.
+#[tauri::command]
+#[specta::specta]
+async fn set_proxy_settings(
+    app: AppHandle,
+    proxy: settings::ProxySettings,
+) -> Result<(), ShellError> {
+    proxy::validate(&proxy).map_err(ShellError::InvalidInput)?;
+    settings::update(&user_data_dir(&app), |s| s.proxy = proxy)?;
+
+    restart_if_running(&app).await
+}
+
+/// The extra CA bundle, if one is configured, with what's in it.
+#[tauri::command]
+#[specta::specta]
+fn get_ca_bundle(app: AppHandle) -> Result<Option<certs::CaBundleInfo>, ShellError> {
+    let bundle = settings::load(&user_data_dir(&app)).extra_ca_bundle;
+    Ok(bundle.as_deref().map(certs::inspect).transpose()?)
+}
+
+/// Trust the CA certificates in `path` (a PEM file) on top of the system's,
+/// for the shell's HTTPS requests and the backend's; None goes back to the
+/// system roots. Restarts the backend to pick it up.
+#[tauri::command]
+#[specta::specta]
+async fn set_ca_bundle(
+    app: AppHandle,
+    path: Option<PathBuf>,
+) -> Result<Option<certs::CaBundleInfo>, ShellError> {
+    let info = path
+        .as_deref()
+        .map(certs::inspect)
+        .transpose()
+        .map_err(ShellError::InvalidInput)?;
+    let data = user_data_dir(&app);
+    certs::apply(&data, path.as_deref())?;
+    settings::update(&data, |s| s.extra_ca_bundle = path)?;
+
+    restart_if_running(&app).await?;
+    Ok(info)
+}
+
+/// Extra environment the backend is started with, over the .env values.
+#[tauri::command]
+#[specta::specta]
+fn get_backend_env(app: AppHandle) -> BTreeMap<String, String> {
+    settings::load(&user_data_dir(&app)).backend_env
+}
+
+/// Replace the extra backend environment and restart the backend with it.
+#[tauri::command]
+#[specta::specta]
+async fn set_backend_env(app: AppHandle, env: BTreeMap<String, String>) -> Result<(), ShellError> {
+    for key in env.keys() {
+        let valid = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(ShellError::InvalidInput(format!(
+                "Invalid variable name: {}",
+                key
+            )));
+        }
+        if SHELL_MANAGED_ENV.contains(&key.as_str()) {
+            return Err(ShellError::InvalidInput(format!(
+                "{} is managed by NanoClaw and can't be overridden",
+                key
+            )));
+        }
+    }
+    settings::update(&user_data_dir(&app), |s| s.backend_env = env)?;
+
+    restart_if_running(&app).await
+}
+
+#[derive(Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+struct WarmPoolStatus {
+    /// Configured size; 0 when the pool is off.
+    size: u32,
+    /// Warm containers waiting for a request right now.
+    containers: Vec<String>,
+}
+
+/// The warm agent container pool: its size and the containers in it.
+#[tauri::command]
+#[specta::specta]
+async fn get_warm_pool(app: AppHandle) -> Result<WarmPoolStatus, ShellError> {
+    let size = settings::load(&user_data_dir(&app)).warm_pool_size;
+    tauri::async_runtime::spawn_blocking(move || {
+        let names = image::docker_output(&[
+            "ps",
+            "--filter",
+            "name=nanoclaw-warm-",
+            "--format",
+            "{{.Names}}",
+        ])?;
+        Ok(WarmPoolStatus {
+            size,
+            containers: names.lines().map(str::to_string).collect(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Warm pool check failed: {}", e))?
+}
+
+/// Keep `size` agent containers booted so requests skip `docker run` (0 turns
+/// the pool off). The backend runs the pool, so it restarts to apply this.
+#[tauri::command]
+#[specta::specta]
+async fn set_warm_pool_size(app: AppHandle, size: u32) -> Result<(), ShellError> {
+    if size > MAX_WARM_POOL_SIZE {
+        return Err(ShellError::InvalidInput(format!(
+            "The warm pool holds at most {} containers",
+            MAX_WARM_POOL_SIZE
+        )));
+    }
+    settings::update(&user_data_dir(&app), |s| s.warm_pool_size = size)?;
+
+    restart_if_running(&app).await
+}
+
+/// Pass the machine's GPUs to agent containers. Turning it on needs a Docker
+/// that supports it (`gpuAvailable` in the setup status). Restarts the
+/// backend to apply.
+#[tauri::command]
+#[specta::specta]
+async fn set_gpu_passthrough(app: AppHandle, enabled: bool) -> Result<(), ShellError> {
+    let probe = app.clone();
+    let available =
+        tauri::async_runtime::spawn_blocking(move || !enabled || setup::gpu_available(&probe))
+            .await
+            .map_err(|e| format!("GPU check failed: {}", e))?;
+    if !available {
+        return Err(ShellError::InvalidInput(
+            "Docker on this machine can't pass GPUs to containers".to_string(),
+        ));
+    }
+    settings::update(&user_data_dir(&app), |s| s.gpu_passthrough = enabled)?;
+    restart_if_running(&app).await
+}
+
+/// Sign in to a model provider in the browser and pass the token it issues
+/// to the backend as `provider.envKey`, restarting the backend to apply it.
+#[tauri::command]
+#[specta::specta]
+async fn oauth_sign_in(app: AppHandle, provider: oauth::OAuthProvider) -> Result<(), ShellError> {
+    let signing_in = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        oauth::sign_in(&signing_in, &provider)?;
+        setup::refresh(&signing_in);
+        Ok::<_, ShellError>(())
+    })
+    .await
+    .map_err(|e| format!("Sign-in failed: {}", e))??;
+    restart_if_running(&app).await
+}
+
+/// Forget a provider's OAuth token. The backend keeps it until it restarts.
+#[tauri::command]
+#[specta::specta]
+fn oauth_sign_out(app: AppHandle, provider_id: String) -> Result<(), ShellError> {
+    oauth::sign_out(&user_data_dir(&app), &provider_id)?;
+    std::thread::spawn(move || {
+        setup::refresh(&app);
+    });
+    Ok(())
+}
+
+/// Turn dev mode on or off (or change its options) and restart the backend
+/// to apply it. The new config, with the inspector port, is pushed to the
+/// frontend.
+#[tauri::command]
+#[specta::specta]
+async fn set_dev_mode(
+    app: AppHandle,
+    dev_mode: settings::DevMode,
+) -> Result<BackendConfig, ShellError> {
+    if dev_mode.enabled && dev_mode.inspect_port == 0 {
+        return Err(ShellError::InvalidInput(
+            "Inspector port must be between 1 and 65535".to_string(),
+        ));
+    }
+    settings::update(&user_data_dir(&app), |s| s.dev_mode = dev_mode)?;
+
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = manager.restart();
+        let config = get_backend_config();
+        let _ = app.emit("backend-config-changed", config.clone());
+        result.map(|_| config)
+    })
+    .await
+    .map_err(|e| format!("Backend restart failed: {}", e))?
+}
+
+/// The remote backend the shell connects to, if any.
+#[tauri::command]
+#[specta::specta]
+fn get_remote_backend(app: AppHandle) -> Option<String> {
+    settings::load(&user_data_dir(&app)).remote_backend_url
+}
+
+/// Connect to the backend at `url` (`http://host[:port]`) with `token`
+/// instead of starting one, or go back to the local backend with no URL. The
+/// local backend is stopped or started to match, and the new config is
+/// pushed to the frontend.
+#[tauri::command]
+#[specta::specta]
+async fn set_remote_backend(
+    app: AppHandle,
+    url: Option<String>,
+    token: Option<String>,
+) -> Result<BackendConfig, ShellError> {
+    let url = url
+        .map(|url| url.trim().trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty());
+    if let Some(url) = &url {
+        parse_backend_url(url).map_err(ShellError::InvalidInput)?;
+    }
+    let token = token
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty() && url.is_some());
+
+    let data = user_data_dir(&app);
+    secrets::store_remote_token(&data, token.as_deref())?;
+    settings::update(&data, |s| s.remote_backend_url = url.clone())?;
+
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        // Let go of the current backend before the config points elsewhere
+        manager.stop();
+        apply_remote_backend(&data, url.as_deref());
+        let result = manager.start();
+        let config = get_backend_config();
+        let _ = app.emit("backend-config-changed", config.clone());
+        result.map(|_| config)
+    })
+    .await
+    .map_err(|e| format!("Backend restart failed: {}", e))?
+}
+
+/// Install the `nanoclaw` terminal command (`nanoclaw open`, `logs`,
+/// `restart`, ...) into /usr/local/bin, asking for an administrator password
+/// if needed. Returns where it went.
+#[tauri::command]
+#[specta::specta]
+async fn install_cli(app: AppHandle) -> Result<PathBuf, ShellError> {
+    let data = user_data_dir(&app);
+    tauri::async_runtime::spawn_blocking(move || cli_install::install(&data))
+        .await
+        .map_err(|e| format!("CLI install failed: {}", e))?
+        .map_err(ShellError::from)
+}
+
+/// How PATH was resolved at startup and where node and docker come from,
+/// for debugging a packaged app that can't find them.
+#[tauri::command]
+#[specta::specta]
+fn debug_path_resolution() -> path_env::PathResolution {
+    path_env::resolution()
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn check_setup(app: AppHandle) -> Result<setup::SetupStatus, ShellError> {
+    tauri::async_runtime::spawn_blocking(move || setup::status(&app))
+        .await
+        .map_err(|e| format!("Setup check failed: {}", e).into())
+}
+
+/// Drop cached setup checks and re-probe now.
+#[tauri::command]
+#[specta::specta]
+async fn refresh_setup_status(app: AppHandle) -> Result<setup::SetupStatus, ShellError> {
+    tauri::async_runtime::spawn_blocking(move || setup::refresh(&app))
+        .await
+        .map_err(|e| format!("Setup check failed: {}", e).into())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn save_env_config(app: AppHandle, entries: Vec<(String, String)>) -> Result<(), ShellError> {
+    let data = user_data_dir(&app);
+    // Entries not being changed, and comments, are kept as written
+    let existing = std::fs::read_to_string(env_file::path(&data)).unwrap_or_default();
+    env_file::write(&data, &env_file::update(&existing, &entries))?;
+
+    // Credentials feed into the setup status; let open screens know
+    std::thread::spawn(move || {
+        setup::refresh(&app);
+    });
+    Ok(())
+}
+
+/// Use a model from the local Ollama server: writes the AGNO_* settings to
+/// `.env` so the agent runs fully offline.
+#[tauri::command]
+#[specta::specta]
+fn configure_ollama(app: AppHandle, model: String) -> Result<(), ShellError> {
+    configure_local_llm(app, local_llm::OLLAMA_PORT, model)
+}
+
+/// OpenAI-compatible model servers on this machine (LM Studio, llama.cpp,
+/// Ollama, ...), found by probing their usual ports.
+#[tauri::command]
+#[specta::specta]
+async fn discover_local_llms() -> Result<Vec<local_llm::LocalServer>, ShellError> {
+    tauri::async_runtime::spawn_blocking(local_llm::discover)
+        .await
+        .map_err(|e| format!("Discovery failed: {}", e).into())
+}
+
+/// Use a model from a local server `discover_local_llms` found.
+#[tauri::command]
+#[specta::specta]
+fn configure_local_llm(app: AppHandle, port: u16, model: String) -> Result<(), ShellError> {
+    local_llm::configure(&user_data_dir(&app), port, &model)?;
+    std::thread::spawn(move || {
+        setup::refresh(&app);
+    });
+    Ok(())
+}
+
+/// Model ids the configured provider offers, for a picker in settings.
+#[tauri::command]
+#[specta::specta]
+async fn list_models(app: AppHandle) -> Result<models::ModelList, ShellError> {
+    let data = user_data_dir(&app);
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut env = load_user_env(&data);
+        env.extend(oauth::env(&data, &settings::load(&data)));
+        models::list(&env).map_err(ShellError::from)
+    })
+    .await
+    .map_err(|e| format!("Model list failed: {}", e))?
+}
+
+/// Earlier versions of `.env`, newest first; one is kept per save.
+#[tauri::command]
+#[specta::specta]
+fn list_env_backups(app: AppHandle) -> Vec<env_file::EnvBackup> {
+    env_file::list(&user_data_dir(&app))
+}
+
+/// Put back an earlier `.env` (the latest backup if no name is given). The
+/// current one is backed up first, so this can be undone the same way.
+#[tauri::command]
+#[specta::specta]
+fn restore_env_backup(app: AppHandle, name: Option<String>) -> Result<(), ShellError> {
+    env_file::restore(&user_data_dir(&app), name.as_deref())?;
+    std::thread::spawn(move || {
+        setup::refresh(&app);
+    });
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn read_env_config(app: AppHandle) -> Vec<(String, String)> {
+    let data = user_data_dir(&app);
+    load_user_env(&data)
+}
+
+/// Set the pending-reply badge on the tray icon. Ignored while the main window
+/// is visible, since the user is already looking at the replies.
+#[tauri::command]
+#[specta::specta]
+fn set_tray_badge(app: AppHandle, count: u32) {
+    let count = if is_main_window_visible(&app) {
+        0
+    } else {
+        count
+    };
+    update_tray_badge(&app, count);
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_locale(app: AppHandle) -> String {
+    current_locale(&app).code().to_string()
+}
+
+/// Persist the UI locale ("en", "zh", or null to follow the system) and
+/// re-render shell-owned strings such as the tray menu.
+#[tauri::command]
+#[specta::specta]
+fn set_locale(app: AppHandle, locale: Option<String>) -> Result<(), ShellError> {
+    if let Some(tag) = locale.as_deref() {
+        if i18n::Locale::from_tag(tag).is_none() {
+            return Err(ShellError::InvalidInput(format!(
+                "Unsupported locale: {}",
+                tag
+            )));
+        }
+    }
+    settings::update(&user_data_dir(&app), |s| s.locale = locale)?;
+    rebuild_tray_menu(&app);
+    #[cfg(target_os = "macos")]
+    if let Err(e) = app_menu::install(&app) {
+        eprintln!("Failed to rebuild the menu bar: {}", e);
+    }
+    Ok(())
+}
+
+fn apply_always_on_top(app: &AppHandle, enabled: bool) -> Result<(), String> {
+    settings::update(&user_data_dir(app), |s| s.always_on_top = enabled)?;
+    if let Some(window) = app.get_webview_window("main") {
+        window
+            .set_always_on_top(enabled)
+            .map_err(|e| format!("Failed to set always on top: {}", e))?;
+    }
+    // Keep the tray check mark in sync when toggled from the frontend
+    rebuild_tray_menu(app);
+    Ok(())
+}
+
+/// Pin the main window above other apps (persisted across launches).
+#[tauri::command]
+#[specta::specta]
+fn set_always_on_top(app: AppHandle, enabled: bool) -> Result<(), ShellError> {
+    apply_always_on_top(&app, enabled).map_err(ShellError::from)
+}
+
+/// The windows' page zoom; 1.0 is actual size.
+#[tauri::command]
+#[specta::specta]
+fn get_zoom(app: AppHandle) -> f64 {
+    zoom::get(&app)
+}
+
+/// Zoom every window, between 50% and 300%, and keep the level for next
+/// time. Returns the level applied.
+#[tauri::command]
+#[specta::specta]
+fn set_zoom(app: AppHandle, level: f64) -> Result<f64, ShellError> {
+    Ok(zoom::set(&app, level)?)
+}
+
+/// Hold off idle sleep while an agent run is in progress. The frontend turns
+/// it on for the length of a run; it is also released if the backend exits.
+#[tauri::command]
+#[specta::specta]
+fn set_keep_awake(
+    keep_awake: tauri::State<power::KeepAwake>,
+    enabled: bool,
+) -> Result<(), ShellError> {
+    keep_awake.set(enabled).map_err(ShellError::from)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_power_state(monitor: tauri::State<power::PowerMonitor>) -> power::PowerState {
+    monitor.state()
+}
+
+/// Open (or focus) a secondary window such as "settings" or "logs".
+#[tauri::command]
+#[specta::specta]
+fn open_window(app: AppHandle, name: String) -> Result<(), ShellError> {
+    windows::open(&app, &name).map_err(ShellError::from)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_backend_logs(logs: tauri::State<logs::BackendLogs>) -> Vec<logs::LogLine> {
+    logs.snapshot()
+}
+
+#[tauri::command]
+#[specta::specta]
+fn clear_backend_logs(logs: tauri::State<logs::BackendLogs>) {
+    logs.clear();
+}
+
+/// Post a native notification from the frontend under `category`; `route`/`id`
+/// are opened when the user comes back. Returns false if preferences suppressed it.
+#[tauri::command]
+#[specta::specta]
+fn notify(
+    app: AppHandle,
+    title: String,
+    body: String,
+    category: NotificationCategory,
+    route: Option<String>,
+    id: Option<String>,
+) -> bool {
+    notifications::notify(
+        &app,
+        category,
+        &title,
+        &body,
+        route.map(|route| (route, id)),
+    )
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_notification_prefs(app: AppHandle) -> settings::NotificationPrefs {
+    settings::load(&user_data_dir(&app)).notifications
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_notification_prefs(
+    app: AppHandle,
+    prefs: settings::NotificationPrefs,
+) -> Result<(), ShellError> {
+    settings::update(&user_data_dir(&app), |s| s.notifications = prefs)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_close_behavior(app: AppHandle) -> settings::CloseBehavior {
+    settings::load(&user_data_dir(&app)).close_behavior
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_close_behavior(app: AppHandle, behavior: settings::CloseBehavior) -> Result<(), ShellError> {
+    settings::update(&user_data_dir(&app), |s| s.close_behavior = behavior)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_allow_lan_access(app: AppHandle) -> bool {
+    settings::load(&user_data_dir(&app)).allow_lan_access
+}
+
+/// Opt in or out of non-loopback binds; takes effect on the next backend start.
+#[tauri::command]
+#[specta::specta]
+fn set_allow_lan_access(app: AppHandle, allowed: bool) -> Result<(), ShellError> {
+    settings::update(&user_data_dir(&app), |s| s.allow_lan_access = allowed)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_update_channel(app: AppHandle) -> settings::UpdateChannel {
+    settings::load(&user_data_dir(&app)).update_channel
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_update_channel(app: AppHandle, channel: settings::UpdateChannel) -> Result<(), ShellError> {
+    settings::update(&user_data_dir(&app), |s| s.update_channel = channel)?;
+    Ok(())
+}
+
+/// True if any window other than `except` (and the transient quick-chat popover) is showing.
+fn has_other_visible_window(app: &AppHandle, except: &str) -> bool {
+    app.webview_windows().iter().any(|(label, window)| {
+        label != except && label != quick_chat::LABEL && window.is_visible().unwrap_or(false)
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn check_for_updates(app: AppHandle) -> Result<Option<updater::UpdateInfo>, ShellError> {
+    updater::check(&app).await.map_err(ShellError::from)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn install_update(app: AppHandle) -> Result<(), ShellError> {
+    let task = tasks::start(&app, "install_update", false)?;
+    let result = updater::install(&app, &task)
+        .await
+        .map_err(ShellError::from);
+    task.finish(&result);
+    result
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn check_backend_update(
+    app: AppHandle,
+) -> Result<backend_update::BackendUpdateInfo, ShellError> {
+    let data = user_data_dir(&app);
+    let bundled_version = app.package_info().version.to_string();
+    let channel = settings::load(&data).update_channel;
+    tauri::async_runtime::spawn_blocking(move || {
+        backend_update::check(&data, &bundled_version, channel)
+    })
+    .await
+    .map_err(|e| format!("Backend update check failed: {}", e))?
+    .map_err(ShellError::from)
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn check_image_update(app: AppHandle) -> Result<image::ImageUpdateInfo, ShellError> {
+    let channel = settings::load(&user_data_dir(&app)).update_channel;
+    tauri::async_runtime::spawn_blocking(move || image::check_update(channel))
+        .await
+        .map_err(|e| format!("Image update check failed: {}", e))?
+        .map_err(ShellError::from)
+}
+
+/// Pull the channel's prebuilt agent image, verified against the pinned
+/// digest and release signature, and use it instead of a local build.
+/// Returns the verified digest, which is also recorded in the settings.
+#[tauri::command]
+#[specta::specta]
+async fn pull_container_image(app: AppHandle) -> Result<String, ShellError> {
+    let data = user_data_dir(&app);
+    let prefs = settings::load(&data);
+    let handle = app.clone();
+    run_task(&app, "pull_container_image", true, move |task| {
+        let digest = image::pull(prefs.update_channel, &prefs.image_trust, task)?;
+        settings::update(&data, |s| {
+            s.image_trust.verified_digest = Some(digest.clone())
+        })?;
+        setup::refresh(&handle);
+        Ok::<_, ShellError>(digest)
+    })
+    .await
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_image_trust(app: AppHandle) -> settings::ImageTrust {
+    settings::load(&user_data_dir(&app)).image_trust
+}
+
+/// Pin a digest and/or require signatures; the verified digest is kept.
+#[tauri::command]
+#[specta::specta]
+fn set_image_trust(
+    app: AppHandle,
+    pinned_digest: Option<String>,
+    require_signature: bool,
+) -> Result<(), ShellError> {
+    let pinned_digest = pinned_digest.map(|d| d.trim().to_string());
+    if let Some(digest) = pinned_digest.as_deref().filter(|d| !d.is_empty()) {
+        if !image::is_digest(digest) {
+            return Err(ShellError::InvalidInput(format!(
+                "Not an image digest (sha256:…): {}",
+                digest
+            )));
+        }
+    }
+    settings::update(&user_data_dir(&app), |s| {
+        s.image_trust.pinned_digest = pinned_digest.filter(|d| !d.is_empty());
+        s.image_trust.require_signature = require_signature;
+    })?;
+    Ok(())
+}
+
+/// Update status of each layer. A failing check only fills in that layer's
+/// error, so one unreachable feed doesn't hide the others.
+#[derive(Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+struct AvailableUpdates {
+    channel: settings::UpdateChannel,
+    app: Option<updater::UpdateInfo>,
+    app_error: Option<String>,
+    backend: Option<backend_update::BackendUpdateInfo>,
+    backend_error: Option<String>,
+    image: Option<image::ImageUpdateInfo>,
+    image_error: Option<String>,
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_available_updates(app: AppHandle) -> AvailableUpdates {
+    let channel = settings::load(&user_data_dir(&app)).update_channel;
+    let app_update = updater::check(&app).await;
+    let backend = check_backend_update(app.clone()).await;
+    let image = check_image_update(app).await;
+    AvailableUpdates {
+        channel,
+        app_error: app_update.as_ref().err().cloned(),
+        app: app_update.ok().flatten(),
+        backend_error: backend.as_ref().err().map(ToString::to_string),
+        backend: backend.ok(),
+        image_error: image.as_ref().err().map(ToString::to_string),
+        image: image.ok(),
+    }
+}
+
+/// Stop the backend, switch the active backend bundle, and start it again.
+fn switch_backend_bundle(app: &AppHandle, version: Option<&str>) -> Result<(), ShellError> {
+    let manager = app.state::<backend::BackendManager>();
+    manager.stop();
+    let result = backend_update::activate(&user_data_dir(app), version);
+    let spawned = manager.start();
+    result?;
+    spawned
+}
+
+/// Download and verify the latest backend bundle into the data dir, then
+/// restart the backend on it. Returns the installed version.
+#[tauri::command]
+#[specta::specta]
+async fn install_backend_update(app: AppHandle) -> Result<String, ShellError> {
+    let handle = app.clone();
+    run_task(
+        &app,
+        "install_backend_update",
+        false,
+        move |task| -> Result<_, ShellError> {
+            let data = user_data_dir(&handle);
+            let channel = settings::load(&data).update_channel;
+            task.progress(None, "Downloading backend bundle");
+            let version = backend_update::download(&data, &bundle_dir(&handle), channel)?;
+            task.progress(None, format!("Restarting backend on {}", version));
+            switch_backend_bundle(&handle, Some(&version))?;
+            Ok(version)
+        },
+    )
+    .await
+}
+
+/// Go back to the backend shipped inside the app bundle.
+#[tauri::command]
+#[specta::specta]
+async fn revert_backend_update(app: AppHandle) -> Result<(), ShellError> {
+    tauri::async_runtime::spawn_blocking(move || switch_backend_bundle(&app, None))
+        .await
+        .map_err(|e| format!("Backend revert failed: {}", e))?
+}
+
+#[tauri::command]
+#[specta::specta]
+fn show_quick_chat(app: AppHandle) -> Result<(), ShellError> {
+    quick_chat::show(&app).map_err(|e| format!("Failed to show quick chat: {}", e).into())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn hide_quick_chat(app: AppHandle) {
+    quick_chat::hide(&app);
+}
+
+#[tauri::command]
+#[specta::specta]
+fn resize_quick_chat(app: AppHandle, width: f64, height: f64) -> Result<(), ShellError> {
+    quick_chat::resize(&app, width, height)
+        .map_err(|e| format!("Failed to resize quick chat: {}", e).into())
+}
+
+#[cfg(target_os = "macos")]
+fn activation_policy_for(show_dock_icon: bool) -> tauri::ActivationPolicy {
+    if show_dock_icon {
+        tauri::ActivationPolicy::Regular
+    } else {
+        tauri::ActivationPolicy::Accessory
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_activation_policy(app: AppHandle) -> String {
+    let prefs = settings::load(&user_data_dir(&app));
+    if prefs.show_dock_icon {
+        "regular".to_string()
+    } else {
+        "accessory".to_string()
+    }
+}
+
+/// Switch between showing the Dock icon ("regular") and menu-bar-only ("accessory").
+/// The choice is persisted and applied again on next launch.
+#[tauri::command]
+#[specta::specta]
+fn set_activation_policy(app: AppHandle, policy: String) -> Result<(), ShellError> {
+    let show_dock_icon = match policy.as_str() {
+        "regular" => true,
+        "accessory" => false,
+        other => {
+            return Err(ShellError::InvalidInput(format!(
+                "Unknown activation policy: {}",
+                other
+            )))
+        }
+    };
+
+    settings::update(&user_data_dir(&app), |s| s.show_dock_icon = show_dock_icon)?;
+
+    #[cfg(target_os = "macos")]
+    {
+        app.set_activation_policy(activation_policy_for(show_dock_icon))
+            .map_err(|e| format!("Failed to set activation policy: {}", e))?;
+        // Switching to Accessory hides the window along with the Dock icon
+        show_main_window(&app);
+    }
+
+    Ok(())
+}
+
+/// Build args, `--no-cache` and an extra tag for `build_container_image`.
+#[tauri::command]
+#[specta::specta]
+fn get_image_build_options(app: AppHandle) -> settings::ImageBuildOptions {
+    settings::load(&user_data_dir(&app)).image_build
 }
 
 #[tauri::command]
-fn restart_backend(
+#[specta::specta]
+fn set_image_build_options(
     app: AppHandle,
-    state: tauri::State<Arc<Mutex<BackendState>>>,
-) -> Result<(), String> {
-    let state = Arc::clone(&state);
-    kill_backend(&app, &state);
-    wait_for_backend_exit(&state, Duration::from_secs(5));
-    spawn_backend(&app, &state);
+    options: settings::ImageBuildOptions,
+) -> Result<(), ShellError> {
+    settings::update(&user_data_dir(&app), |s| s.image_build = options)?;
     Ok(())
 }
 
 #[tauri::command]
-fn get_dirs(app: AppHandle) -> DirConfig {
-    DirConfig {
-        bundle_dir: bundle_dir(&app).to_string_lossy().to_string(),
-        user_data_dir: user_data_dir(&app).to_string_lossy().to_string(),
+#[specta::specta]
+async fn build_container_image(app: AppHandle) -> Result<String, ShellError> {
+    let bundle = bundle_dir(&app);
+    let container_dir = bundle.join("container-agno");
+
+    if !container_dir.exists() {
+        return Err(format!("Container directory not found: {}", container_dir.display()).into());
     }
+
+    let prefs = settings::load(&user_data_dir(&app));
+    let options = prefs.image_build;
+    let proxy_env = proxy::resolve(&prefs.proxy).env();
+    let handle = app.clone();
+    run_task(&app, "build_container_image", true, move |task| {
+        let result = image::build(&container_dir, &options, &proxy_env, task);
+        setup::refresh(&handle);
+        result.map(|_| "Container image built successfully".to_string())
+    })
+    .await
 }
 
+/// Back up the user's data to `dest` (default: the data dir's backups folder).
 #[tauri::command]
-fn check_setup(app: AppHandle) -> SetupStatus {
+#[specta::specta]
+async fn create_backup(
+    app: AppHandle,
+    dest: Option<PathBuf>,
+    password: Option<String>,
+) -> Result<backup::BackupInfo, ShellError> {
     let data = user_data_dir(&app);
-    let bundle = bundle_dir(&app);
+    let password = password.filter(|p| !p.is_empty());
+    let dest = dest.unwrap_or_else(|| backup::default_backup_path(&data, password.is_some()));
+    run_task(&app, "create_backup", true, move |task| {
+        backup::create(&data, &dest, password.as_deref(), task)
+    })
+    .await
+}
 
-    // Check Node.js
-    let (node_installed, node_version) = match Command::new("node").arg("--version").output() {
-        Ok(output) if output.status.success() => {
-            let ver = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            (true, ver)
-        }
-        _ => (false, String::new()),
-    };
+/// Replace the user's data with a backup. The backend is stopped for the swap
+/// and started again afterwards, whether or not the restore succeeded.
+#[tauri::command]
+#[specta::specta]
+async fn restore_backup(
+    app: AppHandle,
+    path: PathBuf,
+    password: Option<String>,
+) -> Result<(), ShellError> {
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    let handle = app.clone();
+    run_task(&app, "restore_backup", false, move |task| {
+        task.progress(None, "Stopping backend");
+        manager.stop();
+        let result = backup::restore(
+            &user_data_dir(&handle),
+            &path,
+            password.as_deref().filter(|p| !p.is_empty()),
+            task,
+        );
+        task.progress(Some(0.9), "Starting backend");
+        let spawned = manager.start();
+        setup::refresh(&handle);
+        result?;
+        spawned
+    })
+    .await
+}
 
-    // Check Docker running
-    let docker_running = Command::new("docker")
-        .args(["info"])
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|s| s.success())
-        .unwrap_or(false);
-
-    // Check container image built
-    let container_image_built = Command::new("docker")
-        .args(["image", "inspect", "nanoclaw-agent-agno:latest"])
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .status()
-        .map(|s| s.success())
-        .unwrap_or(false);
-
-    // Check required bundled resource for image build
-    let container_resources_ready = bundle.join("container-agno").exists();
-
-    // Check model credentials configured
-    let api_key_configured = {
-        let env_vars = load_user_env(&data);
-        let has_value = |key: &str| {
-            env_vars
-                .iter()
-                .any(|(k, v)| k == key && !v.trim().is_empty())
-        };
+/// Relocate the data dir to `new_path`: stop the backend, copy and verify the
+/// data, remember the new location, and start the backend from there. The old
+/// folder is left in place; returns its path so the user can remove it.
+#[tauri::command]
+#[specta::specta]
+async fn move_data_dir(app: AppHandle, new_path: PathBuf) -> Result<PathBuf, ShellError> {
+    if std::env::var_os("NANOCLAW_DATA_DIR").is_some() {
+        return Err("The data folder is set by NANOCLAW_DATA_DIR; change it there".into());
+    }
+    if !is_release_build() {
+        return Err("Use NANOCLAW_DATA_DIR to relocate data in development builds".into());
+    }
+    // The override moves the base that every profile lives under, so moving
+    // just this profile's data there would strand the others
+    if cli::args().profile.is_some() {
+        return Err("Relaunch without --profile to move the data folder".into());
+    }
+    let config_dir = app
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to resolve app config dir: {}", e))?;
+    let current = user_data_dir(&app);
+    data_dir::validate_target(&current, &new_path)?;
 
-        has_value("ANTHROPIC_API_KEY")
-            || (has_value("AGNO_API_KEY")
-                && has_value("AGNO_MODEL_ID")
-                && has_value("AGNO_BASE_URL"))
-    };
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    let handle = app.clone();
+    run_task(&app, "move_data_dir", false, move |task| {
+        task.progress(None, "Stopping backend");
+        manager.stop();
+        let _ = std::fs::remove_file(current.join(BACKEND_SOCKET_FILE));
 
-    SetupStatus {
-        node_installed,
-        node_version,
-        docker_running,
-        container_image_built,
-        container_resources_ready,
-        api_key_configured,
-        user_data_dir: data.to_string_lossy().to_string(),
-    }
+        let result = data_dir::copy_verified(&current, &new_path, task)
+            .and_then(|_| data_dir::write_override(&config_dir, &new_path))
+            .and_then(|_| data_dir::lock(&new_path));
+        if result.is_err() {
+            // The target was empty or missing before, so nothing of the user's is lost
+            let _ = std::fs::remove_dir_all(&new_path);
+        }
+
+        task.progress(Some(0.9), "Starting backend");
+        let spawned = manager.start();
+        setup::refresh(&handle);
+        result?;
+        spawned.map(|_| current)
+    })
+    .await
 }
 
+/// Wipe the app back to first-run state: stop the backend, back up the data,
+/// remove NanoClaw containers and images, clear the data dir (keeping the
+/// backups folder, where the safety backup lands), and relaunch into onboarding.
+/// A relocated data dir stays where the user put it.
 #[tauri::command]
-fn save_env_config(app: AppHandle, entries: Vec<(String, String)>) -> Result<(), String> {
+#[specta::specta]
+async fn factory_reset(app: AppHandle) -> Result<(), ShellError> {
+    if std::env::var_os("NANOCLAW_DATA_DIR").is_none() && !is_release_build() {
+        // The dev data dir is the repo checkout
+        return Err("Set NANOCLAW_DATA_DIR to factory reset a development build".into());
+    }
     let data = user_data_dir(&app);
-    let env_path = data.join(".env");
-
-    // Read existing .env content, preserving entries not being overwritten
-    let mut existing: Vec<(String, String)> = Vec::new();
-    if let Ok(content) = std::fs::read_to_string(&env_path) {
-        for line in content.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() || trimmed.starts_with('#') {
-                continue;
-            }
-            if let Some(eq_pos) = trimmed.find('=') {
-                let key = trimmed[..eq_pos].trim().to_string();
-                let val = trimmed[eq_pos + 1..].trim().to_string();
-                existing.push((key, val));
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    let result = run_task(&app, "factory_reset", false, move |task| {
+        // Stop first so the backup doesn't catch the database mid-write
+        task.progress(Some(0.0), "Stopping backend");
+        manager.stop();
+
+        if data.join("store").exists() || data.join("groups").exists() {
+            task.progress(Some(0.1), "Backing up data");
+            let dest = backup::default_backup_path(&data, false);
+            backup::create(&data, &dest, None, task)?;
+        }
+
+        task.progress(Some(0.6), "Removing containers and images");
+        if let Err(e) = image::remove_all() {
+            eprintln!("Factory reset: {}", e);
+        }
+
+        task.progress(Some(0.8), "Clearing data");
+        data_dir::clear(&data, &[backup::BACKUPS_DIR])
+    })
+    .await;
+
+    if let Err(e) = result {
+        let manager = app.state::<backend::BackendManager>().inner().clone();
+        if !manager.status().ready {
+            if let Err(e) = manager.start() {
+                eprintln!("{}", e);
             }
         }
+        return Err(e);
     }
+    // Settings and setup state are gone; a fresh launch lands in onboarding
+    app.restart();
+}
 
-    // Merge: new entries override existing
-    let new_keys: Vec<&str> = entries.iter().map(|(k, _)| k.as_str()).collect();
-    let mut merged: Vec<(String, String)> = existing
-        .into_iter()
-        .filter(|(k, _)| !new_keys.contains(&k.as_str()))
-        .collect();
-    for (k, v) in entries {
-        if !v.is_empty() {
-            merged.push((k, v));
+/// Remove everything NanoClaw left on this machine, once the user confirms:
+/// stop the backend, remove its containers, images, launch agents, launcher,
+/// Keychain items and the data dir. Shows what was removed and quits; None
+/// when the user cancelled.
+#[tauri::command]
+#[specta::specta]
+async fn uninstall_cleanup(app: AppHandle) -> Result<Option<uninstall::CleanupReport>, ShellError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if !uninstall::confirm(&app) {
+            return Ok(None);
         }
+        let report = uninstall::run(&app)?;
+        let shown = report.clone();
+        std::thread::spawn(move || uninstall::finish(&app, &shown));
+        Ok(Some(report))
+    })
+    .await
+    .map_err(|e| format!("Cleanup failed: {}", e))?
+}
+
+/// Disk usage of the data dir and NanoClaw's Docker artifacts, for the storage page.
+#[tauri::command]
+#[specta::specta]
+async fn get_storage_usage(app: AppHandle) -> Result<storage::StorageUsage, ShellError> {
+    let data = user_data_dir(&app);
+    tauri::async_runtime::spawn_blocking(move || storage::usage(&data))
+        .await
+        .map_err(|e| format!("Failed to measure storage: {}", e).into())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_resource_usage(
+    monitor: tauri::State<resources::ResourceMonitor>,
+) -> resources::ResourceUsage {
+    monitor.latest()
+}
+
+/// The idle-container reaper: its settings, the containers idle now and the
+/// ones it stopped recently.
+#[tauri::command]
+#[specta::specta]
+fn get_reaper_status(app: AppHandle) -> reaper::ReaperStatus {
+    reaper::status(&app)
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_container_reaper(
+    app: AppHandle,
+    reaper: settings::ContainerReaper,
+) -> Result<(), ShellError> {
+    if reaper.idle_timeout_secs < 60 {
+        return Err(ShellError::InvalidInput(
+            "The idle timeout must be at least a minute".to_string(),
+        ));
     }
+    settings::update(&user_data_dir(&app), |s| s.container_reaper = reaper)?;
+    Ok(())
+}
 
-    let content: String = merged
-        .iter()
-        .map(|(k, v)| format!("{}={}", k, v))
-        .collect::<Vec<_>>()
-        .join("\n")
-        + "\n";
+/// How long each step of this launch took, in the order they started.
+#[tauri::command]
+#[specta::specta]
+fn get_startup_timings() -> Vec<startup::StartupPhase> {
+    startup::timings()
+}
 
-    std::fs::write(&env_path, content).map_err(|e| format!("Failed to write .env: {}", e))
+/// Where launch is; `startup-stage` events follow changes.
+#[tauri::command]
+#[specta::specta]
+fn get_startup_stage() -> startup::StartupStage {
+    startup::stage()
 }
 
 #[tauri::command]
-fn read_env_config(app: AppHandle) -> Vec<(String, String)> {
-    let data = user_data_dir(&app);
-    load_user_env(&data)
+#[specta::specta]
+fn get_scheduled_restart(app: AppHandle) -> settings::ScheduledRestart {
+    settings::load(&user_data_dir(&app)).scheduled_restart
 }
 
+/// Restart the backend daily at `time` (local `HH:MM`) once it's idle.
 #[tauri::command]
-async fn build_container_image(app: AppHandle) -> Result<String, String> {
-    let bundle = bundle_dir(&app);
-    let container_dir = bundle.join("container-agno");
+#[specta::specta]
+fn set_scheduled_restart(
+    app: AppHandle,
+    restart: settings::ScheduledRestart,
+) -> Result<(), ShellError> {
+    if scheduled_restart::parse_time(&restart.time).is_none() {
+        return Err(ShellError::InvalidInput(format!(
+            "Invalid time of day: {} (expected HH:MM)",
+            restart.time
+        )));
+    }
+    settings::update(&user_data_dir(&app), |s| s.scheduled_restart = restart)?;
+    Ok(())
+}
 
-    if !container_dir.exists() {
-        return Err(format!(
-            "Container directory not found: {}",
-            container_dir.display()
+#[tauri::command]
+#[specta::specta]
+fn get_auto_suspend(app: AppHandle) -> settings::AutoSuspend {
+    settings::load(&user_data_dir(&app)).auto_suspend
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_auto_suspend(app: AppHandle, suspend: settings::AutoSuspend) -> Result<(), ShellError> {
+    if suspend.idle_minutes == 0 {
+        return Err(ShellError::InvalidInput(
+            "The idle period must be at least a minute".to_string(),
         ));
     }
+    settings::update(&user_data_dir(&app), |s| s.auto_suspend = suspend)?;
+    Ok(())
+}
 
-    let output = Command::new("docker")
-        .args([
-            "build",
-            "-t",
-            "nanoclaw-agent-agno:latest",
-            ".",
-        ])
-        .current_dir(&container_dir)
-        .output()
-        .map_err(|e| format!("Failed to run docker build: {}", e))?;
+#[tauri::command]
+#[specta::specta]
+fn get_memory_watchdog(app: AppHandle) -> settings::MemoryWatchdog {
+    settings::load(&user_data_dir(&app)).memory_watchdog
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_memory_watchdog(
+    app: AppHandle,
+    watchdog: settings::MemoryWatchdog,
+) -> Result<(), ShellError> {
+    if !(MIN_WATCHDOG_THRESHOLD_MB..=MAX_WATCHDOG_THRESHOLD_MB).contains(&watchdog.threshold_mb) {
+        return Err(ShellError::InvalidInput(format!(
+            "The memory limit must be between {} and {} MB",
+            MIN_WATCHDOG_THRESHOLD_MB, MAX_WATCHDOG_THRESHOLD_MB
+        )));
+    }
+    if watchdog.sustain_secs < MIN_WATCHDOG_SUSTAIN_SECS {
+        return Err(ShellError::InvalidInput(format!(
+            "The backend must stay over the limit for at least {}s",
+            MIN_WATCHDOG_SUSTAIN_SECS
+        )));
+    }
+    settings::update(&user_data_dir(&app), |s| s.memory_watchdog = watchdog.clone())?;
+    app.state::<resources::ResourceMonitor>().set_watchdog(watchdog);
+    Ok(())
+}
+
+/// Check the configured model API (`AGNO_BASE_URL`) answers, so connectivity
+/// problems can be told apart from agent errors.
+#[tauri::command]
+#[specta::specta]
+async fn check_provider_reachability(
+    app: AppHandle,
+) -> Result<network::ProviderReachability, ShellError> {
+    let data = user_data_dir(&app);
+    tauri::async_runtime::spawn_blocking(move || {
+        let base_url = load_user_env(&data)
+            .into_iter()
+            .find(|(key, _)| key == "AGNO_BASE_URL")
+            .map(|(_, value)| value);
+        network::check_provider(base_url.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Reachability check failed: {}", e).into())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_headless(app: AppHandle) -> bool {
+    settings::load(&user_data_dir(&app)).headless
+}
+
+/// Takes effect on the next launch.
+#[tauri::command]
+#[specta::specta]
+fn set_headless(app: AppHandle, enabled: bool) -> Result<(), ShellError> {
+    settings::update(&user_data_dir(&app), |s| s.headless = enabled)?;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_telemetry_enabled(app: AppHandle) -> bool {
+    settings::load(&user_data_dir(&app)).telemetry.enabled
+}
 
-    if output.status.success() {
-        Ok("Container image built successfully".to_string())
+/// Opting in creates a fresh anonymous install id; opting out forgets it and
+/// deletes any events not yet sent.
+#[tauri::command]
+#[specta::specta]
+fn set_telemetry_enabled(app: AppHandle, enabled: bool) -> Result<(), ShellError> {
+    let install_id = if enabled {
+        Some(telemetry::new_install_id()?)
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Docker build failed: {}", stderr))
+        None
+    };
+    settings::update(&user_data_dir(&app), |s| {
+        if s.telemetry.enabled != enabled {
+            s.telemetry.install_id = install_id;
+        }
+        s.telemetry.enabled = enabled;
+    })?;
+    if !enabled {
+        telemetry::clear(&app);
     }
+    Ok(())
 }
 
-pub fn run() {
-    let backend_state = Arc::new(Mutex::new(BackendState {
-        child: None,
-        ready: false,
-    }));
+/// Shell crash reports that haven't been uploaded, oldest first.
+#[tauri::command]
+#[specta::specta]
+fn list_crash_reports(app: AppHandle) -> Vec<crash::CrashReport> {
+    crash::pending_reports(&user_data_dir(&app))
+        .into_iter()
+        .map(|(_, report)| report)
+        .collect()
+}
 
-    let state_for_setup = Arc::clone(&backend_state);
-    let state_for_exit = Arc::clone(&backend_state);
+#[tauri::command]
+#[specta::specta]
+fn get_crash_upload_consent(app: AppHandle) -> bool {
+    settings::load(&user_data_dir(&app)).upload_crash_reports
+}
 
-    tauri::Builder::default()
-        .plugin(tauri_plugin_opener::init())
-        .manage(backend_state)
-        .invoke_handler(tauri::generate_handler![
+/// Granting consent also sends the reports collected so far.
+#[tauri::command]
+#[specta::specta]
+async fn set_crash_upload_consent(app: AppHandle, allowed: bool) -> Result<usize, ShellError> {
+    let data = user_data_dir(&app);
+    settings::update(&data, |s| s.upload_crash_reports = allowed)?;
+    if !allowed {
+        return Ok(0);
+    }
+    tauri::async_runtime::spawn_blocking(move || crash::upload_pending(&data))
+        .await
+        .map_err(|e| format!("Crash report upload failed: {}", e))?
+        .map_err(ShellError::from)
+}
+
+/// Settings whose change has to be applied, not just stored; the generic
+/// setter points callers at the command that does both.
+const SETTINGS_WITH_SIDE_EFFECTS: &[(&str, &str)] = &[
+    ("version", "(read-only)"),
+    ("showDockIcon", "set_activation_policy"),
+    ("locale", "set_locale"),
+    ("alwaysOnTop", "set_always_on_top"),
+    ("zoom", "set_zoom"),
+    ("backendHost", "set_backend_listen_config"),
+    ("backendPort", "set_backend_listen_config"),
+    ("remoteBackendUrl", "set_remote_backend"),
+    ("devMode", "set_dev_mode"),
+    ("backendEnv", "set_backend_env"),
+    ("oauthEnv", "oauth_sign_in"),
+    ("warmPoolSize", "set_warm_pool_size"),
+    ("gpuPassthrough", "set_gpu_passthrough"),
+    ("proxy", "set_proxy_settings"),
+    ("extraCaBundle", "set_ca_bundle"),
+    ("tunnel", "set_tunnel_provider"),
+    ("telemetry", "set_telemetry_enabled"),
+    ("uploadCrashReports", "set_crash_upload_consent"),
+    ("windowGeometry", "(managed by the shell)"),
+];
+
+/// Read a setting by camelCase key, e.g. `closeBehavior` or `notifications.enabled`.
+#[tauri::command]
+#[specta::specta]
+fn get_setting(app: AppHandle, key: String) -> Result<serde_json::Value, ShellError> {
+    let prefs = settings::load(&user_data_dir(&app));
+    settings::get_value(&prefs, &key)
+        .ok_or_else(|| ShellError::InvalidInput(format!("Unknown setting: {}", key)))
+}
+
+/// Store a setting and emit `settings-changed`. Settings with side effects
+/// must go through their dedicated command.
+#[tauri::command]
+#[specta::specta]
+fn set_setting(app: AppHandle, key: String, value: serde_json::Value) -> Result<(), ShellError> {
+    let top_level = key.split('.').next().unwrap_or(&key);
+    if let Some((_, command)) = SETTINGS_WITH_SIDE_EFFECTS
+        .iter()
+        .find(|(name, _)| *name == top_level)
+    {
+        return Err(ShellError::InvalidInput(format!(
+            "{} can't be set directly; use {}",
+            key, command
+        )));
+    }
+    let data = user_data_dir(&app);
+    let updated = settings::try_update(&data, |s| {
+        *s = settings::set_value(s, &key, value)?;
+        Ok(())
+    })?;
+    let value = settings::get_value(&updated, &key).unwrap_or_default();
+    let _ = app.emit("settings-changed", SettingChanged { key, value });
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+fn list_backups(app: AppHandle) -> Vec<backup::BackupInfo> {
+    backup::list(&user_data_dir(&app))
+}
+
+#[tauri::command]
+#[specta::specta]
+fn get_backup_schedule(app: AppHandle) -> settings::BackupSchedule {
+    settings::load(&user_data_dir(&app)).backup_schedule
+}
+
+#[tauri::command]
+#[specta::specta]
+fn set_backup_schedule(
+    app: AppHandle,
+    schedule: settings::BackupSchedule,
+) -> Result<(), ShellError> {
+    settings::update(&user_data_dir(&app), |s| s.backup_schedule = schedule)?;
+    Ok(())
+}
+
+/// Every command the frontend can invoke, plus the event payload types, for
+/// both the invoke handler and the generated TypeScript bindings.
+fn bindings_builder() -> tauri_specta::Builder {
+    tauri_specta::Builder::<Wry>::new()
+        .commands(tauri_specta::collect_commands![
             get_backend_status,
+            get_backend_stats,
+            open_in_browser,
             get_backend_config,
+            proxy_request,
+            start_chat_stream,
+            stop_chat_stream,
+            pick_attachments,
+            attach_files,
+            save_file_as,
+            reveal_in_file_manager,
+            reveal_group_folder,
+            rotate_api_token,
+            diagnose_port,
             restart_backend,
+            send_backend_command,
+            list_tasks,
+            cancel_task,
             get_dirs,
+            get_backend_listen_config,
+            set_backend_listen_config,
+            get_remote_backend,
+            set_dev_mode,
+            get_backend_env,
+            set_backend_env,
+            oauth_sign_in,
+            oauth_sign_out,
+            configure_ollama,
+            discover_local_llms,
+            configure_local_llm,
+            list_models,
+            get_warm_pool,
+            set_warm_pool_size,
+            set_gpu_passthrough,
+            list_groups,
+            stop_group_containers,
+            get_group_logs,
+            export_group,
+            export_conversations,
+            get_startup_timings,
+            get_startup_stage,
+            get_proxy_status,
+            set_proxy_settings,
+            get_ca_bundle,
+            set_ca_bundle,
+            set_lan_mode,
+            get_lan_pairing,
+            get_tunnel_status,
+            set_tunnel_provider,
+            get_scheduled_restart,
+            set_scheduled_restart,
+            get_auto_suspend,
+            set_auto_suspend,
+            get_image_build_options,
+            set_image_build_options,
+            debug_path_resolution,
+            install_cli,
+            set_remote_backend,
             check_setup,
+            refresh_setup_status,
             save_env_config,
+            list_env_backups,
+            restore_env_backup,
             read_env_config,
             build_container_image,
+            create_backup,
+            restore_backup,
+            list_backups,
+            get_backup_schedule,
+            set_backup_schedule,
+            move_data_dir,
+            factory_reset,
+            uninstall_cleanup,
+            get_storage_usage,
+            get_resource_usage,
+            get_memory_watchdog,
+            set_memory_watchdog,
+            get_reaper_status,
+            set_container_reaper,
+            check_provider_reachability,
+            get_headless,
+            set_headless,
+            get_telemetry_enabled,
+            set_telemetry_enabled,
+            list_crash_reports,
+            get_crash_upload_consent,
+            set_crash_upload_consent,
+            get_setting,
+            set_setting,
+            get_activation_policy,
+            set_activation_policy,
+            set_tray_badge,
+            get_locale,
+            set_locale,
+            set_always_on_top,
+            get_zoom,
+            set_zoom,
+            set_keep_awake,
+            get_power_state,
+            notify,
+            get_notification_prefs,
+            set_notification_prefs,
+            get_close_behavior,
+            set_close_behavior,
+            get_update_channel,
+            set_update_channel,
+            get_allow_lan_access,
+            set_allow_lan_access,
+            open_window,
+            check_for_updates,
+            install_update,
+            check_backend_update,
+            install_backend_update,
+            revert_backend_update,
+            check_image_update,
+            pull_container_image,
+            get_image_trust,
+            set_image_trust,
+            get_available_updates,
+            get_backend_logs,
+            clear_backend_logs,
+            show_quick_chat,
+            hide_quick_chat,
+            resize_quick_chat,
         ])
+        .typ::<BindWarning>()
+        .typ::<NavigatePayload>()
+        .typ::<ScheduledBackupResult>()
+        .typ::<SettingChanged>()
+        .typ::<attachments::DroppedFiles>()
+        .typ::<backend::BackendStatus>()
+        .typ::<orphans::OrphanScan>()
+        .typ::<chat_stream::ChatStreamEvent>()
+        .typ::<logs::LogLine>()
+        .typ::<updater::DownloadProgress>()
+        .error_handling(tauri_specta::ErrorHandlingMode::Throw)
+}
+
+/// Regenerate `desktop/src/lib/bindings.ts` from the Rust types. Runs in
+/// debug builds only, so the file is refreshed by `npm run tauri dev`.
+#[cfg(debug_assertions)]
+fn export_bindings(builder: &tauri_specta::Builder) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../src/lib/bindings.ts");
+    let language = specta_typescript::Typescript::default()
+        .bigint(specta_typescript::BigIntExportBehavior::Number)
+        .header("// Generated by the desktop shell in debug builds; do not edit.\n");
+    if let Err(e) = builder.export(language, &path) {
+        eprintln!("Failed to export TypeScript bindings: {}", e);
+    }
+}
+
+pub fn run() {
+    telemetry::mark_launch();
+    startup::mark_launch();
+    crash::install_panic_hook(env!("CARGO_PKG_VERSION"));
+    cli::init();
+
+    let (manager, manager_rx) = backend::BackendManager::new();
+
+    let manager_for_crash = manager.clone();
+    crash::set_context(move || {
+        serde_json::json!({
+            "backend": manager_for_crash.try_status(),
+            "host": backend_host(),
+            "port": backend_port(),
+            "unixSocket": backend_socket_path().is_some(),
+            "headless": cli::args().headless,
+        })
+    });
+
+    let manager_for_setup = manager.clone();
+
+    let bindings = bindings_builder();
+    #[cfg(debug_assertions)]
+    export_bindings(&bindings);
+
+    tauri::Builder::default()
+        .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_deep_link::init())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin({
+            // Release builds inject the update signing key; the config placeholder
+            // makes unsigned dev builds refuse to install anything.
+            let builder = tauri_plugin_updater::Builder::new();
+            match option_env!("NANOCLAW_UPDATER_PUBKEY") {
+                Some(pubkey) => builder.pubkey(pubkey).build(),
+                None => builder.build(),
+            }
+        })
+        .manage(manager)
+        .manage(TrayBadge::default())
+        .manage(TrayMenuState::default())
+        .manage(window_state::WindowStateTracker::default())
+        .manage(quick_chat::QuickChatState::default())
+        .manage(logs::BackendLogs::default())
+        .manage(updater::PendingUpdate::default())
+        .manage(tasks::TaskManager::default())
+        .manage(setup::SetupCache::default())
+        .manage(chat_stream::ChatStreams::default())
+        .manage(notifications::NotificationState::default())
+        .manage(resources::ResourceMonitor::default())
+        .manage(reaper::Reaper::default())
+        .manage(suspend::Suspender::default())
+        .manage(telemetry::Telemetry::default())
+        .manage(power::KeepAwake::default())
+        .manage(power::PowerMonitor::default())
+        .manage(shutdown::Shutdown::default())
+        .register_asynchronous_uri_scheme_protocol(API_SCHEME, |_ctx, request, responder| {
+            proxy_api_scheme(request, responder)
+        })
+        .invoke_handler(bindings.invoke_handler())
         .setup(move |app| {
-            // Fix PATH for macOS GUI apps so node/docker are found
-            fix_path_env();
+            let data = user_data_dir(&app.handle());
+            if let Err(e) = startup::time("lock", || data_dir::lock(&data)) {
+                eprintln!("{}", e);
+                let locale = i18n::resolve(settings::load(&data).locale.as_deref());
+                data_dir::exit_locked(app.handle(), locale, e);
+                return Ok(());
+            }
+            let prefs = settings::load(&data);
+
+            // Fix PATH for macOS GUI apps so node/docker are found. The login
+            // shell can take seconds; everything that runs a program waits for it
+            path_env::fix_path_env_in_background(prefs.clone());
 
             // Create user data directories on startup
-            let data = user_data_dir(&app.handle());
-            for subdir in ["store", "data", "groups"] {
-                let dir = data.join(subdir);
-                if !dir.exists() {
-                    let _ = std::fs::create_dir_all(&dir);
+            startup::time("dirs", || {
+                for subdir in ["store", "data", "groups"] {
+                    let dir = data.join(subdir);
+                    if !dir.exists() {
+                        let _ = std::fs::create_dir_all(&dir);
+                    }
+                }
+                crash::init_dir(&data);
+            });
+
+            startup::time("config", || {
+                power::apply_app_nap(&app.config().identifier, prefs.prevent_app_nap);
+                init_api_token(&data, prefs.require_api_token);
+                init_listen_config(&prefs);
+                apply_remote_backend(&data, prefs.remote_backend_url.as_deref());
+                if let Err(e) = certs::apply(&data, prefs.extra_ca_bundle.as_deref()) {
+                    eprintln!("Extra CA bundle not used: {}", e);
+                }
+            });
+
+            if is_release_build() {
+                app_location::check(app.handle(), i18n::resolve(prefs.locale.as_deref()));
+            }
+
+            // Spawn the backend first; it waits for PATH while the window and
+            // tray are built
+            let app_handle = app.handle().clone();
+            backend::start_manager(app_handle.clone(), manager_for_setup, manager_rx);
+            startup::watch_backend_ready(&app_handle);
+            lan::advertise(&app_handle);
+            let manager = app.state::<backend::BackendManager>().inner().clone();
+            let orphan_app = app_handle.clone();
+            std::thread::spawn(move || {
+                path_env::wait_ready();
+                let started = Instant::now();
+                // A crashed earlier run may have left its backend behind
+                startup::set_stage(&orphan_app, startup::StartupStage::CleaningUp);
+                orphans::scan(&orphan_app);
+                startup::set_stage(&orphan_app, startup::StartupStage::StartingBackend);
+                match manager.start() {
+                    Ok(()) => {
+                        startup::set_stage(&orphan_app, startup::StartupStage::WaitingForBackend)
+                    }
+                    Err(e) => eprintln!("{}", e),
+                }
+                startup::record("backend_spawn", started, true);
+            });
+
+            // Nothing the window needs; done off the main thread
+            let deferred_data = data.clone();
+            let upload_crash_reports = prefs.upload_crash_reports;
+            std::thread::spawn(move || {
+                attachments::clear_staging(&deferred_data);
+                if upload_crash_reports && !crash::pending_reports(&deferred_data).is_empty() {
+                    if let Err(e) = crash::upload_pending(&deferred_data) {
+                        eprintln!("Crash report upload failed: {}", e);
+                    }
                 }
+            });
+
+            // Headless runs the supervisor and tray only, with no webview until asked for
+            let headless = cli::args().headless || prefs.headless;
+
+            #[cfg(target_os = "macos")]
+            {
+                app.set_activation_policy(activation_policy_for(prefs.show_dock_icon && !headless));
+            }
+
+            if !headless {
+                startup::time("window", || {
+                    if let Err(e) = windows::ensure_main(app.handle()) {
+                        eprintln!("{}", e);
+                    }
+                });
             }
 
+            // The menu bar, for the standard shortcuts the webview lacks
             #[cfg(target_os = "macos")]
             {
-                app.set_activation_policy(tauri::ActivationPolicy::Regular);
+                app_menu::install(&app_handle)?;
+                app.on_menu_event(|app, event| {
+                    app_menu::on_menu_event(app, event.id().as_ref());
+                });
             }
 
-            // Build tray menu
-            let open_item =
-                MenuItemBuilder::with_id("open", "Open Chat").build(app)?;
-            let restart_item =
-                MenuItemBuilder::with_id("restart", "Restart Backend").build(app)?;
-            let quit_item =
-                MenuItemBuilder::with_id("quit", "Quit").build(app)?;
-            let menu = MenuBuilder::new(app)
-                .item(&open_item)
-                .item(&restart_item)
-                .separator()
-                .item(&quit_item)
-                .build()?;
+            // Build tray menu; recent chats and tasks are filled in by start_tray_refresh
+            let tray_started = Instant::now();
+            let menu = build_tray_menu(&app_handle, &TrayActivity::default())?;
 
-            let app_handle = app.handle().clone();
-            let tray_state = Arc::clone(&state_for_setup);
             let tray_builder = {
                 #[cfg(target_os = "macos")]
                 {
-                    TrayIconBuilder::new()
+                    TrayIconBuilder::with_id(TRAY_ID)
                         .icon(TRAY_TEMPLATE_ICON)
                         .icon_as_template(true)
                 }
@@ -779,63 +3881,264 @@ pub fn run() {
                 #[cfg(not(target_os = "macos"))]
                 {
                     if let Some(icon) = app.default_window_icon() {
-                        TrayIconBuilder::new().icon(icon.clone())
+                        TrayIconBuilder::with_id(TRAY_ID).icon(icon.clone())
                     } else {
-                        TrayIconBuilder::new()
+                        TrayIconBuilder::with_id(TRAY_ID)
                     }
                 }
             };
 
             tray_builder
                 .menu(&menu)
-                .show_menu_on_left_click(true)
+                // Left click toggles the quick-chat popover; the menu opens on right click
+                .show_menu_on_left_click(false)
+                .on_tray_icon_event(|tray, event| {
+                    if let TrayIconEvent::Click {
+                        rect,
+                        button: MouseButton::Left,
+                        button_state: MouseButtonState::Up,
+                        ..
+                    } = event
+                    {
+                        let app = tray.app_handle();
+                        let scale_factor = app
+                            .primary_monitor()
+                            .ok()
+                            .flatten()
+                            .map(|m| m.scale_factor())
+                            .unwrap_or(1.0);
+                        app.state::<quick_chat::QuickChatState>()
+                            .set_anchor(rect, scale_factor);
+                        if let Err(e) = quick_chat::toggle(app) {
+                            eprintln!("Failed to toggle quick chat: {}", e);
+                        }
+                    }
+                })
                 .on_menu_event(move |app, event| match event.id().as_ref() {
                     "open" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
+                        show_main_window(app);
                     }
                     "restart" => {
-                        let state = Arc::clone(&tray_state);
-                        let app = app.clone();
+                        let manager = app.state::<backend::BackendManager>().inner().clone();
+                        manager.reveal_when_ready();
                         std::thread::spawn(move || {
-                            kill_backend(&app, &state);
-                            wait_for_backend_exit(&state, Duration::from_secs(5));
-                            spawn_backend(&app, &state);
+                            if let Err(e) = manager.restart() {
+                                eprintln!("{}", e);
+                            }
                         });
                     }
+                    "always_on_top" => {
+                        let enabled = !settings::load(&user_data_dir(app)).always_on_top;
+                        if let Err(e) = apply_always_on_top(app, enabled) {
+                            eprintln!("{}", e);
+                        }
+                    }
+                    "open_in_browser" => {
+                        open_in_browser_from_tray(app);
+                    }
+                    "copy_api_url" => {
+                        if let Err(e) = clipboard::copy(app, &backend_url_for_tools()) {
+                            eprintln!("{}", e);
+                        }
+                    }
+                    "copy_api_token" => {
+                        copy_api_token_from_tray(app);
+                    }
+                    "check_updates" => {
+                        check_for_updates_from_tray(app);
+                    }
+                    "remove_all_data" => {
+                        uninstall::run_from_tray(app);
+                    }
                     "quit" => {
                         app.exit(0);
                     }
-                    _ => {}
+                    id => {
+                        if let Some(chat_id) = id
+                            .strip_prefix("chat:")
+                            .or_else(|| id.strip_prefix("task:"))
+                        {
+                            navigate_main_window(app, "chat", Some(chat_id.to_string()));
+                        }
+                    }
                 })
                 .build(app)?;
+            startup::record("tray", tray_started, false);
 
-            // Spawn backend on startup
-            spawn_backend(&app_handle, &state_for_setup);
+            // nanoclaw:// links, both at launch and while running
+            let deep_link_handle = app_handle.clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls() {
+                    handle_deep_link(&deep_link_handle, &url);
+                }
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls {
+                    handle_deep_link(&app_handle, &url);
+                }
+            }
+
+            start_tray_refresh(app_handle.clone());
+            start_window_state_saver(app_handle.clone());
+            start_wake_monitor(app_handle.clone());
+            start_backup_scheduler(app_handle.clone());
+            start_resource_sampler(app_handle.clone());
+            reaper::start(app_handle.clone());
+            scheduled_restart::start(app_handle.clone());
+            suspend::start(app_handle.clone());
+            network::start_monitor(app_handle.clone());
+            power::start_monitor(app_handle.clone());
+            notifications::start_focus_monitor(app_handle.clone());
+            dev_mode::start_dist_watcher(app_handle.clone());
+            control::start_server(app_handle.clone());
+            telemetry::start_uploader(app_handle.clone());
+            setup::start_monitor(app_handle.clone());
 
             Ok(())
         })
-        .on_window_event(|window, event| {
-            // Close hides instead of destroying
-            if let WindowEvent::CloseRequested { api, .. } = event {
+        .on_window_event(|window, event| match event {
+            // Main follows the close-behavior preference; the popover always hides
+            WindowEvent::CloseRequested { api, .. } if windows::hides_on_close(window.label()) => {
                 api.prevent_close();
+                let app = window.app_handle();
+                let quit = window.label() == "main"
+                    && match settings::load(&user_data_dir(app)).close_behavior {
+                        settings::CloseBehavior::Hide => false,
+                        settings::CloseBehavior::Quit => true,
+                        settings::CloseBehavior::QuitOnLastWindow => {
+                            !has_other_visible_window(app, "main")
+                        }
+                    };
+                if quit {
+                    app.exit(0);
+                } else {
+                    let _ = window.hide();
+                }
+            }
+            // Secondary windows close normally; the last one may take the app with it
+            WindowEvent::Destroyed if !windows::hides_on_close(window.label()) => {
+                let app = window.app_handle();
+                if settings::load(&user_data_dir(app)).close_behavior
+                    == settings::CloseBehavior::QuitOnLastWindow
+                    && !has_other_visible_window(app, window.label())
+                {
+                    app.exit(0);
+                }
+            }
+            // Stage dropped files so the frontend can attach them to the open chat
+            WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) => {
+                let app = window.app_handle().clone();
+                let label = window.label().to_string();
+                let paths = paths.clone();
+                std::thread::spawn(move || {
+                    let dropped = attachments::stage_dropped(&user_data_dir(&app), &paths);
+                    let _ = app.emit_to(label.as_str(), "files-dropped", dropped);
+                });
+            }
+            // Replies are seen once the window is back in front
+            WindowEvent::Focused(true) if window.label() == "main" => {
+                update_tray_badge(window.app_handle(), 0);
+                suspend::resume(window.app_handle());
+            }
+            // The quick-chat popover dismisses itself when it loses focus
+            WindowEvent::Focused(false) if window.label() == quick_chat::LABEL => {
                 let _ = window.hide();
             }
+            WindowEvent::Moved(_) | WindowEvent::Resized(_)
+                if window.label() != quick_chat::LABEL =>
+            {
+                window
+                    .state::<window_state::WindowStateTracker>()
+                    .track(window);
+            }
+            _ => {}
         })
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(move |app, event| match event {
-            RunEvent::Reopen { .. } => {
-                if let Some(w) = app.get_webview_window("main") {
-                    let _ = w.show();
-                    let _ = w.set_focus();
-                }
-            }
-            RunEvent::ExitRequested { .. } => {
-                kill_backend(app, &state_for_exit);
+            RunEvent::Reopen { .. } => show_main_window(app),
+            // Cleanup runs first and exits again when it's done
+            RunEvent::ExitRequested { api, .. } if !shutdown::on_exit_requested(app) => {
+                api.prevent_exit();
             }
             _ => {}
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::fake;
+
+    /// lsof reports two listeners; only one is node running this bundle.
+    fn script_port_owners(backend: &Path) {
+        let entry = backend.join("dist/index.js").to_string_lossy().to_string();
+        fake::script(move |argv| match argv[0].as_str() {
+            "lsof" => fake::exited(0, "p101\nLalice\np202\nLalice\n"),
+            "ps" if argv[2] == "101" => fake::exited(0, &format!("node {}\n", entry)),
+            "ps" => fake::exited(0, "python3 -m http.server\n"),
+            _ => fake::exited(1, ""),
+        });
+    }
+
+    #[test]
+    fn port_owners_recognizes_our_backend() {
+        let _turn = fake::install();
+        let backend = Path::new("/opt/nanoclaw/backend");
+        script_port_owners(backend);
+
+        let owners = port_owners(3000, backend);
+        let summary: Vec<_> = owners
+            .iter()
+            .map(|o| (o.pid, o.user.as_str(), o.is_nanoclaw_backend))
+            .collect();
+        assert_eq!(summary, vec![(101, "alice", true), (202, "alice", false)]);
+        assert!(fake::commands()[0].contains(&"-iTCP:3000".to_string()));
+    }
+
+    #[test]
+    fn orphan_cleanup_only_signals_our_backend() {
+        let _turn = fake::install();
+        let backend = Path::new("/opt/nanoclaw/backend");
+        script_port_owners(backend);
+
+        kill_orphan_backend_on_port(backend);
+
+        assert_eq!(fake::signals(), vec![(101, Signal::SIGTERM)]);
+    }
+
+    #[test]
+    fn stop_containers_stops_all_in_one_call() {
+        let _turn = fake::install();
+        fake::script(|argv| match argv[1].as_str() {
+            "ps" => fake::exited(0, "nanoclaw-main-1\nnanoclaw-dev-2\n"),
+            _ => fake::exited(0, ""),
+        });
+
+        stop_containers(Duration::from_secs(5));
+
+        let commands = fake::commands();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(
+            commands[1],
+            [
+                "docker",
+                "stop",
+                "--time",
+                "5",
+                "nanoclaw-main-1",
+                "nanoclaw-dev-2"
+            ]
+        );
+    }
+
+    #[test]
+    fn stop_containers_skips_stop_when_none_run() {
+        let _turn = fake::install();
+        fake::script(|_| fake::exited(0, ""));
+
+        stop_containers(Duration::from_secs(5));
+
+        assert_eq!(fake::commands().len(), 1);
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Serialize;
+use specta::Type;
+
+use crate::{env_file, http};
+
+pub(crate) const OLLAMA_PORT: u16 = 11434;
+/// Default ports of common local servers, with what usually runs there.
+const KNOWN_PORTS: &[(u16, &str)] = &[
+    (1234, "LM Studio"),
+    (8080, "llama.cpp"),
+    (OLLAMA_PORT, "Ollama"),
+];
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A model server running on this machine.
+#[derive(Serialize, Clone, PartialEq, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LocalServer {
+    /// What usually listens on this port; a guess, since the API is the same.
+    pub name: String,
+    pub port: u16,
+    /// Installed models, by the id the API expects.
+    pub models: Vec<String>,
+}
+
+fn get_json(port: u16, path: &str) -> Option<serde_json::Value> {
+    let response = http::request("GET", "127.0.0.1", port, path, &[], None, PROBE_TIMEOUT).ok()?;
+    if !response.is_success() {
+        return None;
+    }
+    response.json().ok()
+}
+
+/// Ollama on its default port, with the models pulled into it.
+pub(crate) fn probe_ollama() -> Option<LocalServer> {
+    let tags = get_json(OLLAMA_PORT, "/api/tags")?;
+    let models = tags
+        .get("models")?
+        .as_array()?
+        .iter()
+        .filter_map(|m| m.get("name")?.as_str().map(str::to_string))
+        .collect();
+    Some(LocalServer {
+        name: "Ollama".to_string(),
+        port: OLLAMA_PORT,
+        models,
+    })
+}
+
+/// An OpenAI-compatible server on `port`, with the models `/v1/models` lists.
+fn probe_openai(port: u16, name: &str) -> Option<LocalServer> {
+    let list = get_json(port, "/v1/models")?;
+    let models = list
+        .get("data")?
+        .as_array()?
+        .iter()
+        .filter_map(|m| m.get("id")?.as_str().map(str::to_string))
+        .collect();
+    Some(LocalServer {
+        name: name.to_string(),
+        port,
+        models,
+    })
+}
+
+/// OpenAI-compatible servers on the usual local ports, probed in parallel.
+pub(crate) fn discover() -> Vec<LocalServer> {
+    let probes: Vec<_> = KNOWN_PORTS
+        .iter()
+        .map(|&(port, name)| std::thread::spawn(move || probe_openai(port, name)))
+        .collect();
+    probes
+        .into_iter()
+        .filter_map(|probe| probe.join().ok().flatten())
+        .collect()
+}
+
+/// Point the agent at a local OpenAI-compatible server. Containers reach the
+/// host through host.docker.internal; local servers don't check the key, but
+/// the agent needs one set.
+pub(crate) fn configure(data_dir: &Path, port: u16, model: &str) -> Result<(), String> {
+    if model.trim().is_empty() {
+        return Err("No model selected".to_string());
+    }
+    let changes = [
+        (
+            "AGNO_BASE_URL".to_string(),
+            format!("http://host.docker.internal:{}/v1", port),
+        ),
+        ("AGNO_MODEL_ID".to_string(), model.to_string()),
+        ("AGNO_API_KEY".to_string(), "local".to_string()),
+    ];
+    let existing = std::fs::read_to_string(env_file::path(data_dir)).unwrap_or_default();
+    env_file::write(data_dir, &env_file::update(&existing, &changes))
+}
@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Lines kept in memory for the log viewer window.
+const MAX_LOG_LINES: usize = 2000;
+/// Env keys whose values are scrubbed from log output.
+const SECRET_KEY_MARKERS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "CREDENTIAL"];
+/// Shorter values would redact ordinary words and numbers.
+const MIN_SECRET_LEN: usize = 8;
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LogLine {
+    /// "stdout" or "stderr"
+    pub stream: &'static str,
+    pub line: String,
+    /// Unix epoch milliseconds
+    pub timestamp: u64,
+}
+
+/// Recent backend output, so the log viewer can show history when opened.
+#[derive(Default)]
+pub(crate) struct BackendLogs {
+    lines: Mutex<VecDeque<LogLine>>,
+    secrets: Mutex<Vec<String>>,
+}
+
+impl BackendLogs {
+    /// Remember the secret-looking values in the backend's environment so
+    /// they are masked in every line recorded afterwards.
+    pub fn set_secrets<'a>(&self, env: impl IntoIterator<Item = (&'a str, &'a str)>) {
+        let mut secrets: Vec<String> = env
+            .into_iter()
+            .filter(|(key, val)| {
+                let key = key.to_ascii_uppercase();
+                val.len() >= MIN_SECRET_LEN && SECRET_KEY_MARKERS.iter().any(|m| key.contains(m))
+            })
+            .map(|(_, val)| val.to_string())
+            .collect();
+        // Longest first, so a secret containing another is masked whole
+        secrets.sort_by_key(|v| std::cmp::Reverse(v.len()));
+        secrets.dedup();
+        *self.secrets.lock().unwrap() = secrets;
+    }
+
+    fn redact(&self, line: String) -> String {
+        let secrets = self.secrets.lock().unwrap();
+        if !secrets.iter().any(|secret| line.contains(secret.as_str())) {
+            return line;
+        }
+        secrets.iter().fold(line, |line, secret| {
+            line.replace(secret.as_str(), "[REDACTED]")
+        })
+    }
+
+    pub fn snapshot(&self) -> Vec<LogLine> {
+        self.lines.lock().unwrap().iter().cloned().collect()
+    }
+
+    pub fn clear(&self) {
+        self.lines.lock().unwrap().clear();
+    }
+}
+
+/// Print a redacted backend log line, keep it in the ring buffer, and stream it to open windows.
+pub(crate) fn record(app: &AppHandle, stream: &'static str, line: String) {
+    let logs = app.state::<BackendLogs>();
+    let line = logs.redact(line);
+    let prefix = if stream == "stderr" {
+        "[backend:err]"
+    } else {
+        "[backend]"
+    };
+    eprintln!("{} {}", prefix, line);
+
+    let entry = LogLine {
+        stream,
+        line,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    };
+
+    {
+        let mut lines = logs.lines.lock().unwrap();
+        if lines.len() >= MAX_LOG_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(entry.clone());
+    }
+
+    let _ = app.emit("backend-log", entry);
+}
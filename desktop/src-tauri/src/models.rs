@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use serde::Serialize;
+use specta::Type;
+
+use crate::http;
+
+const LIST_TIMEOUT: Duration = Duration::from_secs(10);
+const ANTHROPIC_URL: &str = "https://api.anthropic.com";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+#[derive(Serialize, Clone, Copy, PartialEq, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ProviderKind {
+    Anthropic,
+    OpenAiCompatible,
+}
+
+/// The models the configured provider offers.
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ModelList {
+    pub provider: ProviderKind,
+    pub base_url: String,
+    pub models: Vec<String>,
+    /// `AGNO_MODEL_ID`, if set.
+    pub configured_model: Option<String>,
+    /// Whether the provider lists `configured_model`.
+    pub configured_model_found: Option<bool>,
+}
+
+fn env_value<'a>(env: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    env.iter()
+        .find(|(k, v)| k == key && !v.trim().is_empty())
+        .map(|(_, v)| v.trim())
+}
+
+fn ids(body: &[u8]) -> Result<Vec<String>, String> {
+    let list: serde_json::Value =
+        serde_json::from_slice(body).map_err(|e| format!("Invalid model list: {}", e))?;
+    let data = list
+        .get("data")
+        .and_then(|d| d.as_array())
+        .ok_or_else(|| "The response has no model list".to_string())?;
+    Ok(data
+        .iter()
+        .filter_map(|m| m.get("id")?.as_str().map(str::to_string))
+        .collect())
+}
+
+/// Fetch the model ids of the provider the backend env points at: Anthropic's
+/// models endpoint, or `/models` of an OpenAI-compatible `AGNO_BASE_URL`.
+pub(crate) fn list(env: &[(String, String)]) -> Result<ModelList, String> {
+    let base_url = env_value(env, "AGNO_BASE_URL");
+    let configured_model = env_value(env, "AGNO_MODEL_ID").map(str::to_string);
+    let is_anthropic = match base_url {
+        Some(url) => url.contains("anthropic.com"),
+        None => env_value(env, "ANTHROPIC_API_KEY").is_some(),
+    };
+    // Hosts that only resolve inside Docker are this machine
+    let base_url = base_url
+        .unwrap_or(ANTHROPIC_URL)
+        .trim_end_matches('/')
+        .replace("host.docker.internal", "127.0.0.1");
+
+    let (provider, models) = if is_anthropic {
+        let key = env_value(env, "AGNO_API_KEY")
+            .or_else(|| env_value(env, "ANTHROPIC_API_KEY"))
+            .ok_or_else(|| "No Anthropic API key is configured".to_string())?;
+        let headers = [
+            ("x-api-key".to_string(), key.to_string()),
+            (
+                "anthropic-version".to_string(),
+                ANTHROPIC_VERSION.to_string(),
+            ),
+        ];
+        let root = base_url.trim_end_matches("/v1");
+        let body = http::curl_get_with_headers(
+            &format!("{}/v1/models?limit=1000", root),
+            &headers,
+            LIST_TIMEOUT,
+        )?;
+        (ProviderKind::Anthropic, ids(&body)?)
+    } else {
+        let headers: Vec<(String, String)> = env_value(env, "AGNO_API_KEY")
+            .map(|key| ("Authorization".to_string(), format!("Bearer {}", key)))
+            .into_iter()
+            .collect();
+        // Base URLs usually end in /v1 already; some are the bare host
+        let body =
+            http::curl_get_with_headers(&format!("{}/models", base_url), &headers, LIST_TIMEOUT)
+                .or_else(|e| {
+                    if base_url.ends_with("/v1") {
+                        return Err(e);
+                    }
+                    http::curl_get_with_headers(
+                        &format!("{}/v1/models", base_url),
+                        &headers,
+                        LIST_TIMEOUT,
+                    )
+                    .map_err(|_| e)
+                })?;
+        (ProviderKind::OpenAiCompatible, ids(&body)?)
+    };
+
+    let configured_model_found = configured_model
+        .as_ref()
+        .map(|model| models.contains(model));
+    Ok(ModelList {
+        provider,
+        base_url,
+        models,
+        configured_model,
+        configured_model_found,
+    })
+}
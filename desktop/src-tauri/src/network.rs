@@ -0,0 +1,100 @@
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter};
+
+use crate::{http, process};
+
+const NETWORK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProviderReachability {
+    /// The URL probed, None when no model endpoint is configured.
+    pub url: Option<String>,
+    pub reachable: bool,
+    /// Any HTTP status counts as reachable; auth errors are not connectivity errors.
+    pub status: Option<u16>,
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Whether the system has a default route. This changes as soon as Wi-Fi or
+/// Ethernet drops, without sending any traffic.
+fn has_default_route() -> bool {
+    #[cfg(target_os = "macos")]
+    let output = process::runner().output(
+        Command::new("route")
+            .args(["-n", "get", "default"])
+            .stdin(Stdio::null()),
+    );
+    #[cfg(not(target_os = "macos"))]
+    let output = process::runner().output(
+        Command::new("ip")
+            .args(["route", "show", "default"])
+            .stdin(Stdio::null()),
+    );
+    match output {
+        Ok(output) => output.status.success() && !output.stdout.is_empty(),
+        // Can't tell; don't report the machine as offline
+        Err(_) => true,
+    }
+}
+
+/// Emit `network-offline` / `network-online` when reachability changes.
+pub(crate) fn start_monitor(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut online = has_default_route();
+        loop {
+            std::thread::sleep(NETWORK_POLL_INTERVAL);
+            let now = has_default_route();
+            if now != online {
+                online = now;
+                eprintln!("Network {}", if online { "online" } else { "offline" });
+                let event = if online {
+                    "network-online"
+                } else {
+                    "network-offline"
+                };
+                let _ = app.emit(event, ());
+            }
+        }
+    });
+}
+
+/// Probe the model API endpoint the agent containers use. Hosts that only
+/// resolve inside Docker are mapped back to this machine.
+pub(crate) fn check_provider(base_url: Option<&str>) -> ProviderReachability {
+    let Some(base_url) = base_url.map(str::trim).filter(|u| !u.is_empty()) else {
+        return ProviderReachability {
+            url: None,
+            reachable: false,
+            status: None,
+            latency_ms: None,
+            error: Some("No model API endpoint is configured".to_string()),
+        };
+    };
+    let url = base_url.replace("host.docker.internal", "127.0.0.1");
+    let started = Instant::now();
+    let result = http::curl_status(&url, PROVIDER_TIMEOUT);
+    let latency_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(status) => ProviderReachability {
+            url: Some(url),
+            reachable: true,
+            status: Some(status),
+            latency_ms: Some(latency_ms),
+            error: None,
+        },
+        Err(e) => ProviderReachability {
+            url: Some(url),
+            reachable: false,
+            status: None,
+            latency_ms: None,
+            error: Some(e),
+        },
+    }
+}
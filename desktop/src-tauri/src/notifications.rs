@@ -0,0 +1,163 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::settings::{self, FocusBehavior, NotificationCategory};
+
+/// How often the queue checks whether Focus has ended.
+const FOCUS_POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// Notifications kept for after Focus; older ones are dropped beyond this.
+const MAX_QUEUED: usize = 10;
+
+/// A notification held back until Focus ends.
+struct Queued {
+    title: String,
+    body: String,
+    route: Option<(String, Option<String>)>,
+}
+
+/// Route to open when the user comes back after a notification. The desktop
+/// notification plugin has no click callback, but clicking a notification
+/// activates the app, so the route is applied the next time the main window
+/// is shown.
+#[derive(Default)]
+pub(crate) struct NotificationState {
+    pending_route: Mutex<Option<(String, Option<String>)>>,
+    queued: Mutex<Vec<Queued>>,
+}
+
+/// Whether a macOS Focus mode (Do Not Disturb included) is on. Focus state
+/// isn't exposed through a public API; the assertion store lists the active
+/// modes and is empty when none is on.
+///
+/// The store is private to macOS and protected by TCC: without Full Disk
+/// Access the app can't read it. Then this reports no Focus, so notifications
+/// are posted as usual and macOS holds them back itself. The failure is
+/// logged once per run.
+#[cfg(target_os = "macos")]
+pub(crate) fn focus_active() -> bool {
+    use std::sync::Once;
+    static UNREADABLE: Once = Once::new();
+
+    let Some(home) = std::env::var_os("HOME") else {
+        return false;
+    };
+    let path = std::path::Path::new(&home).join("Library/DoNotDisturb/DB/Assertions.json");
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => {
+            UNREADABLE.call_once(|| {
+                eprintln!(
+                    "Can't read Focus state from {} ({}); notifications ignore Focus",
+                    path.display(),
+                    e
+                );
+            });
+            return false;
+        }
+    };
+    serde_json::from_str::<serde_json::Value>(&text)
+        .ok()
+        .and_then(|v| {
+            let records = v.get("data")?.get(0)?.get("storeAssertionRecords")?;
+            Some(records.as_array().is_some_and(|r| !r.is_empty()))
+        })
+        .unwrap_or(false)
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn focus_active() -> bool {
+    false
+}
+
+/// Post a native notification unless its category is switched off. During
+/// Focus the category's `FocusBehavior` decides: post it, drop it, or queue
+/// it on the tray badge until Focus ends. `route` is opened when the user
+/// returns to the app. Returns whether a notification was shown.
+pub(crate) fn notify(
+    app: &AppHandle,
+    category: NotificationCategory,
+    title: &str,
+    body: &str,
+    route: Option<(String, Option<String>)>,
+) -> bool {
+    let prefs = settings::load(&crate::user_data_dir(app)).notifications;
+    if !prefs.allows(category) {
+        return false;
+    }
+    match prefs.during_focus(category) {
+        FocusBehavior::Show => {}
+        _ if !focus_active() => {}
+        FocusBehavior::Suppress => return false,
+        FocusBehavior::Queue => {
+            {
+                let state = app.state::<NotificationState>();
+                let mut queued = state.queued.lock().unwrap();
+                if queued.len() >= MAX_QUEUED {
+                    queued.remove(0);
+                }
+                queued.push(Queued {
+                    title: title.to_string(),
+                    body: body.to_string(),
+                    route,
+                });
+            }
+            crate::refresh_tray_badge(app);
+            return false;
+        }
+    }
+    show(app, title, body, route)
+}
+
+fn show(app: &AppHandle, title: &str, body: &str, route: Option<(String, Option<String>)>) -> bool {
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        eprintln!("Failed to show notification: {}", e);
+        return false;
+    }
+    if route.is_some() {
+        *app.state::<NotificationState>()
+            .pending_route
+            .lock()
+            .unwrap() = route;
+    }
+    true
+}
+
+/// Notifications waiting for Focus to end, for the tray badge.
+pub(crate) fn queued_count(app: &AppHandle) -> u32 {
+    app.state::<NotificationState>()
+        .queued
+        .lock()
+        .unwrap()
+        .len() as u32
+}
+
+/// Post the queued notifications once Focus is off.
+pub(crate) fn start_focus_monitor(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(FOCUS_POLL_INTERVAL);
+        if queued_count(&app) == 0 || focus_active() {
+            continue;
+        }
+        let queued = std::mem::take(&mut *app.state::<NotificationState>().queued.lock().unwrap());
+        for notification in queued {
+            show(
+                &app,
+                &notification.title,
+                &notification.body,
+                notification.route,
+            );
+        }
+        crate::refresh_tray_badge(&app);
+    });
+}
+
+pub(crate) fn take_pending_route(app: &AppHandle) -> Option<(String, Option<String>)> {
+    app.state::<NotificationState>()
+        .pending_route
+        .lock()
+        .unwrap()
+        .take()
+}
@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use specta::Type;
+use tauri::{AppHandle, Url};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::{http, integrity, secrets, settings};
+
+/// How long the browser has to come back to the callback listener.
+const SIGN_IN_TIMEOUT: Duration = Duration::from_secs(300);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const TOKEN_TIMEOUT: Duration = Duration::from_secs(30);
+const CALLBACK_PATH: &str = "/callback";
+/// Backend variables a sign-in may fill: the model credentials the backend
+/// passes on to agents. Anything else (NODE_OPTIONS, PATH, ...) would let
+/// the webview change how the backend runs.
+const TOKEN_ENV_KEYS: &[&str] = &[
+    "ANTHROPIC_API_KEY",
+    "AGNO_API_KEY",
+    "CLAUDE_CODE_OAUTH_TOKEN",
+];
+
+/// A provider's OAuth endpoints, and the backend variable its token goes in.
+#[derive(Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OAuthProvider {
+    /// Short id ("openrouter", ...), used to store the token.
+    pub id: String,
+    pub authorize_url: String,
+    /// Where the authorization code is exchanged for the token.
+    pub token_url: String,
+    pub client_id: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Backend variable the token is passed in; one of TOKEN_ENV_KEYS.
+    pub env_key: String,
+}
+
+fn check_id(id: &str) -> Result<(), String> {
+    let valid = !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("Invalid provider id: {}", id));
+    }
+    Ok(())
+}
+
+/// The provider comes from the webview: only HTTPS endpoints, so the code and
+/// token never travel in the clear, and only a model credential as `env_key`.
+fn check_provider(provider: &OAuthProvider) -> Result<(), String> {
+    check_id(&provider.id)?;
+    if !TOKEN_ENV_KEYS.contains(&provider.env_key.as_str()) {
+        return Err(format!(
+            "Sign-in can't set {}; expected one of {}",
+            provider.env_key,
+            TOKEN_ENV_KEYS.join(", ")
+        ));
+    }
+    for url in [&provider.authorize_url, &provider.token_url] {
+        let parsed = Url::parse(url).map_err(|e| format!("Invalid provider URL {}: {}", url, e))?;
+        if parsed.scheme() != "https" || parsed.host_str().is_none() {
+            return Err(format!("Provider URLs must be https: {}", url));
+        }
+    }
+    if provider.client_id.is_empty() || provider.client_id.chars().any(char::is_control) {
+        return Err("Invalid client id".to_string());
+    }
+    Ok(())
+}
+
+fn base64_url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+/// PKCE S256 challenge: base64url(sha256(verifier)), without padding.
+fn pkce_challenge(verifier: &str) -> Result<String, String> {
+    let hex = integrity::sha256_hex(verifier.as_bytes())?;
+    let digest: Vec<u8> = hex
+        .as_bytes()
+        .chunks(2)
+        .filter_map(|pair| u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect();
+    Ok(base64_url(&digest))
+}
+
+/// Wait for the browser's redirect and return its query parameters, answering
+/// with a page telling the user to go back to the app.
+fn await_callback(listener: &TcpListener) -> Result<BTreeMap<String, String>, String> {
+    listener
+        .set_nonblocking(true)
+        .map_err(|e| format!("Failed to set up the callback listener: {}", e))?;
+    let started = Instant::now();
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Some(params) = read_callback(stream) {
+                    return Ok(params);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                if started.elapsed() > SIGN_IN_TIMEOUT {
+                    return Err("Sign-in timed out".to_string());
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+            Err(e) => return Err(format!("Callback listener failed: {}", e)),
+        }
+    }
+}
+
+/// The query of a `GET /callback` request; other requests (favicons, ...)
+/// get a 404 and None.
+fn read_callback(mut stream: TcpStream) -> Option<BTreeMap<String, String>> {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line).ok()?;
+    let target = request_line.split_whitespace().nth(1).unwrap_or("");
+    let url = Url::parse(&format!("http://127.0.0.1{}", target)).ok();
+    let Some(url) = url.filter(|u| u.path() == CALLBACK_PATH) else {
+        let _ = stream
+            .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        return None;
+    };
+    let body = "<!doctype html><meta charset=utf-8><title>NanoClaw</title>\
+                <p>Sign-in finished. You can close this tab and return to NanoClaw.</p>";
+    let _ = write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    );
+    Some(url.query_pairs().into_owned().collect())
+}
+
+fn exchange_code(
+    provider: &OAuthProvider,
+    code: &str,
+    redirect_uri: &str,
+    verifier: &str,
+) -> Result<String, String> {
+    let mut form = Url::parse("http://localhost/").expect("static URL");
+    form.query_pairs_mut()
+        .append_pair("grant_type", "authorization_code")
+        .append_pair("code", code)
+        .append_pair("redirect_uri", redirect_uri)
+        .append_pair("client_id", &provider.client_id)
+        .append_pair("code_verifier", verifier);
+    let body = http::curl_post_form(
+        &provider.token_url,
+        form.query().unwrap_or(""),
+        TOKEN_TIMEOUT,
+    )?;
+    let response: serde_json::Value =
+        serde_json::from_slice(&body).map_err(|e| format!("Invalid token response: {}", e))?;
+    response
+        .get("access_token")
+        .or_else(|| response.get("key"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .ok_or_else(|| "The token response has no access token".to_string())
+}
+
+/// Sign in with the provider in the default browser and store the token it
+/// issues: authorization code with PKCE through a one-off listener on
+/// 127.0.0.1. A token handed straight to the callback (implicit flow) is
+/// refused, as it would bypass PKCE. The backend gets it as `env_key` from its
+/// next start.
+pub(crate) fn sign_in(app: &AppHandle, provider: &OAuthProvider) -> Result<(), String> {
+    check_provider(provider)?;
+    let listener = TcpListener::bind("127.0.0.1:0")
+        .map_err(|e| format!("Failed to start the callback listener: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to start the callback listener: {}", e))?
+        .port();
+    let redirect_uri = format!("http://127.0.0.1:{}{}", port, CALLBACK_PATH);
+    let state = secrets::generate_token()?;
+    let verifier = secrets::generate_token()?;
+
+    let mut auth_url = Url::parse(&provider.authorize_url)
+        .map_err(|e| format!("Invalid authorization URL: {}", e))?;
+    {
+        let mut query = auth_url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &provider.client_id)
+            .append_pair("redirect_uri", &redirect_uri)
+            .append_pair("state", &state)
+            .append_pair("code_challenge", &pkce_challenge(&verifier)?)
+            .append_pair("code_challenge_method", "S256");
+        if !provider.scopes.is_empty() {
+            query.append_pair("scope", &provider.scopes.join(" "));
+        }
+    }
+    app.opener()
+        .open_url(auth_url.as_str(), None::<&str>)
+        .map_err(|e| format!("Failed to open the browser: {}", e))?;
+
+    let params = await_callback(&listener)?;
+    if params.get("state") != Some(&state) {
+        return Err("The sign-in response doesn't match this request".to_string());
+    }
+    if let Some(error) = params.get("error") {
+        let detail = params.get("error_description").unwrap_or(error);
+        return Err(format!("Sign-in failed: {}", detail));
+    }
+    let code = params
+        .get("code")
+        .ok_or_else(|| "The sign-in response has no authorization code".to_string())?;
+    let token = exchange_code(provider, code, &redirect_uri, &verifier)?;
+
+    let data = crate::user_data_dir(app);
+    secrets::store_oauth_token(&data, &provider.id, Some(&token))?;
+    settings::update(&data, |s| {
+        s.oauth_env
+            .insert(provider.env_key.clone(), provider.id.clone());
+    })?;
+    Ok(())
+}
+
+/// Forget a provider's token and stop passing it to the backend.
+pub(crate) fn sign_out(data_dir: &Path, provider_id: &str) -> Result<(), String> {
+    check_id(provider_id)?;
+    secrets::store_oauth_token(data_dir, provider_id, None)?;
+    settings::update(data_dir, |s| s.oauth_env.retain(|_, id| id != provider_id))?;
+    Ok(())
+}
+
+/// Backend variables filled from stored OAuth tokens. Entries outside
+/// TOKEN_ENV_KEYS (a hand-edited settings file) are ignored.
+pub(crate) fn env(data_dir: &Path, prefs: &settings::Settings) -> Vec<(String, String)> {
+    prefs
+        .oauth_env
+        .iter()
+        .filter(|(key, _)| TOKEN_ENV_KEYS.contains(&key.as_str()))
+        .filter_map(|(key, id)| Some((key.clone(), secrets::load_oauth_token(data_dir, id)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process;
+
+    fn provider() -> OAuthProvider {
+        OAuthProvider {
+            id: "openrouter".to_string(),
+            authorize_url: "https://openrouter.ai/auth".to_string(),
+            token_url: "https://openrouter.ai/api/v1/auth/keys".to_string(),
+            client_id: "nanoclaw".to_string(),
+            scopes: Vec::new(),
+            env_key: "ANTHROPIC_API_KEY".to_string(),
+        }
+    }
+
+    #[test]
+    fn provider_with_https_endpoints_and_a_model_key_is_accepted() {
+        assert_eq!(check_provider(&provider()), Ok(()));
+    }
+
+    #[test]
+    fn provider_may_only_set_model_credentials() {
+        let mut provider = provider();
+        provider.env_key = "NODE_OPTIONS".to_string();
+        assert!(check_provider(&provider).is_err());
+    }
+
+    #[test]
+    fn provider_endpoints_must_be_https() {
+        let mut plain = provider();
+        plain.token_url = "http://openrouter.ai/api/v1/auth/keys".to_string();
+        assert!(check_provider(&plain).is_err());
+
+        let mut local = provider();
+        local.authorize_url = "file:///etc/passwd".to_string();
+        assert!(check_provider(&local).is_err());
+    }
+
+    #[test]
+    fn provider_id_and_client_id_are_checked() {
+        let mut bad_id = provider();
+        bad_id.id = "../tokens".to_string();
+        assert!(check_provider(&bad_id).is_err());
+
+        let mut bad_client = provider();
+        bad_client.client_id = "nano\nclaw".to_string();
+        assert!(check_provider(&bad_client).is_err());
+        bad_client.client_id = String::new();
+        assert!(check_provider(&bad_client).is_err());
+    }
+
+    /// The example from RFC 7636, appendix B.
+    #[test]
+    fn pkce_challenge_matches_the_rfc() {
+        let _turn = process::fake::install();
+        process::fake::spawn_as(&["shasum", "-a", "256"]);
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk").unwrap(),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::Path;
+
+use nix::sys::signal::Signal;
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{backend, process};
+
+/// Ports the backend was started on lately, newest first.
+const RECENT_PORTS_FILE: &str = "recent-ports.json";
+const MAX_RECENT_PORTS: usize = 5;
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OrphanBackend {
+    pub pid: i32,
+    pub port: u16,
+}
+
+/// Payload of `orphan-backends`.
+#[derive(Serialize, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct OrphanScan {
+    /// Stale backends that were sent SIGTERM.
+    pub terminated: Vec<OrphanBackend>,
+    /// A healthy backend on the configured port, kept and reused.
+    pub adopted: Option<OrphanBackend>,
+}
+
+fn recent_ports(data_dir: &Path) -> Vec<u16> {
+    std::fs::read_to_string(data_dir.join(RECENT_PORTS_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Note the port a backend is being started on, so a later scan knows where
+/// a crashed run may have left it.
+pub(crate) fn remember_port(data_dir: &Path, port: u16) {
+    let mut ports = recent_ports(data_dir);
+    ports.retain(|&p| p != port);
+    ports.insert(0, port);
+    ports.truncate(MAX_RECENT_PORTS);
+    if let Ok(content) = serde_json::to_string(&ports) {
+        let _ = std::fs::write(data_dir.join(RECENT_PORTS_FILE), content);
+    }
+}
+
+/// Look for NanoClaw backends left behind by an earlier run on any recently
+/// used port. A healthy one on the configured port is adopted while the shell
+/// has no backend of its own; the rest are terminated. Emits `orphan-backends`
+/// when anything was found.
+pub(crate) fn scan(app: &AppHandle) -> OrphanScan {
+    // The backend lives elsewhere; local processes aren't ours to judge
+    if crate::remote_backend().is_some() {
+        return OrphanScan::default();
+    }
+    let status = app.state::<backend::BackendManager>().status();
+    let backend = crate::backend_dir(app);
+    let host = crate::backend_host();
+    let configured = crate::backend_port();
+
+    let mut ports = recent_ports(&crate::user_data_dir(app));
+    if !ports.contains(&configured) {
+        ports.insert(0, configured);
+    }
+
+    let mut result = OrphanScan::default();
+    for port in ports {
+        for owner in crate::port_owners(port, &backend) {
+            if !owner.is_nanoclaw_backend || status.pid == Some(owner.pid as u32) {
+                continue;
+            }
+            let orphan = OrphanBackend {
+                pid: owner.pid,
+                port,
+            };
+            if port == configured
+                && !status.running
+                && result.adopted.is_none()
+                && crate::is_backend_healthy(&host, port)
+            {
+                eprintln!("Adopting backend {} on port {}", owner.pid, port);
+                result.adopted = Some(orphan);
+                continue;
+            }
+            eprintln!("Stopping stale backend {} on port {}", owner.pid, port);
+            if let Err(e) = process::runner().signal(owner.pid as u32, Signal::SIGTERM) {
+                eprintln!("{}", e);
+            }
+            result.terminated.push(orphan);
+        }
+    }
+
+    if !result.terminated.is_empty() || result.adopted.is_some() {
+        let _ = app.emit("orphan-backends", result.clone());
+    }
+    result
+}
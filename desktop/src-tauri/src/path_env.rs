@@ -0,0 +1,220 @@
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Condvar, Mutex};
+use std::time::Instant;
+
+use serde::Serialize;
+use specta::Type;
+
+use crate::{settings, startup};
+
+/// Common macOS binary locations, appended when the shell gives us nothing.
+const FALLBACK_DIRS: &[&str] = &[
+    "/opt/homebrew/bin",
+    "/opt/homebrew/sbin",
+    "/usr/local/bin",
+    "/usr/local/sbin",
+];
+
+/// Printed before PATH so it can be picked out of whatever an interactive
+/// shell prints around it.
+const MARKER: &str = "__NANOCLAW_PATH__=";
+
+/// Shells differ in how PATH is read and which flags they take.
+#[derive(Serialize, Clone, Copy, Default, PartialEq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ShellFamily {
+    /// sh, bash, zsh, ksh, dash.
+    #[default]
+    Posix,
+    /// PATH is a list; `$PATH` joins it with spaces.
+    Fish,
+    /// csh and tcsh can't combine -l with -c.
+    Csh,
+    /// Anything else (nushell, xonsh, ...): asked for the exported PATH.
+    Other,
+}
+
+impl ShellFamily {
+    fn of(shell: &str) -> Self {
+        let name = shell.rsplit('/').next().unwrap_or(shell);
+        match name {
+            "sh" | "bash" | "zsh" | "ksh" | "mksh" | "dash" => ShellFamily::Posix,
+            "fish" => ShellFamily::Fish,
+            "csh" | "tcsh" => ShellFamily::Csh,
+            _ => ShellFamily::Other,
+        }
+    }
+
+    /// Arguments that print PATH after the shell's startup files ran.
+    fn args(self) -> Vec<String> {
+        match self {
+            // -i -l: interactive login — sources .zprofile/.zshrc so nvm, pyenv, etc. are loaded
+            ShellFamily::Posix => vec![
+                "-i".into(),
+                "-l".into(),
+                "-c".into(),
+                format!("echo \"{}$PATH\"", MARKER),
+            ],
+            ShellFamily::Fish => vec![
+                "-l".into(),
+                "-i".into(),
+                "-c".into(),
+                format!("echo {}(string join : $PATH)", MARKER),
+            ],
+            ShellFamily::Csh => vec!["-i".into(), "-c".into(), "printenv PATH".into()],
+            ShellFamily::Other => vec![
+                "-l".into(),
+                "-i".into(),
+                "-c".into(),
+                "printenv PATH".into(),
+            ],
+        }
+    }
+}
+
+/// How PATH was worked out at startup, for `debug_path_resolution`.
+#[derive(Serialize, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PathResolution {
+    pub shell: String,
+    pub family: ShellFamily,
+    /// PATH came from the `pathOverride` setting; the shell wasn't asked.
+    pub overridden: bool,
+    /// Everything the shell printed; interactive shells add their own noise.
+    pub raw_output: String,
+    /// Why the shell's answer wasn't used, if it wasn't.
+    pub shell_error: Option<String>,
+    /// The shell's PATH was used rather than the fallback directories.
+    pub from_shell: bool,
+    pub path: String,
+    pub node: Option<String>,
+    pub docker: Option<String>,
+}
+
+static RESOLUTION: Mutex<Option<PathResolution>> = Mutex::new(None);
+/// Set while the shell is being asked for PATH in the background.
+static PENDING: Mutex<bool> = Mutex::new(false);
+static RESOLVED: Condvar = Condvar::new();
+
+/// The PATH in a shell's output: the marked line if there is one, else the
+/// last non-empty line containing '/' (interactive shells may print extra
+/// lines — motd, etc.).
+fn parse_path(raw: &str) -> Option<String> {
+    let marked = raw
+        .lines()
+        .rev()
+        .find_map(|l| l.split_once(MARKER).map(|(_, path)| path));
+    marked
+        .or_else(|| {
+            raw.lines()
+                .rev()
+                .find(|l| !l.trim().is_empty() && l.contains('/'))
+        })
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+}
+
+/// macOS GUI apps don't inherit the user's shell PATH.
+/// Resolve the full PATH from an interactive login shell (the `shell` setting,
+/// else $SHELL) and set it for this process, so all Command::new() calls can
+/// find node, docker, etc. A `pathOverride` setting is used as is instead.
+pub(crate) fn fix_path_env(prefs: &settings::Settings) {
+    let shell = prefs
+        .shell
+        .clone()
+        .or_else(|| std::env::var("SHELL").ok())
+        .unwrap_or_else(|| "/bin/zsh".to_string());
+    let family = ShellFamily::of(&shell);
+    let mut resolution = PathResolution {
+        shell: shell.clone(),
+        family,
+        ..Default::default()
+    };
+
+    if let Some(path) = prefs.path_override.as_deref().filter(|p| !p.is_empty()) {
+        std::env::set_var("PATH", path);
+        resolution.overridden = true;
+        resolution.path = path.to_string();
+        *RESOLUTION.lock().unwrap() = Some(resolution);
+        return;
+    }
+
+    match Command::new(&shell)
+        .args(family.args())
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+    {
+        Ok(output) => {
+            let raw = String::from_utf8_lossy(&output.stdout).to_string();
+            let shell_path = parse_path(&raw);
+            resolution.raw_output = raw;
+            match shell_path {
+                _ if !output.status.success() => {
+                    resolution.shell_error =
+                        Some(format!("The shell exited with {}", output.status));
+                }
+                None => {
+                    resolution.shell_error =
+                        Some("No line of the output looks like a PATH".to_string());
+                }
+                Some(path) => {
+                    std::env::set_var("PATH", &path);
+                    resolution.from_shell = true;
+                }
+            }
+        }
+        Err(e) => resolution.shell_error = Some(format!("Failed to run {}: {}", shell, e)),
+    }
+
+    if !resolution.from_shell {
+        // Fallback: append common macOS binary locations
+        let current = std::env::var("PATH").unwrap_or_default();
+        let combined = format!("{}:{}", current, FALLBACK_DIRS.join(":"));
+        std::env::set_var("PATH", &combined);
+    }
+
+    resolution.path = std::env::var("PATH").unwrap_or_default();
+    if let Some(error) = &resolution.shell_error {
+        eprintln!("PATH from {} not used: {}", shell, error);
+    }
+    *RESOLUTION.lock().unwrap() = Some(resolution);
+}
+
+/// `fix_path_env` on a background thread, so startup can go on while the
+/// login shell runs its startup files. Anything about to run a program calls
+/// `wait_ready` first.
+pub(crate) fn fix_path_env_in_background(prefs: settings::Settings) {
+    *PENDING.lock().unwrap() = true;
+    std::thread::spawn(move || {
+        let started = Instant::now();
+        fix_path_env(&prefs);
+        startup::record("path", started, true);
+        *PENDING.lock().unwrap() = false;
+        RESOLVED.notify_all();
+    });
+}
+
+/// Wait for a background PATH probe to finish; returns at once otherwise.
+pub(crate) fn wait_ready() {
+    let pending = PENDING.lock().unwrap();
+    drop(RESOLVED.wait_while(pending, |pending| *pending));
+}
+
+/// First `name` on the current PATH.
+pub(crate) fn which(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// The startup resolution, with where node and docker resolve to now.
+pub(crate) fn resolution() -> PathResolution {
+    wait_ready();
+    let mut resolution = RESOLUTION.lock().unwrap().clone().unwrap_or_default();
+    resolution.node = which("node").map(|p| p.display().to_string());
+    resolution.docker = which("docker").map(|p| p.display().to_string());
+    resolution
+}
@@ -0,0 +1,254 @@
+use std::process::{Child, Command};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{image, settings};
+
+const POWER_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Keeps the machine from idle-sleeping while an agent run is in progress,
+/// so a laptop left alone doesn't suspend Docker and kill the containers.
+/// Held by a `caffeinate` child that watches our pid, so the assertion goes
+/// away with the shell even if it crashes.
+#[derive(Default)]
+pub(crate) struct KeepAwake {
+    assertion: Mutex<Option<Child>>,
+}
+
+impl KeepAwake {
+    pub fn set(&self, enabled: bool) -> Result<(), String> {
+        let mut assertion = self.assertion.lock().unwrap();
+        if let Some(child) = assertion.as_mut() {
+            if let Ok(None) = child.try_wait() {
+                if enabled {
+                    return Ok(());
+                }
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+            *assertion = None;
+        }
+        if enabled {
+            *assertion = take_assertion()?;
+        }
+        Ok(())
+    }
+
+    /// Drop the assertion, e.g. because the backend running the agent is gone.
+    pub fn release(&self) {
+        if let Err(e) = self.set(false) {
+            eprintln!("Failed to release keep-awake: {}", e);
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn take_assertion() -> Result<Option<Child>, String> {
+    use std::process::{Command, Stdio};
+
+    // -i: prevent idle sleep; -w: until our process exits
+    crate::process::runner()
+        .spawn(
+            Command::new("caffeinate")
+                .args(["-i", "-w", &std::process::id().to_string()])
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null()),
+        )
+        .map(Some)
+        .map_err(|e| format!("Failed to start caffeinate: {}", e))
+}
+
+/// Elsewhere the shell doesn't hold an assertion.
+#[cfg(not(target_os = "macos"))]
+fn take_assertion() -> Result<Option<Child>, String> {
+    Ok(None)
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PowerSource {
+    Ac,
+    Battery,
+}
+
+/// Payload of `power-source-changed` and `get_power_state`.
+#[derive(Serialize, Clone, Copy, PartialEq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PowerState {
+    pub source: PowerSource,
+    /// On battery with low-power mode on: agent containers are paused and
+    /// scheduled work waits for AC power.
+    pub low_power: bool,
+}
+
+/// Latest power state seen by the monitor.
+pub(crate) struct PowerMonitor {
+    state: Mutex<PowerState>,
+}
+
+impl Default for PowerMonitor {
+    fn default() -> Self {
+        PowerMonitor {
+            state: Mutex::new(PowerState {
+                source: PowerSource::Ac,
+                low_power: false,
+            }),
+        }
+    }
+}
+
+impl PowerMonitor {
+    pub fn state(&self) -> PowerState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Whether scheduled work should wait for AC power.
+    pub fn defer_work(&self) -> bool {
+        self.state().low_power
+    }
+}
+
+/// Where the machine draws power from, per `pmset -g batt` ("Now drawing from
+/// 'Battery Power'"). None when it can't tell, which counts as AC.
+#[cfg(target_os = "macos")]
+fn power_source() -> Option<PowerSource> {
+    use std::process::{Command, Stdio};
+
+    let output = crate::process::runner()
+        .output(
+            Command::new("pmset")
+                .args(["-g", "batt"])
+                .stdin(Stdio::null()),
+        )
+        .ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let first = text.lines().next()?;
+    if first.contains("'Battery Power'") {
+        Some(PowerSource::Battery)
+    } else if first.contains("'AC Power'") {
+        Some(PowerSource::Ac)
+    } else {
+        None
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn power_source() -> Option<PowerSource> {
+    let entries = std::fs::read_dir("/sys/class/power_supply").ok()?;
+    let mut saw_mains = false;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let kind = std::fs::read_to_string(path.join("type")).unwrap_or_default();
+        if kind.trim() == "Mains" {
+            saw_mains = true;
+            if std::fs::read_to_string(path.join("online"))
+                .unwrap_or_default()
+                .trim()
+                == "1"
+            {
+                return Some(PowerSource::Ac);
+            }
+        }
+    }
+    saw_mains.then_some(PowerSource::Battery)
+}
+
+/// Pause (or resume) the running agent containers.
+fn set_containers_paused(paused: bool) {
+    let (filter, command) = if paused {
+        ("status=running", "pause")
+    } else {
+        ("status=paused", "unpause")
+    };
+    let Ok(containers) =
+        image::docker_output(&["ps", "-q", "--filter", "name=nanoclaw-", "--filter", filter])
+    else {
+        return;
+    };
+    for id in containers.lines().filter(|l| !l.is_empty()) {
+        if let Err(e) = image::docker_output(&[command, id]) {
+            eprintln!("Failed to {} container {}: {}", command, id, e);
+        }
+    }
+}
+
+/// Follow the power source, emitting `power-source-changed` when it (or the
+/// low-power state) changes. With low-power mode on, agent containers are
+/// paused while on battery and resumed on AC power.
+pub(crate) fn start_monitor(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        let source = power_source().unwrap_or(PowerSource::Ac);
+        let low_power_mode = settings::load(&crate::user_data_dir(&app)).low_power_mode;
+        let next = PowerState {
+            source,
+            low_power: low_power_mode && source == PowerSource::Battery,
+        };
+
+        let previous = {
+            let monitor = app.state::<PowerMonitor>();
+            let mut state = monitor.state.lock().unwrap();
+            std::mem::replace(&mut *state, next)
+        };
+        if previous != next {
+            if previous.source != next.source {
+                eprintln!("Power source: {:?}", next.source);
+            }
+            if previous.low_power != next.low_power {
+                set_containers_paused(next.low_power);
+            }
+            let _ = app.emit("power-source-changed", next);
+        }
+
+        std::thread::sleep(POWER_POLL_INTERVAL);
+    });
+}
+
+/// The command to run the backend with. On macOS, unless throttling is
+/// allowed, node runs under `taskpolicy` at the highest latency and
+/// throughput tiers, so App Nap doesn't coalesce its timers while the window
+/// is hidden and scheduled jobs fire on time.
+pub(crate) fn node_command(prevent_throttling: bool) -> Command {
+    #[cfg(target_os = "macos")]
+    if prevent_throttling {
+        let mut cmd = Command::new("taskpolicy");
+        cmd.args(["-l", "0", "-t", "0"]).arg(find_node());
+        return cmd;
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = prevent_throttling;
+    Command::new("node")
+}
+
+/// Full path of `node` on PATH, so a missing node still fails the spawn
+/// itself rather than surfacing as `taskpolicy` exiting.
+#[cfg(target_os = "macos")]
+fn find_node() -> std::path::PathBuf {
+    crate::path_env::which("node").unwrap_or_else(|| "node".into())
+}
+
+/// Opt the shell itself out of App Nap (or back in), so its supervisor and
+/// scheduler threads keep their pace while the window is hidden. macOS reads
+/// the flag at launch, so a change applies from the next one.
+pub(crate) fn apply_app_nap(identifier: &str, prevent: bool) {
+    #[cfg(target_os = "macos")]
+    {
+        use std::process::Stdio;
+
+        let flag = if prevent { "YES" } else { "NO" };
+        let result = crate::process::runner().output(
+            Command::new("defaults")
+                .args(["write", identifier, "NSAppSleepDisabled", "-bool", flag])
+                .stdin(Stdio::null()),
+        );
+        if let Err(e) = result {
+            eprintln!("Failed to set NSAppSleepDisabled: {}", e);
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (identifier, prevent);
+}
@@ -0,0 +1,199 @@
+use std::io::{self, Write};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::OnceLock;
+
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+
+/// How the shell runs external programs (node, docker, curl, ps, ...) and
+/// signals processes. Every program the shell starts goes through `runner()`,
+/// so it can be pointed at a fake that answers with canned output or spawns a
+/// stand-in binary. The one exception is the login-shell probe in `path_env`,
+/// which works out the PATH the runner waits for.
+pub(crate) trait ProcessRunner: Send + Sync {
+    /// Run `command` to completion, capturing its output.
+    fn output(&self, command: &mut Command) -> io::Result<Output>;
+    /// Start `command` and hand back the child.
+    fn spawn(&self, command: &mut Command) -> io::Result<Child>;
+    fn signal(&self, pid: u32, signal: Signal) -> Result<(), String>;
+}
+
+/// The real thing.
+struct SystemRunner;
+
+impl ProcessRunner for SystemRunner {
+    // Programs are looked up on PATH, which may still be resolving at startup
+    fn output(&self, command: &mut Command) -> io::Result<Output> {
+        crate::path_env::wait_ready();
+        command.output()
+    }
+
+    fn spawn(&self, command: &mut Command) -> io::Result<Child> {
+        crate::path_env::wait_ready();
+        command.spawn()
+    }
+
+    fn signal(&self, pid: u32, signal: Signal) -> Result<(), String> {
+        signal::kill(Pid::from_raw(pid as i32), signal)
+            .map_err(|e| format!("Failed to signal process {}: {}", pid, e))
+    }
+}
+
+static RUNNER: OnceLock<Box<dyn ProcessRunner>> = OnceLock::new();
+
+pub(crate) fn runner() -> &'static dyn ProcessRunner {
+    RUNNER.get_or_init(|| Box::new(SystemRunner)).as_ref()
+}
+
+/// Swap in another runner. Only takes effect before the first `runner()`
+/// call, so a harness has to install its fake before starting anything.
+#[cfg(test)]
+pub(crate) fn set_runner(runner: Box<dyn ProcessRunner>) -> Result<(), String> {
+    RUNNER
+        .set(runner)
+        .map_err(|_| "A process runner is already in use".to_string())
+}
+
+/// Run `command` to completion with `input` on its stdin, capturing its
+/// output. For secrets that mustn't show up in `ps`.
+pub(crate) fn output_with_stdin(command: &mut Command, input: &[u8]) -> io::Result<Output> {
+    let mut child = runner().spawn(
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped()),
+    )?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input)?;
+    }
+    child.wait_with_output()
+}
+
+/// Run a command and report whether it exited successfully.
+pub(crate) fn succeeds(command: &mut Command) -> bool {
+    runner()
+        .output(command)
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// A runner for tests: answers `output` from a script the test sets, spawns
+/// the program the test names in place of the real one, and records every
+/// command and signal instead of sending it.
+#[cfg(test)]
+pub(crate) mod fake {
+    use std::io;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{Child, Command, ExitStatus, Output, Stdio};
+    use std::sync::{Mutex, MutexGuard, Once};
+
+    use nix::sys::signal::Signal;
+
+    use super::ProcessRunner;
+
+    type Script = Box<dyn Fn(&[String]) -> Output + Send>;
+
+    #[derive(Default)]
+    struct State {
+        script: Option<Script>,
+        /// Program and arguments of each command run or spawned.
+        commands: Vec<Vec<String>>,
+        signals: Vec<(u32, Signal)>,
+        /// Stand-in for whatever `spawn` is asked to start.
+        spawn_as: Option<Vec<String>>,
+    }
+
+    static STATE: Mutex<Option<State>> = Mutex::new(None);
+    /// The runner is process-wide, so tests using it take turns.
+    static TURN: Mutex<()> = Mutex::new(());
+
+    struct FakeRunner;
+
+    fn with_state<T>(f: impl FnOnce(&mut State) -> T) -> T {
+        let mut state = STATE.lock().unwrap_or_else(|e| e.into_inner());
+        f(state.get_or_insert_with(State::default))
+    }
+
+    fn argv(command: &Command) -> Vec<String> {
+        std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect()
+    }
+
+    impl ProcessRunner for FakeRunner {
+        fn output(&self, command: &mut Command) -> io::Result<Output> {
+            let argv = argv(command);
+            with_state(|state| {
+                state.commands.push(argv.clone());
+                match &state.script {
+                    Some(script) => Ok(script(&argv)),
+                    None => Err(io::Error::new(io::ErrorKind::NotFound, "no script")),
+                }
+            })
+        }
+
+        fn spawn(&self, command: &mut Command) -> io::Result<Child> {
+            let stand_in = with_state(|state| {
+                state.commands.push(argv(command));
+                state.spawn_as.clone()
+            });
+            let Some((program, args)) = stand_in.as_deref().and_then(|a| a.split_first()) else {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "no stand-in"));
+            };
+            // Callers expect to write to the child and read its output
+            Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+        }
+
+        fn signal(&self, pid: u32, signal: Signal) -> Result<(), String> {
+            with_state(|state| state.signals.push((pid, signal)));
+            Ok(())
+        }
+    }
+
+    /// Install the fake (once per test binary) and start from a clean
+    /// record. Hold the guard for the length of the test. Panics if the real
+    /// runner got there first, rather than letting tests touch the host.
+    pub(crate) fn install() -> MutexGuard<'static, ()> {
+        static INSTALLED: Once = Once::new();
+        let turn = TURN.lock().unwrap_or_else(|e| e.into_inner());
+        INSTALLED.call_once(|| {
+            super::set_runner(Box::new(FakeRunner))
+                .expect("runner() was called before fake::install()");
+        });
+        *STATE.lock().unwrap_or_else(|e| e.into_inner()) = Some(State::default());
+        turn
+    }
+
+    /// Answer every `output` call with `script(argv)`.
+    pub(crate) fn script(script: impl Fn(&[String]) -> Output + Send + 'static) {
+        with_state(|state| state.script = Some(Box::new(script)));
+    }
+
+    /// Run `argv` whenever something is spawned.
+    pub(crate) fn spawn_as(argv: &[&str]) {
+        with_state(|state| state.spawn_as = Some(argv.iter().map(|a| a.to_string()).collect()));
+    }
+
+    pub(crate) fn commands() -> Vec<Vec<String>> {
+        with_state(|state| state.commands.clone())
+    }
+
+    pub(crate) fn signals() -> Vec<(u32, Signal)> {
+        with_state(|state| state.signals.clone())
+    }
+
+    /// An `Output` with the given exit code and stdout.
+    pub(crate) fn exited(code: i32, stdout: &str) -> Output {
+        Output {
+            status: ExitStatus::from_raw(code << 8),
+            stdout: stdout.as_bytes().to_vec(),
+            stderr: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use serde::Serialize;
+use specta::Type;
+
+use crate::settings::{ProxyMode, ProxySettings};
+
+/// Always reached directly: the backend itself, and local model servers
+/// agents reach from their containers.
+const LOCAL_HOSTS: &[&str] = &["localhost", "127.0.0.1", "::1", "host.docker.internal"];
+
+/// Proxies in the form HTTP_PROXY and friends take.
+#[derive(Serialize, Clone, Default, PartialEq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProxyConfig {
+    pub http_proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    pub no_proxy: Option<String>,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProxyStatus {
+    pub settings: ProxySettings,
+    /// What macOS network settings say, whatever the mode.
+    pub system: ProxyConfig,
+    /// What the backend and image builds get.
+    pub effective: ProxyConfig,
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+}
+
+/// Parse `scutil --proxy`: a dictionary of `Key : value` lines, with the
+/// bypass list as a nested array.
+#[cfg(target_os = "macos")]
+fn parse_scutil(output: &str) -> ProxyConfig {
+    let mut values = std::collections::HashMap::new();
+    let mut exceptions = Vec::new();
+    let mut in_exceptions = false;
+    for line in output.lines().map(str::trim) {
+        if in_exceptions {
+            if line == "}" {
+                in_exceptions = false;
+            } else if let Some((_, host)) = line.split_once(" : ") {
+                // `*.corp.example` means the domain; NO_PROXY spells it `.corp.example`
+                exceptions.push(host.trim().trim_start_matches('*').to_string());
+            }
+        } else if line.starts_with("ExceptionsList :") {
+            in_exceptions = true;
+        } else if let Some((key, value)) = line.split_once(" : ") {
+            values.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    let proxy = |kind: &str| {
+        if values.get(&format!("{}Enable", kind)).map(String::as_str) != Some("1") {
+            return None;
+        }
+        let host = values.get(&format!("{}Proxy", kind))?;
+        Some(match values.get(&format!("{}Port", kind)) {
+            Some(port) => format!("http://{}:{}", host, port),
+            None => format!("http://{}", host),
+        })
+    };
+    ProxyConfig {
+        http_proxy: proxy("HTTP"),
+        https_proxy: proxy("HTTPS"),
+        no_proxy: (!exceptions.is_empty()).then(|| exceptions.join(",")),
+    }
+}
+
+/// The macOS system proxies. PAC files and auto-discovery aren't evaluated;
+/// set the proxy manually for networks that rely on them.
+#[cfg(target_os = "macos")]
+pub(crate) fn system() -> ProxyConfig {
+    use std::process::{Command, Stdio};
+
+    crate::process::runner()
+        .output(Command::new("scutil").arg("--proxy").stdin(Stdio::null()))
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_scutil(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// Elsewhere the environment the app was started with is the system setting.
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn system() -> ProxyConfig {
+    let var = |name: &str| {
+        non_empty(std::env::var(name).ok().as_deref())
+            .or_else(|| non_empty(std::env::var(name.to_lowercase()).ok().as_deref()))
+    };
+    ProxyConfig {
+        http_proxy: var("HTTP_PROXY"),
+        https_proxy: var("HTTPS_PROXY"),
+        no_proxy: var("NO_PROXY"),
+    }
+}
+
+/// The proxies to use under `prefs`.
+pub(crate) fn resolve(prefs: &ProxySettings) -> ProxyConfig {
+    let mut config = match prefs.mode {
+        ProxyMode::Off => return ProxyConfig::default(),
+        ProxyMode::System => system(),
+        ProxyMode::Manual => ProxyConfig {
+            http_proxy: non_empty(prefs.http_proxy.as_deref()),
+            https_proxy: non_empty(prefs.https_proxy.as_deref()),
+            no_proxy: non_empty(prefs.no_proxy.as_deref()),
+        },
+    };
+    if config.http_proxy.is_some() || config.https_proxy.is_some() {
+        let mut hosts: Vec<String> = LOCAL_HOSTS.iter().map(|h| h.to_string()).collect();
+        for host in config.no_proxy.iter().flat_map(|list| list.split(',')) {
+            let host = host.trim();
+            if !host.is_empty() && !hosts.iter().any(|h| h == host) {
+                hosts.push(host.to_string());
+            }
+        }
+        config.no_proxy = Some(hosts.join(","));
+    }
+    config
+}
+
+/// Manual proxies must be URLs curl, node and Docker all understand.
+pub(crate) fn validate(prefs: &ProxySettings) -> Result<(), String> {
+    if prefs.mode != ProxyMode::Manual {
+        return Ok(());
+    }
+    for url in [&prefs.http_proxy, &prefs.https_proxy]
+        .into_iter()
+        .filter_map(|url| non_empty(url.as_deref()))
+    {
+        let valid = (url.starts_with("http://") || url.starts_with("https://"))
+            && !url.contains(char::is_whitespace);
+        if !valid {
+            return Err(format!(
+                "Invalid proxy URL: {} (expected http://host:port)",
+                url
+            ));
+        }
+    }
+    Ok(())
+}
+
+impl ProxyConfig {
+    /// As environment variables, in both the upper- and lowercase spellings
+    /// since tools disagree on which they read.
+    pub(crate) fn env(&self) -> Vec<(String, String)> {
+        [
+            ("HTTP_PROXY", &self.http_proxy),
+            ("HTTPS_PROXY", &self.https_proxy),
+            ("NO_PROXY", &self.no_proxy),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value.clone()?)))
+        .flat_map(|(name, value)| {
+            [
+                (name.to_string(), value.clone()),
+                (name.to_lowercase(), value),
+            ]
+        })
+        .collect()
+    }
+}
@@ -0,0 +1,106 @@
+use std::sync::Mutex;
+
+use tauri::{
+    AppHandle, LogicalSize, Manager, PhysicalPosition, PhysicalSize, Rect, WebviewUrl,
+    WebviewWindow, WebviewWindowBuilder,
+};
+
+pub(crate) const LABEL: &str = "quick-chat";
+
+const DEFAULT_WIDTH: f64 = 380.0;
+const DEFAULT_HEIGHT: f64 = 160.0;
+/// Gap between the tray icon and the top of the popover (physical px).
+const ANCHOR_GAP: i32 = 4;
+
+/// Tray icon bounds from the last click, used to anchor the popover.
+#[derive(Default)]
+pub(crate) struct QuickChatState {
+    anchor: Mutex<Option<(PhysicalPosition<i32>, PhysicalSize<u32>)>>,
+}
+
+impl QuickChatState {
+    pub fn set_anchor(&self, rect: Rect, scale_factor: f64) {
+        let position = rect.position.to_physical(scale_factor);
+        let size = rect.size.to_physical(scale_factor);
+        *self.anchor.lock().unwrap() = Some((position, size));
+    }
+}
+
+/// The popover is created lazily on first use and hidden (not destroyed) on dismiss.
+fn ensure_window(app: &AppHandle) -> tauri::Result<WebviewWindow> {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        return Ok(window);
+    }
+    WebviewWindowBuilder::new(app, LABEL, WebviewUrl::App("index.html#/quick-chat".into()))
+        .title("Quick Chat")
+        .inner_size(DEFAULT_WIDTH, DEFAULT_HEIGHT)
+        .decorations(false)
+        .resizable(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .visible(false)
+        .build()
+}
+
+/// Place the popover centered under the tray icon, kept inside the monitor's
+/// work area. Without an anchor (e.g. opened via command) it is centered.
+fn position_window(app: &AppHandle, window: &WebviewWindow) {
+    let anchor = *app.state::<QuickChatState>().anchor.lock().unwrap();
+    let (Some((icon_pos, icon_size)), Ok(size)) = (anchor, window.outer_size()) else {
+        let _ = window.center();
+        return;
+    };
+
+    let mut x = icon_pos.x + icon_size.width as i32 / 2 - size.width as i32 / 2;
+    let mut y = icon_pos.y + icon_size.height as i32 + ANCHOR_GAP;
+
+    if let Ok(Some(monitor)) = window.monitor_from_point(icon_pos.x as f64, icon_pos.y as f64) {
+        let area = monitor.work_area();
+        let right = area.position.x + area.size.width as i32;
+        let bottom = area.position.y + area.size.height as i32;
+        x = x.clamp(
+            area.position.x,
+            (right - size.width as i32).max(area.position.x),
+        );
+        // Taskbars at the bottom of the screen: open above the icon instead
+        if y + size.height as i32 > bottom {
+            y = icon_pos.y - size.height as i32 - ANCHOR_GAP;
+        }
+    }
+
+    let _ = window.set_position(PhysicalPosition::new(x, y));
+}
+
+pub(crate) fn show(app: &AppHandle) -> tauri::Result<()> {
+    let window = ensure_window(app)?;
+    position_window(app, &window);
+    window.show()?;
+    window.set_focus()
+}
+
+pub(crate) fn hide(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(LABEL) {
+        let _ = window.hide();
+    }
+}
+
+pub(crate) fn toggle(app: &AppHandle) -> tauri::Result<()> {
+    let visible = app
+        .get_webview_window(LABEL)
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false);
+    if visible {
+        hide(app);
+        Ok(())
+    } else {
+        show(app)
+    }
+}
+
+/// Resize the popover (logical px) as its content grows, keeping it anchored.
+pub(crate) fn resize(app: &AppHandle, width: f64, height: f64) -> tauri::Result<()> {
+    let window = ensure_window(app)?;
+    window.set_size(LogicalSize::new(width, height))?;
+    position_window(app, &window);
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::settings::{self, NotificationCategory};
+use crate::{backend, i18n, image, logs, notifications};
+
+const REAP_INTERVAL: Duration = Duration::from_secs(60);
+/// Below this a container is doing nothing but waiting.
+const IDLE_CPU_PERCENT: f64 = 1.0;
+/// Kept for `get_reaper_status`.
+const MAX_REAPED: usize = 20;
+/// Warm pool containers are idle by design and expire on their own.
+const WARM_PREFIX: &str = "nanoclaw-warm-";
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct IdleContainer {
+    pub name: String,
+    pub idle_secs: u64,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReapedContainer {
+    pub name: String,
+    pub idle_secs: u64,
+    /// Unix epoch milliseconds.
+    pub stopped_at: u64,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ReaperStatus {
+    pub enabled: bool,
+    pub idle_timeout_secs: u64,
+    /// Containers idle right now, and for how long.
+    pub idle: Vec<IdleContainer>,
+    /// Recently stopped containers, newest first.
+    pub reaped: Vec<ReapedContainer>,
+}
+
+/// What a container looked like last time, to tell whether it did anything since.
+struct Seen {
+    io: String,
+    idle_since: Option<Instant>,
+}
+
+#[derive(Default)]
+pub(crate) struct Reaper {
+    seen: Mutex<HashMap<String, Seen>>,
+    reaped: Mutex<Vec<ReapedContainer>>,
+}
+
+/// Activity counters per running `nanoclaw-*` container: CPU, and network
+/// plus block I/O totals, which only move when the agent does something.
+fn sample() -> Option<Vec<(String, f64, String)>> {
+    let output = image::docker_output(&["stats", "--no-stream", "--format", "{{json .}}"]).ok()?;
+    Some(
+        output
+            .lines()
+            .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+            .filter_map(|stats| {
+                let field = |key: &str| stats.get(key)?.as_str().map(str::to_string);
+                let name = field("Name")?;
+                if !name.starts_with("nanoclaw-") || name.starts_with(WARM_PREFIX) {
+                    return None;
+                }
+                let cpu = field("CPUPerc")?.trim_end_matches('%').parse().ok()?;
+                let io = format!("{} {}", field("NetIO")?, field("BlockIO")?);
+                Some((name, cpu, io))
+            })
+            .collect(),
+    )
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn tick(app: &AppHandle) {
+    let prefs = settings::load(&crate::user_data_dir(app)).container_reaper;
+    let reaper = app.state::<Reaper>();
+    // Agent containers only run under a live backend; don't poke Docker otherwise
+    let running = app.state::<backend::BackendManager>().status().running;
+    let samples = match sample() {
+        Some(samples) if prefs.enabled && running => samples,
+        _ => {
+            reaper.seen.lock().unwrap().clear();
+            return;
+        }
+    };
+
+    let timeout = Duration::from_secs(prefs.idle_timeout_secs);
+    let mut expired = Vec::new();
+    {
+        let mut seen = reaper.seen.lock().unwrap();
+        seen.retain(|name, _| samples.iter().any(|(n, _, _)| n == name));
+        for (name, cpu, io) in samples {
+            let entry = seen.entry(name.clone()).or_insert(Seen {
+                io: io.clone(),
+                idle_since: None,
+            });
+            if cpu >= IDLE_CPU_PERCENT || entry.io != io {
+                entry.io = io;
+                entry.idle_since = None;
+                continue;
+            }
+            let since = *entry.idle_since.get_or_insert_with(Instant::now);
+            if since.elapsed() >= timeout {
+                expired.push((name, since.elapsed()));
+            }
+        }
+    }
+
+    for (name, idle) in expired {
+        if let Err(e) = image::docker_output(&["stop", &name]) {
+            eprintln!("Failed to stop idle container {}: {}", name, e);
+            continue;
+        }
+        reaper.seen.lock().unwrap().remove(&name);
+        let minutes = idle.as_secs() / 60;
+        logs::record(
+            app,
+            "stderr",
+            format!("[reaper] Stopped {} after {} min idle", name, minutes),
+        );
+        let entry = ReapedContainer {
+            name: name.clone(),
+            idle_secs: idle.as_secs(),
+            stopped_at: now_millis(),
+        };
+        {
+            let mut reaped = reaper.reaped.lock().unwrap();
+            reaped.insert(0, entry.clone());
+            reaped.truncate(MAX_REAPED);
+        }
+        let _ = app.emit("container-reaped", &entry);
+
+        let locale = crate::current_locale(app);
+        notifications::notify(
+            app,
+            NotificationCategory::IdleContainers,
+            i18n::tr(locale, "notify.container_reaped"),
+            &name,
+            None,
+        );
+    }
+}
+
+/// Stop agent containers that have sat idle (no CPU, network or disk
+/// activity) for longer than the configured timeout, so forgotten sessions
+/// don't run overnight. Emits `container-reaped` for each.
+pub(crate) fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(REAP_INTERVAL);
+        tick(&app);
+    });
+}
+
+pub(crate) fn status(app: &AppHandle) -> ReaperStatus {
+    let prefs = settings::load(&crate::user_data_dir(app)).container_reaper;
+    let reaper = app.state::<Reaper>();
+    let idle = reaper
+        .seen
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|(name, seen)| {
+            Some(IdleContainer {
+                name: name.clone(),
+                idle_secs: seen.idle_since?.elapsed().as_secs(),
+            })
+        })
+        .collect();
+    let reaped = reaper.reaped.lock().unwrap().clone();
+    ReaperStatus {
+        enabled: prefs.enabled,
+        idle_timeout_secs: prefs.idle_timeout_secs,
+        idle,
+        reaped,
+    }
+}
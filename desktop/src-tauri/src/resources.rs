@@ -0,0 +1,150 @@
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use specta::Type;
+
+use crate::settings::MemoryWatchdog;
+use crate::{image, process};
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProcessUsage {
+    pub pid: u32,
+    /// Percent of one core, as `ps` reports it (can exceed 100).
+    pub cpu_percent: f64,
+    pub rss_bytes: u64,
+    pub uptime_secs: u64,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ContainerUsage {
+    pub name: String,
+    pub cpu_percent: f64,
+    pub memory_bytes: u64,
+}
+
+#[derive(Serialize, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ResourceUsage {
+    /// None while the backend isn't running.
+    pub backend: Option<ProcessUsage>,
+    pub containers: Vec<ContainerUsage>,
+    /// Unix epoch milliseconds of the sample, 0 before the first one.
+    pub sampled_at: u64,
+}
+
+/// Latest sample from the background sampler, and the watchdog settings it
+/// applies (kept here so sampling doesn't read settings.json every time).
+#[derive(Default)]
+pub(crate) struct ResourceMonitor {
+    latest: Mutex<ResourceUsage>,
+    watchdog: Mutex<MemoryWatchdog>,
+}
+
+impl ResourceMonitor {
+    pub fn watchdog(&self) -> MemoryWatchdog {
+        self.watchdog.lock().unwrap().clone()
+    }
+
+    pub fn set_watchdog(&self, watchdog: MemoryWatchdog) {
+        *self.watchdog.lock().unwrap() = watchdog;
+    }
+
+    pub fn latest(&self) -> ResourceUsage {
+        self.latest.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, usage: ResourceUsage) {
+        *self.latest.lock().unwrap() = usage;
+    }
+}
+
+/// `ps` elapsed time: `[[dd-]hh:]mm:ss`.
+fn parse_etime(text: &str) -> u64 {
+    let (days, rest) = match text.split_once('-') {
+        Some((days, rest)) => (days.parse().unwrap_or(0), rest),
+        None => (0, text),
+    };
+    let clock = rest.split(':').fold(0u64, |acc, part| {
+        acc * 60 + part.parse::<u64>().unwrap_or(0)
+    });
+    days * 86_400 + clock
+}
+
+pub(crate) fn process_usage(pid: u32) -> Option<ProcessUsage> {
+    let output = process::runner()
+        .output(
+            Command::new("ps")
+                .args(["-o", "%cpu=,rss=,etime=", "-p", &pid.to_string()])
+                .stdin(Stdio::null()),
+        )
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let mut fields = text.split_whitespace();
+    let cpu_percent = fields.next()?.parse().ok()?;
+    let rss_kib: u64 = fields.next()?.parse().ok()?;
+    let uptime_secs = parse_etime(fields.next()?);
+    Some(ProcessUsage {
+        pid,
+        cpu_percent,
+        rss_bytes: rss_kib * 1024,
+        uptime_secs,
+    })
+}
+
+/// Parse `docker stats` memory ("12.5MiB"); these use binary units.
+fn parse_binary_size(text: &str) -> u64 {
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number.parse().unwrap_or(0.0);
+    let multiplier = match unit {
+        "KiB" | "kB" => 1024.0,
+        "MiB" | "MB" => 1024.0 * 1024.0,
+        "GiB" | "GB" => 1024.0 * 1024.0 * 1024.0,
+        "TiB" | "TB" => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+        _ => 1.0,
+    };
+    (number * multiplier) as u64
+}
+
+/// One-shot stats for running `nanoclaw-*` containers. Empty when Docker is down.
+pub(crate) fn container_usage() -> Vec<ContainerUsage> {
+    let Ok(output) = image::docker_output(&["stats", "--no-stream", "--format", "{{json .}}"])
+    else {
+        return Vec::new();
+    };
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter_map(|stats| {
+            let name = stats.get("Name")?.as_str()?.to_string();
+            // `docker stats` has no name filter
+            if !name.starts_with("nanoclaw-") {
+                return None;
+            }
+            let cpu_percent = stats
+                .get("CPUPerc")
+                .and_then(|v| v.as_str())
+                .and_then(|v| v.trim_end_matches('%').parse().ok())
+                .unwrap_or(0.0);
+            let memory_bytes = stats
+                .get("MemUsage")
+                .and_then(|v| v.as_str())
+                .and_then(|v| v.split('/').next())
+                .map(|v| parse_binary_size(v.trim()))
+                .unwrap_or(0);
+            Some(ContainerUsage {
+                name,
+                cpu_percent,
+                memory_bytes,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,133 @@
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate, Timelike};
+use tauri::{AppHandle, Manager};
+
+use crate::{backend, logs, settings};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// A restart that can't find a quiet moment this long after the scheduled
+/// time waits for the next day.
+const MAX_DELAY_MINUTES: u32 = 3 * 60;
+const MINUTES_PER_DAY: u32 = 24 * 60;
+
+/// Minutes since midnight for `HH:MM`.
+pub(crate) fn parse_time(time: &str) -> Option<u32> {
+    let (hours, minutes) = time.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Today's local date and the minutes since local midnight.
+fn local_now() -> (NaiveDate, u32) {
+    let now = Local::now();
+    (now.date_naive(), now.hour() * 60 + now.minute())
+}
+
+/// The day of the scheduled restart whose window `now` falls in, if any. The
+/// window may run past midnight, into the day after the one it belongs to.
+fn due(at: u32, today: NaiveDate, now: u32) -> Option<NaiveDate> {
+    let late = (now + MINUTES_PER_DAY - at) % MINUTES_PER_DAY;
+    if late > MAX_DELAY_MINUTES {
+        return None;
+    }
+    if now >= at {
+        Some(today)
+    } else {
+        today.pred_opt()
+    }
+}
+
+/// No agent container running or waiting and no chat reply in flight,
+/// per the backend. Unknown counts as busy.
+pub(crate) fn backend_idle() -> bool {
+    let Ok(activity) = crate::backend_get_json("/api/activity") else {
+        return false;
+    };
+    ["activeContainers", "queuedGroups", "activeRequests"]
+        .iter()
+        .all(|key| activity.get(*key).and_then(|v| v.as_u64()) == Some(0))
+}
+
+/// Restart the backend once a day at the configured local time, clearing
+/// memory and Docker handles a long-running node process accumulates. Waits
+/// for the backend to be idle, for up to a few hours past the time.
+pub(crate) fn start(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut restarted_for: Option<NaiveDate> = None;
+        loop {
+            std::thread::sleep(CHECK_INTERVAL);
+
+            let prefs = settings::load(&crate::user_data_dir(&app)).scheduled_restart;
+            let Some(at) = parse_time(&prefs.time).filter(|_| prefs.enabled) else {
+                continue;
+            };
+            let (today, now) = local_now();
+            let Some(day) = due(at, today, now).filter(|day| restarted_for != Some(*day)) else {
+                continue;
+            };
+            let manager = app.state::<backend::BackendManager>();
+            if !manager.status().ready || !backend_idle() {
+                continue;
+            }
+
+            restarted_for = Some(day);
+            logs::record(
+                &app,
+                "stderr",
+                format!("[scheduler] Daily restart at {}", prefs.time.trim()),
+            );
+            if let Err(e) = manager.restart() {
+                eprintln!("Scheduled backend restart failed: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 3, d).unwrap()
+    }
+
+    #[test]
+    fn parses_hours_and_minutes() {
+        assert_eq!(parse_time("04:00"), Some(240));
+        assert_eq!(parse_time(" 23:59 "), Some(23 * 60 + 59));
+        assert_eq!(parse_time("0:5"), Some(5));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("12:60"), None);
+        assert_eq!(parse_time("noon"), None);
+        assert_eq!(parse_time("-1:00"), None);
+    }
+
+    #[test]
+    fn window_opens_at_the_time_for_a_few_hours() {
+        let at = parse_time("04:00").unwrap();
+        assert_eq!(due(at, day(10), at - 1), None);
+        assert_eq!(due(at, day(10), at), Some(day(10)));
+        assert_eq!(due(at, day(10), at + MAX_DELAY_MINUTES), Some(day(10)));
+        assert_eq!(due(at, day(10), at + MAX_DELAY_MINUTES + 1), None);
+    }
+
+    #[test]
+    fn window_runs_past_midnight_into_the_next_day() {
+        let at = parse_time("23:30").unwrap();
+        assert_eq!(due(at, day(10), at), Some(day(10)));
+        // Still the 10th's restart after midnight
+        assert_eq!(
+            due(at, day(11), parse_time("00:15").unwrap()),
+            Some(day(10))
+        );
+        assert_eq!(
+            due(at, day(11), parse_time("02:30").unwrap()),
+            Some(day(10))
+        );
+        assert_eq!(due(at, day(11), parse_time("02:31").unwrap()), None);
+        assert_eq!(due(at, day(11), parse_time("23:29").unwrap()), None);
+        assert_eq!(due(at, day(11), at), Some(day(11)));
+    }
+}
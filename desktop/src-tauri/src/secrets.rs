@@ -0,0 +1,211 @@
+use std::io::{Read, Write};
+use std::path::Path;
+#[cfg(target_os = "macos")]
+use std::process::Command;
+
+#[cfg(target_os = "macos")]
+const KEYCHAIN_SERVICE: &str = "com.nanoclaw.desktop";
+const API_TOKEN_ACCOUNT: &str = "api-token";
+/// Fallback store when the Keychain is unavailable (or off macOS).
+const API_TOKEN_FILE: &str = ".api-token";
+/// Token for a remote backend the shell connects to instead of its own.
+const REMOTE_TOKEN_ACCOUNT: &str = "remote-api-token";
+const REMOTE_TOKEN_FILE: &str = ".remote-api-token";
+
+/// 32 random bytes from the OS, hex-encoded.
+pub(crate) fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    std::fs::File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut bytes))
+        .map_err(|e| format!("Failed to read random bytes: {}", e))?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_read(account: &str) -> Option<String> {
+    let output = crate::process::runner()
+        .output(Command::new("security").args([
+            "find-generic-password",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+            "-w",
+        ]))
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// The value goes in on stdin: `-w` as the last argument makes `security`
+/// ask for it (twice, to confirm), so it never shows up in `ps`.
+#[cfg(target_os = "macos")]
+fn keychain_write(account: &str, value: &str) -> Result<(), String> {
+    let output = crate::process::output_with_stdin(
+        Command::new("security").args([
+            "add-generic-password",
+            "-U",
+            "-s",
+            KEYCHAIN_SERVICE,
+            "-a",
+            account,
+            "-w",
+        ]),
+        format!("{}\n{}\n", value, value).as_bytes(),
+    )
+    .map_err(|e| format!("Failed to run security: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Keychain write failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_delete(account: &str) {
+    let _ = crate::process::runner().output(Command::new("security").args([
+        "delete-generic-password",
+        "-s",
+        KEYCHAIN_SERVICE,
+        "-a",
+        account,
+    ]));
+}
+
+#[cfg(not(target_os = "macos"))]
+fn keychain_read(_account: &str) -> Option<String> {
+    None
+}
+
+#[cfg(not(target_os = "macos"))]
+fn keychain_write(_account: &str, _value: &str) -> Result<(), String> {
+    Err("Keychain is only available on macOS".to_string())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn keychain_delete(_account: &str) {}
+
+/// Delete every NanoClaw Keychain item, whatever its account. Returns how
+/// many there were.
+#[cfg(target_os = "macos")]
+pub(crate) fn delete_all() -> usize {
+    let mut deleted = 0;
+    // Each call deletes one match; the cap guards against a Keychain that
+    // keeps reporting success
+    while deleted < 100
+        && crate::process::runner()
+            .output(Command::new("security").args([
+                "delete-generic-password",
+                "-s",
+                KEYCHAIN_SERVICE,
+            ]))
+            .is_ok_and(|output| output.status.success())
+    {
+        deleted += 1;
+    }
+    deleted
+}
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn delete_all() -> usize {
+    0
+}
+
+fn write_private_file(path: &Path, value: &str) -> Result<(), String> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+        .open(path)
+        .and_then(|mut f| f.write_all(value.as_bytes()))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn load(data_dir: &Path, account: &str, file: &str) -> Option<String> {
+    keychain_read(account).or_else(|| {
+        std::fs::read_to_string(data_dir.join(file))
+            .ok()
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+    })
+}
+
+/// Save to the Keychain, falling back to a 0600 file in the data dir.
+fn store(data_dir: &Path, account: &str, file: &str, value: &str) -> Result<(), String> {
+    match keychain_write(account, value) {
+        Ok(()) => {
+            // Don't leave a stale fallback copy behind
+            let _ = std::fs::remove_file(data_dir.join(file));
+            Ok(())
+        }
+        Err(e) => {
+            if cfg!(target_os = "macos") {
+                eprintln!("{}; storing {} in the data dir instead", e, account);
+            }
+            write_private_file(&data_dir.join(file), value)
+        }
+    }
+}
+
+pub(crate) fn load_api_token(data_dir: &Path) -> Option<String> {
+    load(data_dir, API_TOKEN_ACCOUNT, API_TOKEN_FILE)
+}
+
+pub(crate) fn store_api_token(data_dir: &Path, token: &str) -> Result<(), String> {
+    store(data_dir, API_TOKEN_ACCOUNT, API_TOKEN_FILE, token)
+}
+
+pub(crate) fn load_remote_token(data_dir: &Path) -> Option<String> {
+    load(data_dir, REMOTE_TOKEN_ACCOUNT, REMOTE_TOKEN_FILE)
+}
+
+/// Store the remote backend's token, or forget it when there is none.
+pub(crate) fn store_remote_token(data_dir: &Path, token: Option<&str>) -> Result<(), String> {
+    match token {
+        Some(token) => store(data_dir, REMOTE_TOKEN_ACCOUNT, REMOTE_TOKEN_FILE, token),
+        None => {
+            keychain_delete(REMOTE_TOKEN_ACCOUNT);
+            let _ = std::fs::remove_file(data_dir.join(REMOTE_TOKEN_FILE));
+            Ok(())
+        }
+    }
+}
+
+pub(crate) fn load_oauth_token(data_dir: &Path, provider: &str) -> Option<String> {
+    let account = format!("oauth-{}", provider);
+    load(data_dir, &account, &format!(".{}", account))
+}
+
+/// Store a provider's OAuth token, or forget it when there is none.
+pub(crate) fn store_oauth_token(
+    data_dir: &Path,
+    provider: &str,
+    token: Option<&str>,
+) -> Result<(), String> {
+    let account = format!("oauth-{}", provider);
+    let file = format!(".{}", account);
+    match token {
+        Some(token) => store(data_dir, &account, &file, token),
+        None => {
+            keychain_delete(&account);
+            let _ = std::fs::remove_file(data_dir.join(file));
+            Ok(())
+        }
+    }
+}
+
+/// The persisted API token, generated on first run.
+pub(crate) fn ensure_api_token(data_dir: &Path) -> Result<String, String> {
+    if let Some(token) = load_api_token(data_dir) {
+        return Ok(token);
+    }
+    let token = generate_token()?;
+    store_api_token(data_dir, &token)?;
+    Ok(token)
+}
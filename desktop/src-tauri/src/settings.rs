@@ -0,0 +1,647 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+const SETTINGS_FILE: &str = "settings.json";
+/// Bump together with a new entry in MIGRATIONS when the layout changes.
+const CURRENT_VERSION: u64 = 1;
+
+/// `MIGRATIONS[n]` upgrades a version-n document to version n+1.
+const MIGRATIONS: &[fn(&mut serde_json::Value)] = &[
+    // 0 → 1: files written before versioning; same layout, just stamped
+    |_| {},
+];
+
+/// Shell preferences persisted as settings.json in the user data dir.
+/// Unknown or missing fields fall back to defaults so older files keep loading.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct Settings {
+    /// Layout version of the file, see MIGRATIONS.
+    pub version: u64,
+    /// Show the Dock icon (Regular activation policy) instead of running menu-bar-only.
+    pub show_dock_icon: bool,
+    /// UI locale code ("en", "zh"); None follows the system language.
+    pub locale: Option<String>,
+    /// Keep the main window above other apps.
+    pub always_on_top: bool,
+    /// Page zoom of the app's windows; 1.0 is actual size.
+    pub zoom: f64,
+    /// Run only the backend supervisor and tray; the main window is created
+    /// only when opened from the tray. Applies from the next launch.
+    pub headless: bool,
+    /// What the main window's close button does.
+    pub close_behavior: CloseBehavior,
+    /// Release channel for the app, backend bundle, and container image.
+    pub update_channel: UpdateChannel,
+    /// Generate and enforce an API token for the local backend.
+    pub require_api_token: bool,
+    /// Allow the backend to listen on a non-loopback HTTP_HOST (reachable from the LAN).
+    pub allow_lan_access: bool,
+    /// Serve the backend on a unix socket in the data dir instead of a TCP port.
+    pub unix_socket: bool,
+    /// Backend listen host; None uses HTTP_HOST or 127.0.0.1.
+    pub backend_host: Option<String>,
+    /// Backend listen port; None uses PORT or 3000.
+    pub backend_port: Option<u16>,
+    /// Connect to the backend at this URL (e.g. on a home server) instead of
+    /// starting one; its token is kept with the other secrets.
+    pub remote_backend_url: Option<String>,
+    /// Reach the backend from away from home through a tunnel client run
+    /// next to it.
+    pub tunnel: TunnelProvider,
+    /// Native notification preferences.
+    pub notifications: NotificationPrefs,
+    /// Automatic backups of the data dir.
+    pub backup_schedule: BackupSchedule,
+    /// Restart the backend when its memory stays too high.
+    pub memory_watchdog: MemoryWatchdog,
+    pub container_reaper: ContainerReaper,
+    /// Restart the backend once a day while it's idle.
+    pub scheduled_restart: ScheduledRestart,
+    /// Stop the backend while the app goes unused.
+    pub auto_suspend: AutoSuspend,
+    /// Restart the backend after a crash, unless it keeps crashing.
+    pub auto_restart: AutoRestart,
+    /// Debugging aids for working on the backend itself.
+    pub dev_mode: DevMode,
+    /// Shell whose login PATH is used to find node and docker; None uses
+    /// $SHELL. Applies from the next launch.
+    pub shell: Option<String>,
+    /// Literal PATH to use instead of asking a shell.
+    pub path_override: Option<String>,
+    /// Run prompts from `nanoclaw://send` links (Shortcuts, Raycast) without
+    /// asking first.
+    pub url_automation: bool,
+    /// Extra environment for the backend (e.g. NODE_OPTIONS, proxy vars),
+    /// applied over the .env values.
+    pub backend_env: BTreeMap<String, String>,
+    /// Backend variables filled from OAuth sign-ins: variable name to the
+    /// provider whose stored token it gets.
+    pub oauth_env: BTreeMap<String, String>,
+    /// Rebuild the agent image when an app update changes its sources.
+    pub auto_rebuild_image: bool,
+    pub image_build: ImageBuildOptions,
+    pub image_trust: ImageTrust,
+    /// HTTP(S) proxy for the backend, its agents and image builds.
+    pub proxy: ProxySettings,
+    /// PEM file of CA certificates to trust on top of the system's, e.g. a
+    /// corporate TLS-inspecting proxy's.
+    pub extra_ca_bundle: Option<PathBuf>,
+    /// Give agent containers the machine's GPUs, for local inference. Only
+    /// applied while Docker supports it.
+    pub gpu_passthrough: bool,
+    /// Agent containers the backend keeps booted for the next request; 0 turns
+    /// the pool off.
+    pub warm_pool_size: u32,
+    /// How long quitting waits for the backend and agent containers to stop
+    /// before they're killed.
+    pub shutdown_timeout_secs: u64,
+    /// Opt-in anonymous usage reporting.
+    pub telemetry: TelemetryPrefs,
+    /// On battery, pause agent containers and defer scheduled backups until
+    /// the machine is back on AC power.
+    pub low_power_mode: bool,
+    /// Keep macOS from throttling the backend (and the shell) with App Nap
+    /// while the window is hidden, so scheduled agent jobs fire on time.
+    pub prevent_app_nap: bool,
+    /// Consent to send shell crash reports; they are always kept locally.
+    pub upload_crash_reports: bool,
+    /// Last known geometry per window label, restored on launch.
+    pub window_geometry: BTreeMap<String, WindowGeometry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum CloseBehavior {
+    /// Hide the window and keep running in the menu bar.
+    Hide,
+    /// Quit the app (stopping the backend).
+    Quit,
+    /// Quit once no other window is left open; otherwise just hide.
+    QuitOnLastWindow,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum UpdateChannel {
+    Stable,
+    Beta,
+}
+
+impl UpdateChannel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => "stable",
+            UpdateChannel::Beta => "beta",
+        }
+    }
+}
+
+/// Window position and size in physical pixels.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+    /// Monitor name the window was on, used to prefer the same display.
+    #[serde(default)]
+    pub monitor: Option<String>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: CURRENT_VERSION,
+            show_dock_icon: true,
+            locale: None,
+            always_on_top: false,
+            zoom: 1.0,
+            headless: false,
+            close_behavior: CloseBehavior::Hide,
+            update_channel: UpdateChannel::Stable,
+            require_api_token: true,
+            allow_lan_access: false,
+            unix_socket: false,
+            backend_host: None,
+            backend_port: None,
+            remote_backend_url: None,
+            tunnel: TunnelProvider::Off,
+            notifications: NotificationPrefs::default(),
+            backup_schedule: BackupSchedule::default(),
+            memory_watchdog: MemoryWatchdog::default(),
+            auto_restart: AutoRestart::default(),
+            dev_mode: DevMode::default(),
+            container_reaper: ContainerReaper::default(),
+            scheduled_restart: ScheduledRestart::default(),
+            auto_suspend: AutoSuspend::default(),
+            shell: None,
+            path_override: None,
+            url_automation: false,
+            backend_env: BTreeMap::new(),
+            oauth_env: BTreeMap::new(),
+            auto_rebuild_image: true,
+            image_build: ImageBuildOptions::default(),
+            image_trust: ImageTrust::default(),
+            proxy: ProxySettings::default(),
+            extra_ca_bundle: None,
+            gpu_passthrough: false,
+            warm_pool_size: 0,
+            shutdown_timeout_secs: 10,
+            telemetry: TelemetryPrefs::default(),
+            low_power_mode: false,
+            prevent_app_nap: true,
+            upload_crash_reports: false,
+            window_geometry: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct NotificationPrefs {
+    pub enabled: bool,
+    /// Hold notifications back while a macOS Focus / Do Not Disturb mode is
+    /// on, as `focus` says; off posts them regardless. Best effort: Focus is
+    /// read from a private store macOS may not let the app see, in which case
+    /// notifications go out and macOS applies the Focus itself.
+    pub respect_focus: bool,
+    /// What each category does during Focus.
+    pub focus: FocusPrefs,
+    /// Agent replies finishing while the window is hidden.
+    pub completions: bool,
+    /// Agent runs failing while the window is hidden.
+    pub errors: bool,
+    /// The backend exiting without the shell asking it to.
+    pub backend_crashes: bool,
+    pub updates: bool,
+    /// The reaper stopping an agent container that sat idle.
+    pub idle_containers: bool,
+}
+
+impl Default for NotificationPrefs {
+    fn default() -> Self {
+        NotificationPrefs {
+            enabled: true,
+            respect_focus: true,
+            focus: FocusPrefs::default(),
+            completions: true,
+            errors: true,
+            backend_crashes: true,
+            updates: true,
+            idle_containers: true,
+        }
+    }
+}
+
+/// What happens to a notification that comes in during Focus.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FocusBehavior {
+    /// Post it anyway; the Focus mode decides whether it gets through.
+    Show,
+    /// Drop it.
+    Suppress,
+    /// Count it on the tray badge and post it when Focus ends.
+    Queue,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct FocusPrefs {
+    pub completions: FocusBehavior,
+    pub errors: FocusBehavior,
+    pub backend_crashes: FocusBehavior,
+    pub updates: FocusBehavior,
+    pub idle_containers: FocusBehavior,
+}
+
+impl Default for FocusPrefs {
+    fn default() -> Self {
+        FocusPrefs {
+            completions: FocusBehavior::Queue,
+            errors: FocusBehavior::Queue,
+            backend_crashes: FocusBehavior::Queue,
+            updates: FocusBehavior::Suppress,
+            idle_containers: FocusBehavior::Suppress,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum NotificationCategory {
+    Completions,
+    Errors,
+    BackendCrashes,
+    Updates,
+    IdleContainers,
+}
+
+impl NotificationPrefs {
+    pub fn allows(&self, category: NotificationCategory) -> bool {
+        self.enabled
+            && match category {
+                NotificationCategory::Completions => self.completions,
+                NotificationCategory::Errors => self.errors,
+                NotificationCategory::BackendCrashes => self.backend_crashes,
+                NotificationCategory::Updates => self.updates,
+                NotificationCategory::IdleContainers => self.idle_containers,
+            }
+    }
+
+    /// What `category` does while a Focus mode is on.
+    pub fn during_focus(&self, category: NotificationCategory) -> FocusBehavior {
+        if !self.respect_focus {
+            return FocusBehavior::Show;
+        }
+        match category {
+            NotificationCategory::Completions => self.focus.completions,
+            NotificationCategory::Errors => self.focus.errors,
+            NotificationCategory::BackendCrashes => self.focus.backend_crashes,
+            NotificationCategory::Updates => self.focus.updates,
+            NotificationCategory::IdleContainers => self.focus.idle_containers,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum BackupFrequency {
+    Off,
+    Daily,
+    Weekly,
+}
+
+impl BackupFrequency {
+    pub fn interval(self) -> Option<Duration> {
+        match self {
+            BackupFrequency::Off => None,
+            BackupFrequency::Daily => Some(Duration::from_secs(24 * 60 * 60)),
+            BackupFrequency::Weekly => Some(Duration::from_secs(7 * 24 * 60 * 60)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct BackupSchedule {
+    pub frequency: BackupFrequency,
+    /// Automatic backups kept; older ones are deleted after each run.
+    pub retention: u32,
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        BackupSchedule {
+            frequency: BackupFrequency::Off,
+            retention: 7,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct MemoryWatchdog {
+    pub enabled: bool,
+    /// Backend RSS limit in megabytes.
+    pub threshold_mb: u64,
+    /// How long RSS must stay above the limit before a restart.
+    pub sustain_secs: u64,
+}
+
+impl Default for MemoryWatchdog {
+    fn default() -> Self {
+        MemoryWatchdog {
+            enabled: false,
+            threshold_mb: 2048,
+            sustain_secs: 300,
+        }
+    }
+}
+
+/// How `build_container_image` runs `docker build`, for users behind proxies
+/// or registry mirrors.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ImageBuildOptions {
+    /// `--build-arg`s: HTTP_PROXY / HTTPS_PROXY / NO_PROXY, PIP_INDEX_URL,
+    /// BASE_IMAGE, ...
+    pub build_args: BTreeMap<String, String>,
+    /// Build every layer from scratch.
+    pub no_cache: bool,
+    /// Extra tag for the built image, e.g. to push it to a private registry.
+    pub extra_tag: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ProxyMode {
+    /// Whatever macOS network settings say.
+    #[default]
+    System,
+    /// The proxies given in the settings.
+    Manual,
+    /// Connect directly.
+    Off,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ProxySettings {
+    pub mode: ProxyMode,
+    /// Manual mode only, e.g. `http://proxy.corp:8080`.
+    pub http_proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    /// Comma-separated hosts and domains to reach directly.
+    pub no_proxy: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TunnelProvider {
+    #[default]
+    Off,
+    /// A Cloudflare quick tunnel: a random public `trycloudflare.com` URL.
+    Cloudflared,
+    /// `tailscale serve`: only devices on the user's tailnet get in.
+    Tailscale,
+}
+
+/// Supply-chain checks for prebuilt agent images.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ImageTrust {
+    /// Only ever pull this digest (`sha256:…`), whatever the channel tag says.
+    pub pinned_digest: Option<String>,
+    /// Refuse images whose signature can't be checked, e.g. without cosign.
+    pub require_signature: bool,
+    /// Digest of the last image that passed the checks and was put in use.
+    pub verified_digest: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ScheduledRestart {
+    pub enabled: bool,
+    /// Local time of day, `HH:MM`.
+    pub time: String,
+}
+
+impl Default for ScheduledRestart {
+    fn default() -> Self {
+        ScheduledRestart {
+            enabled: false,
+            time: "04:00".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct AutoSuspend {
+    pub enabled: bool,
+    /// Minutes with no window open before the backend is stopped.
+    pub idle_minutes: u64,
+}
+
+impl Default for AutoSuspend {
+    fn default() -> Self {
+        AutoSuspend {
+            enabled: false,
+            idle_minutes: 30,
+        }
+    }
+}
+
+/// Stop agent containers that sit idle too long.
+#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ContainerReaper {
+    pub enabled: bool,
+    /// No CPU, network or disk activity for this long counts as forgotten.
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for ContainerReaper {
+    fn default() -> Self {
+        ContainerReaper {
+            enabled: true,
+            idle_timeout_secs: 2 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct AutoRestart {
+    pub enabled: bool,
+    /// This many crashes within `window_secs` is a crash loop: restarts stop
+    /// and the user is asked to look into it.
+    pub max_crashes: u32,
+    pub window_secs: u64,
+}
+
+impl Default for AutoRestart {
+    fn default() -> Self {
+        AutoRestart {
+            enabled: true,
+            max_crashes: 3,
+            window_secs: 300,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Type)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct DevMode {
+    /// Run node with `--inspect` on `inspect_port`.
+    pub enabled: bool,
+    pub inspect_port: u16,
+    /// Restart the backend when `dist/index.js` is rebuilt.
+    pub watch_dist: bool,
+}
+
+impl Default for DevMode {
+    fn default() -> Self {
+        DevMode {
+            enabled: false,
+            inspect_port: 9229,
+            watch_dist: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct TelemetryPrefs {
+    /// Off until the user turns it on.
+    pub enabled: bool,
+    /// Random per-install id sent with events; cleared when telemetry is off.
+    pub install_id: Option<String>,
+}
+
+/// Run the migrations a document of an older version needs. Returns whether
+/// anything changed.
+fn migrate(value: &mut serde_json::Value) -> bool {
+    let from = value.get("version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if from >= CURRENT_VERSION {
+        return false;
+    }
+    for migration in MIGRATIONS.iter().skip(from as usize) {
+        migration(value);
+    }
+    if let Some(object) = value.as_object_mut() {
+        object.insert("version".to_string(), CURRENT_VERSION.into());
+    }
+    true
+}
+
+/// Settings for reading. A file that can't be parsed yields defaults but is
+/// left alone, so a hand-edit typo doesn't cost the user every preference.
+pub(crate) fn load(data_dir: &Path) -> Settings {
+    try_load(data_dir).unwrap_or_else(|e| {
+        eprintln!("{}; using defaults", e);
+        Settings::default()
+    })
+}
+
+/// Like `load`, but an unreadable or invalid file is an error rather than
+/// defaults. A missing file is a fresh install and loads as defaults.
+fn try_load(data_dir: &Path) -> Result<Settings, String> {
+    let path = data_dir.join(SETTINGS_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Settings::default()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    let mut value = serde_json::from_str::<serde_json::Value>(&content)
+        .map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+    let migrated = migrate(&mut value);
+    let settings: Settings =
+        serde_json::from_value(value).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+    if migrated {
+        if let Err(e) = save(data_dir, &settings) {
+            eprintln!("Failed to save migrated settings: {}", e);
+        }
+    }
+    Ok(settings)
+}
+
+/// Write through a temp file and rename, so a crash mid-write leaves the
+/// old file rather than a truncated one.
+fn save(data_dir: &Path, settings: &Settings) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let path = data_dir.join(SETTINGS_FILE);
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, content + "\n")
+        .and_then(|_| std::fs::rename(&tmp, &path))
+        .map_err(|e| {
+            let _ = std::fs::remove_file(&tmp);
+            format!("Failed to write settings: {}", e)
+        })
+}
+
+/// Serializes read-modify-write cycles, which run from commands, the window
+/// state saver and background threads alike.
+static UPDATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Load, modify, and persist settings in one step.
+pub(crate) fn update(
+    data_dir: &Path,
+    apply: impl FnOnce(&mut Settings),
+) -> Result<Settings, String> {
+    try_update(data_dir, |settings| {
+        apply(settings);
+        Ok(())
+    })
+}
+
+/// `update` for changes that can be rejected; nothing is written on error.
+/// Refuses to run while the file on disk can't be parsed, rather than
+/// replacing it with defaults plus one change.
+pub(crate) fn try_update(
+    data_dir: &Path,
+    apply: impl FnOnce(&mut Settings) -> Result<(), String>,
+) -> Result<Settings, String> {
+    let _guard = UPDATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut settings = try_load(data_dir)?;
+    apply(&mut settings)?;
+    save(data_dir, &settings)?;
+    Ok(settings)
+}
+
+/// Read one setting by its camelCase key; nested fields use dots
+/// (`notifications.enabled`).
+pub(crate) fn get_value(settings: &Settings, key: &str) -> Option<serde_json::Value> {
+    let root = serde_json::to_value(settings).ok()?;
+    key.split('.')
+        .try_fold(&root, |value, part| value.get(part))
+        .cloned()
+}
+
+/// Return `settings` with one key replaced. Unknown keys and values of the
+/// wrong type are rejected.
+pub(crate) fn set_value(
+    settings: &Settings,
+    key: &str,
+    new_value: serde_json::Value,
+) -> Result<Settings, String> {
+    let mut root = serde_json::to_value(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    let slot = key
+        .split('.')
+        .try_fold(&mut root, |value, part| value.get_mut(part))
+        .ok_or_else(|| format!("Unknown setting: {}", key))?;
+    *slot = new_value;
+    serde_json::from_value(root).map_err(|e| format!("Invalid value for {}: {}", key, e))
+}
@@ -0,0 +1,287 @@
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{image, local_llm, models, process, settings};
+
+/// Node rarely changes under us; Docker flips whenever Docker Desktop starts or stops.
+const NODE_TTL: Duration = Duration::from_secs(300);
+const DOCKER_TTL: Duration = Duration::from_secs(15);
+const IMAGE_TTL: Duration = Duration::from_secs(60);
+const GPU_TTL: Duration = Duration::from_secs(300);
+const OLLAMA_TTL: Duration = Duration::from_secs(15);
+const MODEL_TTL: Duration = Duration::from_secs(300);
+const MONITOR_INTERVAL: Duration = Duration::from_secs(20);
+
+#[derive(Serialize, Clone, PartialEq, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SetupStatus {
+    node_installed: bool,
+    node_version: String,
+    docker_running: bool,
+    container_image_built: bool,
+    /// The image was built from older container-agno sources than the
+    /// bundle's and should be rebuilt.
+    container_image_stale: bool,
+    container_resources_ready: bool,
+    /// Docker can hand GPUs to containers (the NVIDIA runtime is installed),
+    /// so GPU passthrough can be turned on.
+    gpu_available: bool,
+    api_key_configured: bool,
+    /// Whether the provider lists `AGNO_MODEL_ID`; None when that couldn't
+    /// be checked (no model set, provider unreachable).
+    model_available: Option<bool>,
+    user_data_dir: String,
+    /// A running Ollama server, offered as a no-key local setup. Not a check:
+    /// it's fine for it to be missing.
+    ollama: Option<local_llm::LocalServer>,
+}
+
+impl SetupStatus {
+    /// Each check by name, with whether it passes.
+    pub fn checks(&self) -> [(&'static str, bool); 5] {
+        [
+            ("node", self.node_installed),
+            ("docker", self.docker_running),
+            ("image", self.container_image_built),
+            ("containerResources", self.container_resources_ready),
+            ("apiKey", self.api_key_configured),
+        ]
+    }
+}
+
+type Slot<T> = Mutex<Option<(Instant, T)>>;
+/// The provider env a model check ran against, and its answer.
+type ModelCheck = (Vec<(String, String)>, Option<bool>);
+
+/// Results of the slow probes (`node --version`, `docker info`, image
+/// inspect, Ollama's model list), each reused until its TTL runs out or the cache is invalidated.
+#[derive(Default)]
+pub(crate) struct SetupCache {
+    node: Slot<Option<String>>,
+    docker_running: Slot<bool>,
+    image_built: Slot<bool>,
+    image_stale: Slot<bool>,
+    gpu: Slot<bool>,
+    ollama: Slot<Option<local_llm::LocalServer>>,
+    /// Keyed by the env it was checked against, so edits re-check at once
+    model_available: Slot<ModelCheck>,
+    /// Last status pushed to the frontend, to only emit real changes
+    last_emitted: Mutex<Option<SetupStatus>>,
+}
+
+fn cached<T: Clone>(slot: &Slot<T>, ttl: Duration, probe: impl FnOnce() -> T) -> T {
+    if let Some((at, value)) = slot.lock().unwrap().as_ref() {
+        if at.elapsed() < ttl {
+            return value.clone();
+        }
+    }
+    let value = probe();
+    *slot.lock().unwrap() = Some((Instant::now(), value.clone()));
+    value
+}
+
+fn probe_node() -> Option<String> {
+    match process::runner().output(Command::new("node").arg("--version")) {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        }
+        _ => None,
+    }
+}
+
+fn probe_docker_running() -> bool {
+    process::succeeds(Command::new("docker").args(["info"]).stdin(Stdio::null()))
+}
+
+fn probe_image_built() -> bool {
+    process::succeeds(
+        Command::new("docker")
+            .args(["image", "inspect", "nanoclaw-agent-agno:latest"])
+            .stdin(Stdio::null()),
+    )
+}
+
+/// Whether the provider lists the configured model, reusing the last answer
+/// while the provider settings are unchanged and the TTL hasn't run out.
+fn check_model(slot: &Slot<ModelCheck>, env_vars: &[(String, String)]) -> Option<bool> {
+    const PROVIDER_KEYS: [&str; 4] = [
+        "AGNO_BASE_URL",
+        "AGNO_MODEL_ID",
+        "AGNO_API_KEY",
+        "ANTHROPIC_API_KEY",
+    ];
+    let provider_env: Vec<(String, String)> = env_vars
+        .iter()
+        .filter(|(key, _)| PROVIDER_KEYS.contains(&key.as_str()))
+        .cloned()
+        .collect();
+    if let Some((at, (checked, available))) = slot.lock().unwrap().as_ref() {
+        if at.elapsed() < MODEL_TTL && *checked == provider_env {
+            return *available;
+        }
+    }
+    let available = models::list(&provider_env)
+        .ok()
+        .and_then(|list| list.configured_model_found);
+    *slot.lock().unwrap() = Some((Instant::now(), (provider_env, available)));
+    available
+}
+
+fn probe_gpu() -> bool {
+    image::docker_output(&["info", "--format", "{{json .Runtimes}}"])
+        .is_ok_and(|runtimes| runtimes.contains("\"nvidia\""))
+}
+
+/// Whether containers can get GPUs, from the cached probe.
+pub(crate) fn gpu_available(app: &AppHandle) -> bool {
+    cached(&app.state::<SetupCache>().gpu, GPU_TTL, probe_gpu)
+}
+
+pub(crate) fn status(app: &AppHandle) -> SetupStatus {
+    let cache = app.state::<SetupCache>();
+    let data = crate::user_data_dir(app);
+    let bundle = crate::bundle_dir(app);
+
+    let node_version = cached(&cache.node, NODE_TTL, probe_node);
+    let docker_running = cached(&cache.docker_running, DOCKER_TTL, probe_docker_running);
+    // No daemon means no image info; don't cache a false negative
+    let container_image_built =
+        docker_running && cached(&cache.image_built, IMAGE_TTL, probe_image_built);
+    let container_dir = bundle.join("container-agno");
+    let container_image_stale = container_image_built
+        && cached(&cache.image_stale, IMAGE_TTL, || {
+            image::is_stale(&container_dir)
+        });
+
+    let gpu_available = docker_running && gpu_available(app);
+    let ollama = cached(&cache.ollama, OLLAMA_TTL, local_llm::probe_ollama);
+
+    // Cheap file checks always run fresh
+    let container_resources_ready = container_dir.exists();
+    let mut env_vars = crate::load_user_env(&data);
+    env_vars.extend(crate::oauth::env(&data, &crate::settings::load(&data)));
+    let api_key_configured = {
+        let has_value = |key: &str| {
+            env_vars
+                .iter()
+                .any(|(k, v)| k == key && !v.trim().is_empty())
+        };
+
+        has_value("ANTHROPIC_API_KEY")
+            || (has_value("AGNO_API_KEY")
+                && has_value("AGNO_MODEL_ID")
+                && has_value("AGNO_BASE_URL"))
+    };
+    let model_available = if api_key_configured {
+        check_model(&cache.model_available, &env_vars)
+    } else {
+        None
+    };
+
+    SetupStatus {
+        node_installed: node_version.is_some(),
+        node_version: node_version.unwrap_or_default(),
+        docker_running,
+        container_image_built,
+        container_image_stale,
+        container_resources_ready,
+        gpu_available,
+        api_key_configured,
+        model_available,
+        user_data_dir: data.to_string_lossy().to_string(),
+        ollama,
+    }
+}
+
+/// Forget all cached probes, e.g. after building the image or editing .env.
+pub(crate) fn invalidate(app: &AppHandle) {
+    let cache = app.state::<SetupCache>();
+    *cache.node.lock().unwrap() = None;
+    *cache.docker_running.lock().unwrap() = None;
+    *cache.image_built.lock().unwrap() = None;
+    *cache.image_stale.lock().unwrap() = None;
+    *cache.gpu.lock().unwrap() = None;
+    *cache.ollama.lock().unwrap() = None;
+    *cache.model_available.lock().unwrap() = None;
+}
+
+/// Re-probe everything and emit `setup-status-changed` if the result differs
+/// from what the frontend last saw. For user actions ("Recheck", after an
+/// install); the monitor only re-probes what has expired.
+pub(crate) fn refresh(app: &AppHandle) -> SetupStatus {
+    invalidate(app);
+    poll(app)
+}
+
+/// The status with cached probes reused until their TTLs run out, emitting
+/// `setup-status-changed` if it differs from what the frontend last saw.
+fn poll(app: &AppHandle) -> SetupStatus {
+    let status = status(app);
+    let cache = app.state::<SetupCache>();
+    let mut last = cache.last_emitted.lock().unwrap();
+    if last.as_ref() != Some(&status) {
+        *last = Some(status.clone());
+        let _ = app.emit("setup-status-changed", status.clone());
+        record_failures(app, &status);
+    }
+    status
+}
+
+/// Report which setup checks fail (names only) when the status changes.
+fn record_failures(app: &AppHandle, status: &SetupStatus) {
+    let failed: Vec<&str> = status
+        .checks()
+        .into_iter()
+        .filter(|(_, ok)| !ok)
+        .map(|(name, _)| name)
+        .collect();
+    if !failed.is_empty() {
+        crate::telemetry::record(
+            app,
+            "setup_check_failed",
+            serde_json::json!({ "failed": failed }),
+        );
+    }
+}
+
+/// Rebuild a stale image in the background, once per set of sources so a
+/// failing build isn't retried every poll.
+fn rebuild_stale_image(app: &AppHandle, rebuilt_for: &mut Option<String>) {
+    if !settings::load(&crate::user_data_dir(app)).auto_rebuild_image {
+        return;
+    }
+    let Ok(hash) = image::source_hash(&crate::bundle_dir(app).join("container-agno")) else {
+        return;
+    };
+    if rebuilt_for.as_ref() == Some(&hash) {
+        return;
+    }
+    *rebuilt_for = Some(hash);
+    eprintln!("Container image is out of date; rebuilding");
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = crate::build_container_image(app).await {
+            eprintln!("Automatic image rebuild failed: {}", e);
+        }
+    });
+}
+
+/// Poll the probes in the background so the settings screen learns about
+/// Docker starting/stopping without calling `check_setup` in a loop. A stale
+/// image is rebuilt automatically unless that's turned off.
+pub(crate) fn start_monitor(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut rebuilt_for = None;
+        loop {
+            if poll(&app).container_image_stale {
+                rebuild_stale_image(&app, &mut rebuilt_for);
+            }
+            std::thread::sleep(MONITOR_INTERVAL);
+        }
+    });
+}
@@ -0,0 +1,86 @@
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{backend, power, settings, window_state, windows};
+
+/// Extra time after the shutdown timeout before quitting regardless, for the
+/// SIGKILL and `docker stop`'s own kill to land.
+const EXIT_GRACE: Duration = Duration::from_secs(3);
+
+#[derive(Default, Clone, Copy, PartialEq)]
+enum Phase {
+    #[default]
+    Running,
+    CleaningUp,
+    Done,
+}
+
+/// Quitting goes through cleanup first: the first exit request is held back
+/// while the backend and agent containers are stopped, then exit is asked
+/// for again and goes through.
+#[derive(Default)]
+pub(crate) struct Shutdown {
+    phase: Mutex<Phase>,
+}
+
+/// Handle an exit request. Returns whether the app may exit now; if not,
+/// cleanup is underway and will exit the app when it finishes or times out.
+pub(crate) fn on_exit_requested(app: &AppHandle) -> bool {
+    {
+        let shutdown = app.state::<Shutdown>();
+        let mut phase = shutdown.phase.lock().unwrap();
+        match *phase {
+            Phase::Done => return true,
+            Phase::CleaningUp => return false,
+            Phase::Running => *phase = Phase::CleaningUp,
+        }
+    }
+
+    app.state::<window_state::WindowStateTracker>()
+        .flush(&crate::user_data_dir(app));
+    for window in app.webview_windows().values() {
+        let _ = window.hide();
+    }
+    if let Err(e) = windows::open(app, "shutdown") {
+        eprintln!("{}", e);
+    }
+
+    let timeout =
+        Duration::from_secs(settings::load(&crate::user_data_dir(app)).shutdown_timeout_secs);
+    let (done_tx, done_rx) = mpsc::channel();
+    let cleanup_app = app.clone();
+    std::thread::spawn(move || {
+        cleanup(&cleanup_app, timeout);
+        let _ = done_tx.send(());
+    });
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if done_rx.recv_timeout(timeout + EXIT_GRACE).is_err() {
+            eprintln!("Shutdown cleanup timed out; quitting anyway");
+        }
+        *app.state::<Shutdown>().phase.lock().unwrap() = Phase::Done;
+        app.exit(0);
+    });
+    false
+}
+
+fn cleanup(app: &AppHandle, timeout: Duration) {
+    let started = Instant::now();
+    let _ = app.emit("shutdown-progress", "stoppingBackend");
+    app.state::<backend::BackendManager>().stop();
+    crate::lan::stop_advertising();
+    app.state::<power::KeepAwake>().release();
+
+    // A remote backend's containers run on its own machine
+    if crate::remote_backend().is_none() {
+        let _ = app.emit("shutdown-progress", "stoppingContainers");
+        crate::stop_containers(timeout.saturating_sub(started.elapsed()));
+    }
+    crate::heartbeat::clear(&crate::user_data_dir(app));
+    crate::control::stop_server(&crate::user_data_dir(app));
+    let _ = app.emit("shutdown-progress", "done");
+}
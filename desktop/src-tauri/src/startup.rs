@@ -0,0 +1,114 @@
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::backend;
+
+/// Give up timing the backend if it isn't ready by then.
+const BACKEND_READY_TIMEOUT: Duration = Duration::from_secs(180);
+
+static LAUNCHED_AT: OnceLock<Instant> = OnceLock::new();
+static PHASES: Mutex<Vec<StartupPhase>> = Mutex::new(Vec::new());
+static STAGE: Mutex<StartupStage> = Mutex::new(StartupStage::ResolvingPath);
+
+/// What launch is waiting on, shown in the window until the backend is up.
+#[derive(Serialize, Clone, Copy, PartialEq, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StartupStage {
+    /// Asking the login shell for PATH to find node and docker.
+    ResolvingPath,
+    /// Stopping a backend or containers a crashed run left behind.
+    CleaningUp,
+    StartingBackend,
+    /// Spawned; waiting for the first health check to pass.
+    WaitingForBackend,
+    Ready,
+}
+
+/// One timed step of this launch.
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StartupPhase {
+    pub name: String,
+    /// Milliseconds from launch to the phase starting.
+    pub start_ms: u64,
+    pub duration_ms: u64,
+    /// Ran on a background thread, overlapping the phases after it.
+    pub background: bool,
+}
+
+pub(crate) fn mark_launch() {
+    LAUNCHED_AT.get_or_init(Instant::now);
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+/// Record a phase that began at `started` and has just finished.
+pub(crate) fn record(name: &str, started: Instant, background: bool) {
+    let launched = *LAUNCHED_AT.get_or_init(|| started);
+    PHASES.lock().unwrap().push(StartupPhase {
+        name: name.to_string(),
+        start_ms: millis(started.saturating_duration_since(launched)),
+        duration_ms: millis(started.elapsed()),
+        background,
+    });
+}
+
+/// Run one step of the startup sequence, timing it.
+pub(crate) fn time<T>(name: &str, step: impl FnOnce() -> T) -> T {
+    let started = Instant::now();
+    let result = step();
+    record(name, started, false);
+    result
+}
+
+/// Move launch on to `stage`, emitting `startup-stage` for the window.
+/// Once ready it stays ready; later restarts have their own events.
+pub(crate) fn set_stage(app: &AppHandle, stage: StartupStage) {
+    {
+        let mut current = STAGE.lock().unwrap();
+        if *current == StartupStage::Ready {
+            return;
+        }
+        *current = stage;
+    }
+    let _ = app.emit("startup-stage", stage);
+}
+
+pub(crate) fn stage() -> StartupStage {
+    *STAGE.lock().unwrap()
+}
+
+/// Time from now until the backend first answers health checks, as the
+/// `backend_ready` phase, and move to the `Ready` stage then. Call before asking the manager to start it.
+pub(crate) fn watch_backend_ready(app: &AppHandle) {
+    let manager = app.state::<backend::BackendManager>();
+    let changes = manager.subscribe();
+    let started = Instant::now();
+    let app = app.clone();
+    std::thread::spawn(move || {
+        while started.elapsed() < BACKEND_READY_TIMEOUT {
+            match changes.recv_timeout(BACKEND_READY_TIMEOUT - started.elapsed()) {
+                Ok(status) if status.ready => {
+                    record("backend_ready", started, true);
+                    set_stage(&app, StartupStage::Ready);
+                    return;
+                }
+                Ok(_) => {}
+                Err(_) => return,
+            }
+        }
+    });
+}
+
+/// Every phase so far, in the order they started.
+pub(crate) fn timings() -> Vec<StartupPhase> {
+    let mut phases = PHASES.lock().unwrap().clone();
+    phases.sort_by_key(|phase| phase.start_ms);
+    phases
+}
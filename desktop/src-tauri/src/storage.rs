@@ -0,0 +1,121 @@
+use std::path::Path;
+
+use serde::Serialize;
+use specta::Type;
+
+use crate::backup::BACKUPS_DIR;
+use crate::image;
+
+/// Bytes used by each part of the user data dir. `groups` excludes the
+/// per-group `logs` folders, which are counted under `logs`.
+#[derive(Serialize, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StorageUsage {
+    pub store: u64,
+    pub data: u64,
+    pub groups: u64,
+    pub logs: u64,
+    pub backups: u64,
+    /// None when Docker isn't running.
+    pub docker: Option<DockerUsage>,
+}
+
+/// Bytes held by NanoClaw's Docker artifacts (`nanoclaw-*` names).
+#[derive(Serialize, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DockerUsage {
+    pub images: u64,
+    /// Writable layers only; the image layers are counted under `images`.
+    pub containers: u64,
+    pub volumes: u64,
+}
+
+/// Apparent size of everything under `path`, not following symlinks.
+pub(crate) fn dir_size(path: &Path) -> u64 {
+    let Ok(meta) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| dir_size(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Parse Docker's human-readable sizes ("1.21GB", "12.3kB", "0B"). Docker
+/// formats these with decimal (1000-based) units.
+fn parse_docker_size(text: &str) -> u64 {
+    let text = text.split_whitespace().next().unwrap_or("");
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let Ok(number) = number.parse::<f64>() else {
+        return 0;
+    };
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "" | "B" => 1.0,
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        _ => return 0,
+    };
+    (number * multiplier) as u64
+}
+
+fn is_nanoclaw_name(name: &str) -> bool {
+    let name = name.split(':').next().unwrap_or(name);
+    name.rsplit('/')
+        .next()
+        .is_some_and(|n| n.starts_with("nanoclaw-") || n.starts_with("nanoclaw_"))
+}
+
+fn docker_usage() -> Option<DockerUsage> {
+    let output = image::docker_output(&["system", "df", "-v", "--format", "{{json .}}"]).ok()?;
+    let report: serde_json::Value = serde_json::from_str(&output).ok()?;
+    let sum = |section: &str, name_key: &str| -> u64 {
+        report
+            .get(section)
+            .and_then(|v| v.as_array())
+            .map(|items| {
+                items
+                    .iter()
+                    .filter(|item| {
+                        item.get(name_key)
+                            .and_then(|v| v.as_str())
+                            .is_some_and(is_nanoclaw_name)
+                    })
+                    .filter_map(|item| item.get("Size")?.as_str())
+                    .map(parse_docker_size)
+                    .sum()
+            })
+            .unwrap_or(0)
+    };
+    Some(DockerUsage {
+        images: sum("Images", "Repository"),
+        containers: sum("Containers", "Names"),
+        volumes: sum("Volumes", "Name"),
+    })
+}
+
+pub(crate) fn usage(data_dir: &Path) -> StorageUsage {
+    let groups_dir = data_dir.join("groups");
+    let logs = std::fs::read_dir(&groups_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|group| dir_size(&group.path().join("logs")))
+                .sum()
+        })
+        .unwrap_or(0);
+    StorageUsage {
+        store: dir_size(&data_dir.join("store")),
+        data: dir_size(&data_dir.join("data")),
+        groups: dir_size(&groups_dir).saturating_sub(logs),
+        logs,
+        backups: dir_size(&data_dir.join(BACKUPS_DIR)),
+        docker: docker_usage(),
+    }
+}
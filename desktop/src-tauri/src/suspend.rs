@@ -0,0 +1,111 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::{backend, logs, scheduled_restart, settings};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Tracks window activity and whether the backend was stopped for idling.
+#[derive(Default)]
+pub(crate) struct Suspender {
+    last_activity: Mutex<Option<Instant>>,
+    suspended: AtomicBool,
+}
+
+/// Note that the user is looking at the app.
+pub(crate) fn touch(app: &AppHandle) {
+    *app.state::<Suspender>().last_activity.lock().unwrap() = Some(Instant::now());
+}
+
+/// Bring back a backend stopped for idling; a no-op otherwise. Called when
+/// a window is shown, so the user never sees it gone.
+pub(crate) fn resume(app: &AppHandle) {
+    touch(app);
+    if !app
+        .state::<Suspender>()
+        .suspended
+        .swap(false, Ordering::SeqCst)
+    {
+        return;
+    }
+    logs::record(
+        app,
+        "stderr",
+        "[suspend] Window shown; starting the backend".to_string(),
+    );
+    let _ = app.emit("backend-resumed", ());
+    let manager = app.state::<backend::BackendManager>().inner().clone();
+    std::thread::spawn(move || {
+        if let Err(e) = manager.start() {
+            eprintln!("Failed to resume the backend: {}", e);
+        }
+    });
+}
+
+/// Scheduled tasks would never fire with the backend stopped.
+fn has_scheduled_tasks() -> bool {
+    crate::backend_get_json("/api/activity")
+        .ok()
+        .and_then(|activity| activity.get("scheduledTasks")?.as_u64())
+        != Some(0)
+}
+
+fn tick(app: &AppHandle) {
+    let prefs = settings::load(&crate::user_data_dir(app)).auto_suspend;
+    let suspender = app.state::<Suspender>();
+    let manager = app.state::<backend::BackendManager>();
+    if suspender.suspended.load(Ordering::SeqCst) {
+        // Started some other way (tray, CLI); idle from now on
+        if manager.status().running {
+            suspender.suspended.store(false, Ordering::SeqCst);
+            touch(app);
+        }
+        return;
+    }
+    if !prefs.enabled {
+        return;
+    }
+    // An open window is activity, whether or not it has focus
+    if crate::is_main_window_visible(app) {
+        touch(app);
+        return;
+    }
+    let idle_for = suspender
+        .last_activity
+        .lock()
+        .unwrap()
+        .get_or_insert_with(Instant::now)
+        .elapsed();
+    if idle_for < Duration::from_secs(prefs.idle_minutes.max(1) * 60) {
+        return;
+    }
+    // Only a backend we own can be stopped and brought back
+    if !manager.status().running || !scheduled_restart::backend_idle() || has_scheduled_tasks() {
+        return;
+    }
+
+    suspender.suspended.store(true, Ordering::SeqCst);
+    logs::record(
+        app,
+        "stderr",
+        format!(
+            "[suspend] No activity for {} min; stopping the backend",
+            idle_for.as_secs() / 60
+        ),
+    );
+    let _ = app.emit("backend-suspended", ());
+    manager.stop();
+}
+
+/// Stop the backend and its containers after the configured idle period with
+/// no window open and no scheduled tasks, to free memory for users who open
+/// NanoClaw only now and then. Showing a window starts it again.
+pub(crate) fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(CHECK_INTERVAL);
+        tick(&app);
+    });
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager};
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum TaskStatus {
+    Running,
+    Cancelling,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Snapshot of a long-running operation, sent with every `task-progress` event.
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TaskInfo {
+    pub id: u64,
+    pub kind: &'static str,
+    pub status: TaskStatus,
+    /// 0.0–1.0, None while the total amount of work is unknown
+    pub progress: Option<f64>,
+    pub message: Option<String>,
+    pub cancellable: bool,
+}
+
+struct TaskEntry {
+    info: TaskInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+/// Registry of running background tasks (image builds, updates, backups, ...).
+/// Only one task per kind runs at a time; a duplicate request is rejected
+/// instead of queuing behind the first.
+#[derive(Default)]
+pub(crate) struct TaskManager {
+    next_id: AtomicU64,
+    tasks: Mutex<BTreeMap<u64, TaskEntry>>,
+}
+
+impl TaskManager {
+    pub fn list(&self) -> Vec<TaskInfo> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    /// Ask a task to stop. The work notices at its next check and finishes as cancelled.
+    pub fn cancel(&self, app: &AppHandle, id: u64) -> Result<(), String> {
+        let info = {
+            let mut tasks = self.tasks.lock().unwrap();
+            let entry = tasks
+                .get_mut(&id)
+                .ok_or_else(|| format!("No running task with id {}", id))?;
+            if !entry.info.cancellable {
+                return Err(format!("{} can't be cancelled", entry.info.kind));
+            }
+            entry.cancel.store(true, Ordering::SeqCst);
+            entry.info.status = TaskStatus::Cancelling;
+            entry.info.clone()
+        };
+        let _ = app.emit("task-progress", info);
+        Ok(())
+    }
+}
+
+/// Registered task, reporting progress until `finish` (or drop) removes it.
+pub(crate) struct TaskHandle {
+    app: AppHandle,
+    id: u64,
+    cancel: Arc<AtomicBool>,
+    finished: bool,
+}
+
+/// Register a task of `kind`, failing if one is already running.
+pub(crate) fn start(
+    app: &AppHandle,
+    kind: &'static str,
+    cancellable: bool,
+) -> Result<TaskHandle, String> {
+    let manager = app.state::<TaskManager>();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let info = {
+        let mut tasks = manager.tasks.lock().unwrap();
+        if tasks.values().any(|entry| entry.info.kind == kind) {
+            return Err(format!("{} is already in progress", kind));
+        }
+        let id = manager.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let info = TaskInfo {
+            id,
+            kind,
+            status: TaskStatus::Running,
+            progress: None,
+            message: None,
+            cancellable,
+        };
+        tasks.insert(
+            id,
+            TaskEntry {
+                info: info.clone(),
+                cancel: Arc::clone(&cancel),
+            },
+        );
+        info
+    };
+    let _ = app.emit("task-progress", info.clone());
+    Ok(TaskHandle {
+        app: app.clone(),
+        id: info.id,
+        cancel,
+        finished: false,
+    })
+}
+
+impl TaskHandle {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::SeqCst)
+    }
+
+    pub fn progress(&self, fraction: Option<f64>, message: impl Into<String>) {
+        let manager = self.app.state::<TaskManager>();
+        let info = {
+            let mut tasks = manager.tasks.lock().unwrap();
+            let Some(entry) = tasks.get_mut(&self.id) else {
+                return;
+            };
+            if fraction.is_some() {
+                entry.info.progress = fraction.map(|f| f.clamp(0.0, 1.0));
+            }
+            entry.info.message = Some(message.into());
+            entry.info.clone()
+        };
+        let _ = self.app.emit("task-progress", info);
+    }
+
+    /// Record the outcome, emit the final event, and drop the task from the registry.
+    pub fn finish<T, E: std::fmt::Display>(mut self, result: &Result<T, E>) {
+        let status = match result {
+            Ok(_) => TaskStatus::Completed,
+            Err(_) if self.is_cancelled() => TaskStatus::Cancelled,
+            Err(_) => TaskStatus::Failed,
+        };
+        self.complete(status, result.as_ref().err().map(|e| e.to_string()));
+    }
+
+    fn complete(&mut self, status: TaskStatus, message: Option<String>) {
+        self.finished = true;
+        let manager = self.app.state::<TaskManager>();
+        let entry = manager.tasks.lock().unwrap().remove(&self.id);
+        if let Some(mut entry) = entry {
+            entry.info.status = status;
+            if status == TaskStatus::Completed {
+                entry.info.progress = Some(1.0);
+            }
+            if message.is_some() {
+                entry.info.message = message;
+            }
+            let _ = self.app.emit("task-progress", entry.info);
+        }
+    }
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.complete(
+                TaskStatus::Failed,
+                Some("Task ended unexpectedly".to_string()),
+            );
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::{http, secrets, settings};
+
+/// Collection endpoint. Telemetry is never sent from builds without it.
+const TELEMETRY_URL: Option<&str> = option_env!("NANOCLAW_TELEMETRY_URL");
+const BUFFER_FILE: &str = "telemetry.jsonl";
+/// Oldest events are dropped past this, e.g. while offline for weeks.
+const MAX_BUFFERED_EVENTS: usize = 1000;
+const UPLOAD_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const UPLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+static LAUNCHED_AT: OnceLock<Instant> = OnceLock::new();
+static STARTUP_RECORDED: AtomicBool = AtomicBool::new(false);
+
+/// Serializes buffer appends with uploads.
+#[derive(Default)]
+pub(crate) struct Telemetry {
+    buffer: Mutex<()>,
+}
+
+/// One anonymized event. Properties never include paths, chat content,
+/// or anything from .env.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Event<'a> {
+    name: &'a str,
+    props: serde_json::Value,
+    /// Unix epoch milliseconds
+    timestamp: u64,
+}
+
+/// Note the process start, for the startup-duration event.
+pub(crate) fn mark_launch() {
+    LAUNCHED_AT.get_or_init(Instant::now);
+}
+
+fn enabled(app: &AppHandle) -> bool {
+    TELEMETRY_URL.is_some() && settings::load(&crate::user_data_dir(app)).telemetry.enabled
+}
+
+/// Buffer an event locally if the user opted in; a no-op otherwise.
+pub(crate) fn record(app: &AppHandle, name: &str, props: serde_json::Value) {
+    if !enabled(app) {
+        return;
+    }
+    let event = Event {
+        name,
+        props,
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+    };
+    let Ok(line) = serde_json::to_string(&event) else {
+        return;
+    };
+    let path = crate::user_data_dir(app).join(BUFFER_FILE);
+    let state = app.state::<Telemetry>();
+    let _guard = state.buffer.lock().unwrap();
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    let mut lines: Vec<&str> = existing.lines().filter(|l| !l.is_empty()).collect();
+    lines.push(&line);
+    let keep = &lines[lines.len().saturating_sub(MAX_BUFFERED_EVENTS)..];
+    if let Err(e) = std::fs::write(&path, keep.join("\n") + "\n") {
+        eprintln!("Failed to buffer telemetry event: {}", e);
+    }
+}
+
+/// Time from launch until the backend first became ready, once per run.
+pub(crate) fn record_startup(app: &AppHandle) {
+    if STARTUP_RECORDED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    if let Some(launched) = LAUNCHED_AT.get() {
+        record(
+            app,
+            "startup",
+            serde_json::json!({ "durationMs": launched.elapsed().as_millis() as u64 }),
+        );
+    }
+}
+
+/// Drop everything buffered, e.g. when the user opts out.
+pub(crate) fn clear(app: &AppHandle) {
+    let state = app.state::<Telemetry>();
+    let _guard = state.buffer.lock().unwrap();
+    let _ = std::fs::remove_file(crate::user_data_dir(app).join(BUFFER_FILE));
+}
+
+fn upload(app: &AppHandle, url: &str, data_dir: &Path) -> Result<(), String> {
+    let state = app.state::<Telemetry>();
+    let _guard = state.buffer.lock().unwrap();
+    let path = data_dir.join(BUFFER_FILE);
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let events: Vec<serde_json::Value> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let install_id = settings::load(data_dir)
+        .telemetry
+        .install_id
+        .ok_or_else(|| "No telemetry install id".to_string())?;
+    let body = serde_json::json!({
+        "installId": install_id,
+        "appVersion": app.package_info().version.to_string(),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "events": events,
+    });
+    http::curl_post_json(url, &body.to_string(), UPLOAD_TIMEOUT)?;
+    std::fs::remove_file(&path).map_err(|e| format!("Failed to clear telemetry buffer: {}", e))
+}
+
+/// Send buffered events every UPLOAD_INTERVAL while telemetry is on.
+pub(crate) fn start_uploader(app: AppHandle) {
+    let Some(url) = TELEMETRY_URL else {
+        return;
+    };
+    std::thread::spawn(move || loop {
+        std::thread::sleep(UPLOAD_INTERVAL);
+        if !enabled(&app) {
+            continue;
+        }
+        if let Err(e) = upload(&app, url, &crate::user_data_dir(&app)) {
+            eprintln!("Telemetry upload failed: {}", e);
+        }
+    });
+}
+
+/// A random id that identifies this install, not the user. Regenerated
+/// every time telemetry is switched back on.
+pub(crate) fn new_install_id() -> Result<String, String> {
+    secrets::generate_token().map(|token| token[..32].to_string())
+}
@@ -0,0 +1,137 @@
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use nix::sys::signal::Signal;
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter};
+
+use crate::settings::TunnelProvider;
+use crate::{logs, path_env, process, settings};
+
+/// The Tailscale CLI inside the Mac app, for when it isn't on PATH.
+const TAILSCALE_APP_CLI: &str = "/Applications/Tailscale.app/Contents/MacOS/Tailscale";
+/// How long the client gets to exit after SIGTERM; `tailscale serve` takes
+/// its config down on the way out.
+const STOP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The URL the running client printed; cleared when it exits.
+static PUBLIC_URL: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TunnelStatus {
+    pub provider: TunnelProvider,
+    /// None until the client has printed it, or when no tunnel runs.
+    pub public_url: Option<String>,
+}
+
+fn program(provider: TunnelProvider) -> Option<PathBuf> {
+    match provider {
+        TunnelProvider::Off => None,
+        TunnelProvider::Cloudflared => path_env::which("cloudflared"),
+        TunnelProvider::Tailscale => path_env::which("tailscale").or_else(|| {
+            let app_cli = PathBuf::from(TAILSCALE_APP_CLI);
+            app_cli.is_file().then_some(app_cli)
+        }),
+    }
+}
+
+/// The tunnel's URL if `line` announces it: cloudflared prints the quick
+/// tunnel's `trycloudflare.com` address in a banner, `tailscale serve` the
+/// machine's `ts.net` one.
+fn parse_url(provider: TunnelProvider, line: &str) -> Option<String> {
+    let domain = match provider {
+        TunnelProvider::Off => return None,
+        TunnelProvider::Cloudflared => ".trycloudflare.com",
+        TunnelProvider::Tailscale => ".ts.net",
+    };
+    line.split(|c: char| c.is_whitespace() || c == '|')
+        .filter(|word| word.starts_with("https://"))
+        .map(|word| word.trim_end_matches('/'))
+        .find(|url| url.ends_with(domain) || url.contains(&format!("{}:", domain)))
+        .map(str::to_string)
+}
+
+/// Start the configured tunnel client towards the backend on `port`. None
+/// when tunnels are off. The backend has to require a token first: whoever
+/// has the URL reaches it.
+pub(crate) fn launch(app: &AppHandle, port: u16) -> Result<Option<Child>, String> {
+    let provider = settings::load(&crate::user_data_dir(app)).tunnel;
+    if provider == TunnelProvider::Off {
+        return Ok(None);
+    }
+    if crate::backend_auth_token().is_none() {
+        return Err("A tunnel needs the backend to require an API token".to_string());
+    }
+    if crate::backend_socket_path().is_some() {
+        return Err("A tunnel needs the backend on a TCP port, not a socket".to_string());
+    }
+    let program = program(provider).ok_or_else(|| match provider {
+        TunnelProvider::Tailscale => "Tailscale isn't installed".to_string(),
+        _ => "cloudflared isn't installed; try `brew install cloudflared`".to_string(),
+    })?;
+
+    let target = format!("http://127.0.0.1:{}", port);
+    let mut cmd = Command::new(&program);
+    match provider {
+        TunnelProvider::Cloudflared => cmd.args(["tunnel", "--no-autoupdate", "--url", &target]),
+        _ => cmd.args(["serve", &target]),
+    };
+    let mut child = process::runner()
+        .spawn(
+            cmd.stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+        )
+        .map_err(|e| format!("Failed to start {}: {}", program.display(), e))?;
+    eprintln!("Started {} for {}", program.display(), target);
+
+    if let Some(stdout) = child.stdout.take() {
+        forward_output(app, provider, stdout);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_output(app, provider, stderr);
+    }
+    Ok(Some(child))
+}
+
+/// Log the client's output and pick the tunnel URL out of it.
+fn forward_output(app: &AppHandle, provider: TunnelProvider, output: impl Read + Send + 'static) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for line in BufReader::new(output).lines().map_while(Result::ok) {
+            if let Some(url) = parse_url(provider, &line) {
+                let mut current = PUBLIC_URL.lock().unwrap();
+                if current.as_deref() != Some(url.as_str()) {
+                    *current = Some(url.clone());
+                    let _ = app.emit("tunnel-url", &url);
+                }
+            }
+            logs::record(&app, "stderr", format!("[tunnel] {}", line));
+        }
+    });
+}
+
+/// Stop the client, gracefully if it lets us.
+pub(crate) fn stop(child: &mut Child) {
+    let _ = process::runner().signal(child.id(), Signal::SIGTERM);
+    let started = Instant::now();
+    while matches!(child.try_wait(), Ok(None)) && started.elapsed() < STOP_TIMEOUT {
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    forget_url();
+}
+
+pub(crate) fn forget_url() {
+    *PUBLIC_URL.lock().unwrap() = None;
+}
+
+pub(crate) fn public_url() -> Option<String> {
+    PUBLIC_URL.lock().unwrap().clone()
+}
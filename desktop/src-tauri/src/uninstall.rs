@@ -0,0 +1,201 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::{backend, cli_install, i18n, image, lan, process, secrets};
+
+/// launchd jobs NanoClaw (or its setup scripts) installed are labelled so.
+const LAUNCH_AGENT_PREFIX: &str = "com.nanoclaw";
+
+/// Everything `run` removed, and what it couldn't.
+#[derive(Serialize, Clone, Default, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CleanupReport {
+    pub containers: Vec<String>,
+    pub images: Vec<String>,
+    /// Plists taken out of ~/Library/LaunchAgents, their jobs unloaded.
+    pub launch_agents: Vec<PathBuf>,
+    /// The `nanoclaw` command-line launcher.
+    pub cli_launcher: Option<PathBuf>,
+    /// Tokens deleted from the Keychain.
+    pub keychain_items: usize,
+    /// The data dir and the app's own config and cache dirs.
+    pub directories: Vec<PathBuf>,
+    /// Steps that failed; the others went ahead regardless.
+    pub errors: Vec<String>,
+}
+
+impl CleanupReport {
+    /// A few lines for the closing dialog.
+    fn summary(&self) -> String {
+        let mut lines = vec![
+            format!("Containers: {}", self.containers.len()),
+            format!("Images: {}", self.images.len()),
+            format!("Launch agents: {}", self.launch_agents.len()),
+            format!("Keychain items: {}", self.keychain_items),
+        ];
+        if let Some(launcher) = &self.cli_launcher {
+            lines.push(format!("Command-line launcher: {}", launcher.display()));
+        }
+        lines.extend(self.directories.iter().map(|dir| dir.display().to_string()));
+        lines.extend(self.errors.iter().map(|e| format!("Not removed: {}", e)));
+        lines.join("\n")
+    }
+}
+
+/// Unload and delete `com.nanoclaw*` plists from ~/Library/LaunchAgents.
+fn remove_launch_agents(report: &mut CleanupReport) {
+    let Some(dir) =
+        std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/LaunchAgents"))
+    else {
+        return;
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with(LAUNCH_AGENT_PREFIX) || !name.ends_with(".plist") {
+            continue;
+        }
+        let path = entry.path();
+        // Not loaded is fine; the file goes either way
+        let _ = process::runner().output(
+            Command::new("launchctl")
+                .arg("unload")
+                .arg(&path)
+                .stdin(Stdio::null()),
+        );
+        match std::fs::remove_file(&path) {
+            Ok(()) => report.launch_agents.push(path),
+            Err(e) => report
+                .errors
+                .push(format!("Failed to remove {}: {}", path.display(), e)),
+        }
+    }
+}
+
+/// Where NanoClaw keeps things: the data dir, wherever it was moved, and in
+/// release builds the app's config, data and cache dirs (WebView storage).
+fn directories(app: &AppHandle) -> Vec<PathBuf> {
+    let mut dirs = vec![crate::user_data_dir(app)];
+    if crate::is_release_build() {
+        let path = app.path();
+        dirs.extend(
+            [
+                path.app_config_dir(),
+                path.app_data_dir(),
+                path.app_cache_dir(),
+            ]
+            .into_iter()
+            .flatten(),
+        );
+    }
+    let mut unique: Vec<PathBuf> = Vec::new();
+    for dir in dirs {
+        // A dir inside one already listed goes with it
+        if !unique.iter().any(|kept| dir.starts_with(kept)) {
+            unique.retain(|kept| !kept.starts_with(&dir));
+            unique.push(dir);
+        }
+    }
+    unique
+}
+
+fn remove_dir(dir: &Path, report: &mut CleanupReport) {
+    if !dir.exists() {
+        return;
+    }
+    match std::fs::remove_dir_all(dir) {
+        Ok(()) => report.directories.push(dir.to_path_buf()),
+        Err(e) => report
+            .errors
+            .push(format!("Failed to remove {}: {}", dir.display(), e)),
+    }
+}
+
+/// Stop the backend and everything around it, then remove NanoClaw's
+/// containers, images, launch agents, launcher, Keychain items and data.
+/// Nothing is backed up first. The app can't go on after this; `finish`
+/// quits it.
+pub(crate) fn run(app: &AppHandle) -> Result<CleanupReport, String> {
+    if std::env::var_os("NANOCLAW_DATA_DIR").is_none() && !crate::is_release_build() {
+        // The dev data dir is the repo checkout
+        return Err("Set NANOCLAW_DATA_DIR to clean up a development build".to_string());
+    }
+    let mut report = CleanupReport::default();
+
+    app.state::<backend::BackendManager>().stop();
+    lan::stop_advertising();
+    match image::remove_all() {
+        Ok(removed) => {
+            report.containers = removed.containers;
+            report.images = removed.images;
+        }
+        Err(e) => report.errors.push(e),
+    }
+    remove_launch_agents(&mut report);
+    match cli_install::uninstall() {
+        Ok(launcher) => report.cli_launcher = launcher,
+        Err(e) => report.errors.push(e),
+    }
+    report.keychain_items = secrets::delete_all();
+    for dir in directories(app) {
+        remove_dir(&dir, &mut report);
+    }
+    Ok(report)
+}
+
+/// Ask before removing everything; the data isn't backed up first.
+pub(crate) fn confirm(app: &AppHandle) -> bool {
+    let locale = crate::current_locale(app);
+    let t = |key| i18n::tr(locale, key);
+    app.dialog()
+        .message(t("uninstall.confirm_body"))
+        .title(t("uninstall.confirm_title"))
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancelCustom(
+            t("uninstall.remove").to_string(),
+            t("uninstall.cancel").to_string(),
+        ))
+        .blocking_show()
+}
+
+/// Show what was removed, then quit. Skips the usual shutdown cleanup, which
+/// would write settings back into the data dir that was just removed.
+pub(crate) fn finish(app: &AppHandle, report: &CleanupReport) {
+    let locale = crate::current_locale(app);
+    app.dialog()
+        .message(format!(
+            "{}\n\n{}",
+            i18n::tr(locale, "uninstall.done_body"),
+            report.summary()
+        ))
+        .title(i18n::tr(locale, "uninstall.done_title"))
+        .blocking_show();
+    std::process::exit(0);
+}
+
+/// The tray's "Remove All NanoClaw Data…": confirm, clean up, report, quit.
+pub(crate) fn run_from_tray(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        if !confirm(&app) {
+            return;
+        }
+        match run(&app) {
+            Ok(report) => finish(&app, &report),
+            Err(e) => {
+                eprintln!("{}", e);
+                app.dialog()
+                    .message(e)
+                    .kind(MessageDialogKind::Error)
+                    .blocking_show();
+            }
+        }
+    });
+}
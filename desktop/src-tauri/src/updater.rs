@@ -0,0 +1,119 @@
+use std::sync::Mutex;
+
+use serde::Serialize;
+use specta::Type;
+use tauri::{AppHandle, Emitter, Manager, Url};
+use tauri_plugin_updater::{Update, Updater, UpdaterExt};
+
+use crate::backend::BackendManager;
+use crate::settings::{self, UpdateChannel};
+use crate::tasks::TaskHandle;
+
+fn feed_url(channel: UpdateChannel) -> &'static str {
+    match channel {
+        UpdateChannel::Stable => {
+            "https://github.com/Aixtrade/nanoclaw/releases/latest/download/latest.json"
+        }
+        UpdateChannel::Beta => {
+            "https://github.com/Aixtrade/nanoclaw/releases/download/beta/latest.json"
+        }
+    }
+}
+
+/// Updater pointed at the feed for the configured release channel.
+fn channel_updater(app: &AppHandle) -> Result<Updater, String> {
+    let channel = settings::load(&crate::user_data_dir(app)).update_channel;
+    let endpoint = Url::parse(feed_url(channel)).map_err(|e| format!("Invalid feed URL: {}", e))?;
+    app.updater_builder()
+        .endpoints(vec![endpoint])
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("Updater unavailable: {}", e))
+}
+
+/// Update found by the last check, kept so install doesn't need to re-query the feed.
+#[derive(Default)]
+pub(crate) struct PendingUpdate(Mutex<Option<Update>>);
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct UpdateInfo {
+    version: String,
+    current_version: String,
+    notes: Option<String>,
+    date: Option<String>,
+}
+
+#[derive(Serialize, Clone, Type)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct DownloadProgress {
+    downloaded: u64,
+    total: Option<u64>,
+}
+
+/// Query the signed update feed. Returns None when already up to date.
+pub(crate) async fn check(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    let update = channel_updater(app)?
+        .check()
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+
+    let info = update.as_ref().map(|u| UpdateInfo {
+        version: u.version.clone(),
+        current_version: u.current_version.clone(),
+        notes: u.body.clone(),
+        date: u.date.map(|d| d.to_string()),
+    });
+    *app.state::<PendingUpdate>().0.lock().unwrap() = update;
+    Ok(info)
+}
+
+/// Download the pending update (emitting `update-download-progress`), stop the
+/// backend so nothing holds files in the old bundle, swap the app, and relaunch.
+pub(crate) async fn install(app: &AppHandle, task: &TaskHandle) -> Result<(), String> {
+    let pending = app.state::<PendingUpdate>().0.lock().unwrap().take();
+    let update = match pending {
+        Some(update) => update,
+        None => channel_updater(app)?
+            .check()
+            .await
+            .map_err(|e| format!("Failed to check for updates: {}", e))?
+            .ok_or_else(|| "No update available".to_string())?,
+    };
+
+    let progress_app = app.clone();
+    let mut downloaded: u64 = 0;
+    let bytes = update
+        .download(
+            move |chunk, total| {
+                downloaded += chunk as u64;
+                task.progress(
+                    total.map(|total| downloaded as f64 / total.max(1) as f64),
+                    "Downloading update",
+                );
+                let _ = progress_app.emit(
+                    "update-download-progress",
+                    DownloadProgress { downloaded, total },
+                );
+            },
+            || {},
+        )
+        .await
+        .map_err(|e| format!("Failed to download update: {}", e))?;
+
+    task.progress(Some(1.0), "Installing update");
+    let manager = app.state::<BackendManager>().inner().clone();
+    let stop_manager = manager.clone();
+    tauri::async_runtime::spawn_blocking(move || stop_manager.stop())
+        .await
+        .map_err(|e| format!("Failed to stop backend: {}", e))?;
+
+    if let Err(e) = update.install(bytes) {
+        // Bring the backend back so a failed install doesn't leave the app dead
+        if let Err(e) = manager.start() {
+            eprintln!("{}", e);
+        }
+        return Err(format!("Failed to install update: {}", e));
+    }
+
+    app.restart();
+}
@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Mutex;
+
+use tauri::window::Monitor;
+use tauri::{PhysicalPosition, PhysicalSize, Runtime, WebviewWindow, Window};
+
+use crate::settings::{self, WindowGeometry};
+
+/// Minimum overlap (physical px) between a restored window and a monitor,
+/// so the title bar stays reachable after a display is unplugged or rearranged.
+const MIN_VISIBLE_PX: i64 = 64;
+
+/// Geometry captured from move/resize events, waiting to be flushed to settings.
+/// Move events fire continuously while dragging, so writes are batched.
+#[derive(Default)]
+pub(crate) struct WindowStateTracker {
+    pending: Mutex<BTreeMap<String, WindowGeometry>>,
+}
+
+impl WindowStateTracker {
+    pub fn track<R: Runtime>(&self, window: &Window<R>) {
+        if let Some(geometry) = capture(window) {
+            self.pending
+                .lock()
+                .unwrap()
+                .insert(window.label().to_string(), geometry);
+        }
+    }
+
+    /// Persist any pending geometry. Cheap no-op when nothing moved.
+    pub fn flush(&self, data_dir: &Path) {
+        let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+        if pending.is_empty() {
+            return;
+        }
+        if let Err(e) = settings::update(data_dir, |s| s.window_geometry.extend(pending)) {
+            eprintln!("Failed to save window state: {}", e);
+        }
+    }
+}
+
+fn capture<R: Runtime>(window: &Window<R>) -> Option<WindowGeometry> {
+    if window.is_minimized().unwrap_or(false) || !window.is_visible().unwrap_or(false) {
+        return None;
+    }
+    let position = window.outer_position().ok()?;
+    let size = window.inner_size().ok()?;
+    if size.width == 0 || size.height == 0 {
+        return None;
+    }
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|m| m.name().cloned());
+
+    Some(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized: window.is_maximized().unwrap_or(false),
+        monitor,
+    })
+}
+
+fn overlaps(monitor: &Monitor, geometry: &WindowGeometry) -> bool {
+    let area = monitor.work_area();
+    let (mx, my) = (area.position.x as i64, area.position.y as i64);
+    let (mw, mh) = (area.size.width as i64, area.size.height as i64);
+    let (wx, wy) = (geometry.x as i64, geometry.y as i64);
+    let (ww, wh) = (geometry.width as i64, geometry.height as i64);
+
+    let overlap_w = (wx + ww).min(mx + mw) - wx.max(mx);
+    let overlap_h = (wy + wh).min(my + mh) - wy.max(my);
+    overlap_w >= MIN_VISIBLE_PX && overlap_h >= MIN_VISIBLE_PX
+}
+
+/// Apply saved geometry, preferring the monitor it was saved on. If that
+/// monitor is gone or the window would land off-screen, keep the saved size
+/// (clamped to the primary display) and center it instead.
+pub(crate) fn restore<R: Runtime>(window: &WebviewWindow<R>, geometry: &WindowGeometry) {
+    let monitors = window.available_monitors().unwrap_or_default();
+    let target = monitors
+        .iter()
+        .find(|m| geometry.monitor.is_some() && m.name() == geometry.monitor.as_ref())
+        .filter(|m| overlaps(m, geometry))
+        .or_else(|| monitors.iter().find(|m| overlaps(m, geometry)));
+
+    let primary = window.primary_monitor().ok().flatten();
+    let bounds = target.or(primary.as_ref()).map(|m| *m.work_area());
+
+    let (mut width, mut height) = (geometry.width, geometry.height);
+    if let Some(bounds) = bounds {
+        width = width.min(bounds.size.width);
+        height = height.min(bounds.size.height);
+    }
+    let _ = window.set_size(PhysicalSize::new(width, height));
+
+    if target.is_some() {
+        let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+    } else {
+        let _ = window.center();
+    }
+
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+}
@@ -0,0 +1,105 @@
+use tauri::{AppHandle, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+use crate::settings;
+use crate::window_state;
+use crate::zoom;
+
+/// Secondary windows opened on demand. Unlike "main" (and the quick-chat
+/// popover), these are destroyed on close and recreated on next open.
+struct WindowSpec {
+    label: &'static str,
+    route: &'static str,
+    title: &'static str,
+    width: f64,
+    height: f64,
+}
+
+const WINDOWS: &[WindowSpec] = &[
+    WindowSpec {
+        label: "settings",
+        route: "index.html#/settings",
+        title: "NanoClaw Settings",
+        width: 720.0,
+        height: 560.0,
+    },
+    WindowSpec {
+        label: "logs",
+        route: "index.html#/logs",
+        title: "NanoClaw Logs",
+        width: 900.0,
+        height: 600.0,
+    },
+    WindowSpec {
+        label: "shutdown",
+        route: "index.html#/shutting-down",
+        title: "Quitting NanoClaw",
+        width: 320.0,
+        height: 120.0,
+    },
+];
+
+/// Windows that hide instead of closing, so they keep their state.
+pub(crate) fn hides_on_close(label: &str) -> bool {
+    label == "main" || label == crate::quick_chat::LABEL
+}
+
+/// The main window, created from its tauri.conf.json entry on first use.
+/// It isn't created at launch so headless mode never spawns a webview.
+pub(crate) fn ensure_main(app: &AppHandle) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window("main") {
+        return Ok(window);
+    }
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|w| w.label == "main")
+        .ok_or_else(|| "Main window is missing from the app config".to_string())?;
+    let window = WebviewWindowBuilder::from_config(app, config)
+        .and_then(|builder| builder.build())
+        .map_err(|e| format!("Failed to create main window: {}", e))?;
+
+    // Restore the main window where the user left it last session
+    let prefs = settings::load(&crate::user_data_dir(app));
+    if let Some(geometry) = prefs.window_geometry.get("main") {
+        window_state::restore(&window, geometry);
+    }
+    if prefs.always_on_top {
+        let _ = window.set_always_on_top(true);
+    }
+    zoom::restore(app, &window);
+    Ok(window)
+}
+
+/// Focus the named window, creating it (at its last saved geometry) if needed.
+pub(crate) fn open(app: &AppHandle, name: &str) -> Result<(), String> {
+    let spec = WINDOWS
+        .iter()
+        .find(|w| w.label == name)
+        .ok_or_else(|| format!("Unknown window: {}", name))?;
+
+    if let Some(window) = app.get_webview_window(spec.label) {
+        let _ = window.unminimize();
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    let window = WebviewWindowBuilder::new(app, spec.label, WebviewUrl::App(spec.route.into()))
+        .title(spec.title)
+        .inner_size(spec.width, spec.height)
+        .visible(false)
+        .build()
+        .map_err(|e| format!("Failed to open {} window: {}", spec.label, e))?;
+
+    let prefs = settings::load(&crate::user_data_dir(app));
+    if let Some(geometry) = prefs.window_geometry.get(spec.label) {
+        window_state::restore(&window, geometry);
+    }
+    zoom::restore(app, &window);
+
+    let _ = window.show();
+    let _ = window.set_focus();
+    Ok(())
+}
@@ -0,0 +1,82 @@
+#[cfg(target_os = "macos")]
+use tauri::menu::{MenuItem, MenuItemBuilder};
+#[cfg(target_os = "macos")]
+use tauri::Wry;
+use tauri::{AppHandle, Emitter, Manager, WebviewWindow};
+
+#[cfg(target_os = "macos")]
+use crate::i18n;
+use crate::settings;
+
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 3.0;
+/// What ⌘+ and ⌘- add or take away.
+#[cfg(target_os = "macos")]
+const ZOOM_STEP: f64 = 0.1;
+
+/// `level` within the supported range, to the percent.
+fn clamp(level: f64) -> f64 {
+    (level.clamp(MIN_ZOOM, MAX_ZOOM) * 100.0).round() / 100.0
+}
+
+pub(crate) fn get(app: &AppHandle) -> f64 {
+    clamp(settings::load(&crate::user_data_dir(app)).zoom)
+}
+
+/// Zoom a newly created window to the saved level.
+pub(crate) fn restore(app: &AppHandle, window: &WebviewWindow) {
+    let level = get(app);
+    if level != 1.0 {
+        let _ = window.set_zoom(level);
+    }
+}
+
+/// Zoom every window to `level` and remember it. Returns the level applied.
+pub(crate) fn set(app: &AppHandle, level: f64) -> Result<f64, String> {
+    if !level.is_finite() {
+        return Err(format!("Invalid zoom level: {}", level));
+    }
+    let level = clamp(level);
+    settings::update(&crate::user_data_dir(app), |s| s.zoom = level)?;
+    for window in app.webview_windows().values() {
+        window
+            .set_zoom(level)
+            .map_err(|e| format!("Failed to zoom {}: {}", window.label(), e))?;
+    }
+    let _ = app.emit("zoom-changed", level);
+    Ok(level)
+}
+
+/// Handle the zoom menu items; false for any other item.
+#[cfg(target_os = "macos")]
+pub(crate) fn on_menu_event(app: &AppHandle, id: &str) -> bool {
+    let level = match id {
+        "zoom_in" => get(app) + ZOOM_STEP,
+        "zoom_out" => get(app) - ZOOM_STEP,
+        "zoom_reset" => 1.0,
+        _ => return false,
+    };
+    if let Err(e) = set(app, level) {
+        eprintln!("{}", e);
+    }
+    true
+}
+
+/// Zoom In, Zoom Out and Actual Size with their ⌘ shortcuts, for the View
+/// menu.
+#[cfg(target_os = "macos")]
+pub(crate) fn menu_items(app: &AppHandle) -> tauri::Result<[MenuItem<Wry>; 3]> {
+    let locale = crate::current_locale(app);
+    let t = |key| i18n::tr(locale, key);
+    Ok([
+        MenuItemBuilder::with_id("zoom_in", t("menu.zoom_in"))
+            .accelerator("CmdOrCtrl+=")
+            .build(app)?,
+        MenuItemBuilder::with_id("zoom_out", t("menu.zoom_out"))
+            .accelerator("CmdOrCtrl+-")
+            .build(app)?,
+        MenuItemBuilder::with_id("zoom_reset", t("menu.actual_size"))
+            .accelerator("CmdOrCtrl+0")
+            .build(app)?,
+    ])
+}
@@ -1,126 +1,74 @@
-let apiBase = 'http://127.0.0.1:3000';
-let apiToken: string | null = null;
+/**
+ * Backend API client
+ * Requests go through the shell, which attaches the API token, so the
+ * webview never holds it.
+ */
+import { listen } from '@tauri-apps/api/event';
 
-export interface ApiConfig {
-  baseUrl?: string;
-  authToken?: string | null;
-}
+import { commands } from './bindings';
+import type { ChatStreamEvent, ShellError } from './bindings';
 
-export function configureApi(config: ApiConfig): void {
-  if (config.baseUrl) {
-    apiBase = config.baseUrl;
-  }
-  apiToken = config.authToken ?? null;
+export interface ChatEvent {
+  type: 'message' | 'error' | 'done';
+  data: { text?: string; error?: string; sessionId?: string | null };
 }
 
-function buildHeaders(extra?: Record<string, string>): Record<string, string> {
-  const headers: Record<string, string> = { ...(extra ?? {}) };
-  if (apiToken) {
-    headers.Authorization = `Bearer ${apiToken}`;
-  }
-  return headers;
+// Same as chat_stream::channel in the shell
+function chatChannel(groupId: string): string {
+  return `chat-stream:${groupId.replace(/[^A-Za-z0-9\-/:_]/g, '_')}`;
 }
 
-export interface ChatEvent {
-  type: 'message' | 'error' | 'done';
-  data: { text?: string; error?: string; sessionId?: string | null };
+function errorText(err: unknown): string {
+  if (typeof err === 'string') return err;
+  return (err as Partial<ShellError>)?.message ?? String(err);
 }
 
 export async function* streamChat(
   prompt: string,
   groupId: string,
 ): AsyncGenerator<ChatEvent> {
-  const res = await fetch(`${apiBase}/api/chat`, {
-    method: 'POST',
-    headers: buildHeaders({ 'Content-Type': 'application/json' }),
-    body: JSON.stringify({ prompt, groupId }),
-  });
-
-  if (!res.ok) {
-    const err = await res.json().catch(() => ({}));
-    yield {
-      type: 'error',
-      data: { error: err.error || `HTTP ${res.status}` },
-    };
-    return;
-  }
-
-  const reader = res.body?.getReader();
-  if (!reader) {
-    yield {
-      type: 'error',
-      data: { error: 'No response stream from backend' },
-    };
-    return;
-  }
-
-  const decoder = new TextDecoder();
-  let buffer = '';
-
-  while (true) {
-    const { done, value } = await reader.read();
-    if (done) break;
-
-    buffer += decoder.decode(value, { stream: true });
+  const pending: ChatEvent[] = [];
+  let wake: (() => void) | null = null;
+  const unlisten = await listen<ChatStreamEvent>(
+    chatChannel(groupId),
+    (event) => {
+      pending.push({
+        type: event.payload.event as ChatEvent['type'],
+        data: (event.payload.data ?? {}) as ChatEvent['data'],
+      });
+      wake?.();
+    },
+  );
 
-    // Parse SSE format
-    const parts = buffer.split('\n\n');
-    buffer = parts.pop() || '';
-
-    for (const part of parts) {
-      let eventType = 'message';
-      let data = '';
-
-      for (const line of part.split('\n')) {
-        if (line.startsWith('event: ')) {
-          eventType = line.slice(7).trim();
-        } else if (line.startsWith('data: ')) {
-          data = line.slice(6);
-        }
-      }
-
-      if (!data) continue;
-
-      try {
-        const parsed = JSON.parse(data);
-        yield { type: eventType as ChatEvent['type'], data: parsed };
-      } catch {
-        // skip malformed data
-      }
+  try {
+    try {
+      await commands.startChatStream(groupId, prompt);
+    } catch (err) {
+      yield { type: 'error', data: { error: errorText(err) } };
+      return;
     }
-  }
 
-  buffer += decoder.decode();
-  const tail = buffer.trim();
-  if (!tail) return;
-
-  let tailData = '';
-  let tailType = 'message';
-  for (const line of tail.split('\n')) {
-    if (line.startsWith('event: ')) {
-      tailType = line.slice(7).trim();
-    } else if (line.startsWith('data: ')) {
-      tailData = line.slice(6);
+    // The shell always finishes a stream with "done", even when it drops
+    while (true) {
+      while (pending.length === 0) {
+        await new Promise<void>((resolve) => {
+          wake = resolve;
+        });
+        wake = null;
+      }
+      const event = pending.shift()!;
+      yield event;
+      if (event.type === 'done') return;
     }
-  }
-
-  if (!tailData) return;
-
-  try {
-    const parsed = JSON.parse(tailData);
-    yield { type: tailType as ChatEvent['type'], data: parsed };
-  } catch {
-    // ignore malformed tail
+  } finally {
+    unlisten();
   }
 }
 
 export async function checkHealth(): Promise<boolean> {
   try {
-    const res = await fetch(`${apiBase}/api/health`, {
-      headers: buildHeaders(),
-      signal: AbortSignal.timeout(2000),
-    });
-    return res.ok;
+    const res = await commands.proxyRequest('GET', '/api/health', null, null);
+    return res.status >= 200 && res.status < 300;
   } catch {
     return false;
   }
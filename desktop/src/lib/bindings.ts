@@ -0,0 +1,1322 @@
+// Generated by the desktop shell in debug builds; do not edit.
+
+// This file was generated by [tauri-specta](https://github.com/oscartbeaumont/tauri-specta). Do not edit this file manually.
+
+/** user-defined commands **/
+
+
+export const commands = {
+async getBackendStatus() : Promise<boolean> {
+    return await TAURI_INVOKE("get_backend_status");
+},
+/**
+ * Uptime, restarts and crashes of the backend since the app started.
+ */
+async getBackendStats() : Promise<BackendStats> {
+    return await TAURI_INVOKE("get_backend_stats");
+},
+/**
+ * Open the backend in the default browser, signed in through a one-time
+ * login link when it requires a token.
+ */
+async openInBrowser() : Promise<null> {
+    return await TAURI_INVOKE("open_in_browser");
+},
+async getBackendConfig() : Promise<BackendConfig> {
+    return await TAURI_INVOKE("get_backend_config");
+},
+/**
+ * Forward an API request to the backend with the auth token attached, so
+ * the webview needs neither CORS nor the token itself.
+ */
+async proxyRequest(method: string, path: string, headers: ([string, string])[] | null, body: string | null) : Promise<ProxyResponse> {
+    return await TAURI_INVOKE("proxy_request", { method, path, headers, body });
+},
+/**
+ * Send a chat prompt through the shell; the reply streams as events on
+ * `chat-stream:<groupId>`.
+ */
+async startChatStream(groupId: string, prompt: string) : Promise<null> {
+    return await TAURI_INVOKE("start_chat_stream", { groupId, prompt });
+},
+async stopChatStream(groupId: string) : Promise<void> {
+    await TAURI_INVOKE("stop_chat_stream", { groupId });
+},
+/**
+ * Let the user pick files with the native dialog and copy them into the
+ * group's attachments folder, where its agent can read them.
+ */
+async pickAttachments(groupId: string) : Promise<Attachment[]> {
+    return await TAURI_INVOKE("pick_attachments", { groupId });
+},
+/**
+ * Attach files dropped onto the window, by their staged path.
+ */
+async attachFiles(groupId: string, paths: string[]) : Promise<Attachment[]> {
+    return await TAURI_INVOKE("attach_files", { groupId, paths });
+},
+/**
+ * Save a copy of a data-dir file wherever the user chooses.
+ */
+async saveFileAs(path: string) : Promise<string | null> {
+    return await TAURI_INVOKE("save_file_as", { path });
+},
+/**
+ * Show a file or folder in Finder / the platform file manager.
+ */
+async revealInFileManager(path: string) : Promise<null> {
+    return await TAURI_INVOKE("reveal_in_file_manager", { path });
+},
+/**
+ * Show a conversation's folder (its artifacts and attachments) in the file manager.
+ */
+async revealGroupFolder(groupId: string) : Promise<null> {
+    return await TAURI_INVOKE("reveal_group_folder", { groupId });
+},
+/**
+ * Replace the API token with a fresh one and restart the backend on it, so a
+ * leaked token stops working. The new config is pushed to the frontend.
+ */
+async rotateApiToken() : Promise<BackendConfig> {
+    return await TAURI_INVOKE("rotate_api_token");
+},
+/**
+ * Who is listening on the configured backend port, so the UI can tell the
+ * user exactly what to stop.
+ */
+async diagnosePort() : Promise<PortDiagnosis> {
+    return await TAURI_INVOKE("diagnose_port");
+},
+async restartBackend() : Promise<null> {
+    return await TAURI_INVOKE("restart_backend");
+},
+/**
+ * Send a control message to the backend over its stdin, as one JSON object
+ * with a `command` field, e.g. `{ "command": "dumpState" }`. Only a backend
+ * the shell started can take them.
+ */
+async sendBackendCommand(message: JsonValue) : Promise<null> {
+    return await TAURI_INVOKE("send_backend_command", { message });
+},
+async listTasks() : Promise<TaskInfo[]> {
+    return await TAURI_INVOKE("list_tasks");
+},
+async cancelTask(id: number) : Promise<null> {
+    return await TAURI_INVOKE("cancel_task", { id });
+},
+async getDirs() : Promise<DirConfig> {
+    return await TAURI_INVOKE("get_dirs");
+},
+async getBackendListenConfig() : Promise<ListenConfig> {
+    return await TAURI_INVOKE("get_backend_listen_config");
+},
+/**
+ * Validate and persist a new listen address, then restart the backend on it.
+ */
+async setBackendListenConfig(host: string, port: number) : Promise<ListenConfig> {
+    return await TAURI_INVOKE("set_backend_listen_config", { host, port });
+},
+/**
+ * The remote backend the shell connects to, if any.
+ */
+async getRemoteBackend() : Promise<string | null> {
+    return await TAURI_INVOKE("get_remote_backend");
+},
+/**
+ * Turn dev mode on or off (or change its options) and restart the backend
+ * to apply it. The new config, with the inspector port, is pushed to the
+ * frontend.
+ */
+async setDevMode(devMode: DevMode) : Promise<BackendConfig> {
+    return await TAURI_INVOKE("set_dev_mode", { devMode });
+},
+/**
+ * Extra environment the backend is started with, over the .env values.
+ */
+async getBackendEnv() : Promise<Partial<{ [key in string]: string }>> {
+    return await TAURI_INVOKE("get_backend_env");
+},
+/**
+ * Replace the extra backend environment and restart the backend with it.
+ */
+async setBackendEnv(env: Partial<{ [key in string]: string }>) : Promise<null> {
+    return await TAURI_INVOKE("set_backend_env", { env });
+},
+/**
+ * Sign in to a model provider in the browser and pass the token it issues
+ * to the backend as `provider.envKey`, restarting the backend to apply it.
+ */
+async oauthSignIn(provider: OAuthProvider) : Promise<null> {
+    return await TAURI_INVOKE("oauth_sign_in", { provider });
+},
+/**
+ * Forget a provider's OAuth token. The backend keeps it until it restarts.
+ */
+async oauthSignOut(providerId: string) : Promise<null> {
+    return await TAURI_INVOKE("oauth_sign_out", { providerId });
+},
+/**
+ * Use a model from the local Ollama server: writes the AGNO_* settings to
+ * `.env` so the agent runs fully offline.
+ */
+async configureOllama(model: string) : Promise<null> {
+    return await TAURI_INVOKE("configure_ollama", { model });
+},
+/**
+ * OpenAI-compatible model servers on this machine (LM Studio, llama.cpp,
+ * Ollama, ...), found by probing their usual ports.
+ */
+async discoverLocalLlms() : Promise<LocalServer[]> {
+    return await TAURI_INVOKE("discover_local_llms");
+},
+/**
+ * Use a model from a local server `discover_local_llms` found.
+ */
+async configureLocalLlm(port: number, model: string) : Promise<null> {
+    return await TAURI_INVOKE("configure_local_llm", { port, model });
+},
+/**
+ * Model ids the configured provider offers, for a picker in settings.
+ */
+async listModels() : Promise<ModelList> {
+    return await TAURI_INVOKE("list_models");
+},
+/**
+ * The warm agent container pool: its size and the containers in it.
+ */
+async getWarmPool() : Promise<WarmPoolStatus> {
+    return await TAURI_INVOKE("get_warm_pool");
+},
+/**
+ * Keep `size` agent containers booted so requests skip `docker run` (0 turns
+ * the pool off). The backend runs the pool, so it restarts to apply this.
+ */
+async setWarmPoolSize(size: number) : Promise<null> {
+    return await TAURI_INVOKE("set_warm_pool_size", { size });
+},
+/**
+ * Pass the machine's GPUs to agent containers. Turning it on needs a Docker
+ * that supports it (`gpuAvailable` in the setup status). Restarts the
+ * backend to apply.
+ */
+async setGpuPassthrough(enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_gpu_passthrough", { enabled });
+},
+/**
+ * Every group folder with its containers and, while the backend runs, its
+ * name and chat id.
+ */
+async listGroups() : Promise<GroupInfo[]> {
+    return await TAURI_INVOKE("list_groups");
+},
+/**
+ * Stop one group's containers without touching the others; its next message
+ * starts a fresh one. Returns the containers stopped.
+ */
+async stopGroupContainers(groupId: string) : Promise<string[]> {
+    return await TAURI_INVOKE("stop_group_containers", { groupId });
+},
+/**
+ * A group's most recent container run logs, newest first (5 by default).
+ */
+async getGroupLogs(groupId: string, count: number | null) : Promise<GroupLog[]> {
+    return await TAURI_INVOKE("get_group_logs", { groupId, count });
+},
+/**
+ * Package a group's folder and its store rows into a zip at `dest` (default:
+ * the data dir's exports folder). Returns where it was written.
+ */
+async exportGroup(groupId: string, dest: string | null) : Promise<string> {
+    return await TAURI_INVOKE("export_group", { groupId, dest });
+},
+/**
+ * Export every conversation as Markdown, JSON or HTML into a folder the user
+ * picks, as a task with progress. Returns the folder written, or None if the
+ * dialog is dismissed.
+ */
+async exportConversations(format: ExportFormat) : Promise<string | null> {
+    return await TAURI_INVOKE("export_conversations", { format });
+},
+/**
+ * How long each step of this launch took, in the order they started.
+ */
+async getStartupTimings() : Promise<StartupPhase[]> {
+    return await TAURI_INVOKE("get_startup_timings");
+},
+/**
+ * Where launch is; `startup-stage` events follow changes.
+ */
+async getStartupStage() : Promise<StartupStage> {
+    return await TAURI_INVOKE("get_startup_stage");
+},
+/**
+ * The proxy settings, with what macOS reports and what's in effect.
+ */
+async getProxyStatus() : Promise<ProxyStatus> {
+    return await TAURI_INVOKE("get_proxy_status");
+},
+/**
+ * Save the proxy settings and restart the backend with them.
+ */
+async setProxySettings(proxy: ProxySettings) : Promise<null> {
+    return await TAURI_INVOKE("set_proxy_settings", { proxy });
+},
+/**
+ * The extra CA bundle, if one is configured, with what's in it.
+ */
+async getCaBundle() : Promise<CaBundleInfo | null> {
+    return await TAURI_INVOKE("get_ca_bundle");
+},
+/**
+ * Trust the CA certificates in `path` (a PEM file) on top of the system's,
+ * for the shell's HTTPS requests and the backend's; None goes back to the
+ * system roots. Restarts the backend to pick it up.
+ */
+async setCaBundle(path: string | null) : Promise<CaBundleInfo | null> {
+    return await TAURI_INVOKE("set_ca_bundle", { path });
+},
+/**
+ * Turn LAN mode on or off. On, the backend binds every interface and
+ * requires a freshly generated pairing token (unless .env pins the API
+ * token); off, it goes back to loopback. Returns the pairing info when
+ * turning it on.
+ */
+async setLanMode(enabled: boolean) : Promise<LanPairing | null> {
+    return await TAURI_INVOKE("set_lan_mode", { enabled });
+},
+/**
+ * The URL, token and QR code a phone on the same network pairs with.
+ */
+async getLanPairing() : Promise<LanPairing> {
+    return await TAURI_INVOKE("get_lan_pairing");
+},
+/**
+ * The configured tunnel and the public URL its client reported.
+ */
+async getTunnelStatus() : Promise<TunnelStatus> {
+    return await TAURI_INVOKE("get_tunnel_status");
+},
+/**
+ * Run the backend behind a tunnel client, or stop doing so. A tunnel makes
+ * the backend require an API token; the URL arrives as `tunnel-url` once the
+ * client prints it.
+ */
+async setTunnelProvider(provider: TunnelProvider) : Promise<null> {
+    return await TAURI_INVOKE("set_tunnel_provider", { provider });
+},
+async getScheduledRestart() : Promise<ScheduledRestart> {
+    return await TAURI_INVOKE("get_scheduled_restart");
+},
+/**
+ * Restart the backend daily at `time` (local `HH:MM`) once it's idle.
+ */
+async setScheduledRestart(restart: ScheduledRestart) : Promise<null> {
+    return await TAURI_INVOKE("set_scheduled_restart", { restart });
+},
+async getAutoSuspend() : Promise<AutoSuspend> {
+    return await TAURI_INVOKE("get_auto_suspend");
+},
+async setAutoSuspend(suspend: AutoSuspend) : Promise<null> {
+    return await TAURI_INVOKE("set_auto_suspend", { suspend });
+},
+/**
+ * Build args, `--no-cache` and an extra tag for `build_container_image`.
+ */
+async getImageBuildOptions() : Promise<ImageBuildOptions> {
+    return await TAURI_INVOKE("get_image_build_options");
+},
+async setImageBuildOptions(options: ImageBuildOptions) : Promise<null> {
+    return await TAURI_INVOKE("set_image_build_options", { options });
+},
+/**
+ * How PATH was resolved at startup and where node and docker come from,
+ * for debugging a packaged app that can't find them.
+ */
+async debugPathResolution() : Promise<PathResolution> {
+    return await TAURI_INVOKE("debug_path_resolution");
+},
+/**
+ * Install the `nanoclaw` terminal command (`nanoclaw open`, `logs`,
+ * `restart`, ...) into /usr/local/bin, asking for an administrator password
+ * if needed. Returns where it went.
+ */
+async installCli() : Promise<string> {
+    return await TAURI_INVOKE("install_cli");
+},
+/**
+ * Connect to the backend at `url` (`http://host[:port]`) with `token`
+ * instead of starting one, or go back to the local backend with no URL. The
+ * local backend is stopped or started to match, and the new config is
+ * pushed to the frontend.
+ */
+async setRemoteBackend(url: string | null, token: string | null) : Promise<BackendConfig> {
+    return await TAURI_INVOKE("set_remote_backend", { url, token });
+},
+async checkSetup() : Promise<SetupStatus> {
+    return await TAURI_INVOKE("check_setup");
+},
+/**
+ * Drop cached setup checks and re-probe now.
+ */
+async refreshSetupStatus() : Promise<SetupStatus> {
+    return await TAURI_INVOKE("refresh_setup_status");
+},
+async saveEnvConfig(entries: ([string, string])[]) : Promise<null> {
+    return await TAURI_INVOKE("save_env_config", { entries });
+},
+/**
+ * Earlier versions of `.env`, newest first; one is kept per save.
+ */
+async listEnvBackups() : Promise<EnvBackup[]> {
+    return await TAURI_INVOKE("list_env_backups");
+},
+/**
+ * Put back an earlier `.env` (the latest backup if no name is given). The
+ * current one is backed up first, so this can be undone the same way.
+ */
+async restoreEnvBackup(name: string | null) : Promise<null> {
+    return await TAURI_INVOKE("restore_env_backup", { name });
+},
+async readEnvConfig() : Promise<([string, string])[]> {
+    return await TAURI_INVOKE("read_env_config");
+},
+async buildContainerImage() : Promise<string> {
+    return await TAURI_INVOKE("build_container_image");
+},
+/**
+ * Back up the user's data to `dest` (default: the data dir's backups folder).
+ */
+async createBackup(dest: string | null, password: string | null) : Promise<BackupInfo> {
+    return await TAURI_INVOKE("create_backup", { dest, password });
+},
+/**
+ * Replace the user's data with a backup. The backend is stopped for the swap
+ * and started again afterwards, whether or not the restore succeeded.
+ */
+async restoreBackup(path: string, password: string | null) : Promise<null> {
+    return await TAURI_INVOKE("restore_backup", { path, password });
+},
+async listBackups() : Promise<BackupInfo[]> {
+    return await TAURI_INVOKE("list_backups");
+},
+async getBackupSchedule() : Promise<BackupSchedule> {
+    return await TAURI_INVOKE("get_backup_schedule");
+},
+async setBackupSchedule(schedule: BackupSchedule) : Promise<null> {
+    return await TAURI_INVOKE("set_backup_schedule", { schedule });
+},
+/**
+ * Relocate the data dir to `new_path`: stop the backend, copy and verify the
+ * data, remember the new location, and start the backend from there. The old
+ * folder is left in place; returns its path so the user can remove it.
+ */
+async moveDataDir(newPath: string) : Promise<string> {
+    return await TAURI_INVOKE("move_data_dir", { newPath });
+},
+/**
+ * Wipe the app back to first-run state: stop the backend, back up the data,
+ * remove NanoClaw containers and images, clear the data dir (keeping the
+ * backups folder, where the safety backup lands), and relaunch into onboarding.
+ * A relocated data dir stays where the user put it.
+ */
+async factoryReset() : Promise<null> {
+    return await TAURI_INVOKE("factory_reset");
+},
+/**
+ * Remove everything NanoClaw left on this machine, once the user confirms:
+ * stop the backend, remove its containers, images, launch agents, launcher,
+ * Keychain items and the data dir. Shows what was removed and quits; None
+ * when the user cancelled.
+ */
+async uninstallCleanup() : Promise<CleanupReport | null> {
+    return await TAURI_INVOKE("uninstall_cleanup");
+},
+/**
+ * Disk usage of the data dir and NanoClaw's Docker artifacts, for the storage page.
+ */
+async getStorageUsage() : Promise<StorageUsage> {
+    return await TAURI_INVOKE("get_storage_usage");
+},
+async getResourceUsage() : Promise<ResourceUsage> {
+    return await TAURI_INVOKE("get_resource_usage");
+},
+async getMemoryWatchdog() : Promise<MemoryWatchdog> {
+    return await TAURI_INVOKE("get_memory_watchdog");
+},
+async setMemoryWatchdog(watchdog: MemoryWatchdog) : Promise<null> {
+    return await TAURI_INVOKE("set_memory_watchdog", { watchdog });
+},
+/**
+ * The idle-container reaper: its settings, the containers idle now and the
+ * ones it stopped recently.
+ */
+async getReaperStatus() : Promise<ReaperStatus> {
+    return await TAURI_INVOKE("get_reaper_status");
+},
+async setContainerReaper(reaper: ContainerReaper) : Promise<null> {
+    return await TAURI_INVOKE("set_container_reaper", { reaper });
+},
+/**
+ * Check the configured model API (`AGNO_BASE_URL`) answers, so connectivity
+ * problems can be told apart from agent errors.
+ */
+async checkProviderReachability() : Promise<ProviderReachability> {
+    return await TAURI_INVOKE("check_provider_reachability");
+},
+async getHeadless() : Promise<boolean> {
+    return await TAURI_INVOKE("get_headless");
+},
+/**
+ * Takes effect on the next launch.
+ */
+async setHeadless(enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_headless", { enabled });
+},
+async getTelemetryEnabled() : Promise<boolean> {
+    return await TAURI_INVOKE("get_telemetry_enabled");
+},
+/**
+ * Opting in creates a fresh anonymous install id; opting out forgets it and
+ * deletes any events not yet sent.
+ */
+async setTelemetryEnabled(enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_telemetry_enabled", { enabled });
+},
+/**
+ * Shell crash reports that haven't been uploaded, oldest first.
+ */
+async listCrashReports() : Promise<CrashReport[]> {
+    return await TAURI_INVOKE("list_crash_reports");
+},
+async getCrashUploadConsent() : Promise<boolean> {
+    return await TAURI_INVOKE("get_crash_upload_consent");
+},
+/**
+ * Granting consent also sends the reports collected so far.
+ */
+async setCrashUploadConsent(allowed: boolean) : Promise<number> {
+    return await TAURI_INVOKE("set_crash_upload_consent", { allowed });
+},
+/**
+ * Read a setting by camelCase key, e.g. `closeBehavior` or `notifications.enabled`.
+ */
+async getSetting(key: string) : Promise<JsonValue> {
+    return await TAURI_INVOKE("get_setting", { key });
+},
+/**
+ * Store a setting and emit `settings-changed`. Settings with side effects
+ * must go through their dedicated command.
+ */
+async setSetting(key: string, value: JsonValue) : Promise<null> {
+    return await TAURI_INVOKE("set_setting", { key, value });
+},
+async getActivationPolicy() : Promise<string> {
+    return await TAURI_INVOKE("get_activation_policy");
+},
+/**
+ * Switch between showing the Dock icon ("regular") and menu-bar-only ("accessory").
+ * The choice is persisted and applied again on next launch.
+ */
+async setActivationPolicy(policy: string) : Promise<null> {
+    return await TAURI_INVOKE("set_activation_policy", { policy });
+},
+/**
+ * Set the pending-reply badge on the tray icon. Ignored while the main window
+ * is visible, since the user is already looking at the replies.
+ */
+async setTrayBadge(count: number) : Promise<void> {
+    await TAURI_INVOKE("set_tray_badge", { count });
+},
+async getLocale() : Promise<string> {
+    return await TAURI_INVOKE("get_locale");
+},
+/**
+ * Persist the UI locale ("en", "zh", or null to follow the system) and
+ * re-render shell-owned strings such as the tray menu.
+ */
+async setLocale(locale: string | null) : Promise<null> {
+    return await TAURI_INVOKE("set_locale", { locale });
+},
+/**
+ * Pin the main window above other apps (persisted across launches).
+ */
+async setAlwaysOnTop(enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_always_on_top", { enabled });
+},
+/**
+ * The windows' page zoom; 1.0 is actual size.
+ */
+async getZoom() : Promise<number> {
+    return await TAURI_INVOKE("get_zoom");
+},
+/**
+ * Zoom every window, between 50% and 300%, and keep the level for next
+ * time. Returns the level applied.
+ */
+async setZoom(level: number) : Promise<number> {
+    return await TAURI_INVOKE("set_zoom", { level });
+},
+/**
+ * Hold off idle sleep while an agent run is in progress. The frontend turns
+ * it on for the length of a run; it is also released if the backend exits.
+ */
+async setKeepAwake(enabled: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_keep_awake", { enabled });
+},
+async getPowerState() : Promise<PowerState> {
+    return await TAURI_INVOKE("get_power_state");
+},
+/**
+ * Post a native notification from the frontend under `category`; `route`/`id`
+ * are opened when the user comes back. Returns false if preferences suppressed it.
+ */
+async notify(title: string, body: string, category: NotificationCategory, route: string | null, id: string | null) : Promise<boolean> {
+    return await TAURI_INVOKE("notify", { title, body, category, route, id });
+},
+async getNotificationPrefs() : Promise<NotificationPrefs> {
+    return await TAURI_INVOKE("get_notification_prefs");
+},
+async setNotificationPrefs(prefs: NotificationPrefs) : Promise<null> {
+    return await TAURI_INVOKE("set_notification_prefs", { prefs });
+},
+async getCloseBehavior() : Promise<CloseBehavior> {
+    return await TAURI_INVOKE("get_close_behavior");
+},
+async setCloseBehavior(behavior: CloseBehavior) : Promise<null> {
+    return await TAURI_INVOKE("set_close_behavior", { behavior });
+},
+async getUpdateChannel() : Promise<UpdateChannel> {
+    return await TAURI_INVOKE("get_update_channel");
+},
+async setUpdateChannel(channel: UpdateChannel) : Promise<null> {
+    return await TAURI_INVOKE("set_update_channel", { channel });
+},
+async getAllowLanAccess() : Promise<boolean> {
+    return await TAURI_INVOKE("get_allow_lan_access");
+},
+/**
+ * Opt in or out of non-loopback binds; takes effect on the next backend start.
+ */
+async setAllowLanAccess(allowed: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_allow_lan_access", { allowed });
+},
+/**
+ * Open (or focus) a secondary window such as "settings" or "logs".
+ */
+async openWindow(name: string) : Promise<null> {
+    return await TAURI_INVOKE("open_window", { name });
+},
+async checkForUpdates() : Promise<UpdateInfo | null> {
+    return await TAURI_INVOKE("check_for_updates");
+},
+async installUpdate() : Promise<null> {
+    return await TAURI_INVOKE("install_update");
+},
+async checkBackendUpdate() : Promise<BackendUpdateInfo> {
+    return await TAURI_INVOKE("check_backend_update");
+},
+/**
+ * Download and verify the latest backend bundle into the data dir, then
+ * restart the backend on it. Returns the installed version.
+ */
+async installBackendUpdate() : Promise<string> {
+    return await TAURI_INVOKE("install_backend_update");
+},
+/**
+ * Go back to the backend shipped inside the app bundle.
+ */
+async revertBackendUpdate() : Promise<null> {
+    return await TAURI_INVOKE("revert_backend_update");
+},
+async checkImageUpdate() : Promise<ImageUpdateInfo> {
+    return await TAURI_INVOKE("check_image_update");
+},
+/**
+ * Pull the channel's prebuilt agent image, verified against the pinned
+ * digest and release signature, and use it instead of a local build.
+ * Returns the verified digest, which is also recorded in the settings.
+ */
+async pullContainerImage() : Promise<string> {
+    return await TAURI_INVOKE("pull_container_image");
+},
+async getImageTrust() : Promise<ImageTrust> {
+    return await TAURI_INVOKE("get_image_trust");
+},
+/**
+ * Pin a digest and/or require signatures; the verified digest is kept.
+ */
+async setImageTrust(pinnedDigest: string | null, requireSignature: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_image_trust", { pinnedDigest, requireSignature });
+},
+async getAvailableUpdates() : Promise<AvailableUpdates> {
+    return await TAURI_INVOKE("get_available_updates");
+},
+async getBackendLogs() : Promise<LogLine[]> {
+    return await TAURI_INVOKE("get_backend_logs");
+},
+async clearBackendLogs() : Promise<void> {
+    await TAURI_INVOKE("clear_backend_logs");
+},
+async showQuickChat() : Promise<null> {
+    return await TAURI_INVOKE("show_quick_chat");
+},
+async hideQuickChat() : Promise<void> {
+    await TAURI_INVOKE("hide_quick_chat");
+},
+async resizeQuickChat(width: number, height: number) : Promise<null> {
+    return await TAURI_INVOKE("resize_quick_chat", { width, height });
+}
+}
+
+/** user-defined events **/
+
+
+
+/** user-defined constants **/
+
+
+
+/** user-defined types **/
+
+export type Attachment = { name: string; 
+/**
+ * Path the agent sees inside its container
+ */
+containerPath: string; size: number }
+export type AutoSuspend = { enabled: boolean; 
+/**
+ * Minutes with no window open before the backend is stopped.
+ */
+idleMinutes: number }
+/**
+ * Update status of each layer. A failing check only fills in that layer's
+ * error, so one unreachable feed doesn't hide the others.
+ */
+export type AvailableUpdates = { channel: UpdateChannel; app: UpdateInfo | null; appError: string | null; backend: BackendUpdateInfo | null; backendError: string | null; image: ImageUpdateInfo | null; imageError: string | null }
+export type BackendConfig = { baseUrl: string; 
+/**
+ * Node inspector port, when dev mode runs the backend with `--inspect`.
+ */
+inspectPort: number | null }
+/**
+ * Counters for the tray and `get_backend_stats`, since the app started.
+ */
+export type BackendStats = { 
+/**
+ * How long the backend process the shell owns has been running.
+ */
+uptimeSecs: number | null; 
+/**
+ * Times a backend was spawned again after the first, whether by the
+ * user or after a crash.
+ */
+restarts: number; crashes: number; 
+/**
+ * Milliseconds since the epoch.
+ */
+lastCrashAt: number | null }
+export type BackendStatus = { 
+/**
+ * The shell owns a running backend process.
+ */
+running: boolean; 
+/**
+ * The backend (ours or a reused one) answers health checks.
+ */
+ready: boolean; 
+/**
+ * A deliberate stop is in progress, so the exit isn't a crash.
+ */
+stopping: boolean; pid: number | null }
+export type BackendUpdateInfo = { currentVersion: string; latestVersion: string; available: boolean }
+export type BackupFrequency = "off" | "daily" | "weekly"
+export type BackupInfo = { path: string; size: number; encrypted: boolean }
+export type BackupSchedule = { frequency: BackupFrequency; 
+/**
+ * Automatic backups kept; older ones are deleted after each run.
+ */
+retention: number }
+export type BindWarning = { host: string; lanAccessAllowed: boolean }
+export type CaBundleInfo = { path: string; 
+/**
+ * One per certificate in the file.
+ */
+subjects: string[] }
+export type ChatStreamEvent = { groupId: string; 
+/**
+ * SSE event name: "message", "error" or "done"
+ */
+event: string; data: JsonValue }
+/**
+ * Everything `run` removed, and what it couldn't.
+ */
+export type CleanupReport = { containers: string[]; images: string[]; 
+/**
+ * Plists taken out of ~/Library/LaunchAgents, their jobs unloaded.
+ */
+launchAgents: string[]; 
+/**
+ * The `nanoclaw` command-line launcher.
+ */
+cliLauncher: string | null; 
+/**
+ * Tokens deleted from the Keychain.
+ */
+keychainItems: number; 
+/**
+ * The data dir and the app's own config and cache dirs.
+ */
+directories: string[]; 
+/**
+ * Steps that failed; the others went ahead regardless.
+ */
+errors: string[] }
+export type CloseBehavior = 
+/**
+ * Hide the window and keep running in the menu bar.
+ */
+"hide" | 
+/**
+ * Quit the app (stopping the backend).
+ */
+"quit" | 
+/**
+ * Quit once no other window is left open; otherwise just hide.
+ */
+"quitOnLastWindow"
+/**
+ * Stop agent containers that sit idle too long.
+ */
+export type ContainerReaper = { enabled: boolean; 
+/**
+ * No CPU, network or disk activity for this long counts as forgotten.
+ */
+idleTimeoutSecs: number }
+export type ContainerUsage = { name: string; cpuPercent: number; memoryBytes: number }
+export type CrashReport = { message: string; location: string | null; thread: string | null; backtrace: string; appVersion: string; os: string; 
+/**
+ * Unix epoch milliseconds
+ */
+timestamp: number; context: JsonValue }
+export type DevMode = { 
+/**
+ * Run node with `--inspect` on `inspect_port`.
+ */
+enabled: boolean; inspectPort: number; 
+/**
+ * Restart the backend when `dist/index.js` is rebuilt.
+ */
+watchDist: boolean }
+export type DirConfig = { bundleDir: string; userDataDir: string }
+/**
+ * Bytes held by NanoClaw's Docker artifacts (`nanoclaw-*` names).
+ */
+export type DockerUsage = { images: number; 
+/**
+ * Writable layers only; the image layers are counted under `images`.
+ */
+containers: number; volumes: number }
+export type DownloadProgress = { downloaded: number; total: number | null }
+export type DroppedFiles = { files: StagedFile[]; 
+/**
+ * Drops that couldn't be staged (folders, unreadable files)
+ */
+errors: string[] }
+export type EnvBackup = { name: string; 
+/**
+ * Seconds since the epoch.
+ */
+createdAt: number }
+export type ExportFormat = "markdown" | "json" | "html"
+/**
+ * What happens to a notification that comes in during Focus.
+ */
+export type FocusBehavior = 
+/**
+ * Post it anyway; the Focus mode decides whether it gets through.
+ */
+"show" | 
+/**
+ * Drop it.
+ */
+"suppress" | 
+/**
+ * Count it on the tray badge and post it when Focus ends.
+ */
+"queue"
+export type FocusPrefs = { completions: FocusBehavior; errors: FocusBehavior; backendCrashes: FocusBehavior; updates: FocusBehavior; idleContainers: FocusBehavior }
+export type GroupContainer = { name: string; 
+/**
+ * Docker's state: running, paused, exited, ...
+ */
+state: string; 
+/**
+ * Waiting in the warm pool rather than serving a request.
+ */
+warm: boolean }
+export type GroupInfo = { 
+/**
+ * Folder under `groups/`, which also names its containers.
+ */
+folder: string; 
+/**
+ * Display name and chat id from the backend, when it's running.
+ */
+name: string | null; chatId: string | null; containers: GroupContainer[] }
+export type GroupLog = { file: string; 
+/**
+ * Unix epoch milliseconds.
+ */
+modified: number; 
+/**
+ * The end of the log, if it's long.
+ */
+content: string; truncated: boolean }
+export type IdleContainer = { name: string; idleSecs: number }
+/**
+ * How `build_container_image` runs `docker build`, for users behind proxies
+ * or registry mirrors.
+ */
+export type ImageBuildOptions = { 
+/**
+ * `--build-arg`s: HTTP_PROXY / HTTPS_PROXY / NO_PROXY, PIP_INDEX_URL,
+ * BASE_IMAGE, ...
+ */
+buildArgs: Partial<{ [key in string]: string }>; 
+/**
+ * Build every layer from scratch.
+ */
+noCache: boolean; 
+/**
+ * Extra tag for the built image, e.g. to push it to a private registry.
+ */
+extraTag: string | null }
+/**
+ * Supply-chain checks for prebuilt agent images.
+ */
+export type ImageTrust = { 
+/**
+ * Only ever pull this digest (`sha256:…`), whatever the channel tag says.
+ */
+pinnedDigest: string | null; 
+/**
+ * Refuse images whose signature can't be checked, e.g. without cosign.
+ */
+requireSignature: boolean; 
+/**
+ * Digest of the last image that passed the checks and was put in use.
+ */
+verifiedDigest: string | null }
+export type ImageUpdateInfo = { reference: string; localDigest: string | null; remoteDigest: string; available: boolean }
+export type JsonValue = null | boolean | number | string | JsonValue[] | Partial<{ [key in string]: JsonValue }>
+/**
+ * What a phone needs to reach this NanoClaw, plus the same as a QR code.
+ */
+export type LanPairing = { 
+/**
+ * The backend on this machine's LAN address.
+ */
+url: string; token: string; 
+/**
+ * `nanoclaw://pair?url=…&token=…`, what the QR code holds.
+ */
+pairingUri: string; qrSvg: string }
+export type ListenConfig = { host: string; port: number; socketPath: string | null }
+/**
+ * A model server running on this machine.
+ */
+export type LocalServer = { 
+/**
+ * What usually listens on this port; a guess, since the API is the same.
+ */
+name: string; port: number; 
+/**
+ * Installed models, by the id the API expects.
+ */
+models: string[] }
+export type LogLine = { 
+/**
+ * "stdout" or "stderr"
+ */
+stream: string; line: string; 
+/**
+ * Unix epoch milliseconds
+ */
+timestamp: number }
+export type MemoryWatchdog = { enabled: boolean; 
+/**
+ * Backend RSS limit in megabytes.
+ */
+thresholdMb: number; 
+/**
+ * How long RSS must stay above the limit before a restart.
+ */
+sustainSecs: number }
+/**
+ * The models the configured provider offers.
+ */
+export type ModelList = { provider: ProviderKind; baseUrl: string; models: string[]; 
+/**
+ * `AGNO_MODEL_ID`, if set.
+ */
+configuredModel: string | null; 
+/**
+ * Whether the provider lists `configured_model`.
+ */
+configuredModelFound: boolean | null }
+export type NavigatePayload = { route: string; id: string | null }
+export type NotificationCategory = "completions" | "errors" | "backendCrashes" | "updates" | "idleContainers"
+export type NotificationPrefs = { enabled: boolean; 
+/**
+ * Hold notifications back while a macOS Focus / Do Not Disturb mode is
+ * on, as `focus` says; off posts them regardless. Best effort: Focus is
+ * read from a private store macOS may not let the app see, in which case
+ * notifications go out and macOS applies the Focus itself.
+ */
+respectFocus: boolean; 
+/**
+ * What each category does during Focus.
+ */
+focus: FocusPrefs; 
+/**
+ * Agent replies finishing while the window is hidden.
+ */
+completions: boolean; 
+/**
+ * Agent runs failing while the window is hidden.
+ */
+errors: boolean; 
+/**
+ * The backend exiting without the shell asking it to.
+ */
+backendCrashes: boolean; updates: boolean; 
+/**
+ * The reaper stopping an agent container that sat idle.
+ */
+idleContainers: boolean }
+/**
+ * A provider's OAuth endpoints, and the backend variable its token goes in.
+ */
+export type OAuthProvider = { 
+/**
+ * Short id ("openrouter", ...), used to store the token.
+ */
+id: string; authorizeUrl: string; 
+/**
+ * Where the authorization code is exchanged for the token.
+ */
+tokenUrl: string; clientId: string; scopes?: string[]; 
+/**
+ * Backend variable the token is passed in; one of TOKEN_ENV_KEYS.
+ */
+envKey: string }
+export type OrphanBackend = { pid: number; port: number }
+/**
+ * Payload of `orphan-backends`.
+ */
+export type OrphanScan = { 
+/**
+ * Stale backends that were sent SIGTERM.
+ */
+terminated: OrphanBackend[]; 
+/**
+ * A healthy backend on the configured port, kept and reused.
+ */
+adopted: OrphanBackend | null }
+/**
+ * How PATH was worked out at startup, for `debug_path_resolution`.
+ */
+export type PathResolution = { shell: string; family: ShellFamily; 
+/**
+ * PATH came from the `pathOverride` setting; the shell wasn't asked.
+ */
+overridden: boolean; 
+/**
+ * Everything the shell printed; interactive shells add their own noise.
+ */
+rawOutput: string; 
+/**
+ * Why the shell's answer wasn't used, if it wasn't.
+ */
+shellError: string | null; 
+/**
+ * The shell's PATH was used rather than the fallback directories.
+ */
+fromShell: boolean; path: string; node: string | null; docker: string | null }
+export type PortDiagnosis = { host: string; port: number; owners: PortOwner[] }
+/**
+ * A process listening on the backend port.
+ */
+export type PortOwner = { pid: number; command: string; user: string; isNanoclawBackend: boolean }
+export type PowerSource = "ac" | "battery"
+/**
+ * Payload of `power-source-changed` and `get_power_state`.
+ */
+export type PowerState = { source: PowerSource; 
+/**
+ * On battery with low-power mode on: agent containers are paused and
+ * scheduled work waits for AC power.
+ */
+lowPower: boolean }
+export type ProcessUsage = { pid: number; 
+/**
+ * Percent of one core, as `ps` reports it (can exceed 100).
+ */
+cpuPercent: number; rssBytes: number; uptimeSecs: number }
+export type ProviderKind = "anthropic" | "openAiCompatible"
+export type ProviderReachability = { 
+/**
+ * The URL probed, None when no model endpoint is configured.
+ */
+url: string | null; reachable: boolean; 
+/**
+ * Any HTTP status counts as reachable; auth errors are not connectivity errors.
+ */
+status: number | null; latencyMs: number | null; error: string | null }
+/**
+ * Proxies in the form HTTP_PROXY and friends take.
+ */
+export type ProxyConfig = { httpProxy: string | null; httpsProxy: string | null; noProxy: string | null }
+export type ProxyMode = 
+/**
+ * Whatever macOS network settings say.
+ */
+"system" | 
+/**
+ * The proxies given in the settings.
+ */
+"manual" | 
+/**
+ * Connect directly.
+ */
+"off"
+export type ProxyResponse = { status: number; headers: ([string, string])[]; body: string }
+export type ProxySettings = { mode: ProxyMode; 
+/**
+ * Manual mode only, e.g. `http://proxy.corp:8080`.
+ */
+httpProxy: string | null; httpsProxy: string | null; 
+/**
+ * Comma-separated hosts and domains to reach directly.
+ */
+noProxy: string | null }
+export type ProxyStatus = { settings: ProxySettings; 
+/**
+ * What macOS network settings say, whatever the mode.
+ */
+system: ProxyConfig; 
+/**
+ * What the backend and image builds get.
+ */
+effective: ProxyConfig }
+export type ReapedContainer = { name: string; idleSecs: number; 
+/**
+ * Unix epoch milliseconds.
+ */
+stoppedAt: number }
+export type ReaperStatus = { enabled: boolean; idleTimeoutSecs: number; 
+/**
+ * Containers idle right now, and for how long.
+ */
+idle: IdleContainer[]; 
+/**
+ * Recently stopped containers, newest first.
+ */
+reaped: ReapedContainer[] }
+export type ResourceUsage = { 
+/**
+ * None while the backend isn't running.
+ */
+backend: ProcessUsage | null; containers: ContainerUsage[]; 
+/**
+ * Unix epoch milliseconds of the sample, 0 before the first one.
+ */
+sampledAt: number }
+export type ScheduledBackupResult = { backup: BackupInfo | null; error: string | null }
+export type ScheduledRestart = { enabled: boolean; 
+/**
+ * Local time of day, `HH:MM`.
+ */
+time: string }
+export type SettingChanged = { key: string; value: JsonValue }
+export type SetupStatus = { nodeInstalled: boolean; nodeVersion: string; dockerRunning: boolean; containerImageBuilt: boolean; 
+/**
+ * The image was built from older container-agno sources than the
+ * bundle's and should be rebuilt.
+ */
+containerImageStale: boolean; containerResourcesReady: boolean; 
+/**
+ * Docker can hand GPUs to containers (the NVIDIA runtime is installed),
+ * so GPU passthrough can be turned on.
+ */
+gpuAvailable: boolean; apiKeyConfigured: boolean; 
+/**
+ * Whether the provider lists `AGNO_MODEL_ID`; None when that couldn't
+ * be checked (no model set, provider unreachable).
+ */
+modelAvailable: boolean | null; userDataDir: string; 
+/**
+ * A running Ollama server, offered as a no-key local setup. Not a check:
+ * it's fine for it to be missing.
+ */
+ollama: LocalServer | null }
+/**
+ * What a `ShellError` looks like on the wire, and in the TypeScript bindings.
+ */
+export type ShellError = { code: string; message: string; context: JsonValue; hint: string | null }
+/**
+ * Shells differ in how PATH is read and which flags they take.
+ */
+export type ShellFamily = 
+/**
+ * sh, bash, zsh, ksh, dash.
+ */
+"posix" | 
+/**
+ * PATH is a list; `$PATH` joins it with spaces.
+ */
+"fish" | 
+/**
+ * csh and tcsh can't combine -l with -c.
+ */
+"csh" | 
+/**
+ * Anything else (nushell, xonsh, ...): asked for the exported PATH.
+ */
+"other"
+export type StagedFile = { name: string; 
+/**
+ * Staged copy, to pass to `attach_files`
+ */
+path: string; size: number }
+/**
+ * One timed step of this launch.
+ */
+export type StartupPhase = { name: string; 
+/**
+ * Milliseconds from launch to the phase starting.
+ */
+startMs: number; durationMs: number; 
+/**
+ * Ran on a background thread, overlapping the phases after it.
+ */
+background: boolean }
+/**
+ * What launch is waiting on, shown in the window until the backend is up.
+ */
+export type StartupStage = 
+/**
+ * Asking the login shell for PATH to find node and docker.
+ */
+"resolvingPath" | 
+/**
+ * Stopping a backend or containers a crashed run left behind.
+ */
+"cleaningUp" | "startingBackend" | 
+/**
+ * Spawned; waiting for the first health check to pass.
+ */
+"waitingForBackend" | "ready"
+/**
+ * Bytes used by each part of the user data dir. `groups` excludes the
+ * per-group `logs` folders, which are counted under `logs`.
+ */
+export type StorageUsage = { store: number; data: number; groups: number; logs: number; backups: number; 
+/**
+ * None when Docker isn't running.
+ */
+docker: DockerUsage | null }
+/**
+ * Snapshot of a long-running operation, sent with every `task-progress` event.
+ */
+export type TaskInfo = { id: number; kind: string; status: TaskStatus; 
+/**
+ * 0.0–1.0, None while the total amount of work is unknown
+ */
+progress: number | null; message: string | null; cancellable: boolean }
+export type TaskStatus = "running" | "cancelling" | "completed" | "failed" | "cancelled"
+export type TunnelProvider = "off" | 
+/**
+ * A Cloudflare quick tunnel: a random public `trycloudflare.com` URL.
+ */
+"cloudflared" | 
+/**
+ * `tailscale serve`: only devices on the user's tailnet get in.
+ */
+"tailscale"
+export type TunnelStatus = { provider: TunnelProvider; 
+/**
+ * None until the client has printed it, or when no tunnel runs.
+ */
+publicUrl: string | null }
+export type UpdateChannel = "stable" | "beta"
+export type UpdateInfo = { version: string; currentVersion: string; notes: string | null; date: string | null }
+export type WarmPoolStatus = { 
+/**
+ * Configured size; 0 when the pool is off.
+ */
+size: number; 
+/**
+ * Warm containers waiting for a request right now.
+ */
+containers: string[] }
+
+/** tauri-specta globals **/
+
+import {
+	invoke as TAURI_INVOKE,
+	Channel as TAURI_CHANNEL,
+} from "@tauri-apps/api/core";
+import * as TAURI_API_EVENT from "@tauri-apps/api/event";
+import { type WebviewWindow as __WebviewWindow__ } from "@tauri-apps/api/webviewWindow";
+
+type __EventObj__<T> = {
+	listen: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.listen<T>>;
+	once: (
+		cb: TAURI_API_EVENT.EventCallback<T>,
+	) => ReturnType<typeof TAURI_API_EVENT.once<T>>;
+	emit: null extends T
+		? (payload?: T) => ReturnType<typeof TAURI_API_EVENT.emit>
+		: (payload: T) => ReturnType<typeof TAURI_API_EVENT.emit>;
+};
+
+export type Result<T, E> =
+	| { status: "ok"; data: T }
+	| { status: "error"; error: E };
+
+function __makeEvents__<T extends Record<string, any>>(
+	mappings: Record<keyof T, string>,
+) {
+	return new Proxy(
+		{} as unknown as {
+			[K in keyof T]: __EventObj__<T[K]> & {
+				(handle: __WebviewWindow__): __EventObj__<T[K]>;
+			};
+		},
+		{
+			get: (_, event) => {
+				const name = mappings[event as keyof T];
+
+				return new Proxy((() => {}) as any, {
+					apply: (_, __, [window]: [__WebviewWindow__]) => ({
+						listen: (arg: any) => window.listen(name, arg),
+						once: (arg: any) => window.once(name, arg),
+						emit: (arg: any) => window.emit(name, arg),
+					}),
+					get: (_, command: keyof __EventObj__<any>) => {
+						switch (command) {
+							case "listen":
+								return (arg: any) => TAURI_API_EVENT.listen(name, arg);
+							case "once":
+								return (arg: any) => TAURI_API_EVENT.once(name, arg);
+							case "emit":
+								return (arg: any) => TAURI_API_EVENT.emit(name, arg);
+						}
+					},
+				});
+			},
+		},
+	);
+}
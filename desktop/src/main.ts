@@ -1,7 +1,11 @@
 import { mount } from "svelte";
 import App from "./App.svelte";
+import ShuttingDown from "./lib/ShuttingDown.svelte";
 import "./app.css";
 
-const app = mount(App, { target: document.getElementById("app")! });
+// The shell opens some windows on a route of their own
+const Root = window.location.hash === "#/shutting-down" ? ShuttingDown : App;
+
+const app = mount(Root, { target: document.getElementById("app")! });
 
 export default app;
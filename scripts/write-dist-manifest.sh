@@ -0,0 +1,8 @@
+#!/bin/bash
+# Record SHA-256 checksums of the built backend so the desktop app can tell a
+# damaged or tampered bundle from a backend that merely fails to start.
+set -e
+cd "$(dirname "$0")/../dist"
+find . -type f ! -name 'SHA256SUMS*' -print0 | LC_ALL=C sort -z | xargs -0 shasum -a 256 > SHA256SUMS.tmp
+mv SHA256SUMS.tmp SHA256SUMS
+echo "Backend checksum manifest written."
@@ -0,0 +1,49 @@
+/**
+ * Browser sessions for NanoClaw
+ * The desktop app asks for a one-time login code with its API token and opens
+ * /auth/one-time?code=… in the browser, which trades it for a session cookie,
+ * so the token itself never ends up in a URL or the browser history.
+ */
+import crypto from 'crypto';
+import http from 'node:http';
+
+const CODE_TTL_MS = 60_000;
+const SESSION_COOKIE = 'nanoclaw_session';
+
+// Code -> expiry time; each code works once
+const codes = new Map<string, number>();
+// Sessions last until the backend restarts
+const sessions = new Set<string>();
+
+export function issueLoginCode(): string {
+  const now = Date.now();
+  for (const [code, expiresAt] of codes) {
+    if (expiresAt <= now) codes.delete(code);
+  }
+  const code = crypto.randomBytes(24).toString('hex');
+  codes.set(code, now + CODE_TTL_MS);
+  return code;
+}
+
+/**
+ * Trade a login code for a new session, returned as the Set-Cookie value.
+ * Undefined when the code is unknown, used or expired.
+ */
+export function redeemLoginCode(code: string): string | undefined {
+  const expiresAt = codes.get(code);
+  codes.delete(code);
+  if (expiresAt === undefined || expiresAt <= Date.now()) return undefined;
+  const session = crypto.randomBytes(32).toString('hex');
+  sessions.add(session);
+  return `${SESSION_COOKIE}=${session}; HttpOnly; SameSite=Strict; Path=/`;
+}
+
+export function hasBrowserSession(req: http.IncomingMessage): boolean {
+  const cookies = req.headers.cookie?.split(';') ?? [];
+  return cookies.some((cookie) => {
+    const [name, value] = cookie.trim().split('=');
+    return (
+      name === SESSION_COOKIE && value !== undefined && sessions.has(value)
+    );
+  });
+}
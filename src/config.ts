@@ -3,6 +3,8 @@ import path from 'path';
 export const ASSISTANT_NAME = process.env.ASSISTANT_NAME || 'Andy';
 export const HTTP_PORT = parseInt(process.env.PORT || '3100', 10);
 export const HTTP_HOST = process.env.HTTP_HOST || '127.0.0.1';
+// When set (by the desktop shell), listen on this unix socket instead of HTTP_HOST:HTTP_PORT.
+export const HTTP_SOCKET_PATH = process.env.NANOCLAW_SOCKET_PATH || '';
 export const API_AUTH_TOKEN = process.env.NANOCLAW_API_TOKEN;
 export const MAX_REQUEST_BODY_BYTES = parseInt(
   process.env.MAX_REQUEST_BODY_BYTES || '1048576',
@@ -50,6 +52,21 @@ export const MAX_CONCURRENT_CONTAINERS = Math.max(
   1,
   parseInt(process.env.MAX_CONCURRENT_CONTAINERS || '5', 10) || 5,
 );
+// GPUs for agent containers (`docker run --gpus` value, e.g. "all"); the
+// desktop app sets this when GPU passthrough is on and Docker supports it
+export const CONTAINER_GPUS = (
+  process.env.NANOCLAW_CONTAINER_GPUS || ''
+).trim();
+// Agent containers kept booted for the next request (0 = off); the desktop
+// app sets this from its settings
+export const WARM_POOL_SIZE = Math.max(
+  0,
+  parseInt(process.env.NANOCLAW_WARM_POOL_SIZE || '0', 10) || 0,
+);
+export const WARM_POOL_IDLE_MS = parseInt(
+  process.env.NANOCLAW_WARM_POOL_IDLE_MS || '600000',
+  10,
+); // 10min default — warm containers unused for this long are stopped
 export const TASK_COMPLETION_WEBHOOK_URL = (
   process.env.TASK_COMPLETION_WEBHOOK_URL || ''
 ).trim();
@@ -9,6 +9,7 @@ import path from 'path';
 
 import {
   BUNDLE_ROOT,
+  CONTAINER_GPUS,
   CONTAINER_IMAGE,
   CONTAINER_MAX_OUTPUT_SIZE,
   CONTAINER_TIMEOUT,
@@ -20,6 +21,7 @@ import {
 import { logger } from './logger.js';
 import { validateAdditionalMounts } from './mount-security.js';
 import { RegisteredGroup } from './types.js';
+import { prewarmContainer, takeWarmContainer } from './warm-pool.js';
 
 // Sentinel markers for robust output parsing (must match agent-runner)
 const OUTPUT_START_MARKER = '---NANOCLAW_OUTPUT_START---';
@@ -275,40 +277,55 @@ function buildVolumeMounts(
   const envDir = path.join(DATA_DIR, 'env');
   fs.mkdirSync(envDir, { recursive: true });
   const envFile = path.join(dataRoot, '.env');
-  if (fs.existsSync(envFile)) {
-    const envContent = fs.readFileSync(envFile, 'utf-8');
-    const allowedVars = [
-      'CLAUDE_CODE_OAUTH_TOKEN',
-      'ANTHROPIC_API_KEY',
-      'AGNO_MODEL_ID',
-      'AGNO_API_KEY',
-      'AGNO_BASE_URL',
-      'AGNO_TEMPERATURE',
-      'AGNO_MAX_TOKENS',
-      'PILOT_BRIDGE_PORT',
-    ];
-    const filteredLines = envContent.split('\n').filter((line) => {
-      const trimmed = line.trim();
-      if (!trimmed || trimmed.startsWith('#')) return false;
-      return allowedVars.some((v) => trimmed.startsWith(`${v}=`));
-    });
+  const envContent = fs.existsSync(envFile)
+    ? fs.readFileSync(envFile, 'utf-8')
+    : '';
+  const allowedVars = [
+    'CLAUDE_CODE_OAUTH_TOKEN',
+    'ANTHROPIC_API_KEY',
+    'AGNO_MODEL_ID',
+    'AGNO_API_KEY',
+    'AGNO_BASE_URL',
+    'AGNO_TEMPERATURE',
+    'AGNO_MAX_TOKENS',
+    'PILOT_BRIDGE_PORT',
+    'HTTP_PROXY',
+    'HTTPS_PROXY',
+    'NO_PROXY',
+  ];
+  const filteredLines = envContent.split('\n').filter((line) => {
+    const trimmed = line.trim();
+    if (!trimmed || trimmed.startsWith('#')) return false;
+    return allowedVars.some((v) => trimmed.startsWith(`${v}=`));
+  });
 
-    // Pilot Protocol: start socat bridge and tell container how to connect
-    if (ensurePilotBridge()) {
-      filteredLines.push(`PILOT_BRIDGE_PORT=${PILOT_BRIDGE_PORT}`);
+  // Variables the desktop shell passes in without writing them to .env
+  // (e.g. tokens from an OAuth sign-in)
+  for (const v of allowedVars) {
+    const value = process.env[v];
+    if (
+      value &&
+      !filteredLines.some((line) => line.trim().startsWith(`${v}=`))
+    ) {
+      filteredLines.push(`${v}=${value}`);
     }
+  }
 
-    if (filteredLines.length > 0) {
-      fs.writeFileSync(
-        path.join(envDir, 'env'),
-        filteredLines.join('\n') + '\n',
-      );
-      mounts.push({
-        hostPath: envDir,
-        containerPath: '/workspace/env-dir',
-        readonly: true,
-      });
-    }
+  // Pilot Protocol: start socat bridge and tell container how to connect
+  if (ensurePilotBridge()) {
+    filteredLines.push(`PILOT_BRIDGE_PORT=${PILOT_BRIDGE_PORT}`);
+  }
+
+  if (filteredLines.length > 0) {
+    fs.writeFileSync(
+      path.join(envDir, 'env'),
+      filteredLines.join('\n') + '\n',
+    );
+    mounts.push({
+      hostPath: envDir,
+      containerPath: '/workspace/env-dir',
+      readonly: true,
+    });
   }
 
   // Pilot Protocol: no socket mount needed — containers connect via
@@ -351,6 +368,11 @@ function buildContainerArgs(
   // Pass timezone so container agent uses correct local time for scheduling
   args.push('-e', `TZ=${TIMEZONE}`);
 
+  // Local GPU inference inside the container
+  if (CONTAINER_GPUS) {
+    args.push('--gpus', CONTAINER_GPUS);
+  }
+
   // --mount for readonly, -v for read-write
   for (const mount of mounts) {
     if (mount.readonly) {
@@ -368,6 +390,13 @@ function buildContainerArgs(
   return args;
 }
 
+// A warm container only fits requests with the same mounts and env
+function warmPoolKey(mounts: VolumeMount[]): string {
+  const envFile = path.join(DATA_DIR, 'env', 'env');
+  const env = fs.existsSync(envFile) ? fs.readFileSync(envFile, 'utf-8') : '';
+  return JSON.stringify({ mounts, env });
+}
+
 export async function runContainerAgent(
   group: RegisteredGroup,
   input: ContainerInput,
@@ -381,7 +410,10 @@ export async function runContainerAgent(
 
   const mounts = buildVolumeMounts(group, input.isMain);
   const safeName = group.folder.replace(/[^a-zA-Z0-9-]/g, '-');
-  const containerName = `nanoclaw-${safeName}-${Date.now()}`;
+  const poolKey = warmPoolKey(mounts);
+  const warm = takeWarmContainer(poolKey);
+  const containerName =
+    warm?.containerName ?? `nanoclaw-${safeName}-${Date.now()}`;
   const containerArgs = buildContainerArgs(mounts, containerName);
 
   logger.debug(
@@ -403,6 +435,7 @@ export async function runContainerAgent(
       containerName,
       mountCount: mounts.length,
       isMain: input.isMain,
+      warm: !!warm,
     },
     'Spawning container agent',
   );
@@ -411,12 +444,18 @@ export async function runContainerAgent(
   fs.mkdirSync(logsDir, { recursive: true });
 
   return new Promise((resolve) => {
-    const container = spawn('docker', containerArgs, {
-      stdio: ['pipe', 'pipe', 'pipe'],
-    });
+    const container =
+      warm?.proc ??
+      spawn('docker', containerArgs, {
+        stdio: ['pipe', 'pipe', 'pipe'],
+      });
 
     onProcess(container, containerName);
 
+    // Have one ready for this group's next request
+    const warmName = `nanoclaw-warm-${safeName}-${Date.now()}`;
+    prewarmContainer(poolKey, warmName, buildContainerArgs(mounts, warmName));
+
     let stdout = '';
     let stderr = '';
     let stdoutTruncated = false;
@@ -0,0 +1,101 @@
+/**
+ * Conversation history for the HTTP API
+ * Prompts and replies live in each group's Agno session database (written by
+ * the agent container), not in the message store, so they are read from there.
+ */
+import Database from 'better-sqlite3';
+import fs from 'fs';
+import path from 'path';
+
+import { GROUPS_DIR } from './config.js';
+
+const SESSION_DB_FILE = 'agno-sessions.db';
+const SESSION_TABLE = 'agent_sessions';
+
+export interface ConversationMessage {
+  sessionId: string;
+  role: 'user' | 'assistant';
+  content: string;
+  timestamp: string | null;
+}
+
+interface AgnoRun {
+  input?: { input_content?: unknown };
+  content?: unknown;
+  created_at?: number;
+}
+
+interface SessionRow {
+  session_id: string;
+  runs: string | null;
+}
+
+function asText(value: unknown): string {
+  if (typeof value === 'string') return value;
+  if (value === null || value === undefined) return '';
+  return JSON.stringify(value);
+}
+
+function asTimestamp(seconds: unknown): string | null {
+  return typeof seconds === 'number'
+    ? new Date(seconds * 1000).toISOString()
+    : null;
+}
+
+/**
+ * Every prompt and reply in a group's sessions, oldest session first. Empty
+ * when the group has never run an agent.
+ */
+export function getConversation(groupFolder: string): ConversationMessage[] {
+  const dbPath = path.join(GROUPS_DIR, groupFolder, SESSION_DB_FILE);
+  if (!fs.existsSync(dbPath)) return [];
+
+  const db = new Database(dbPath, { readonly: true, fileMustExist: true });
+  try {
+    const table = db
+      .prepare(
+        `SELECT name FROM sqlite_master WHERE type = 'table' AND name = ?`,
+      )
+      .get(SESSION_TABLE);
+    if (!table) return [];
+
+    const rows = db
+      .prepare(
+        `SELECT session_id, runs FROM ${SESSION_TABLE} ORDER BY created_at`,
+      )
+      .all() as SessionRow[];
+    const messages: ConversationMessage[] = [];
+    for (const row of rows) {
+      let runs: AgnoRun[] = [];
+      try {
+        runs = row.runs ? JSON.parse(row.runs) : [];
+      } catch {
+        continue;
+      }
+      for (const run of Array.isArray(runs) ? runs : []) {
+        const timestamp = asTimestamp(run.created_at);
+        const prompt = asText(run.input?.input_content);
+        if (prompt) {
+          messages.push({
+            sessionId: row.session_id,
+            role: 'user',
+            content: prompt,
+            timestamp,
+          });
+        }
+        const reply = asText(run.content);
+        if (reply) {
+          messages.push({
+            sessionId: row.session_id,
+            role: 'assistant',
+            content: reply,
+            timestamp,
+          });
+        }
+      }
+    }
+    return messages;
+  } finally {
+    db.close();
+  }
+}
@@ -22,6 +22,8 @@ interface GroupState {
   containerName: string | null;
   groupFolder: string | null;
   retryCount: number;
+  // Scheduled task the container is running, if it is running one
+  runningTaskId: string | null;
 }
 
 export class GroupQueue {
@@ -43,6 +45,7 @@ export class GroupQueue {
         containerName: null,
         groupFolder: null,
         retryCount: 0,
+        runningTaskId: null,
       };
       this.groups.set(groupJid, state);
     }
@@ -177,6 +180,32 @@ export class GroupQueue {
     }
   }
 
+  /**
+   * Containers running now and groups waiting for one, for idle checks.
+   */
+  getActivity(): { activeContainers: number; queuedGroups: number } {
+    let queuedGroups = 0;
+    for (const state of this.groups.values()) {
+      if (state.pendingMessages || state.pendingTasks.length > 0) {
+        queuedGroups++;
+      }
+    }
+    return { activeContainers: this.activeCount, queuedGroups };
+  }
+
+  /**
+   * Scheduled tasks whose containers are running now.
+   */
+  getRunningTasks(): { groupJid: string; taskId: string }[] {
+    const running: { groupJid: string; taskId: string }[] = [];
+    for (const [groupJid, state] of this.groups) {
+      if (state.active && state.runningTaskId) {
+        running.push({ groupJid, taskId: state.runningTaskId });
+      }
+    }
+    return running;
+  }
+
   /**
    * Signal the active container to wind down by writing a close sentinel.
    */
@@ -257,6 +286,7 @@ export class GroupQueue {
   private async runTask(groupJid: string, task: QueuedTask): Promise<void> {
     const state = this.getGroup(groupJid);
     state.active = true;
+    state.runningTaskId = task.id;
     this.activeCount++;
 
     logger.debug(
@@ -270,6 +300,7 @@ export class GroupQueue {
       logger.error({ groupJid, taskId: task.id, err }, 'Error running task');
     } finally {
       state.active = false;
+      state.runningTaskId = null;
       state.process = null;
       state.containerName = null;
       state.groupFolder = null;
@@ -2,9 +2,15 @@ import { execSync } from 'child_process';
 import http from 'node:http';
 import fs from 'fs';
 import path from 'path';
+import readline from 'readline';
 
 import { CronExpressionParser } from 'cron-parser';
 
+import {
+  hasBrowserSession,
+  issueLoginCode,
+  redeemLoginCode,
+} from './browser-auth.js';
 import {
   ASSISTANT_NAME,
   API_AUTH_TOKEN,
@@ -12,6 +18,7 @@ import {
   GROUPS_DIR,
   HTTP_HOST,
   HTTP_PORT,
+  HTTP_SOCKET_PATH,
   IDLE_TIMEOUT,
   IPC_POLL_INTERVAL,
   MAIN_GROUP_FOLDER,
@@ -28,9 +35,11 @@ import {
   writeGroupsSnapshot,
   writeTasksSnapshot,
 } from './container-runner.js';
+import { getConversation } from './conversations.js';
 import {
   createTask,
   deleteTask,
+  getAllChats,
   getAllRegisteredGroups,
   getAllSessions,
   getAllTasks,
@@ -40,12 +49,14 @@ import {
   setRegisteredGroup,
   setRouterState,
   setSession,
+  storeChatMetadata,
   updateTask,
 } from './db.js';
 import { GroupQueue } from './group-queue.js';
 import { startSchedulerLoop } from './task-scheduler.js';
 import { RegisteredGroup } from './types.js';
 import { logger } from './logger.js';
+import { drainWarmPool } from './warm-pool.js';
 
 let sessions: Record<string, string> = {};
 let registeredGroups: Record<string, RegisteredGroup> = {};
@@ -824,6 +835,8 @@ async function handleChat(
 
   const requestId = `${Date.now()}-${Math.random().toString(36).slice(2, 8)}`;
   activeSseRequests.set(chatJid, requestId);
+  // Recent chats are ordered by this
+  storeChatMetadata(chatJid, new Date().toISOString());
 
   // Set up SSE response
   res.writeHead(200, {
@@ -900,15 +913,39 @@ async function handleGetGroups(
   _req: http.IncomingMessage,
   res: http.ServerResponse,
 ): Promise<void> {
+  const lastMessageTimes = new Map(
+    getAllChats().map((chat) => [chat.jid, chat.last_message_time]),
+  );
   const groups = Object.entries(registeredGroups).map(([jid, group]) => ({
     id: jid,
     name: group.name,
     folder: group.folder,
     added_at: group.added_at,
+    last_message_time: lastMessageTimes.get(jid) ?? null,
   }));
   jsonResponse(res, 200, groups);
 }
 
+/**
+ * Agent runs in progress: chat requests being answered and scheduled tasks
+ * whose containers are running.
+ */
+function handleGetRunningTasks(res: http.ServerResponse): void {
+  const chats = [...activeSseRequests.keys()].map((jid) => ({
+    chat_jid: jid,
+    kind: 'chat',
+    name: registeredGroups[jid]?.name ?? jid,
+  }));
+  const tasks = queue.getRunningTasks().map(({ groupJid, taskId }) => ({
+    chat_jid: groupJid,
+    kind: 'task',
+    task_id: taskId,
+    name: registeredGroups[groupJid]?.name ?? groupJid,
+    prompt: getTaskById(taskId)?.prompt ?? null,
+  }));
+  jsonResponse(res, 200, [...chats, ...tasks]);
+}
+
 async function handleCreateGroup(
   req: http.IncomingMessage,
   res: http.ServerResponse,
@@ -1043,12 +1080,14 @@ async function handlePilotWebhook(
   req: http.IncomingMessage,
   res: http.ServerResponse,
 ): Promise<void> {
-  // Only accept from localhost
+  // Only accept from localhost. A tunnel client connects from localhost too,
+  // but marks what it forwards.
   const remoteAddr = req.socket.remoteAddress;
   if (
-    remoteAddr !== '127.0.0.1' &&
-    remoteAddr !== '::1' &&
-    remoteAddr !== '::ffff:127.0.0.1'
+    (remoteAddr !== '127.0.0.1' &&
+      remoteAddr !== '::1' &&
+      remoteAddr !== '::ffff:127.0.0.1') ||
+    req.headers['x-forwarded-for'] !== undefined
   ) {
     jsonResponse(res, 403, { error: 'Forbidden' });
     return;
@@ -1128,6 +1167,26 @@ async function handlePilotWebhook(
   jsonResponse(res, 200, { status: 'ok' });
 }
 
+async function handleGetGroupMessages(
+  res: http.ServerResponse,
+  rawGroupId: string,
+): Promise<void> {
+  let groupFolder: string;
+  try {
+    groupFolder = normalizeGroupId(rawGroupId);
+  } catch {
+    jsonResponse(res, 400, { error: 'Invalid "groupId" field' });
+    return;
+  }
+
+  try {
+    jsonResponse(res, 200, { messages: getConversation(groupFolder) });
+  } catch (err) {
+    logger.error({ err, groupFolder }, 'Failed to read conversation');
+    jsonResponse(res, 500, { error: 'Failed to read conversation' });
+  }
+}
+
 async function handleDeleteSession(
   res: http.ServerResponse,
   folder: string,
@@ -1144,6 +1203,14 @@ async function handleDeleteSession(
   }
 }
 
+function handleGetActivity(res: http.ServerResponse): void {
+  jsonResponse(res, 200, {
+    ...queue.getActivity(),
+    activeRequests: activeSseRequests.size,
+    scheduledTasks: getAllTasks().filter((t) => t.status === 'active').length,
+  });
+}
+
 function handleHealth(
   _req: http.IncomingMessage,
   res: http.ServerResponse,
@@ -1151,8 +1218,64 @@ function handleHealth(
   jsonResponse(res, 200, { status: 'ok' });
 }
 
+function escapeHtml(text: string): string {
+  return text
+    .replace(/&/g, '&amp;')
+    .replace(/</g, '&lt;')
+    .replace(/>/g, '&gt;')
+    .replace(/"/g, '&quot;');
+}
+
+/**
+ * A plain page for browsing the API from a browser: the groups, with links
+ * to their conversations, and the other read-only endpoints.
+ */
+function handleIndexPage(res: http.ServerResponse): void {
+  const groups = Object.entries(registeredGroups)
+    .map(([jid, group]) => {
+      const href = `/api/groups/${encodeURIComponent(group.folder)}/messages`;
+      return `<li><a href="${escapeHtml(href)}">${escapeHtml(group.name)}</a> <small>${escapeHtml(jid)}</small></li>`;
+    })
+    .join('\n');
+  const html = `<!doctype html>
+<html>
+<head><meta charset="utf-8"><title>${escapeHtml(ASSISTANT_NAME)} · NanoClaw</title></head>
+<body style="font-family: system-ui, sans-serif; max-width: 40rem; margin: 2rem auto">
+<h1>${escapeHtml(ASSISTANT_NAME)}</h1>
+<h2>Conversations</h2>
+<ul>
+${groups || '<li>None yet</li>'}
+</ul>
+<h2>API</h2>
+<ul>
+<li><a href="/api/groups">/api/groups</a></li>
+<li><a href="/api/tasks/running">/api/tasks/running</a></li>
+<li><a href="/api/activity">/api/activity</a></li>
+<li><a href="/api/health">/api/health</a></li>
+</ul>
+</body>
+</html>
+`;
+  res.writeHead(200, { 'Content-Type': 'text/html; charset=utf-8' });
+  res.end(html);
+}
+
+function handleRedeemLoginCode(res: http.ServerResponse, code: string): void {
+  const cookie = redeemLoginCode(code);
+  if (!cookie) {
+    res.writeHead(401, { 'Content-Type': 'text/plain' });
+    res.end(
+      'This login link has expired. Open the backend from the app again.',
+    );
+    return;
+  }
+  res.writeHead(302, { 'Set-Cookie': cookie, Location: '/' });
+  res.end();
+}
+
 function isAuthorized(req: http.IncomingMessage): boolean {
   if (!API_AUTH_TOKEN) return true;
+  if (hasBrowserSession(req)) return true;
 
   const auth = req.headers.authorization;
   return auth === `Bearer ${API_AUTH_TOKEN}`;
@@ -1190,6 +1313,12 @@ function startHttpServer(): void {
         return;
       }
 
+      // One-time login link from the desktop app: the code is the credential
+      if (method === 'GET' && pathname === '/auth/one-time') {
+        handleRedeemLoginCode(res, url.searchParams.get('code') ?? '');
+        return;
+      }
+
       if (pathname !== '/api/health' && !isAuthorized(req)) {
         res.writeHead(401, {
           'Content-Type': 'application/json',
@@ -1230,6 +1359,15 @@ function startHttpServer(): void {
         return;
       }
 
+      // GET /api/groups/:groupId/messages
+      const messagesMatch = pathname.match(
+        /^\/api\/groups\/([^/]+)\/messages$/,
+      );
+      if (method === 'GET' && messagesMatch) {
+        await handleGetGroupMessages(res, decodeURIComponent(messagesMatch[1]));
+        return;
+      }
+
       // DELETE /api/groups/:folder/session
       const sessionMatch = pathname.match(/^\/api\/groups\/([^/]+)\/session$/);
       if (method === 'DELETE' && sessionMatch) {
@@ -1237,6 +1375,30 @@ function startHttpServer(): void {
         return;
       }
 
+      // GET /api/tasks/running
+      if (method === 'GET' && pathname === '/api/tasks/running') {
+        handleGetRunningTasks(res);
+        return;
+      }
+
+      // GET /api/activity
+      if (method === 'GET' && pathname === '/api/activity') {
+        handleGetActivity(res);
+        return;
+      }
+
+      // POST /api/auth/one-time
+      if (method === 'POST' && pathname === '/api/auth/one-time') {
+        jsonResponse(res, 200, { code: issueLoginCode() });
+        return;
+      }
+
+      // GET /
+      if (method === 'GET' && pathname === '/') {
+        handleIndexPage(res);
+        return;
+      }
+
       // GET /api/health
       if (method === 'GET' && pathname === '/api/health') {
         handleHealth(req, res);
@@ -1252,6 +1414,16 @@ function startHttpServer(): void {
     }
   });
 
+  if (HTTP_SOCKET_PATH) {
+    // A stale socket from a crashed run would make listen() fail with EADDRINUSE
+    fs.rmSync(HTTP_SOCKET_PATH, { force: true });
+    server.listen(HTTP_SOCKET_PATH, () => {
+      fs.chmodSync(HTTP_SOCKET_PATH, 0o600);
+      logger.info({ socket: HTTP_SOCKET_PATH }, 'HTTP server listening');
+    });
+    return;
+  }
+
   server.listen(HTTP_PORT, HTTP_HOST, () => {
     logger.info({ host: HTTP_HOST, port: HTTP_PORT }, 'HTTP server listening');
   });
@@ -1315,6 +1487,56 @@ function ensureContainerRuntimeRunning(): void {
   }
 }
 
+// --- Shell control messages ---
+// The desktop shell writes one JSON object per line to stdin
+// (send_backend_command), e.g. {"command":"dumpState"}.
+
+function handleStdinCommand(line: string): void {
+  let message: { command?: unknown } | null;
+  try {
+    message = JSON.parse(line);
+  } catch {
+    logger.warn({ line }, 'Ignoring malformed stdin command');
+    return;
+  }
+  switch (message?.command) {
+    case 'dumpState':
+      logger.info(
+        {
+          groups: Object.keys(registeredGroups),
+          sessions: Object.keys(sessions).length,
+          activeSseRequests: [...activeSseRequests.keys()],
+          pendingPrompts: [...pendingPrompts.keys()],
+          bufferedMessages: [...messageBuffers.values()].reduce(
+            (sum, messages) => sum + messages.length,
+            0,
+          ),
+        },
+        'State dump',
+      );
+      break;
+    case 'reloadState':
+      loadState();
+      refreshTaskSnapshots();
+      break;
+    case 'flushCaches':
+      messageBuffers.clear();
+      refreshTaskSnapshots();
+      logger.info('Caches flushed');
+      break;
+    default:
+      logger.warn({ command: message?.command }, 'Unknown stdin command');
+  }
+}
+
+function startStdinCommands(): void {
+  if (process.env.NANOCLAW_STDIN_COMMANDS !== '1') return;
+  const lines = readline.createInterface({ input: process.stdin });
+  lines.on('line', (line) => {
+    if (line.trim()) handleStdinCommand(line);
+  });
+}
+
 async function main(): Promise<void> {
   ensureContainerRuntimeRunning();
   initDatabase();
@@ -1335,6 +1557,7 @@ async function main(): Promise<void> {
   // Graceful shutdown handlers
   const shutdown = async (signal: string) => {
     logger.info({ signal }, 'Shutdown signal received');
+    drainWarmPool();
     await queue.shutdown(10000);
     process.exit(0);
   };
@@ -1356,6 +1579,7 @@ async function main(): Promise<void> {
   });
   startIpcWatcher();
   refreshTaskSnapshots();
+  startStdinCommands();
 
   // Start HTTP server
   startHttpServer();
@@ -0,0 +1,102 @@
+/**
+ * Warm container pool for NanoClaw
+ * Keeps agent containers booted and waiting on stdin so a request only has to
+ * write its input, instead of paying for `docker run` first.
+ */
+import { ChildProcess, exec, spawn } from 'child_process';
+
+import { WARM_POOL_IDLE_MS, WARM_POOL_SIZE } from './config.js';
+import { logger } from './logger.js';
+
+export interface WarmContainer {
+  proc: ChildProcess;
+  containerName: string;
+}
+
+interface PoolEntry extends WarmContainer {
+  key: string;
+  startedAt: number;
+  idleTimer: NodeJS.Timeout;
+}
+
+// Oldest first, so eviction takes from the front
+const pool: PoolEntry[] = [];
+
+function stopEntry(entry: PoolEntry): void {
+  clearTimeout(entry.idleTimer);
+  exec(`docker stop ${entry.containerName}`, { timeout: 15000 }, (err) => {
+    if (err) entry.proc.kill('SIGKILL');
+  });
+}
+
+function remove(entry: PoolEntry): void {
+  const index = pool.indexOf(entry);
+  if (index !== -1) pool.splice(index, 1);
+}
+
+/**
+ * Take a warm container started with the same mounts and env (`key`), if
+ * one is waiting. Containers whose key no longer matches age out on their own.
+ */
+export function takeWarmContainer(key: string): WarmContainer | undefined {
+  const entry = pool.find(
+    (e) => e.key === key && e.proc.exitCode === null && !e.proc.killed,
+  );
+  if (!entry) return undefined;
+  remove(entry);
+  clearTimeout(entry.idleTimer);
+  logger.debug(
+    {
+      containerName: entry.containerName,
+      warmMs: Date.now() - entry.startedAt,
+    },
+    'Using warm container',
+  );
+  return { proc: entry.proc, containerName: entry.containerName };
+}
+
+/**
+ * Start a container for `key` to wait for the next request, evicting the
+ * oldest when the pool is full. No-op when the pool is disabled.
+ */
+export function prewarmContainer(
+  key: string,
+  containerName: string,
+  args: string[],
+): void {
+  if (WARM_POOL_SIZE <= 0) return;
+  if (pool.some((e) => e.key === key)) return;
+  while (pool.length >= WARM_POOL_SIZE) {
+    stopEntry(pool.shift()!);
+  }
+
+  const proc = spawn('docker', args, { stdio: ['pipe', 'pipe', 'pipe'] });
+  const entry: PoolEntry = {
+    key,
+    proc,
+    containerName,
+    startedAt: Date.now(),
+    idleTimer: setTimeout(() => {
+      remove(entry);
+      stopEntry(entry);
+    }, WARM_POOL_IDLE_MS),
+  };
+  proc.on('error', (err) => {
+    logger.warn({ containerName, err }, 'Warm container failed to start');
+    remove(entry);
+    clearTimeout(entry.idleTimer);
+  });
+  proc.on('close', () => {
+    remove(entry);
+    clearTimeout(entry.idleTimer);
+  });
+  pool.push(entry);
+  logger.debug({ containerName, size: pool.length }, 'Prewarmed container');
+}
+
+/** Stop every waiting container, e.g. on shutdown. */
+export function drainWarmPool(): void {
+  for (const entry of pool.splice(0)) {
+    stopEntry(entry);
+  }
+}